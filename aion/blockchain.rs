@@ -29,6 +29,7 @@ use rustc_hex::FromHex;
 use bytes::ToPretty;
 use rlp::PayloadInfo;
 use acore::service::ClientService;
+use acore::state_dump;
 use acore::client::{DatabaseCompactionProfile, VMType, BlockImportError, BlockChainClient, BlockId};
 use acore::error::ImportError;
 use acore::miner::Miner;
@@ -79,6 +80,7 @@ pub enum BlockchainCmd {
     Kill(KillBlockchain),
     Import(ImportBlockchain),
     Export(ExportBlockchain),
+    ExportState(ExportState),
     Revert(RevertBlockchain),
 }
 
@@ -125,6 +127,21 @@ pub struct ExportBlockchain {
     pub to_block: BlockId,
 }
 
+#[derive(Debug, PartialEq)]
+pub struct ExportState {
+    pub spec: SpecType,
+    pub cache_config: CacheConfig,
+    pub dirs: Directories,
+    pub file_path: Option<String>,
+    pub pruning: Pruning,
+    pub pruning_history: u64,
+    pub pruning_memory: usize,
+    pub compaction: DatabaseCompactionProfile,
+    pub wal: bool,
+    pub fat_db: Switch,
+    pub at: BlockId,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct RevertBlockchain {
     pub spec: SpecType,
@@ -144,6 +161,7 @@ pub fn execute(cmd: BlockchainCmd) -> Result<(), String> {
         BlockchainCmd::Kill(kill_cmd) => kill_db(kill_cmd),
         BlockchainCmd::Import(import_cmd) => execute_import(import_cmd),
         BlockchainCmd::Export(export_cmd) => execute_export(export_cmd),
+        BlockchainCmd::ExportState(export_cmd) => execute_export_state(export_cmd),
         BlockchainCmd::Revert(revert_cmd) => execute_revert(revert_cmd),
     }
 }
@@ -459,6 +477,55 @@ fn execute_export(cmd: ExportBlockchain) -> Result<(), String> {
     Ok(())
 }
 
+fn execute_export_state(cmd: ExportState) -> Result<(), String> {
+    let timer = Instant::now();
+    let service = start_client(
+        cmd.dirs,
+        cmd.spec,
+        cmd.pruning,
+        cmd.pruning_history,
+        cmd.pruning_memory,
+        cmd.fat_db,
+        cmd.compaction,
+        cmd.wal,
+        cmd.cache_config,
+        true,
+    )?;
+
+    let client = service.client();
+
+    let mut out: Box<io::Write> = match cmd.file_path {
+        Some(f) => {
+            Box::new(io::BufWriter::new(
+                fs::File::create(&f).map_err(|_| format!("Cannot write to file given: {}", f))?,
+            ))
+        }
+        None => Box::new(io::BufWriter::new(io::stdout())),
+    };
+
+    let number = client
+        .block_number(cmd.at)
+        .ok_or("Block could not be found")?;
+    let state = client
+        .state_at(BlockId::Number(number))
+        .ok_or_else(|| format!("State of block #{} is not available", number))?;
+    let (root, db) = state.drop();
+
+    let accounts = state_dump::export(db.as_hashstore(), &root, &mut out, |accounts| {
+        if accounts % 10000 == 0 {
+            info!(target: "export", "{} accounts", accounts);
+        }
+    })?;
+
+    let ms = timer.elapsed().as_milliseconds();
+
+    info!(
+        target: "export",
+        "Export of {} accounts at block #{} completed in {} ms", accounts, number, ms
+    );
+    Ok(())
+}
+
 pub fn kill_db(cmd: KillBlockchain) -> Result<(), String> {
     let spec = cmd.spec.spec(&cmd.dirs.cache)?;
     let genesis_hash = spec.genesis_header().hash();
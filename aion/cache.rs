@@ -20,11 +20,11 @@
  ******************************************************************************/
 
 use std::cmp::max;
+use acore::client::MemoryBudget;
 
+const MB: usize = 1024 * 1024;
 const MIN_BC_CACHE_MB: u32 = 4;
-const MIN_DB_CACHE_MB: u32 = 8;
 const MIN_BLOCK_QUEUE_SIZE_LIMIT_MB: u32 = 16;
-//const DEFAULT_DB_CACHE_SIZE: u32 = 128;
 const DEFAULT_BC_CACHE_SIZE: u32 = 8;
 const DEFAULT_BLOCK_QUEUE_SIZE_LIMIT_MB: u32 = 40;
 const DEFAULT_STATE_CACHE_SIZE: u32 = 25;
@@ -33,20 +33,22 @@ const DEFAULT_STATE_CACHE_SIZE: u32 = 25;
 /// All    values are represented in MB.
 #[derive(Debug, PartialEq)]
 pub struct CacheConfig {
-    //    /// Size of rocksDB cache. Almost all goes to the state column.
-    //    db: u32,
+    /// Size of rocksDB cache, shared by all the databases. Each database keeps its default
+    /// cache if not set.
+    db: Option<u32>,
     /// Size of blockchain cache.
     blockchain: u32,
-    /// Size of transaction queue cache.
+    /// Size of the block verification queue.
     queue: u32,
     /// Size of the state cache.
     state: u32,
+    /// Size of the transaction queue, `--tx-queue-mem-limit` applies if not set.
+    tx_queue: Option<u32>,
 }
 
 impl Default for CacheConfig {
     fn default() -> Self {
         CacheConfig::new(
-            //            DEFAULT_DB_CACHE_SIZE,
             DEFAULT_BC_CACHE_SIZE,
             DEFAULT_BLOCK_QUEUE_SIZE_LIMIT_MB,
             DEFAULT_STATE_CACHE_SIZE,
@@ -55,41 +57,45 @@ impl Default for CacheConfig {
 }
 
 impl CacheConfig {
-    /// Creates new cache config with cumulative size equal `total`.
+    /// Creates new cache config with cumulative size equal `total`, split between the caches
+    /// the same way as a budget resized at runtime.
     pub fn new_with_total_cache_size(total: u32) -> Self {
+        let budget = MemoryBudget::with_total(total as usize * MB);
         CacheConfig {
-            //            db: total * 7 / 10,
-            blockchain: total / 3,
-            queue: DEFAULT_BLOCK_QUEUE_SIZE_LIMIT_MB,
-            state: total * 2 / 3,
+            db: budget.db.map(|db| (db / MB) as u32),
+            blockchain: (budget.blockchain / MB) as u32,
+            queue: (budget.queue / MB) as u32,
+            state: (budget.state / MB) as u32,
+            tx_queue: budget.tx_queue.map(|tx_queue| (tx_queue / MB) as u32),
         }
     }
 
     /// Creates new cache config with gitven details.
-    pub fn new(/*db: u32, */ blockchain: u32, queue: u32, state: u32) -> Self {
+    pub fn new(blockchain: u32, queue: u32, state: u32) -> Self {
         CacheConfig {
-            //            db: db,
-            blockchain: blockchain,
-            queue: queue,
+            db: None,
+            blockchain: max(blockchain, MIN_BC_CACHE_MB),
+            queue: max(queue, MIN_BLOCK_QUEUE_SIZE_LIMIT_MB),
             state: state,
+            tx_queue: None,
         }
     }
 
     /// Size of db cache.
-    pub fn db_cache_size(&self) -> u32 {
-        /*max(*/
-        MIN_DB_CACHE_MB /*, self.db) */
-    }
+    pub fn db_cache_size(&self) -> Option<u32> { self.db }
 
     /// Size of block queue size limit
-    pub fn queue(&self) -> u32 { max(self.queue, MIN_BLOCK_QUEUE_SIZE_LIMIT_MB) }
+    pub fn queue(&self) -> u32 { self.queue }
 
     /// Size of the blockchain cache.
-    pub fn blockchain(&self) -> u32 { max(self.blockchain, MIN_BC_CACHE_MB) }
+    pub fn blockchain(&self) -> u32 { self.blockchain }
 
     /// Size of the state cache.
     pub fn state(&self) -> u32 { self.state * 3 / 4 }
 
+    /// Size of the transaction queue.
+    pub fn tx_queue(&self) -> Option<u32> { self.tx_queue }
+
     /// Size of the jump-tables cache.
     pub fn jump_tables(&self) -> u32 { self.state / 4 }
 }
@@ -100,29 +106,41 @@ mod tests {
 
     #[test]
     fn test_cache_config_constructor() {
-        let config = CacheConfig::new_with_total_cache_size(60 /*200*/);
-        //        assert_eq!(config.db, 140);
-        assert_eq!(config.blockchain(), 20);
-        assert_eq!(config.queue(), 40);
-        assert_eq!(config.state(), 30);
-        assert_eq!(config.jump_tables(), 10);
+        let config = CacheConfig::new_with_total_cache_size(400);
+        assert_eq!(config.db_cache_size(), Some(100));
+        assert_eq!(config.blockchain(), 80);
+        assert_eq!(config.queue(), 60);
+        assert_eq!(config.state(), 105);
+        assert_eq!(config.jump_tables(), 35);
+        assert_eq!(config.tx_queue(), Some(20));
+    }
+
+    #[test]
+    fn test_cache_config_db_cache_sizes() {
+        assert_eq!(CacheConfig::default().db_cache_size(), None);
+        assert_eq!(
+            CacheConfig::new_with_total_cache_size(40).db_cache_size(),
+            Some(10)
+        );
+    }
+
+    #[test]
+    fn test_cache_config_within_total() {
+        let config = CacheConfig::new_with_total_cache_size(20);
+        let total = config.db_cache_size().unwrap()
+            + config.blockchain()
+            + config.queue()
+            + config.state()
+            + config.jump_tables()
+            + config.tx_queue().unwrap();
+        assert!(total <= 20);
     }
-    /*
-    
-        #[test]
-        fn test_cache_config_db_cache_sizes() {
-            let config = CacheConfig::new_with_total_cache_size(400);
-            assert_eq!(config.db, 280);
-            assert_eq!(config.db_cache_size(), 280);
-        }
-    */
 
     #[test]
     fn test_cache_config_default() {
         assert_eq!(
             CacheConfig::default(),
             CacheConfig::new(
-                //                super::DEFAULT_DB_CACHE_SIZE,
                 super::DEFAULT_BC_CACHE_SIZE,
                 super::DEFAULT_BLOCK_QUEUE_SIZE_LIMIT_MB,
                 super::DEFAULT_STATE_CACHE_SIZE
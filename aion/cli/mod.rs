@@ -112,6 +112,23 @@ usage! {
             "Path to the exported file",
         }
 
+        CMD cmd_state
+        {
+            "Manage the state of the blockchain",
+
+            CMD cmd_state_export {
+                "Export the state at a block as JSON, one account per line. Requires --fat-db.",
+
+                ARG arg_state_export_at: (String) = "latest",
+                "--at=[BLOCK]",
+                "Export the state at block BLOCK, which may be an index, hash or latest.",
+
+                ARG arg_state_export_file: (Option<String>) = None,
+                "[FILE]",
+                "Path to the exported file",
+            }
+        }
+
         CMD cmd_revert
         {
             "Revert blockchain",
@@ -128,6 +145,38 @@ usage! {
             CMD cmd_db_kill {
                 "Clean the database",
             }
+
+            CMD cmd_db_stats {
+                "Show the number of keys and the sizes of each database",
+            }
+
+            CMD cmd_db_compact {
+                "Compact the databases",
+            }
+
+            CMD cmd_db_verify {
+                "Check the headers, bodies, receipts and state roots of the canonical chain",
+
+                ARG arg_db_verify_from: (String) = "0",
+                "--from=[BLOCK]",
+                "Verify from block number BLOCK.",
+            }
+
+            CMD cmd_db_migrate {
+                "Apply the pending database schema migrations",
+            }
+
+            CMD cmd_db_convert {
+                "Convert the database to another pruning method without resyncing",
+
+                ARG arg_db_convert_to: (String) = "fast",
+                "--to=[METHOD]",
+                "Pruning method to convert to. METHOD may be either archive or fast.",
+
+                ARG arg_db_convert_at: (Option<String>) = None,
+                "--at=[BLOCK]",
+                "Convert at block number BLOCK, the best block of a fast database or the block re-executed from for an archive database. (default: best block for fast, earliest available state for archive)",
+            }
         }
     }
     {
@@ -135,7 +184,7 @@ usage! {
         ["Operating Options"]
             ARG arg_chain: (String) = "mainnet", or |c: &Config| c.aion.as_ref()?.chain.clone(),
             "--chain=[CHAIN]",
-            "Specify the blockchain type. CHAIN may be a JSON chain specification file.",
+            "Specify the blockchain type. CHAIN may be either a JSON chain specification file or mainnet, dev.",
 
             ARG arg_keys_path: (Option<String>) = None, or |c: &Config| c.aion.as_ref()?.keys_path.clone(),
             "--keys-path=[PATH]",
@@ -241,7 +290,7 @@ usage! {
 
             ARG arg_http_apis: (Vec<String>) = vec!["all".into(),"-pubsub".into()], or |c: &Config| c.http.as_ref()?.apis.clone(),
             "--http-apis=[APIS]...",
-            "Specify the APIs available through the HTTP interface. APIS is a comma-delimited list of API name. Possible name are all, web3, eth, stratum, net, personal, rpc. You can also disable a specific API by putting '-' in the front: all,-personal.NOTE that rpc doesn’t support pubsub",
+            "Specify the APIs available through the HTTP interface. APIS is a comma-delimited list of API name. Possible name are all, web3, eth, stratum, net, personal, rpc, admin, debug. You can also disable a specific API by putting '-' in the front: all,-personal.NOTE that rpc doesn’t support pubsub, admin and debug are only available on a local interface",
 
             ARG arg_http_hosts: (Vec<String>) = vec!["none".into()], or |c: &Config| c.http.as_ref()?.hosts.clone(),
             "--http-hosts=[HOSTS]...",
@@ -270,7 +319,7 @@ usage! {
 
             ARG arg_ws_apis: (Vec<String>) = vec!["all".into(),"-pubsub".into()], or |c: &Config| c.websockets.as_ref()?.apis.clone(),
             "--ws-apis=[APIS]...",
-            "Specify the APIs available through the WebSockets interface. APIS is a comma-delimited list of API name. Possible name are web3, eth, stratum, net, personal, rpc, pubsub.",
+            "Specify the APIs available through the WebSockets interface. APIS is a comma-delimited list of API name. Possible name are web3, eth, stratum, net, personal, rpc, pubsub, admin, debug. NOTE that admin and debug are only available on a local interface.",
 
             ARG arg_ws_origins: (Vec<String>) = vec!["none".into()], or |c: &Config| c.websockets.as_ref()?.origins.clone(),
             "--ws-origins=[URL]...",
@@ -295,7 +344,7 @@ usage! {
 
             ARG arg_ipc_apis: (Vec<String>) = vec!["all".into(),"-pubsub".into()], or |c: &Config| c.ipc.as_ref()?.apis.clone(),
             "--ipc-apis=[APIS]...",
-            "Specify custom API set available via JSON-RPC over IPC. Possible name are web3, eth, stratum, net, personal, rpc, pubsub.",
+            "Specify custom API set available via JSON-RPC over IPC. Possible name are web3, eth, stratum, net, personal, rpc, pubsub, admin, debug.",
 
         ["Wallet Options"]
             FLAG flag_enable_wallet: (bool) = false, or |c: &Config| c.wallet.as_ref()?.disable.clone().map(|a| !a),
@@ -335,6 +384,22 @@ usage! {
             "--stratum-secret=[STRING]",
             "Secret for authorizing Stratum server for peers.",
 
+            ARG arg_stratum_share_difficulty: (Option<u64>) = None, or |c: &Config| c.stratum.as_ref()?.share_difficulty.clone(),
+            "--stratum-share-difficulty=[DIFFICULTY]",
+            "Initial share difficulty of the Stratum workers. Shares below the block target are accepted and accounted for per worker when set, else shares must solve a block.",
+
+            ARG arg_stratum_vardiff_min: (Option<u64>) = None, or |c: &Config| c.stratum.as_ref()?.vardiff_min.clone(),
+            "--stratum-vardiff-min=[DIFFICULTY]",
+            "Lower bound of the variable share difficulty. Defaults to the share difficulty.",
+
+            ARG arg_stratum_vardiff_max: (Option<u64>) = None, or |c: &Config| c.stratum.as_ref()?.vardiff_max.clone(),
+            "--stratum-vardiff-max=[DIFFICULTY]",
+            "Upper bound of the variable share difficulty. Defaults to the share difficulty.",
+
+            ARG arg_stratum_share_time: (u64) = 10u64, or |c: &Config| c.stratum.as_ref()?.share_time.clone(),
+            "--stratum-share-time=[SECONDS]",
+            "Time between two shares of a worker the variable share difficulty aims at.",
+
         ["Sealing/Mining Options"]
             FLAG flag_force_sealing: (bool) = false, or |c: &Config| c.mining.as_ref()?.force_sealing.clone(),
             "--force-sealing",
@@ -352,6 +417,18 @@ usage! {
             "--dynamic-gas-price",
             "use dynamic gas price which adjust with --gas-price-percentile, --max-blk-traverse, --blk-price-window",
 
+            FLAG flag_calibrate_min_gas_price: (bool) = false, or |c: &Config| c.mining.as_ref()?.calibrate_min_gas_price.clone(),
+            "--calibrate-min-gas-price",
+            "Raise the minimum gas price to the --gas-price-percentile percentile of the prices paid in the last --blk-price-window blocks, at most --local-max-gas-price.",
+
+            FLAG flag_mine_internally: (bool) = false, or |c: &Config| c.mining.as_ref()?.mine_internally.clone(),
+            "--mine-internally",
+            "Mine proof-of-work blocks with the built-in CPU Equihash solver. Only practical with the small Equihash parameters of a devnet spec. Implies --force-sealing.",
+
+            ARG arg_mining_threads: (usize) = 0usize, or |c: &Config| c.mining.as_ref()?.mining_threads.clone(),
+            "--mining-threads=[NUM]",
+            "Number of threads of the built-in CPU Equihash solver, 0 for one per CPU.",
+
             ARG arg_reseal_on_txs: (String) = "own", or |c: &Config| c.mining.as_ref()?.reseal_on_txs.clone(),
             "--reseal-on-txs=[SET]",
             "Specify which transactions should force the node to reseal a block. SET is one of: none - never reseal on new transactions; own - reseal only on a new local transaction; ext - reseal only on a new external transaction; all - reseal on all new transactions.",
@@ -382,12 +459,32 @@ usage! {
 
             ARG arg_tx_queue_mem_limit: (u32) = 2u32, or |c: &Config| c.mining.as_ref()?.tx_queue_mem_limit.clone(),
             "--tx-queue-mem-limit=[MB]",
-            "Maximum amount of memory that can be used by the transaction queue. Setting this parameter to 0 disables limiting.",
+            "Maximum amount of memory that can be used by the transaction queue. Setting this parameter to 0 disables limiting. Overridden by --cache-size.",
 
             ARG arg_tx_queue_strategy: (String) = "gas_price", or |c: &Config| c.mining.as_ref()?.tx_queue_strategy.clone(),
             "--tx-queue-strategy=[S]",
             "Prioritization strategy used to order transactions in the queue. S may be: gas - Prioritize txs with low gas limit; gas_price - Prioritize txs with high gas price; gas_factor - Prioritize txs using gas price and gas limit ratio.",
 
+            ARG arg_tx_selection_order: (String) = "queue", or |c: &Config| c.mining.as_ref()?.tx_selection_order.clone(),
+            "--tx-selection-order=[S]",
+            "Order in which pending transactions are included in new blocks, keeping the nonce order of each sender. S may be: queue - Order of the transaction queue; price_per_byte - Prioritize txs paying the highest fee per byte.",
+
+            FLAG flag_tx_priority_local: (bool) = false, or |c: &Config| c.mining.as_ref()?.tx_priority_local.clone(),
+            "--tx-priority-local",
+            "Include transactions of local senders first in new blocks.",
+
+            ARG arg_tx_priority_senders: (Vec<String>) = Vec::new(), or |c: &Config| c.mining.as_ref()?.tx_priority_senders.clone(),
+            "--tx-priority-senders=[ACCOUNTS]",
+            "Include transactions of these senders first in new blocks. ACCOUNTS is a comma-delimited list of addresses.",
+
+            ARG arg_tx_sender_energy_share: (Option<u64>) = None, or |c: &Config| c.mining.as_ref()?.tx_sender_energy_share.clone(),
+            "--tx-sender-energy-share=[PERCENT]",
+            "Maximal percentage of the block energy limit the transactions of a single sender can use.",
+
+            ARG arg_tx_bridge_energy_reserve: (u64) = 0u64, or |c: &Config| c.mining.as_ref()?.tx_bridge_energy_reserve.clone(),
+            "--tx-bridge-energy-reserve=[PERCENT]",
+            "Percentage of the block energy limit reserved for token bridge transactions.",
+
             ARG arg_tx_queue_ban_count: (u16) = 1u16, or |c: &Config| c.mining.as_ref()?.tx_queue_ban_count.clone(),
             "--tx-queue-ban-count=[C]",
             "Number of times maximal time for execution (--tx-time-limit) can be exceeded before banning sender/recipient/code.",
@@ -449,18 +546,26 @@ usage! {
             "--disable-wal",
             "Disables DB WAL, which gives a significant speed up but means an unclean exit is unrecoverable.",
 
+            FLAG flag_db_column_families: (bool) = false, or |c: &Config| c.db.as_ref()?.db_column_families.clone(),
+            "--db-column-families",
+            "Keep all databases as column families of a single RocksDB, so that each commit is atomic across them. An existing database is migrated on first start.",
+
             FLAG flag_scale_verifiers: (bool) = false, or |c: &Config| c.db.as_ref()?.scale_verifiers.clone(),
             "--scale-verifiers",
             "Automatically scale amount of verifier threads based on workload. Not guaranteed to be faster.",
 
             ARG arg_pruning: (String) = "archive", or |c: &Config| c.db.as_ref()?.pruning.clone(),
             "--pruning=[METHOD]",
-            "Configure pruning of the state/storage trie. METHOD may be one of auto, archive, fast: archive - keep all state trie data. No pruning. fast - maintain journal overlay. Fast but 50MB used. auto - use the method most recently synced or default to fast if none synced.",
+            "Configure pruning of the state/storage trie. METHOD may be one of auto, archive, fast, refcounted: archive - keep all state trie data. No pruning. fast - maintain journal overlay. Fast but 50MB used. refcounted - reference count state nodes on disk and keep exactly --pruning-history states. auto - use the method most recently synced or default to fast if none synced.",
 
             ARG arg_pruning_history: (u64) = 64u64, or |c: &Config| c.db.as_ref()?.pruning_history.clone(),
             "--pruning-history=[NUM]",
             "Set a minimum number of recent states to keep when pruning is active.",
 
+            ARG arg_pruning_checkpoints: (Option<u64>) = None, or |c: &Config| c.db.as_ref()?.pruning_checkpoints.clone(),
+            "--pruning-checkpoints=[NUM]",
+            "Keep the state of every NUM-th block beyond the pruning history. Requires --pruning=refcounted.",
+
             ARG arg_pruning_memory: (usize) = 32usize, or |c: &Config| c.db.as_ref()?.pruning_memory.clone(),
             "--pruning-memory=[MB]",
             "The ideal amount of memory in megabytes to use to store recent states. As many states as possible will be kept within this limit, and at least --pruning-history states will always be kept.",
@@ -485,13 +590,29 @@ usage! {
             "--db-compaction=[TYPE]",
             "Database compaction type. TYPE may be one of: ssd - suitable for SSDs and fast HDDs; hdd - suitable for slow HDDs; auto - determine automatically.",
 
+            ARG arg_db_durability: (String) = "fast", or |c: &Config| c.db.as_ref()?.db_durability.clone(),
+            "--db-durability=[PROFILE]",
+            "Database durability profile. PROFILE may be one of: fast - buffered writes, recent blocks may be lost on a crash; safe - WAL enabled and writes synced to disk whenever the best block changes.",
+
+            ARG arg_db_backend: (String) = "rocksdb", or |c: &Config| c.db.as_ref()?.db_backend.clone(),
+            "--db-backend=[ENGINE]",
+            "Embedded database engine. ENGINE may be one of: rocksdb; lmdb - only if built with the lmdb feature. An existing database must be opened with the engine that created it.",
+
+            ARG arg_freezer_threshold: (Option<u64>) = None, or |c: &Config| c.db.as_ref()?.freezer_threshold.clone(),
+            "--freezer-threshold=[BLOCKS]",
+            "Move headers, bodies and receipts of canonical blocks more than BLOCKS below the best block out of the databases into the append-only freezer files. Must be at least 1024 and the pruning history. Blocks are kept in the databases if not set.",
+
+            FLAG flag_log_index: (bool) = false, or |c: &Config| c.db.as_ref()?.log_index.clone(),
+            "--log-index",
+            "Index the logs of the canonical blocks by address and first topic to speed up log queries for given addresses over large block ranges. The index is built in the background, including for the blocks imported before it was enabled.",
+
             ARG arg_fat_db: (String) = "auto", or |c: &Config| c.db.as_ref()?.fat_db.clone(),
             "--fat-db=[BOOL]",
             "Build appropriate information to allow enumeration of all accounts and storage keys. Doubles the size of the state database. BOOL may be one of on, off or auto.",
 
             ARG arg_cache_size: (Option<u32>) = None, or |c: &Config| c.db.as_ref()?.cache_size.clone(),
             "--cache-size=[MB]",
-            "Set total amount of discretionary memory to use for the entire system, overrides other cache and queue options.",
+            "Set total amount of discretionary memory to use for the entire system, split between the blockchain, state, database and block verification queue caches and the transaction queue. Overrides other cache and block queue options and --tx-queue-mem-limit. Can be changed at runtime with admin_setCacheSize.",
 
             ARG arg_num_verifiers: (Option<usize>) = None, or |c: &Config| c.db.as_ref()?.num_verifiers.clone(),
             "--num-verifiers=[INT]",
@@ -633,11 +754,19 @@ struct Mining {
     extra_data: Option<String>,
     tx_queue_mem_limit: Option<u32>,
     tx_queue_strategy: Option<String>,
+    tx_selection_order: Option<String>,
+    tx_priority_local: Option<bool>,
+    tx_priority_senders: Option<Vec<String>>,
+    tx_sender_energy_share: Option<u64>,
+    tx_bridge_energy_reserve: Option<u64>,
     tx_queue_ban_count: Option<u16>,
     tx_queue_ban_time: Option<u64>,
     remove_solved: Option<bool>,
     infinite_pending_block: Option<bool>,
     dynamic_gas_price: Option<bool>,
+    calibrate_min_gas_price: Option<bool>,
+    mine_internally: Option<bool>,
+    mining_threads: Option<usize>,
     blk_price_window: Option<usize>,
     gas_price_percentile: Option<usize>,
     max_blk_traverse: Option<usize>,
@@ -651,6 +780,10 @@ struct Stratum {
     interface: Option<String>,
     port: Option<u16>,
     secret: Option<String>,
+    share_difficulty: Option<u64>,
+    vardiff_min: Option<u64>,
+    vardiff_max: Option<u64>,
+    share_time: Option<u64>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -659,14 +792,20 @@ struct Database {
     no_persistent_txqueue: Option<bool>,
     pruning: Option<String>,
     pruning_history: Option<u64>,
+    pruning_checkpoints: Option<u64>,
     pruning_memory: Option<usize>,
     disable_wal: Option<bool>,
+    db_column_families: Option<bool>,
     cache_size: Option<u32>,
     //    cache_size_db: Option<u32>,
     cache_size_blocks: Option<u32>,
     cache_size_queue: Option<u32>,
     cache_size_state: Option<u32>,
     db_compaction: Option<String>,
+    db_durability: Option<String>,
+    db_backend: Option<String>,
+    freezer_threshold: Option<u64>,
+    log_index: Option<bool>,
     fat_db: Option<String>,
     scale_verifiers: Option<bool>,
     num_verifiers: Option<usize>,
@@ -828,8 +967,15 @@ mod tests {
                 cmd_account_export_to_key: false,
                 cmd_import: false,
                 cmd_export: false,
+                cmd_state: false,
+                cmd_state_export: false,
                 cmd_db: false,
                 cmd_db_kill: false,
+                cmd_db_stats: false,
+                cmd_db_compact: false,
+                cmd_db_verify: false,
+                cmd_db_migrate: false,
+                cmd_db_convert: false,
                 cmd_revert: false,
 
                 // Arguments
@@ -840,10 +986,15 @@ mod tests {
                 arg_export_blocks_format: None,
                 arg_export_blocks_from: "1".into(),
                 arg_export_blocks_to: "latest".into(),
+                arg_state_export_at: "latest".into(),
+                arg_state_export_file: None,
                 arg_account_import_path: None,
                 arg_account_private_key: None,
                 arg_account_address: None,
                 arg_revert_blocks_to: "0".into(),
+                arg_db_verify_from: "0".into(),
+                arg_db_convert_to: "fast".into(),
+                arg_db_convert_at: None,
 
                 // -- Operating Options
                 arg_chain: "xyz".into(),
@@ -921,6 +1072,13 @@ mod tests {
                 arg_extra_data: Some("Aion".into()),
                 arg_tx_queue_mem_limit: 2u32,
                 arg_tx_queue_strategy: "gas_factor".into(),
+                arg_tx_selection_order: "price_per_byte".into(),
+                flag_tx_priority_local: true,
+                arg_tx_priority_senders: vec![
+                    "0xa0deadbeefcafe000000000000000000000000000000000000000000000000ff".into(),
+                ],
+                arg_tx_sender_energy_share: Some(25u64),
+                arg_tx_bridge_energy_reserve: 10u64,
                 arg_tx_queue_ban_count: 1u16,
                 arg_tx_queue_ban_time: 180u64,
                 flag_remove_solved: true,
@@ -928,6 +1086,9 @@ mod tests {
                 arg_max_blk_traverse: 64usize,
                 arg_blk_price_window: 20usize,
                 flag_dynamic_gas_price: true,
+                flag_calibrate_min_gas_price: false,
+                flag_mine_internally: false,
+                arg_mining_threads: 0usize,
                 arg_local_max_gas_price: 100000000000u64,
 
                 // -- Stratum Options
@@ -935,11 +1096,16 @@ mod tests {
                 arg_stratum_interface: "127.0.0.2".to_owned(),
                 arg_stratum_port: 8089u16,
                 arg_stratum_secret: Some("secret".into()),
+                arg_stratum_share_difficulty: Some(1000u64),
+                arg_stratum_vardiff_min: Some(100u64),
+                arg_stratum_vardiff_max: Some(10000u64),
+                arg_stratum_share_time: 15u64,
 
                 // -- Database Options
                 flag_no_persistent_txqueue: true,
                 arg_pruning: "auto".into(),
                 arg_pruning_history: 64u64,
+                arg_pruning_checkpoints: None,
                 arg_pruning_memory: 500usize,
                 //                arg_cache_size_db: 64u32,
                 arg_cache_size_blocks: 8u32,
@@ -947,7 +1113,12 @@ mod tests {
                 arg_cache_size_state: 25u32,
                 arg_cache_size: Some(128),
                 flag_disable_wal: true,
+                flag_db_column_families: false,
                 arg_db_compaction: "ssd".into(),
+                arg_db_durability: "fast".into(),
+                arg_db_backend: "rocksdb".into(),
+                arg_freezer_threshold: None,
+                flag_log_index: false,
                 arg_fat_db: "auto".into(),
                 flag_scale_verifiers: true,
                 arg_num_verifiers: Some(6),
@@ -1067,6 +1238,11 @@ mod tests {
                     gas_cap: None,
                     tx_queue_mem_limit: None,
                     tx_queue_strategy: None,
+                    tx_selection_order: None,
+                    tx_priority_local: None,
+                    tx_priority_senders: None,
+                    tx_sender_energy_share: None,
+                    tx_bridge_energy_reserve: None,
                     tx_queue_ban_count: None,
                     tx_queue_ban_time: None,
                     tx_gas_limit: None,
@@ -1076,6 +1252,9 @@ mod tests {
                     infinite_pending_block: None,
                     blk_price_window: None,
                     dynamic_gas_price: None,
+                    calibrate_min_gas_price: None,
+                    mine_internally: None,
+                    mining_threads: None,
                     max_blk_traverse: None,
                     local_max_gas_price: None,
                 }),
@@ -1083,14 +1262,20 @@ mod tests {
                     no_persistent_txqueue: None,
                     pruning: Some("fast".into()),
                     pruning_history: Some(64),
+                    pruning_checkpoints: None,
                     pruning_memory: None,
                     disable_wal: None,
+                    db_column_families: None,
                     cache_size: None,
                     //                    cache_size_db: Some(256),
                     cache_size_blocks: Some(16),
                     cache_size_queue: Some(100),
                     cache_size_state: Some(25),
                     db_compaction: Some("ssd".into()),
+                    db_durability: None,
+                    db_backend: None,
+                    freezer_threshold: None,
+                    log_index: None,
                     fat_db: Some("off".into()),
                     scale_verifiers: Some(false),
                     num_verifiers: None,
@@ -19,6 +19,7 @@
  *
  ******************************************************************************/
 
+use std::cmp;
 use std::time::Duration;
 use cli::{Args, ArgsError};
 use blake2b::blake2b;
@@ -26,7 +27,11 @@ use aion_types::{U256, H256, Address};
 use bytes::Bytes;
 use sync::p2p::NetworkConfig;
 use acore::client::{VMType};
-use acore::miner::{MinerOptions, Banning, StratumOptions};
+use acore::miner::{
+    MinerOptions, Banning, GasPriceCalibratorOptions, InternalMinerOptions, ShareDifficulty,
+    StratumOptions, TransactionSelection,
+};
+use acore::blockchain::MAX_ROLLBACK;
 use acore::verification::queue::VerifierSettings;
 
 use pb::WalletApiConfiguration;
@@ -34,21 +39,31 @@ use rpc::{IpcConfiguration, HttpConfiguration, WsConfiguration,};
 use aion_rpc::dispatch::DynamicGasPrice;
 use cache::CacheConfig;
 use helpers::{to_block_id, to_u256, to_pending_set, aion_ipc_path,parse_log_target,
-to_addresses, to_address, to_queue_strategy,validate_log_level};
+to_addresses, to_address, to_queue_strategy, to_selection_ordering, validate_log_level};
 use dir::helpers::{replace_home, replace_home_and_local, absolute};
 use params::{ResealPolicy, AccountsConfig, MinerExtras, SpecType};
 use logger::{LogConfig};
 use dir::{self, Directories, default_local_path, default_data_path};
 use run::RunCmd;
-use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, RevertBlockchain, DataFormat};
+use db::{DbCmd, DbOptions};
+use blockchain::{
+    BlockchainCmd, ImportBlockchain, ExportBlockchain, ExportState, KillBlockchain,
+    RevertBlockchain, DataFormat,
+};
 use account::{AccountCmd, NewAccount, ListAccounts, ImportAccounts, ImportAccount, ExportAccount};
 
+/// Seconds between two calibrations of the minimal gas price.
+const GAS_PRICE_RECALIBRATION_PERIOD_SECS: u64 = 60;
+/// Seconds between two share difficulty retargets of a stratum worker.
+const STRATUM_RETARGET_SECS: u64 = 60;
+
 #[derive(Debug, PartialEq)]
 pub enum Cmd {
     Run(RunCmd),
     Version,
     Account(AccountCmd),
     Blockchain(BlockchainCmd),
+    Db(DbCmd),
 }
 
 pub struct Execute {
@@ -99,6 +114,42 @@ impl Configuration {
                 dirs: dirs,
                 pruning: pruning,
             }))
+        } else if self.args.cmd_db {
+            let options = DbOptions {
+                spec: spec,
+                cache_config: cache_config,
+                dirs: dirs,
+                pruning: pruning,
+                pruning_history: pruning_history,
+                pruning_memory: pruning_memory,
+                compaction: compaction,
+                wal: wal,
+                backend: self.args.arg_db_backend.parse()?,
+            };
+            let db_cmd = if self.args.cmd_db_stats {
+                DbCmd::Stats(options)
+            } else if self.args.cmd_db_compact {
+                DbCmd::Compact(options)
+            } else if self.args.cmd_db_verify {
+                let from = self.args.arg_db_verify_from.parse().map_err(|_| {
+                    format!("Invalid block number: {}", self.args.arg_db_verify_from)
+                })?;
+                DbCmd::Verify(options, from)
+            } else if self.args.cmd_db_migrate {
+                DbCmd::Migrate(options)
+            } else if self.args.cmd_db_convert {
+                let to = self.args.arg_db_convert_to.parse()?;
+                let at = match self.args.arg_db_convert_at {
+                    Some(ref at) => {
+                        Some(at.parse().map_err(|_| format!("Invalid block number: {}", at))?)
+                    }
+                    None => None,
+                };
+                DbCmd::Convert(options, to, at)
+            } else {
+                unreachable!();
+            };
+            Cmd::Db(db_cmd)
         } else if self.args.cmd_account {
             let account_cmd = if self.args.cmd_account_new {
                 let new_acc = NewAccount {
@@ -185,6 +236,21 @@ impl Configuration {
                 to_block: to_block_id(&self.args.arg_export_blocks_to)?,
             };
             Cmd::Blockchain(BlockchainCmd::Export(export_cmd))
+        } else if self.args.cmd_state && self.args.cmd_state_export {
+            let export_cmd = ExportState {
+                spec: spec,
+                cache_config: cache_config,
+                dirs: dirs,
+                file_path: self.args.arg_state_export_file.clone(),
+                pruning: pruning,
+                pruning_history: pruning_history,
+                pruning_memory: pruning_memory,
+                compaction: compaction,
+                wal: wal,
+                fat_db: fat_db,
+                at: to_block_id(&self.args.arg_state_export_at)?,
+            };
+            Cmd::Blockchain(BlockchainCmd::ExportState(export_cmd))
         } else if self.args.cmd_revert {
             let revert_cmd = RevertBlockchain {
                 spec: spec,
@@ -219,6 +285,7 @@ impl Configuration {
                 spec: spec,
                 pruning: pruning,
                 pruning_history: pruning_history,
+                pruning_checkpoints: self.args.arg_pruning_checkpoints,
                 pruning_memory: pruning_memory,
                 daemon: daemon,
                 logger_config: logger_config.clone(),
@@ -232,9 +299,15 @@ impl Configuration {
                 acc_conf: self.accounts_config()?,
                 miner_extras: self.miner_extras()?,
                 stratum: self.stratum_options()?,
+                internal_miner: self.internal_miner_options(),
                 fat_db: fat_db,
                 compaction: compaction,
                 wal: wal,
+                db_column_families: self.args.flag_db_column_families,
+                db_durability: self.args.arg_db_durability.parse()?,
+                db_backend: self.args.arg_db_backend.parse()?,
+                freezer_threshold: self.freezer_threshold()?,
+                log_index: self.args.flag_log_index,
                 vm_type: vm_type,
                 check_seal: !self.args.flag_no_seal_check,
                 verifier_settings: verifier_settings,
@@ -279,7 +352,6 @@ impl Configuration {
             Some(size) => CacheConfig::new_with_total_cache_size(size),
             None => {
                 CacheConfig::new(
-                    //                    self.args.arg_cache_size_db,
                     self.args.arg_cache_size_blocks,
                     self.args.arg_cache_size_queue,
                     self.args.arg_cache_size_state,
@@ -349,25 +421,74 @@ impl Configuration {
                 .arg_stratum_secret
                 .as_ref()
                 .map(|s| s.parse::<H256>().unwrap_or_else(|_| blake2b(s))),
+            share_difficulty: self.stratum_share_difficulty()?,
         })
     }
 
+    fn freezer_threshold(&self) -> Result<Option<u64>, String> {
+        let threshold = match self.args.arg_freezer_threshold {
+            Some(threshold) => threshold,
+            None => return Ok(None),
+        };
+        // frozen blocks can no longer be retracted, so keep every block a reorg or a database
+        // repair may still reach out of the freezer
+        let minimum = cmp::max(MAX_ROLLBACK, self.args.arg_pruning_history);
+        if threshold < minimum {
+            return Err(format!(
+                "Freezer threshold {} is below the minimum of {} blocks",
+                threshold, minimum
+            ));
+        }
+        Ok(Some(threshold))
+    }
+
+    fn stratum_share_difficulty(&self) -> Result<Option<ShareDifficulty>, String> {
+        let initial = match self.args.arg_stratum_share_difficulty {
+            Some(initial) => initial,
+            None => return Ok(None),
+        };
+        let minimum = self.args.arg_stratum_vardiff_min.unwrap_or(initial);
+        let maximum = self.args.arg_stratum_vardiff_max.unwrap_or(initial);
+        if initial == 0 || minimum == 0 || minimum > maximum {
+            return Err(format!(
+                "Invalid stratum share difficulty {} (min {}, max {})",
+                initial, minimum, maximum
+            ));
+        }
+        Ok(Some(ShareDifficulty {
+            initial: initial.into(),
+            minimum: minimum.into(),
+            maximum: maximum.into(),
+            target_share_time: Duration::from_secs(cmp::max(self.args.arg_stratum_share_time, 1)),
+            retarget_time: Duration::from_secs(STRATUM_RETARGET_SECS),
+        }))
+    }
+
+    fn internal_miner_options(&self) -> InternalMinerOptions {
+        InternalMinerOptions {
+            enable: self.args.flag_mine_internally,
+            threads: self.args.arg_mining_threads,
+        }
+    }
+
     fn miner_options(&self) -> Result<MinerOptions, String> {
         let reseal = self.args.arg_reseal_on_txs.parse::<ResealPolicy>()?;
 
         let options = MinerOptions {
             staker_private_key: self.args.arg_staker_private_key.clone(),
-            force_sealing: self.args.flag_force_sealing,
+            force_sealing: self.args.flag_force_sealing || self.args.flag_mine_internally,
             reseal_on_external_tx: reseal.external,
             reseal_on_own_tx: reseal.own,
             tx_gas_limit: match self.args.arg_tx_gas_limit {
                 Some(ref d) => to_u256(d)?,
                 None => U256::max_value(),
             },
-            tx_queue_memory_limit: if self.args.arg_tx_queue_mem_limit > 0 {
-                Some(self.args.arg_tx_queue_mem_limit as usize * 1024 * 1024)
-            } else {
-                None
+            tx_queue_memory_limit: match self.cache_config().tx_queue() {
+                Some(size) => Some(size as usize * 1024 * 1024),
+                None if self.args.arg_tx_queue_mem_limit > 0 => {
+                    Some(self.args.arg_tx_queue_mem_limit as usize * 1024 * 1024)
+                }
+                None => None,
             },
             tx_queue_strategy: to_queue_strategy(&self.args.arg_tx_queue_strategy)?,
             pending_set: to_pending_set(&self.args.arg_relay_set)?,
@@ -390,11 +511,35 @@ impl Configuration {
             minimal_gas_price: U256::from(self.args.arg_min_gas_price),
             maximal_gas_price: U256::from(self.args.arg_max_gas_price),
             local_max_gas_price: U256::from(self.args.arg_local_max_gas_price),
+            gas_price_calibrator: self.gas_price_calibrator()?,
+            tx_selection: self.tx_selection()?,
         };
 
         Ok(options)
     }
 
+    fn tx_selection(&self) -> Result<TransactionSelection, String> {
+        let percent = |p: u64| {
+            if p > 100 {
+                Err(format!("Invalid energy percentage: {}", p))
+            } else {
+                Ok(p)
+            }
+        };
+        Ok(TransactionSelection {
+            ordering: to_selection_ordering(&self.args.arg_tx_selection_order)?,
+            prioritize_local: self.args.flag_tx_priority_local,
+            priority_senders: to_addresses(&self.args.arg_tx_priority_senders)?
+                .into_iter()
+                .collect(),
+            max_sender_energy_percent: match self.args.arg_tx_sender_energy_share {
+                Some(p) => Some(percent(p)?),
+                None => None,
+            },
+            bridge_energy_reserve_percent: percent(self.args.arg_tx_bridge_energy_reserve)?,
+        })
+    }
+
     fn dynamic_gas_price(&self) -> Result<Option<DynamicGasPrice>, String> {
         if !self.args.flag_dynamic_gas_price {
             return Ok(None);
@@ -407,6 +552,25 @@ impl Configuration {
         Ok(Some(dynamic))
     }
 
+    fn gas_price_calibrator(&self) -> Result<Option<GasPriceCalibratorOptions>, String> {
+        if !self.args.flag_calibrate_min_gas_price {
+            return Ok(None);
+        }
+        if self.args.arg_local_max_gas_price < self.args.arg_min_gas_price {
+            return Err(format!(
+                "Invalid energy price bounds: --local-max-gas-price {} is lower than \
+                 --min-gas-price {}",
+                self.args.arg_local_max_gas_price, self.args.arg_min_gas_price
+            ));
+        }
+        Ok(Some(GasPriceCalibratorOptions {
+            blk_price_window: self.args.arg_blk_price_window,
+            max_blk_traverse: self.args.arg_max_blk_traverse,
+            gas_price_percentile: self.args.arg_gas_price_percentile,
+            recalibration_period: Duration::from_secs(GAS_PRICE_RECALIBRATION_PERIOD_SECS),
+        }))
+    }
+
     fn extra_data(&self) -> Result<Bytes, String> {
         match self.args.arg_extra_data.as_ref() {
             Some(x) if x.len() <= 32 => Ok(x.as_bytes().to_owned()),
@@ -654,13 +818,14 @@ impl Configuration {
 #[cfg(test)]
 mod tests {
     use acore::client::{BlockId};
-    use acore::miner::MinerOptions;
+    use acore::miner::{MinerOptions, SelectionOrdering};
     use acore::transaction::transaction_queue::PrioritizationStrategy;
     use account::{AccountCmd, NewAccount, ImportAccounts, ListAccounts};
-    use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, DataFormat};
+    use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, ExportState, DataFormat};
     use cli::Args;
     use dir::Directories;
     use helpers::{default_network_config};
+    use journaldb::Algorithm;
     use params::SpecType;
     use run::RunCmd;
 
@@ -752,6 +917,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_command_db_verify() {
+        let args = vec!["aion", "db", "verify", "--from", "100"];
+        let conf = parse(&args);
+        assert_eq!(
+            conf.into_command().unwrap().cmd,
+            Cmd::Db(DbCmd::Verify(
+                DbOptions {
+                    spec: Default::default(),
+                    cache_config: Default::default(),
+                    dirs: Default::default(),
+                    pruning: Default::default(),
+                    pruning_history: 64,
+                    pruning_memory: 32,
+                    compaction: Default::default(),
+                    wal: true,
+                    backend: Default::default(),
+                },
+                100,
+            ))
+        );
+    }
+
+    #[test]
+    fn test_command_db_convert() {
+        let args = vec!["aion", "db", "convert", "--to", "archive", "--at", "1000"];
+        let conf = parse(&args);
+        assert_eq!(
+            conf.into_command().unwrap().cmd,
+            Cmd::Db(DbCmd::Convert(
+                DbOptions {
+                    spec: Default::default(),
+                    cache_config: Default::default(),
+                    dirs: Default::default(),
+                    pruning: Default::default(),
+                    pruning_history: 64,
+                    pruning_memory: 32,
+                    compaction: Default::default(),
+                    wal: true,
+                    backend: Default::default(),
+                },
+                Algorithm::Archive,
+                Some(1000),
+            ))
+        );
+    }
+
     #[test]
     fn test_command_blockchain_export() {
         let args = vec!["aion", "export", "blockchain.json"];
@@ -800,6 +1012,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_command_state_export() {
+        let args = vec!["aion", "state", "export", "--at", "100", "state.json"];
+        let conf = parse(&args);
+        assert_eq!(
+            conf.into_command().unwrap().cmd,
+            Cmd::Blockchain(BlockchainCmd::ExportState(ExportState {
+                spec: Default::default(),
+                cache_config: Default::default(),
+                dirs: Default::default(),
+                file_path: Some("state.json".into()),
+                pruning: Default::default(),
+                pruning_history: 64,
+                pruning_memory: 32,
+                compaction: Default::default(),
+                wal: true,
+                fat_db: Default::default(),
+                at: BlockId::Number(100),
+            }))
+        );
+    }
+
     #[test]
     fn test_run_cmd() {
         let args = vec!["aion"];
@@ -810,6 +1044,7 @@ mod tests {
             spec: Default::default(),
             pruning: Default::default(),
             pruning_history: 64,
+            pruning_checkpoints: None,
             pruning_memory: 32,
             daemon: None,
             logger_config: Default::default(),
@@ -824,9 +1059,15 @@ mod tests {
             miner_extras: Default::default(),
             compaction: Default::default(),
             wal: true,
+            db_column_families: false,
+            db_durability: Default::default(),
+            db_backend: Default::default(),
+            freezer_threshold: None,
+            log_index: false,
             vm_type: Default::default(),
             fat_db: Default::default(),
             stratum: Default::default(),
+            internal_miner: Default::default(),
             check_seal: true,
             verifier_settings: Default::default(),
             no_persistent_txqueue: false,
@@ -855,6 +1096,146 @@ mod tests {
         assert_eq!(conf3.miner_options().unwrap(), mining_options);
     }
 
+    #[test]
+    fn should_parse_tx_selection() {
+        // when
+        let conf0 = parse(&["aion"]);
+        let conf1 = parse(&[
+            "aion",
+            "--tx-selection-order",
+            "price_per_byte",
+            "--tx-priority-local",
+            "--tx-priority-senders",
+            "0xa0deadbeefcafe000000000000000000000000000000000000000000000000ff",
+            "--tx-sender-energy-share",
+            "25",
+            "--tx-bridge-energy-reserve",
+            "10",
+        ]);
+        let conf2 = parse(&["aion", "--tx-bridge-energy-reserve", "101"]);
+        let conf3 = parse(&["aion", "--tx-selection-order", "random"]);
+
+        // then
+        assert_eq!(
+            conf0.miner_options().unwrap().tx_selection,
+            TransactionSelection::default()
+        );
+        let mut priority_senders = ::std::collections::HashSet::new();
+        priority_senders.insert(
+            "a0deadbeefcafe000000000000000000000000000000000000000000000000ff"
+                .parse()
+                .unwrap(),
+        );
+        assert_eq!(
+            conf1.miner_options().unwrap().tx_selection,
+            TransactionSelection {
+                ordering: SelectionOrdering::EnergyPricePerByte,
+                prioritize_local: true,
+                priority_senders: priority_senders,
+                max_sender_energy_percent: Some(25),
+                bridge_energy_reserve_percent: 10,
+            }
+        );
+        assert!(conf2.miner_options().is_err());
+        assert!(conf3.miner_options().is_err());
+    }
+
+    #[test]
+    fn should_parse_stratum_share_difficulty() {
+        // when
+        let conf0 = parse(&["aion"]);
+        let conf1 = parse(&["aion", "--stratum-share-difficulty", "1000"]);
+        let conf2 = parse(&[
+            "aion",
+            "--stratum-share-difficulty",
+            "1000",
+            "--stratum-vardiff-min",
+            "100",
+            "--stratum-vardiff-max",
+            "5000",
+            "--stratum-share-time",
+            "5",
+        ]);
+        let conf3 = parse(&[
+            "aion",
+            "--stratum-share-difficulty",
+            "1000",
+            "--stratum-vardiff-min",
+            "2000",
+        ]);
+
+        // then
+        assert_eq!(conf0.stratum_options().unwrap().share_difficulty, None);
+        assert_eq!(
+            conf1.stratum_options().unwrap().share_difficulty,
+            Some(ShareDifficulty {
+                initial: U256::from(1000),
+                minimum: U256::from(1000),
+                maximum: U256::from(1000),
+                target_share_time: Duration::from_secs(10),
+                retarget_time: Duration::from_secs(STRATUM_RETARGET_SECS),
+            })
+        );
+        assert_eq!(
+            conf2.stratum_options().unwrap().share_difficulty,
+            Some(ShareDifficulty {
+                initial: U256::from(1000),
+                minimum: U256::from(100),
+                maximum: U256::from(5000),
+                target_share_time: Duration::from_secs(5),
+                retarget_time: Duration::from_secs(STRATUM_RETARGET_SECS),
+            })
+        );
+        assert!(conf3.stratum_options().is_err());
+    }
+
+    #[test]
+    fn should_reject_low_freezer_threshold() {
+        // when
+        let conf0 = parse(&["aion"]);
+        let conf1 = parse(&["aion", "--freezer-threshold", "1024"]);
+        let conf2 = parse(&["aion", "--freezer-threshold", "1023"]);
+        let conf3 = parse(&[
+            "aion",
+            "--freezer-threshold",
+            "2000",
+            "--pruning-history",
+            "4096",
+        ]);
+
+        // then
+        assert_eq!(conf0.freezer_threshold(), Ok(None));
+        assert_eq!(conf1.freezer_threshold(), Ok(Some(1024)));
+        assert!(conf2.freezer_threshold().is_err());
+        assert!(conf3.freezer_threshold().is_err());
+    }
+
+    #[test]
+    fn should_reject_crossed_calibration_bounds() {
+        // when
+        let conf0 = parse(&["aion", "--calibrate-min-gas-price"]);
+        let conf1 = parse(&[
+            "aion",
+            "--calibrate-min-gas-price",
+            "--min-gas-price",
+            "20000000000",
+            "--local-max-gas-price",
+            "10000000000",
+        ]);
+        let conf2 = parse(&[
+            "aion",
+            "--min-gas-price",
+            "20000000000",
+            "--local-max-gas-price",
+            "10000000000",
+        ]);
+
+        // then
+        assert!(conf0.gas_price_calibrator().unwrap().is_some());
+        assert!(conf1.gas_price_calibrator().is_err());
+        assert_eq!(conf2.gas_price_calibrator(), Ok(None));
+    }
+
     #[test]
     fn should_parse_rpc_hosts() {
         // given
@@ -0,0 +1,421 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Offline maintenance of the client databases.
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+use acore::blockchain::{frozen_blocks, revert, verify, BlockChain, BlockProvider, FREEZER_DIR};
+use acore::client::{
+    BlockChainClient, BlockChainConfig, BlockImportError, ClientConfig, DatabaseBackend,
+    DatabaseCompactionProfile, VMType,
+};
+use acore::conversion::{self, Phase};
+use acore::db::{COL_EXTRA, COL_STATE, DB_NAMES};
+use acore::error::ImportError;
+use acore::header::BlockNumber;
+use acore::migrations;
+use acore::miner::Miner;
+use acore::service::{open_database, ClientService};
+use acore::spec::Spec;
+use blake2b::BLAKE2B_NULL_RLP;
+use blockchain::MillisecondDuration;
+use cache::CacheConfig;
+use dir::Directories;
+use helpers::to_client_config;
+use journaldb::{self, Algorithm};
+use kvdb::KeyValueDB;
+use params::{SpecType, Pruning};
+use user_defaults::UserDefaults;
+use fdlimit;
+
+#[derive(Debug, PartialEq)]
+pub enum DbCmd {
+    Stats(DbOptions),
+    Compact(DbOptions),
+    Verify(DbOptions, BlockNumber),
+    Migrate(DbOptions),
+    Convert(DbOptions, Algorithm, Option<BlockNumber>),
+}
+
+/// Options needed to open the databases of a chain.
+#[derive(Debug, PartialEq)]
+pub struct DbOptions {
+    pub spec: SpecType,
+    pub cache_config: CacheConfig,
+    pub dirs: Directories,
+    pub pruning: Pruning,
+    pub pruning_history: u64,
+    pub pruning_memory: usize,
+    pub compaction: DatabaseCompactionProfile,
+    pub wal: bool,
+    pub backend: DatabaseBackend,
+}
+
+pub fn execute(cmd: DbCmd) -> Result<String, String> {
+    match cmd {
+        DbCmd::Stats(options) => execute_stats(options),
+        DbCmd::Compact(options) => execute_compact(options),
+        DbCmd::Verify(options, from) => execute_verify(options, from),
+        DbCmd::Migrate(options) => execute_migrate(options),
+        DbCmd::Convert(options, to, at) => execute_convert(options, to, at),
+    }
+}
+
+/// Client config for the databases of the given pruning method. Seals are not checked, blocks
+/// are only ever imported from a local database here.
+fn client_config(options: &DbOptions, spec: &Spec, algorithm: Algorithm) -> ClientConfig {
+    let mut config = to_client_config(
+        &options.cache_config,
+        spec.name.to_lowercase(),
+        false,
+        options.compaction.clone(),
+        options.wal,
+        VMType::default(),
+        algorithm,
+        options.pruning_history,
+        options.pruning_memory,
+        false,
+    );
+    config.db_backend = options.backend;
+    config
+}
+
+/// Open existing databases without starting the client.
+fn open_existing(config: &ClientConfig, client_path: &Path) -> Result<Arc<KeyValueDB>, String> {
+    if !client_path.exists() {
+        return Err(format!("No database found at {}", client_path.display()));
+    }
+
+    fdlimit::raise_fd_limit();
+
+    open_database(config, client_path).map_err(|e| format!("Database error: {:?}", e))
+}
+
+/// Open the databases of the selected pruning method without starting the client.
+fn open(options: DbOptions) -> Result<(Arc<KeyValueDB>, Algorithm), String> {
+    let spec = options.spec.spec(&options.dirs.cache)?;
+    let genesis_hash = spec.genesis_header().hash();
+    let db_dirs = options
+        .dirs
+        .database(genesis_hash, None, spec.data_dir.clone());
+    let user_defaults = UserDefaults::load(&db_dirs.user_defaults_path())?;
+    let algorithm = options.pruning.to_algorithm(&user_defaults);
+    let config = client_config(&options, &spec, algorithm);
+    let db = open_existing(&config, &db_dirs.client_path(algorithm))?;
+    Ok((db, algorithm))
+}
+
+fn execute_stats(options: DbOptions) -> Result<String, String> {
+    let (db, _) = open(options)?;
+    let mut out = format!(
+        "Schema version: {}\nFrozen blocks: {}\n{:<14} {:>12} {:>16} {:>16} {:>16}\n",
+        migrations::schema_version(&*db)?,
+        frozen_blocks(&*db),
+        "Database",
+        "Keys",
+        "Key bytes",
+        "Value bytes",
+        "Disk bytes"
+    );
+    for &name in DB_NAMES.iter() {
+        let (mut keys, mut key_bytes, mut value_bytes) = (0u64, 0u64, 0u64);
+        for (key, value) in db.iter(name) {
+            keys += 1;
+            key_bytes += key.len() as u64;
+            value_bytes += value.len() as u64;
+        }
+        let disk_size = db
+            .disk_size(name)
+            .map_or_else(|| "-".to_owned(), |size| size.to_string());
+        out.push_str(&format!(
+            "{:<14} {:>12} {:>16} {:>16} {:>16}\n",
+            name, keys, key_bytes, value_bytes, disk_size
+        ));
+    }
+    Ok(out)
+}
+
+fn execute_compact(options: DbOptions) -> Result<String, String> {
+    let (db, _) = open(options)?;
+    let timer = Instant::now();
+    for &name in DB_NAMES.iter() {
+        info!(target: "db", "Compacting {} ...", name);
+        db.compact(name)
+            .map_err(|e| format!("Compacting {} failed: {}", name, e))?;
+    }
+    Ok(format!(
+        "Compacted {} databases in {} ms",
+        DB_NAMES.len(),
+        timer.elapsed().as_milliseconds()
+    ))
+}
+
+fn execute_verify(options: DbOptions, from: BlockNumber) -> Result<String, String> {
+    let (db, algorithm) = open(options)?;
+    let timer = Instant::now();
+    let mut last = from;
+    let missing = verify(&db, algorithm, from, |number| {
+        if number % 10000 == 0 {
+            info!(target: "db", "Verified up to #{}", number);
+        }
+        last = number;
+    })?;
+    let ms = timer.elapsed().as_milliseconds();
+    if missing.is_empty() {
+        return Ok(format!(
+            "Verified blocks #{}..#{} in {} ms, no problems found",
+            from, last, ms
+        ));
+    }
+    let mut report = format!(
+        "Verified blocks #{}..#{} in {} ms, {} problems found:\n",
+        from,
+        last,
+        ms,
+        missing.len()
+    );
+    for (number, part) in missing {
+        report.push_str(&format!("#{}: missing {}\n", number, part));
+    }
+    Err(report)
+}
+
+fn execute_migrate(options: DbOptions) -> Result<String, String> {
+    let (db, _) = open(options)?;
+    let applied = migrations::migrate(&*db)?;
+    if applied.is_empty() {
+        return Ok(format!(
+            "Database already at schema version {}",
+            migrations::schema_version(&*db)?
+        ));
+    }
+    let mut out = String::new();
+    for (version, description) in applied {
+        out.push_str(&format!("Migrated to schema version {}: {}\n", version, description));
+    }
+    Ok(out)
+}
+
+/// Converts the databases of the selected pruning method into the databases of pruning method
+/// `to`. The chain is copied with the state of block `at`: for a pruned target `at` becomes the
+/// best block, for an archive target the blocks above `at` are re-executed to rebuild their
+/// state. An interrupted conversion resumes at the phase it reached.
+fn execute_convert(
+    options: DbOptions,
+    to: Algorithm,
+    at: Option<BlockNumber>,
+) -> Result<String, String>
+{
+    let timer = Instant::now();
+    let spec = options.spec.spec(&options.dirs.cache)?;
+    let genesis_hash = spec.genesis_header().hash();
+    let db_dirs = options
+        .dirs
+        .database(genesis_hash, None, spec.data_dir.clone());
+    let user_defaults_path = db_dirs.user_defaults_path();
+    let mut user_defaults = UserDefaults::load(&user_defaults_path)?;
+    let from = options.pruning.to_algorithm(&user_defaults);
+    if from == to {
+        return Err(format!("The database already uses {} pruning", to));
+    }
+    if to != Algorithm::Archive && to != Algorithm::OverlayRecent {
+        return Err(format!("Conversion to {} pruning is not supported", to));
+    }
+
+    let source_path = db_dirs.client_path(from);
+    let source = open_existing(&client_config(&options, &spec, from), &source_path)?;
+    let source_chain_config = BlockChainConfig {
+        freezer_path: Some(source_path.join(FREEZER_DIR)),
+        ..Default::default()
+    };
+    let source_chain = BlockChain::new(source_chain_config, &spec.genesis_block(), source.clone());
+    let source_state = journaldb::new(source.clone(), from, COL_STATE);
+    let best = source_chain.best_block_number();
+    let earliest_state = source_state.earliest_era().unwrap_or(0);
+
+    let target_path = db_dirs.client_path(to);
+    let target_config = client_config(&options, &spec, to);
+    let (at, phase) = {
+        let target = open_database(&target_config, &target_path)
+            .map_err(|e| format!("Database error: {:?}", e))?;
+        let (at, mut phase) = match conversion::progress(&*target)? {
+            Some((resumed, phase)) => {
+                if at.map_or(false, |at| at != resumed) {
+                    return Err(format!(
+                        "An interrupted conversion at #{} is in {}, resume it with --at={}",
+                        resumed,
+                        target_path.display(),
+                        resumed
+                    ));
+                }
+                info!(target: "convert", "Resuming the conversion at #{}", resumed);
+                (resumed, phase)
+            }
+            None => {
+                if target
+                    .get(COL_EXTRA, b"best")
+                    .map_err(|e| format!("{}", e))?
+                    .is_some()
+                {
+                    return Err(format!(
+                        "A database already exists in {}, remove it first with `aion db kill \
+                         --pruning={}`",
+                        target_path.display(),
+                        to
+                    ));
+                }
+                let at = match at {
+                    Some(at) => at,
+                    None if to == Algorithm::Archive => earliest_state,
+                    None => best,
+                };
+                (at, Phase::Chain)
+            }
+        };
+        if at > best {
+            return Err(format!("Block #{} is above the best block #{}", at, best));
+        }
+        let hash = source_chain
+            .block_hash(at)
+            .ok_or_else(|| format!("Block #{} not found", at))?;
+        let state_root = *source_chain
+            .block_header(&hash)
+            .ok_or_else(|| format!("Block #{} not found", at))?
+            .state_root();
+        let has_state = state_root == BLAKE2B_NULL_RLP || source_state.contains(&state_root);
+        if !has_state && at != 0 {
+            return Err(format!(
+                "The state of block #{} is pruned, the earliest available state is at #{}",
+                at, earliest_state
+            ));
+        }
+
+        if phase == Phase::Chain {
+            conversion::set_progress(&*target, at, Phase::Chain)?;
+            info!(target: "convert", "Copying the chain into {}", target_path.display());
+            copy_freezer(&source_path.join(FREEZER_DIR), &target_path.join(FREEZER_DIR))?;
+            conversion::copy_chain(&*source, &*target, |name, keys| {
+                info!(target: "convert", "Copied {} keys of {}", keys, name)
+            })?;
+            revert(&*target, at, best)?;
+            conversion::set_progress(&*target, at, Phase::State)?;
+            phase = Phase::State;
+        }
+
+        if phase == Phase::State {
+            if has_state {
+                info!(target: "convert", "Copying the state of #{}", at);
+                let nodes = conversion::copy_state(
+                    source_state.as_hashstore(),
+                    &*target,
+                    &state_root,
+                    |nodes| info!(target: "convert", "Copied {} state nodes", nodes),
+                )?;
+                conversion::journal_state(&target, to, at, &hash)?;
+                info!(target: "convert", "Copied {} state nodes of #{}", nodes, at);
+            } else {
+                info!(target: "convert", "Building the genesis state");
+                conversion::write_genesis_state(&spec, &target, to)?;
+            }
+            if to == Algorithm::Archive {
+                conversion::set_progress(&*target, at, Phase::Execute)?;
+                phase = Phase::Execute;
+            } else {
+                conversion::clear_progress(&*target)?;
+            }
+        }
+        target.flush_synced().map_err(|e| format!("{}", e))?;
+        (at, phase)
+    };
+
+    if phase == Phase::Execute {
+        let service = ClientService::start(
+            target_config,
+            &spec,
+            &target_path,
+            &options.dirs.ipc_path(),
+            Arc::new(Miner::with_spec(&spec)),
+        )
+        .map_err(|e| format!("Client service error: {:?}", e))?;
+        let client = service.client();
+        let start = client.chain_info().best_block_number + 1;
+        for number in start..=best {
+            let block = source_chain
+                .block_hash(number)
+                .and_then(|hash| source_chain.block(&hash))
+                .ok_or_else(|| format!("Block #{} not found", number))?;
+            while client.queue_info().is_full() {
+                sleep(Duration::from_millis(100));
+            }
+            match client.import_block(block.into_inner()) {
+                Err(BlockImportError::Import(ImportError::AlreadyInChain)) => {}
+                Err(e) => return Err(format!("Cannot import block #{}: {:?}", number, e)),
+                Ok(_) => {}
+            }
+            if number % 1000 == 0 {
+                info!(target: "convert", "Re-executing blocks, #{} of #{}", number, best);
+            }
+        }
+        client.flush_queue();
+        let executed = client.chain_info().best_block_number;
+        if executed != best {
+            return Err(format!(
+                "Re-execution stopped at #{} of #{}, the conversion can be resumed",
+                executed, best
+            ));
+        }
+        conversion::clear_progress(&*service.db())?;
+    }
+
+    user_defaults.pruning = to;
+    user_defaults.save(&user_defaults_path)?;
+    Ok(format!(
+        "Converted the {} database to {} at #{} in {} ms, the {} database can be removed with \
+         `aion db kill --pruning={}`",
+        from,
+        to,
+        at,
+        timer.elapsed().as_milliseconds(),
+        from,
+        from
+    ))
+}
+
+/// Copies the files of the freezer at `from`, if any, to `to`.
+fn copy_freezer(from: &Path, to: &Path) -> Result<(), String> {
+    if !from.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(to).map_err(|e| format!("Cannot create {}: {}", to.display(), e))?;
+    let entries = fs::read_dir(from).map_err(|e| format!("Cannot read {}: {}", from.display(), e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| format!("{}", e))?.path();
+        if let Some(name) = path.file_name() {
+            fs::copy(&path, to.join(name))
+                .map_err(|e| format!("Cannot copy {}: {}", path.display(), e))?;
+        }
+    }
+    Ok(())
+}
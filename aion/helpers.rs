@@ -25,7 +25,7 @@ use std::fs::File;
 use aion_types::{U256, clean_0x, Address};
 use journaldb::Algorithm;
 use acore::client::{BlockId, VMType, DatabaseCompactionProfile, ClientConfig, VerifierType};
-use acore::miner::PendingSet;
+use acore::miner::{PendingSet, SelectionOrdering};
 use acore::transaction::transaction_queue::PrioritizationStrategy;
 use cache::CacheConfig;
 use dir::helpers::replace_home;
@@ -70,6 +70,14 @@ pub fn to_queue_strategy(s: &str) -> Result<PrioritizationStrategy, String> {
     }
 }
 
+pub fn to_selection_ordering(s: &str) -> Result<SelectionOrdering, String> {
+    match s {
+        "queue" => Ok(SelectionOrdering::Queue),
+        "price_per_byte" => Ok(SelectionOrdering::EnergyPricePerByte),
+        other => Err(format!("Invalid transaction selection order: {}", other)),
+    }
+}
+
 pub fn to_address(s: Option<String>) -> Result<Address, String> {
     match s {
         Some(ref a) => {
@@ -141,7 +149,7 @@ pub fn to_client_config(
     // in bytes
     client_config.blockchain.pref_cache_size = cache_config.blockchain() as usize * 3 / 4 * mb;
     // db cache size, in megabytes
-    client_config.db_cache_size = Some(cache_config.db_cache_size() as usize);
+    client_config.db_cache_size = cache_config.db_cache_size().map(|size| size as usize);
     // db queue cache size, in bytes
     client_config.queue.max_mem_use = cache_config.queue() as usize * mb;
     // in bytes
@@ -63,6 +63,7 @@ extern crate aion_version;
 extern crate path;
 extern crate blake2b;
 extern crate journaldb;
+extern crate db as kvdb;
 extern crate aion_pb_apiserver as pb;
 extern crate tokio;
 #[macro_use]
@@ -88,6 +89,7 @@ mod blockchain;
 mod cache;
 mod cli;
 mod configuration;
+mod db;
 mod helpers;
 mod modules;
 mod params;
@@ -122,6 +124,7 @@ fn execute(command: Execute) -> Result<PostExecutionAction, String> {
         Cmd::Blockchain(blockchain_cmd) => {
             blockchain::execute(blockchain_cmd).map(|_| PostExecutionAction::Quit)
         }
+        Cmd::Db(db_cmd) => db::execute(db_cmd).map(|s| PostExecutionAction::Print(s)),
     }
 }
 
@@ -29,6 +29,7 @@ use user_defaults::UserDefaults;
 #[derive(Debug, PartialEq)]
 pub enum SpecType {
     Foundation,
+    Dev,
     Custom(String),
 }
 
@@ -42,6 +43,7 @@ impl str::FromStr for SpecType {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let spec = match s {
             "foundation" | "mainnet" => SpecType::Foundation,
+            "dev" => SpecType::Dev,
             other => SpecType::Custom(other.into()),
         };
         Ok(spec)
@@ -52,6 +54,7 @@ impl fmt::Display for SpecType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_str(match *self {
             SpecType::Foundation => "mainnet",
+            SpecType::Dev => "dev",
             SpecType::Custom(ref custom) => custom,
         })
     }
@@ -62,6 +65,7 @@ impl SpecType {
         let params = params.into();
         match *self {
             SpecType::Foundation => Ok(ethereum::new_foundation(params)),
+            SpecType::Dev => Ok(ethereum::new_dev(params)),
             SpecType::Custom(ref filename) => {
                 let file = fs::File::open(filename).map_err(|e| {
                     format!("Could not load specification file at {}: {}", filename, e)
@@ -230,6 +234,7 @@ mod tests {
     fn test_spec_type_parsing() {
         assert_eq!(SpecType::Foundation, "mainnet".parse().unwrap());
         assert_eq!(SpecType::Foundation, "foundation".parse().unwrap());
+        assert_eq!(SpecType::Dev, "dev".parse().unwrap());
     }
 
     #[test]
@@ -240,6 +245,7 @@ mod tests {
     #[test]
     fn test_spec_type_display() {
         assert_eq!(format!("{}", SpecType::Foundation), "mainnet");
+        assert_eq!(format!("{}", SpecType::Dev), "dev");
         assert_eq!(format!("{}", SpecType::Custom("foo/bar".into())), "foo/bar");
     }
 
@@ -23,12 +23,13 @@ use std::io;
 use std::sync::Arc;
 use std::path::PathBuf;
 use std::collections::HashSet;
+use std::net::IpAddr;
 
 use helpers::aion_ipc_path;
 use jsonrpc_core::MetaIoHandler;
 use aion_rpc::informant::{RpcStats, Middleware};
 use aion_rpc::{self as rpc, Metadata, DomainsValidation};
-use rpc_apis::{self, ApiSet};
+use rpc_apis::{self, Api, ApiSet};
 use tokio::runtime::TaskExecutor;
 pub use aion_rpc::{IpcServer, HttpServer, WsServer, RequestMiddleware, WsError, WsErrorKind};
 
@@ -126,7 +127,8 @@ pub fn new_ws<D: rpc_apis::Dependencies>(
         .parse()
         .map_err(|_| format!("Invalid WebSockets listen host/port given: {}", url))?;
 
-    let handler = setup_apis(conf.apis, deps);
+    let apis = without_local_only(conf.apis, &conf.interface, "WebSockets");
+    let handler = setup_apis(apis, deps);
 
     let allowed_origins = into_domains(with_domain(conf.origins));
     let allowed_hosts = into_domains(with_domain(conf.hosts));
@@ -170,7 +172,8 @@ pub fn new_http<D: rpc_apis::Dependencies>(
     let addr = url
         .parse()
         .map_err(|_| format!("Invalid {} listen host/port given: {}", id, url))?;
-    let handler = setup_apis(conf.apis, deps);
+    let apis = without_local_only(conf.apis, &conf.interface, id);
+    let handler = setup_apis(apis, deps);
 
     let cors_domains = into_domains(conf.cors);
     let allowed_hosts = into_domains(with_domain(conf.hosts));
@@ -252,6 +255,31 @@ fn with_domain(items: Option<Vec<String>>) -> Option<Vec<String>> {
     })
 }
 
+/// Drops the local only APIs (admin, debug) from a server which is not bound to a loopback
+/// interface.
+fn without_local_only(apis: ApiSet, interface: &str, server: &str) -> ApiSet {
+    let is_loopback = interface
+        .parse::<IpAddr>()
+        .map(|ip| ip.is_loopback())
+        .unwrap_or(interface == "localhost");
+    if is_loopback {
+        return apis;
+    }
+
+    let (local_only, apis): (Vec<Api>, Vec<Api>) = apis
+        .list_apis()
+        .into_iter()
+        .partition(Api::is_local_only);
+    if !local_only.is_empty() {
+        warn!(
+            target: "rpc",
+            "{} server is not bound to a loopback interface, disabled apis: {:?}",
+            server, local_only
+        );
+    }
+    ApiSet::List(apis.into_iter().collect())
+}
+
 fn setup_apis<D>(
     apis: ApiSet,
     deps: &Dependencies<D>,
@@ -268,3 +296,23 @@ where
 
     handler
 }
+
+#[cfg(test)]
+mod tests {
+    use rpc_apis::{Api, ApiSet};
+    use super::without_local_only;
+
+    #[test]
+    fn should_keep_local_only_apis_on_loopback_interface() {
+        let apis = without_local_only(ApiSet::All, "127.0.0.1", "HTTP JSON-RPC");
+        assert_eq!(apis, ApiSet::All);
+    }
+
+    #[test]
+    fn should_drop_local_only_apis_on_public_interface() {
+        let apis = without_local_only(ApiSet::All, "0.0.0.0", "HTTP JSON-RPC").list_apis();
+        assert!(apis.contains(&Api::Eth));
+        assert!(!apis.contains(&Api::Admin));
+        assert!(!apis.contains(&Api::Debug));
+    }
+}
@@ -24,10 +24,10 @@ use std::collections::{BTreeMap, HashSet};
 use std::str::FromStr;
 use std::sync::Arc;
 
-use sync::sync::{SyncProvider};
+use sync::sync::{NetworkManager, SyncProvider};
 use acore::account_provider::AccountProvider;
 use acore::client::Client;
-use acore::miner::Miner;
+use acore::miner::{Miner, Stratum};
 use jsonrpc_core::{self as core, MetaIoHandler};
 use acore::miner::external::ExternalMiner;
 use aion_rpc::dispatch::{FullDispatcher,DynamicGasPrice};
@@ -54,6 +54,20 @@ pub enum Api {
     Rpc,
     /// Ping (Safe)
     Ping,
+    /// Admin (Local only)
+    Admin,
+    /// Debug (Local only)
+    Debug,
+}
+
+impl Api {
+    /// Whether the API may only be served over IPC or a loopback interface.
+    pub fn is_local_only(&self) -> bool {
+        match *self {
+            Api::Admin | Api::Debug => true,
+            _ => false,
+        }
+    }
 }
 
 impl FromStr for Api {
@@ -71,6 +85,8 @@ impl FromStr for Api {
             "personal" => Ok(Personal),
             "rpc" => Ok(Rpc),
             "ping" => Ok(Ping),
+            "admin" => Ok(Admin),
+            "debug" => Ok(Debug),
             api => Err(format!("Unknown api: {}", api)),
         }
     }
@@ -135,6 +151,8 @@ fn to_modules(apis: &HashSet<Api>) -> BTreeMap<String, String> {
             Api::Personal => ("personal", "1.0"),
             Api::Rpc => ("rpc", "1.0"),
             Api::Ping => ("ping", "1.0"),
+            Api::Admin => ("admin", "1.0"),
+            Api::Debug => ("debug", "1.0"),
         };
         modules.insert(name.into(), version.into());
     }
@@ -157,10 +175,12 @@ pub trait Dependencies {
 pub struct FullDependencies {
     pub client: Arc<Client>,
     pub sync: Arc<SyncProvider>,
+    pub network: Arc<NetworkManager>,
     pub account_store: Option<Arc<AccountProvider>>,
     pub miner: Arc<Miner>,
     pub external_miner: Arc<ExternalMiner>,
     pub dynamic_gas_price: Option<DynamicGasPrice>,
+    pub stratum: Option<Stratum>,
     pub executor: TaskExecutor,
 }
 
@@ -234,6 +254,7 @@ impl FullDependencies {
                         &self.sync,
                         &self.miner,
                         &self.account_store,
+                        &self.stratum,
                     );
                     handler.extend_with(client.to_delegate());
                 }
@@ -271,6 +292,14 @@ impl FullDependencies {
                 Api::Ping => {
                     handler.extend_with(PingClient::new().to_delegate());
                 }
+                Api::Admin => {
+                    handler.extend_with(
+                        AdminClient::new(&self.client, &self.sync, &self.network).to_delegate(),
+                    );
+                }
+                Api::Debug => {
+                    handler.extend_with(DebugClient::new(&self.client).to_delegate());
+                }
             }
         }
     }
@@ -302,12 +331,14 @@ impl ApiSet {
             Api::Personal,
             Api::EthPubSub,
             Api::Ping,
+            Api::Admin,
+            Api::Debug,
         ]
             .into_iter()
             .cloned()
             .collect();
 
-        let public_list = [
+        let public_list: HashSet<Api> = [
             Api::Web3,
             Api::Net,
             Api::Eth,
@@ -323,7 +354,12 @@ impl ApiSet {
         match *self {
             ApiSet::List(ref apis) => apis.clone(),
             ApiSet::PublicContext => public_list,
-            ApiSet::IpcContext => public_list,
+            ApiSet::IpcContext => {
+                let mut ipc_list = public_list;
+                ipc_list.insert(Api::Admin);
+                ipc_list.insert(Api::Debug);
+                ipc_list
+            }
             ApiSet::All => all,
         }
     }
@@ -342,6 +378,8 @@ mod test {
         assert_eq!(Api::EthPubSub, "pubsub".parse().unwrap());
         assert_eq!(Api::Personal, "personal".parse().unwrap());
         assert_eq!(Api::Rpc, "rpc".parse().unwrap());
+        assert_eq!(Api::Admin, "admin".parse().unwrap());
+        assert_eq!(Api::Debug, "debug".parse().unwrap());
         assert!("rp".parse::<Api>().is_err());
     }
 
@@ -364,12 +402,30 @@ mod test {
             Api::Rpc,
             Api::Personal,
             Api::Ping,
+            // local only
+            Api::Admin,
+            Api::Debug,
         ]
         .into_iter()
         .collect();
         assert_eq!(ApiSet::IpcContext.list_apis(), expected);
     }
 
+    #[test]
+    fn test_api_set_public_context() {
+        let apis = ApiSet::PublicContext.list_apis();
+        assert!(!apis.contains(&Api::Admin));
+        assert!(!apis.contains(&Api::Debug));
+    }
+
+    #[test]
+    fn test_local_only_apis() {
+        assert!(Api::Admin.is_local_only());
+        assert!(Api::Debug.is_local_only());
+        assert!(!Api::Eth.is_local_only());
+        assert!(!Api::Personal.is_local_only());
+    }
+
     #[test]
     fn test_all_apis() {
         assert_eq!(
@@ -383,7 +439,9 @@ mod test {
                     Api::Rpc,
                     Api::Personal,
                     Api::EthPubSub,
-                    Api::Ping
+                    Api::Ping,
+                    Api::Admin,
+                    Api::Debug
                 ]
                 .into_iter()
                 .collect()
@@ -403,7 +461,9 @@ mod test {
                     Api::Stratum,
                     Api::Rpc,
                     Api::EthPubSub,
-                    Api::Ping
+                    Api::Ping,
+                    Api::Admin,
+                    Api::Debug
                 ]
                 .into_iter()
                 .collect()
@@ -24,10 +24,13 @@ use std::thread;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use acore::account_provider::{AccountProvider, AccountProviderSettings};
-use acore::client::{BlockChainClient, Client, DatabaseCompactionProfile, VMType};
+use acore::client::{
+    BlockChainClient, Client, DatabaseBackend, DatabaseCompactionProfile, DatabaseDurability,
+    VMType,
+};
 use acore::miner::external::ExternalMiner;
 use acore::miner::{Miner, MinerOptions, MinerService, Staker};
-use acore::miner::{Stratum, StratumOptions};
+use acore::miner::{InternalMiner, InternalMinerOptions, Stratum, StratumOptions};
 use acore::service::ClientService;
 use acore::transaction::local_transactions::TxIoMessage;
 use acore::verification::queue::VerifierSettings;
@@ -70,6 +73,7 @@ pub struct RunCmd {
     pub spec: SpecType,
     pub pruning: Pruning,
     pub pruning_history: u64,
+    pub pruning_checkpoints: Option<u64>,
     pub pruning_memory: usize,
     /// Some if execution should be daemonized. Contains pid_file path.
     pub daemon: Option<String>,
@@ -86,8 +90,14 @@ pub struct RunCmd {
     pub fat_db: Switch,
     pub compaction: DatabaseCompactionProfile,
     pub wal: bool,
+    pub db_column_families: bool,
+    pub db_durability: DatabaseDurability,
+    pub db_backend: DatabaseBackend,
+    pub freezer_threshold: Option<u64>,
+    pub log_index: bool,
     pub vm_type: VMType,
     pub stratum: StratumOptions,
+    pub internal_miner: InternalMinerOptions,
     pub check_seal: bool,
     pub verifier_settings: VerifierSettings,
     pub no_persistent_txqueue: bool,
@@ -200,6 +210,12 @@ pub fn execute_impl(cmd: RunCmd) -> Result<(Weak<Client>), String> {
     );
 
     client_config.queue.verifier_settings = cmd.verifier_settings;
+    client_config.db_column_families = cmd.db_column_families;
+    client_config.db_durability = cmd.db_durability;
+    client_config.db_backend = cmd.db_backend;
+    client_config.blockchain.freezer_threshold = cmd.freezer_threshold;
+    client_config.log_index = cmd.log_index;
+    client_config.pruning_checkpoints = cmd.pruning_checkpoints;
 
     // set up bootnodes
     let net_conf = cmd.net_conf;
@@ -242,30 +258,28 @@ pub fn execute_impl(cmd: RunCmd) -> Result<(Weak<Client>), String> {
 
     let client = service.client();
 
-    // !!!FOR POC ONLY!!!
-
-    // periodically update sealing, for purposes:
+    // periodically refresh the PoW and PoS pending blocks, for purposes:
     // 1. get more accurate timestamp
     // 2. generate block even if there is no transaction
+    // both are also refreshed on every new best block.
     let stop = Arc::new(AtomicBool::new(false));
     thread::spawn({
         let stop = stop.clone();
         let client = client.clone();
+        let interval = miner.prepare_block_interval();
 
         move || {
             while !stop.load(Ordering::SeqCst) {
-                thread::sleep(Duration::from_secs(24 * 60 * 60));
+                thread::sleep(interval);
 
                 trace!(target: "run", "update sealing");
                 client.update_sealing();
-
-                // TODO: refresh on best block change
             }
         }
     });
 
     // pos block producing
-    for k in staker_private_key {
+    for (i, k) in staker_private_key.into_iter().enumerate() {
         // parse the staker private keys
         let bytes: Vec<u8>;
         if k.starts_with("0x") {
@@ -292,6 +306,11 @@ pub fn execute_impl(cmd: RunCmd) -> Result<(Weak<Client>), String> {
             sk,
         );
 
+        // the first staker reports double-sign evidence to the double-sign contract
+        if i == 0 {
+            staker.report_evidence_with(&miner);
+        }
+
         thread::spawn({
             let stop = stop.clone();
             let miner = miner.clone();
@@ -370,9 +389,24 @@ pub fn execute_impl(cmd: RunCmd) -> Result<(Weak<Client>), String> {
     let external_miner = Arc::new(ExternalMiner::default());
 
     // start stratum
-    if cmd.stratum.enable {
-        Stratum::register(&cmd.stratum, miner.clone(), Arc::downgrade(&client))
-            .map_err(|e| format!("Stratum start error: {:?}", e))?;
+    let stratum = if cmd.stratum.enable {
+        Some(
+            Stratum::register(&cmd.stratum, miner.clone(), Arc::downgrade(&client))
+                .map_err(|e| format!("Stratum start error: {:?}", e))?,
+        )
+    } else {
+        None
+    };
+
+    // start mining internally
+    if cmd.internal_miner.enable {
+        InternalMiner::register(
+            &cmd.internal_miner,
+            client.engine(),
+            miner.clone(),
+            Arc::downgrade(&client),
+        )
+        .map_err(|e| format!("Internal miner start error: {}", e))?;
     }
 
     // create sync object
@@ -410,10 +444,12 @@ pub fn execute_impl(cmd: RunCmd) -> Result<(Weak<Client>), String> {
     let deps_for_rpc_apis = Arc::new(rpc_apis::FullDependencies {
         client: client.clone(),
         sync: sync_provider.clone(),
+        network: network_manager.clone(),
         account_store,
         miner: miner.clone(),
         external_miner: external_miner.clone(),
         dynamic_gas_price: cmd.dynamic_gas_price.clone(),
+        stratum: stratum,
         executor: runtime_rpc.executor(),
     });
 
@@ -563,6 +599,9 @@ fn print_running_environment(
         SpecType::Foundation => {
             info!(target: "run", "Load built-in Mainnet Genesis Spec.");
         }
+        SpecType::Dev => {
+            info!(target: "run", "Load built-in Development Genesis Spec.");
+        }
         SpecType::Custom(ref filename) => {
             info!(
                 target: "run",
@@ -22,6 +22,7 @@
 
 //! Blockchain database.
 
+use std::cmp;
 use std::collections::{HashMap, hash_map, VecDeque};
 use std::sync::Arc;
 use std::mem;
@@ -42,12 +43,13 @@ use receipt::Receipt;
 use blooms::{BloomGroup, GroupPosition};
 use blockchain::best_block::{BestBlock, BestAncientBlock};
 use blockchain::block_info::{BlockInfo, BlockLocation, BranchBecomingCanonChainData};
-use blockchain::extras::{BlockReceipts, BlockDetails, TransactionAddress, EPOCH_KEY_PREFIX, EpochTransitions};
+use blockchain::extras::{BlockReceipts, BlockDetails, TransactionAddress, EPOCH_KEY_PREFIX, EpochTransitions, OrphanedBlock, OrphanedBlocks};
 use types::blockchain_info::BlockChainInfo;
 use types::tree_route::TreeRoute;
 use blockchain::update::ExtrasUpdate;
+use blockchain::freezer::{frozen_blocks, set_frozen_blocks, Freezer};
 use blockchain::{CacheSize, ImportRoute, Config};
-use db::{self, Writable, Readable, CacheUpdatePolicy};
+use db::{self, Key, Writable, Readable, CacheUpdatePolicy};
 use cache_manager::CacheManager;
 use encoded;
 use engines::epoch::{Transition as EpochTransition, PendingTransition as PendingEpochTransition};
@@ -255,6 +257,9 @@ pub struct BlockChain {
     block_receipts: RwLock<HashMap<H256, BlockReceipts>>,
 
     db: Arc<KeyValueDB>,
+    // old canonical blocks moved out of the databases
+    freezer: Option<Freezer>,
+    freezer_threshold: Option<u64>,
 
     cache_man: Mutex<CacheManager<CacheId>>,
 
@@ -262,6 +267,23 @@ pub struct BlockChain {
     pending_block_hashes: RwLock<HashMap<BlockNumber, H256>>,
     pending_block_details: RwLock<HashMap<H256, BlockDetails>>,
     pending_transaction_addresses: RwLock<HashMap<H256, Option<TransactionAddress>>>,
+
+    reorg_stats: RwLock<ReorgStats>,
+}
+
+/// Reorganisations of the canonical chain since the blockchain was opened.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ReorgStats {
+    /// Number of reorganisations
+    pub reorgs: u64,
+    /// Number of blocks retracted by the last reorganisation
+    pub last_depth: u64,
+    /// Highest number of blocks retracted by a reorganisation
+    pub max_depth: u64,
+    /// Number of retracted PoW blocks
+    pub orphaned_pow: u64,
+    /// Number of retracted PoS blocks
+    pub orphaned_pos: u64,
 }
 
 impl BlockProvider for BlockChain {
@@ -430,11 +452,13 @@ impl BlockProvider for BlockChain {
             }
         }
 
-        // Read from DB and populate cache
+        // Read from DB or the freezer and populate cache
         let opt = self
             .db
             .get(db::COL_HEADERS, hash)
-            .expect("Low level database error. Some issue with disk?");
+            .expect("Low level database error. Some issue with disk?")
+            .map(|b| b.into_vec())
+            .or_else(|| self.frozen(hash, Freezer::header));
 
         let result = match opt {
             Some(b) => {
@@ -468,11 +492,13 @@ impl BlockProvider for BlockChain {
             }
         }
 
-        // Read from DB and populate cache
+        // Read from DB or the freezer and populate cache
         let opt = self
             .db
             .get(db::COL_BODIES, hash)
-            .expect("Low level database error. Some issue with disk?");
+            .expect("Low level database error. Some issue with disk?")
+            .map(|b| b.into_vec())
+            .or_else(|| self.frozen(hash, Freezer::body));
 
         let result = match opt {
             Some(b) => {
@@ -524,7 +550,12 @@ impl BlockProvider for BlockChain {
     fn block_receipts(&self, hash: &H256) -> Option<BlockReceipts> {
         let result = self
             .db
-            .read_with_cache(db::COL_EXTRA, &self.block_receipts, hash);
+            .read_with_cache(db::COL_EXTRA, &self.block_receipts, hash)
+            .or_else(|| {
+                let receipts: BlockReceipts = decode(&self.frozen(hash, Freezer::receipts)?);
+                self.block_receipts.write().insert(*hash, receipts.clone());
+                Some(receipts)
+            });
         self.cache_man
             .lock()
             .note_used(CacheId::BlockReceipts(*hash));
@@ -708,6 +739,29 @@ impl BlockChain {
         // 400 is the avarage size of the key
         let cache_man = CacheManager::new(config.pref_cache_size, config.max_cache_size, 400);
 
+        let frozen = frozen_blocks(&*db);
+        let freezer = match config.freezer_path {
+            Some(ref path) => {
+                let freezer = Freezer::open(path).unwrap_or_else(|e| {
+                    panic!("Cannot open the freezer at {}: {}", path.display(), e)
+                });
+                if freezer.frozen() < frozen {
+                    panic!(
+                        "The freezer at {} holds {} blocks, but {} were moved to it, db may \
+                         crashed",
+                        path.display(),
+                        freezer.frozen(),
+                        frozen
+                    );
+                }
+                Some(freezer)
+            }
+            None if frozen > 0 => {
+                panic!("{} blocks were moved to a freezer, but none is configured", frozen)
+            }
+            None => None,
+        };
+
         let mut bc = BlockChain {
             blooms_config: bc::Config {
                 levels: LOG_BLOOMS_LEVELS,
@@ -724,11 +778,14 @@ impl BlockChain {
             blocks_blooms: RwLock::new(HashMap::new()),
             block_receipts: RwLock::new(HashMap::new()),
             db: db.clone(),
+            freezer: freezer,
+            freezer_threshold: config.freezer_threshold,
             cache_man: Mutex::new(cache_man),
             pending_best_block: RwLock::new(None),
             pending_block_hashes: RwLock::new(HashMap::new()),
             pending_block_details: RwLock::new(HashMap::new()),
             pending_transaction_addresses: RwLock::new(HashMap::new()),
+            reorg_stats: RwLock::new(ReorgStats::default()),
         };
 
         // load best block
@@ -1283,6 +1340,7 @@ impl BlockChain {
                 Colour::White.paint(format!("#{} {}", self.block_details(&d.ancestor).expect("`ancestor` is in the route; qed").number, d.ancestor)),
                 Colour::Green.paint(d.enacted.iter().join(" "))
             );
+            self.note_orphaned_blocks(batch, &d.retracted, &info.hash);
         }
 
         self.prepare_update(
@@ -1303,6 +1361,65 @@ impl BlockChain {
         ImportRoute::from(info)
     }
 
+    /// Records the blocks retracted by a reorganisation to the given best block in the orphaned
+    /// blocks journal.
+    fn note_orphaned_blocks(&self, batch: &mut DBTransaction, retracted: &[H256], new_best: &H256) {
+        let depth = retracted.len() as u64;
+        let mut stats = self.reorg_stats.write();
+        stats.reorgs += 1;
+        stats.last_depth = depth;
+        stats.max_depth = cmp::max(stats.max_depth, depth);
+
+        for hash in retracted {
+            let header = match self.block_header_data(hash) {
+                Some(header) => header.decode(),
+                None => continue,
+            };
+            let seal_type = header.seal_type().clone().unwrap_or(SealType::Pow);
+            match seal_type {
+                SealType::Pow => stats.orphaned_pow += 1,
+                SealType::Pos => stats.orphaned_pos += 1,
+            }
+
+            let number = header.number();
+            let mut orphans: OrphanedBlocks = self
+                .db
+                .read(db::COL_EXTRA, &number)
+                .unwrap_or_else(|| {
+                    OrphanedBlocks {
+                        number: number,
+                        blocks: Vec::with_capacity(1),
+                    }
+                });
+            // a block retracted again replaces its previous record.
+            orphans.blocks.retain(|b| &b.hash != hash);
+            orphans.blocks.push(OrphanedBlock {
+                hash: *hash,
+                number: number,
+                author: *header.author(),
+                seal_type: seal_type,
+                difficulty: *header.difficulty(),
+                reorg_depth: depth,
+                new_best: *new_best,
+            });
+            batch.write(db::COL_EXTRA, &number, &orphans);
+        }
+    }
+
+    /// Blocks retracted from the canonical chain with numbers in the given inclusive range.
+    pub fn orphaned_blocks(&self, from: BlockNumber, to: BlockNumber) -> Vec<OrphanedBlock> {
+        (from..=to)
+            .filter_map(|number| {
+                let orphans: Option<OrphanedBlocks> = self.db.read(db::COL_EXTRA, &number);
+                orphans
+            })
+            .flat_map(|orphans| orphans.blocks)
+            .collect()
+    }
+
+    /// Reorganisations since the blockchain was opened.
+    pub fn reorg_stats(&self) -> ReorgStats { self.reorg_stats.read().clone() }
+
     /// Get inserted block info which is critical to prepare extras updates.
     fn block_info(&self, header: &HeaderView) -> BlockInfo {
         let hash = header.hash();
@@ -1787,6 +1904,109 @@ impl BlockChain {
         }
     }
 
+    /// Data of the canonical block `hash` read by `read` from the freezer, if it was moved there.
+    fn frozen<F>(&self, hash: &H256, read: F) -> Option<Bytes>
+    where F: Fn(&Freezer, BlockNumber) -> Option<Bytes> {
+        let freezer = self.freezer.as_ref()?;
+        let number = self.block_number(hash)?;
+        if number >= freezer.frozen() || self.block_hash(number) != Some(*hash) {
+            return None;
+        }
+        read(freezer, number)
+    }
+
+    /// Compressed header and body, and receipts rlp of the canonical block `number` as stored
+    /// in the databases.
+    fn stored_block(&self, number: BlockNumber) -> Option<(Bytes, Bytes, Bytes)> {
+        let hash = self.block_hash(number)?;
+        let get = |db_name: &'static str, key: &[u8]| {
+            self.db
+                .get(db_name, key)
+                .expect("Low level database error. Some issue with disk?")
+                .map(|value| value.into_vec())
+        };
+        let header = get(db::COL_HEADERS, &*hash)?;
+        let body = get(db::COL_BODIES, &*hash)?;
+        let receipts = match get(db::COL_EXTRA, &*Key::<BlockReceipts>::key(&hash)) {
+            Some(receipts) => receipts,
+            // genesis has no receipts
+            None if number == 0 => Vec::new(),
+            None => return None,
+        };
+        Some((header, body, receipts))
+    }
+
+    /// Moves up to `max_blocks` canonical blocks more than the freezer threshold below the best
+    /// block from the databases to the freezer. Returns the number of blocks moved.
+    pub fn freeze(&self, max_blocks: u64) -> Result<u64, String> {
+        let (freezer, threshold) = match (self.freezer.as_ref(), self.freezer_threshold) {
+            (Some(freezer), Some(threshold)) => (freezer, threshold),
+            _ => return Ok(0),
+        };
+        let from = freezer.frozen();
+        let to = cmp::min(
+            self.best_block_number().saturating_sub(threshold),
+            from + max_blocks,
+        );
+        let mut moved = 0;
+        for number in from..to {
+            let (header, body, receipts) = match self.stored_block(number) {
+                Some(block) => block,
+                None => {
+                    debug!(target: "freezer", "Block #{} is not in the database", number);
+                    break;
+                }
+            };
+            freezer
+                .append(&header, &body, &receipts)
+                .map_err(|e| format!("Freezer write failed: {}", e))?;
+            moved += 1;
+        }
+        if moved > 0 {
+            freezer
+                .sync()
+                .map_err(|e| format!("Freezer sync failed: {}", e))?;
+        }
+        // also completes a removal interrupted by a crash
+        self.remove_frozen(freezer.frozen())?;
+        Ok(moved)
+    }
+
+    /// Removes the data of the blocks moved to the freezer from the databases.
+    fn remove_frozen(&self, frozen: BlockNumber) -> Result<(), String> {
+        let removed = frozen_blocks(&*self.db);
+        if removed >= frozen {
+            return Ok(());
+        }
+        let mut batch = DBTransaction::new();
+        for number in removed..frozen {
+            if let Some(hash) = self.block_hash(number) {
+                batch.delete(db::COL_HEADERS, &hash);
+                batch.delete(db::COL_BODIES, &hash);
+                batch.delete(db::COL_EXTRA, &Key::<BlockReceipts>::key(&hash));
+            }
+        }
+        set_frozen_blocks(&mut batch, frozen);
+        self.db
+            .write(batch)
+            .map_err(|e| format!("Low level database error: {}", e))
+    }
+
+    /// Number of blocks in the freezer and size of its files, if the freezer is used.
+    pub fn freezer_info(&self) -> Option<(BlockNumber, u64)> {
+        self.freezer
+            .as_ref()
+            .map(|freezer| (freezer.frozen(), freezer.disk_size()))
+    }
+
+    /// Changes the preferred and maximum size of the caches in bytes. Caches above the new size
+    /// are shrunk by the next `collect_garbage`.
+    pub fn set_cache_size(&self, pref_cache_size: usize, max_cache_size: usize) {
+        self.cache_man
+            .lock()
+            .set_cache_size(pref_cache_size, max_cache_size);
+    }
+
     /// Ticks our cache system and throws out any old data.
     pub fn collect_garbage(&self) {
         let current_size = self.cache_size().total();
@@ -1886,7 +2106,8 @@ mod tests {
     use aion_types::*;
     use ethbloom::Bloom;
     use receipt::{Receipt, SimpleReceipt};
-    use blockchain::{BlockProvider, BlockChain, Config, ImportRoute};
+    use blockchain::{BlockProvider, BlockChain, Config, ImportRoute, ReorgStats};
+    use header::SealType;
     use tests::helpers::*;
     use blockchain::generator::{BlockGenerator, BlockBuilder, BlockOptions};
     use blockchain::extras::TransactionAddress;
@@ -2321,6 +2542,52 @@ mod tests {
         assert_eq!(r3b_3a.index, 1);
     }
 
+    #[test]
+    fn test_orphaned_blocks_journal() {
+        let genesis = BlockBuilder::genesis();
+        let b1 = genesis.add_block();
+        let b2a = b1.add_block();
+        let b2b = b1.add_block_with_difficulty(9);
+
+        let b2a_hash = b2a.last().hash();
+        let b2b_hash = b2b.last().hash();
+
+        let db = new_db();
+        let bc = new_chain(&genesis.last().encoded(), db.clone());
+
+        let mut batch = DBTransaction::new();
+        let _ = bc.insert_block(&mut batch, &b1.last().encoded(), vec![]);
+        bc.commit();
+        let _ = bc.insert_block(&mut batch, &b2b.last().encoded(), vec![]);
+        bc.commit();
+        db.write(batch).unwrap();
+        assert!(bc.orphaned_blocks(0, 2).is_empty());
+
+        let mut batch = DBTransaction::new();
+        let _ = bc.insert_block(&mut batch, &b2a.last().encoded(), vec![]);
+        bc.commit();
+        db.write(batch).unwrap();
+
+        let orphans = bc.orphaned_blocks(0, 2);
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].hash, b2b_hash);
+        assert_eq!(orphans[0].number, 2);
+        assert_eq!(orphans[0].seal_type, SealType::Pow);
+        assert_eq!(orphans[0].reorg_depth, 1);
+        assert_eq!(orphans[0].new_best, b2a_hash);
+        assert!(bc.orphaned_blocks(3, 10).is_empty());
+        assert_eq!(
+            bc.reorg_stats(),
+            ReorgStats {
+                reorgs: 1,
+                last_depth: 1,
+                max_depth: 1,
+                orphaned_pow: 1,
+                orphaned_pos: 0,
+            }
+        );
+    }
+
     #[test]
     fn test_reopen_blockchain_db() {
         let genesis = BlockBuilder::genesis();
@@ -2778,6 +3045,117 @@ mod tests {
         assert_eq!(bc.best_block_number(), 5);
     }
 
+    #[test]
+    fn frozen_blocks_are_read_from_freezer() {
+        use tempdir::TempDir;
+
+        let genesis = BlockBuilder::genesis();
+        let next_5 = genesis.add_blocks(5);
+        let generator = BlockGenerator::new(iter::once(next_5));
+        let tempdir = TempDir::new("").unwrap();
+        let config = Config {
+            freezer_path: Some(tempdir.path().to_path_buf()),
+            freezer_threshold: Some(2),
+            ..Default::default()
+        };
+
+        let db = new_db();
+        let mut hashes = vec![genesis.last().hash()];
+        {
+            let bc = BlockChain::new(config.clone(), &genesis.last().encoded(), db.clone());
+            for block in generator {
+                let mut batch = DBTransaction::new();
+                bc.insert_block(&mut batch, &block.encoded(), vec![]);
+                db.write(batch).unwrap();
+                bc.commit();
+                hashes.push(block.hash());
+            }
+
+            assert_eq!(bc.freeze(2).unwrap(), 2);
+            assert_eq!(bc.freeze(10).unwrap(), 1);
+            assert_eq!(bc.freeze(10).unwrap(), 0);
+            assert_eq!(bc.freezer_info().map(|(frozen, _)| frozen), Some(3));
+        }
+
+        assert!(db.get(db::COL_HEADERS, &hashes[1]).unwrap().is_none());
+        assert!(db.get(db::COL_HEADERS, &hashes[3]).unwrap().is_some());
+
+        let bc = BlockChain::new(config, &genesis.last().encoded(), db);
+        for hash in &hashes {
+            assert!(bc.block(hash).is_some());
+            assert_eq!(bc.block_header_data(hash).unwrap().hash(), *hash);
+        }
+        assert!(bc.block_receipts(&hashes[1]).is_some());
+    }
+
+    #[test]
+    fn log_index_follows_the_canonical_chain() {
+        use blockchain::LogIndex;
+
+        let receipt = |address: u64, topics: Vec<H256>| {
+            Receipt {
+                simple_receipt: SimpleReceipt {
+                    state_root: H256::default(),
+                    log_bloom: Default::default(),
+                    logs: vec![LogEntry {
+                        address: H256::from(U256::from(address)),
+                        topics: topics,
+                        data: vec![],
+                    }],
+                },
+                gas_used: 10_000.into(),
+                transaction_fee: U256::zero(),
+                output: Bytes::default(),
+                error_message: String::default(),
+            }
+        };
+        let (a, b) = (H256::from(U256::from(1)), H256::from(U256::from(2)));
+        let topic = H256::from(U256::from(3));
+
+        let genesis = BlockBuilder::genesis();
+        let b1 = genesis.add_block();
+        let b2 = b1.add_block();
+        let b3 = b2.add_block();
+        let b4 = b3.add_block();
+        let fork = b3.add_block_with_difficulty(20);
+
+        let db = new_db();
+        let bc = new_chain(&genesis.last().encoded(), db.clone());
+        insert_block(&db, &bc, &b1.last().encoded(), vec![receipt(1, vec![topic])]);
+        insert_block(&db, &bc, &b2.last().encoded(), vec![receipt(2, vec![])]);
+        insert_block(&db, &bc, &b3.last().encoded(), vec![]);
+
+        // blocks imported before the index was enabled are indexed backward
+        let index = LogIndex::new(db.clone());
+        assert_eq!(index.range(), None);
+        assert_eq!(index.update(&bc, 2).unwrap(), 2);
+        assert_eq!(index.range(), Some((2..4, b3.last().hash())));
+        assert_eq!(index.update(&bc, 10).unwrap(), 2);
+        assert_eq!(index.range(), Some((0..4, b3.last().hash())));
+        assert_eq!(index.update(&bc, 10).unwrap(), 0);
+
+        assert_eq!(index.blocks(&[a], None, 0, 3), vec![1]);
+        assert_eq!(index.blocks(&[a], Some(&[topic][..]), 0, 3), vec![1]);
+        let other_topic = H256::from(U256::from(4));
+        assert!(index.blocks(&[a], Some(&[other_topic][..]), 0, 3).is_empty());
+        assert_eq!(index.blocks(&[a, b], None, 0, 3), vec![1, 2]);
+        assert_eq!(index.blocks(&[a, b], None, 2, 3), vec![2]);
+
+        // new blocks are indexed forward
+        insert_block(&db, &bc, &b4.last().encoded(), vec![receipt(1, vec![])]);
+        assert_eq!(index.update(&bc, 10).unwrap(), 1);
+        assert_eq!(index.range(), Some((0..5, b4.last().hash())));
+        assert_eq!(index.blocks(&[a], None, 0, 4), vec![1, 4]);
+
+        // a retracted block is indexed again
+        insert_block(&db, &bc, &fork.last().encoded(), vec![receipt(2, vec![])]);
+        assert_eq!(bc.best_block_hash(), fork.last().hash());
+        assert_eq!(index.range(), Some((0..5, b4.last().hash())));
+        assert_eq!(index.update(&bc, 10).unwrap(), 1);
+        assert_eq!(index.range(), Some((0..5, fork.last().hash())));
+        assert_eq!(index.blocks(&[b], None, 0, 4), vec![2, 4]);
+    }
+
     #[test]
     fn epoch_transitions_iter() {
         use ::engines::EpochTransition;
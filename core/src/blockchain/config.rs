@@ -22,6 +22,8 @@
 
 //! Blockchain configuration.
 
+use std::path::PathBuf;
+
 /// Blockchain configuration.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Config {
@@ -29,6 +31,12 @@ pub struct Config {
     pub pref_cache_size: usize,
     /// Maximum cache size in bytes.
     pub max_cache_size: usize,
+    /// Directory of the freezer of old canonical blocks. Blocks already moved there are only
+    /// readable when it is set.
+    pub freezer_path: Option<PathBuf>,
+    /// Canonical blocks this many blocks below the best block are moved to the freezer,
+    /// `None` to keep all blocks in the databases.
+    pub freezer_threshold: Option<u64>,
 }
 
 impl Default for Config {
@@ -36,6 +44,8 @@ impl Default for Config {
         Config {
             pref_cache_size: 1 << 14,
             max_cache_size: 1 << 20,
+            freezer_path: None,
+            freezer_threshold: None,
         }
     }
 }
@@ -27,11 +27,11 @@ use std::io::Write;
 use blooms::{GroupPosition, BloomGroup};
 use db::Key;
 use engines::epoch::{Transition as EpochTransition};
-use header::BlockNumber;
+use header::{BlockNumber, SealType};
 use receipt::Receipt;
 
 use heapsize::HeapSizeOf;
-use aion_types::{H256, H264, U256};
+use aion_types::{Address, H256, H264, U256};
 use kvdb::PREFIX_LEN as DB_PREFIX_LEN;
 
 /// Represents index of extra data in database
@@ -51,6 +51,8 @@ pub enum ExtrasIndex {
     EpochTransitions = 5,
     /// Pending epoch transition data index.
     PendingEpochTransition = 6,
+    /// Orphaned blocks journal index.
+    OrphanedBlocks = 7,
 }
 
 fn with_index(hash: &H256, i: ExtrasIndex) -> H264 {
@@ -171,6 +173,47 @@ impl Key<EpochTransitions> for u64 {
     }
 }
 
+/// length of orphaned blocks keys.
+pub const ORPHANS_KEY_LEN: usize = DB_PREFIX_LEN + 16;
+
+/// orphaned blocks key prefix.
+pub const ORPHANS_KEY_PREFIX: &'static [u8; DB_PREFIX_LEN] = &[
+    ExtrasIndex::OrphanedBlocks as u8,
+    0,
+    0,
+    0,
+    0,
+    0,
+    0,
+    0,
+    0,
+    0,
+    0,
+    0,
+];
+
+pub struct OrphanedBlocksKey([u8; ORPHANS_KEY_LEN]);
+
+impl ops::Deref for OrphanedBlocksKey {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] { &self.0[..] }
+}
+
+impl Key<OrphanedBlocks> for u64 {
+    type Target = OrphanedBlocksKey;
+
+    fn key(&self) -> Self::Target {
+        let mut arr = [0u8; ORPHANS_KEY_LEN];
+        arr[..DB_PREFIX_LEN].copy_from_slice(&ORPHANS_KEY_PREFIX[..]);
+
+        write!(&mut arr[DB_PREFIX_LEN..], "{:016x}", self)
+            .expect("format arg is valid; no more than 16 chars will be written; qed");
+
+        OrphanedBlocksKey(arr)
+    }
+}
+
 /// Familial details concerning a block
 #[derive(Debug, Clone, RlpEncodable, RlpDecodable)]
 pub struct BlockDetails {
@@ -230,6 +273,32 @@ pub struct EpochTransitions {
     pub candidates: Vec<EpochTransition>,
 }
 
+/// A block retracted from the canonical chain by a reorganisation.
+#[derive(Debug, Clone, PartialEq, RlpEncodable, RlpDecodable)]
+pub struct OrphanedBlock {
+    /// Block hash
+    pub hash: H256,
+    /// Block number
+    pub number: BlockNumber,
+    /// Block author
+    pub author: Address,
+    /// Seal type of the block
+    pub seal_type: SealType,
+    /// Block difficulty
+    pub difficulty: U256,
+    /// Number of blocks retracted by the reorganisation
+    pub reorg_depth: u64,
+    /// Best block hash after the reorganisation
+    pub new_best: H256,
+}
+
+/// Blocks with a specific number retracted from the canonical chain.
+#[derive(Clone, RlpEncodable, RlpDecodable)]
+pub struct OrphanedBlocks {
+    pub number: BlockNumber,
+    pub blocks: Vec<OrphanedBlock>,
+}
+
 #[cfg(test)]
 mod tests {
     use rlp::*;
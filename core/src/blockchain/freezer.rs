@@ -0,0 +1,320 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Append-only store of old canonical blocks.
+//!
+//! Headers, bodies and receipts of canonical blocks below a threshold are moved out of the
+//! databases into flat files, one table per kind of data. A table is a data file holding the
+//! items back to back, and an index file holding the end offset of each item as a big endian
+//! `u64`, so that the item of block `n` is found with two reads. Blocks are always frozen in
+//! order from the genesis, the tables are only ever appended to.
+//!
+//! The databases record up to which block the data was moved in `FROZEN_KEY`. Blocks are
+//! appended to the freezer and synced before they are removed from the databases, so an
+//! interrupted move is completed by the next one.
+
+use std::cmp;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use byteorder::{BigEndian, ByteOrder};
+use parking_lot::Mutex;
+use kvdb::{DBTransaction, KeyValueDB};
+use rlp::{decode, encode};
+use db;
+use header::BlockNumber;
+
+/// Directory of the freezer in the client directory.
+pub const FREEZER_DIR: &'static str = "freezer";
+
+/// Key in the extras of the number of blocks whose data was moved to the freezer.
+pub const FROZEN_KEY: &'static [u8] = b"frozen";
+
+/// Number of blocks whose data was moved from the databases to the freezer.
+pub fn frozen_blocks(db: &KeyValueDB) -> BlockNumber {
+    db.get(db::COL_EXTRA, FROZEN_KEY)
+        .ok()
+        .and_then(|frozen| frozen)
+        .map_or(0, |frozen| decode(&frozen))
+}
+
+/// Record that the data of the blocks below `frozen` was moved to the freezer.
+pub fn set_frozen_blocks(batch: &mut DBTransaction, frozen: BlockNumber) {
+    batch.put(db::COL_EXTRA, FROZEN_KEY, &encode(&frozen));
+}
+
+const INDEX_ENTRY_SIZE: u64 = 8;
+
+/// Items of a single kind of block data.
+struct Table {
+    index: File,
+    data: File,
+    /// Number of items.
+    items: u64,
+    /// Size of the data of all the items.
+    size: u64,
+}
+
+impl Table {
+    /// Open the table, dropping any partially written item.
+    fn open(dir: &Path, name: &str) -> io::Result<Table> {
+        let open = |extension: &str| {
+            OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .open(dir.join(format!("{}.{}", name, extension)))
+        };
+        let mut table = Table {
+            index: open("idx")?,
+            data: open("dat")?,
+            items: 0,
+            size: 0,
+        };
+        let data_len = table.data.metadata()?.len();
+        let mut items = table.index.metadata()?.len() / INDEX_ENTRY_SIZE;
+        // items whose data was not fully written are dropped
+        while items > 0 && table.end_offset(items - 1)? > data_len {
+            items -= 1;
+        }
+        table.truncate(items)?;
+        Ok(table)
+    }
+
+    fn end_offset(&mut self, item: u64) -> io::Result<u64> {
+        let mut entry = [0u8; INDEX_ENTRY_SIZE as usize];
+        self.index.seek(SeekFrom::Start(item * INDEX_ENTRY_SIZE))?;
+        self.index.read_exact(&mut entry)?;
+        Ok(BigEndian::read_u64(&entry))
+    }
+
+    fn start_offset(&mut self, item: u64) -> io::Result<u64> {
+        match item {
+            0 => Ok(0),
+            _ => self.end_offset(item - 1),
+        }
+    }
+
+    /// Keep the first `items` items only.
+    fn truncate(&mut self, items: u64) -> io::Result<()> {
+        let size = self.start_offset(items)?;
+        self.index.set_len(items * INDEX_ENTRY_SIZE)?;
+        self.data.set_len(size)?;
+        self.items = items;
+        self.size = size;
+        Ok(())
+    }
+
+    fn get(&mut self, item: u64) -> io::Result<Option<Vec<u8>>> {
+        if item >= self.items {
+            return Ok(None);
+        }
+        let start = self.start_offset(item)?;
+        let end = self.end_offset(item)?;
+        let mut bytes = vec![0u8; (end - start) as usize];
+        self.data.seek(SeekFrom::Start(start))?;
+        self.data.read_exact(&mut bytes)?;
+        Ok(Some(bytes))
+    }
+
+    fn append(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let mut entry = [0u8; INDEX_ENTRY_SIZE as usize];
+        BigEndian::write_u64(&mut entry, self.size + bytes.len() as u64);
+        self.data.seek(SeekFrom::Start(self.size))?;
+        self.data.write_all(bytes)?;
+        self.index.seek(SeekFrom::Start(self.items * INDEX_ENTRY_SIZE))?;
+        self.index.write_all(&entry)?;
+        self.items += 1;
+        self.size += bytes.len() as u64;
+        Ok(())
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        // the data must be on disk before the index entries pointing to it
+        self.data.sync_data()?;
+        self.index.sync_data()
+    }
+
+    fn disk_size(&self) -> u64 { self.size + self.items * INDEX_ENTRY_SIZE }
+}
+
+/// Tables of the freezer, all holding the same number of items.
+struct Tables {
+    headers: Table,
+    bodies: Table,
+    receipts: Table,
+}
+
+impl Tables {
+    fn items(&self) -> u64 { self.headers.items }
+
+    fn truncate(&mut self, items: u64) -> io::Result<()> {
+        self.headers.truncate(items)?;
+        self.bodies.truncate(items)?;
+        self.receipts.truncate(items)
+    }
+
+    fn append(&mut self, header: &[u8], body: &[u8], receipts: &[u8]) -> io::Result<()> {
+        self.headers.append(header)?;
+        self.bodies.append(body)?;
+        self.receipts.append(receipts)
+    }
+}
+
+/// Flat file store of the headers, bodies and receipts of old canonical blocks, indexed by
+/// block number. The data is stored as it is in the databases: compressed headers and bodies,
+/// and rlp of the receipts, empty for the genesis.
+pub struct Freezer {
+    path: PathBuf,
+    tables: Mutex<Tables>,
+}
+
+impl Freezer {
+    /// Open the freezer in `path`, creating it if it does not exist.
+    pub fn open(path: &Path) -> io::Result<Freezer> {
+        fs::create_dir_all(path)?;
+        let mut tables = Tables {
+            headers: Table::open(path, "headers")?,
+            bodies: Table::open(path, "bodies")?,
+            receipts: Table::open(path, "receipts")?,
+        };
+        // a block is only frozen once it is in all the tables
+        let items = cmp::min(
+            tables.headers.items,
+            cmp::min(tables.bodies.items, tables.receipts.items),
+        );
+        tables.truncate(items)?;
+        Ok(Freezer {
+            path: path.to_path_buf(),
+            tables: Mutex::new(tables),
+        })
+    }
+
+    /// Directory of the freezer.
+    pub fn path(&self) -> &Path { &self.path }
+
+    /// Number of blocks in the freezer, which holds blocks `0..frozen()`.
+    pub fn frozen(&self) -> BlockNumber { self.tables.lock().items() }
+
+    /// Compressed header of the block `number`.
+    pub fn header(&self, number: BlockNumber) -> Option<Vec<u8>> {
+        read(self.tables.lock().headers.get(number))
+    }
+
+    /// Compressed body of the block `number`.
+    pub fn body(&self, number: BlockNumber) -> Option<Vec<u8>> {
+        read(self.tables.lock().bodies.get(number))
+    }
+
+    /// Receipts rlp of the block `number`.
+    pub fn receipts(&self, number: BlockNumber) -> Option<Vec<u8>> {
+        read(self.tables.lock().receipts.get(number))
+            .and_then(|receipts| if receipts.is_empty() { None } else { Some(receipts) })
+    }
+
+    /// Append the block `frozen()`. Nothing is durable until `sync`.
+    pub fn append(&self, header: &[u8], body: &[u8], receipts: &[u8]) -> io::Result<()> {
+        let mut tables = self.tables.lock();
+        let items = tables.items();
+        // keep the tables aligned when the block is only partially appended
+        tables.append(header, body, receipts).or_else(|e| {
+            tables.truncate(items)?;
+            Err(e)
+        })
+    }
+
+    /// Sync the appended blocks to disk.
+    pub fn sync(&self) -> io::Result<()> {
+        let mut tables = self.tables.lock();
+        tables.headers.sync()?;
+        tables.bodies.sync()?;
+        tables.receipts.sync()
+    }
+
+    /// Size of the freezer files, in bytes.
+    pub fn disk_size(&self) -> u64 {
+        let tables = self.tables.lock();
+        tables.headers.disk_size() + tables.bodies.disk_size() + tables.receipts.disk_size()
+    }
+}
+
+fn read(item: io::Result<Option<Vec<u8>>>) -> Option<Vec<u8>> {
+    item.unwrap_or_else(|e| {
+        error!(target: "freezer", "Freezer read failed: {}", e);
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn appends_and_reads_blocks() {
+        let tempdir = TempDir::new("").unwrap();
+        {
+            let freezer = Freezer::open(tempdir.path()).unwrap();
+            assert_eq!(freezer.frozen(), 0);
+            freezer.append(&[1], &[2, 2], &[]).unwrap();
+            freezer.append(&[3, 3, 3], &[], &[4]).unwrap();
+            freezer.sync().unwrap();
+        }
+        let freezer = Freezer::open(tempdir.path()).unwrap();
+        assert_eq!(freezer.frozen(), 2);
+        assert_eq!(freezer.header(0), Some(vec![1]));
+        assert_eq!(freezer.body(0), Some(vec![2, 2]));
+        assert_eq!(freezer.receipts(0), None);
+        assert_eq!(freezer.header(1), Some(vec![3, 3, 3]));
+        assert_eq!(freezer.body(1), Some(vec![]));
+        assert_eq!(freezer.receipts(1), Some(vec![4]));
+        assert_eq!(freezer.header(2), None);
+    }
+
+    #[test]
+    fn drops_partially_written_blocks() {
+        let tempdir = TempDir::new("").unwrap();
+        {
+            let freezer = Freezer::open(tempdir.path()).unwrap();
+            freezer.append(&[1], &[2], &[3]).unwrap();
+            freezer.append(&[4], &[5], &[6]).unwrap();
+            freezer.sync().unwrap();
+        }
+        // the second receipts were not written, and the second body only in the index
+        let truncate = |name: &str, len: u64| {
+            OpenOptions::new()
+                .write(true)
+                .open(tempdir.path().join(name))
+                .unwrap()
+                .set_len(len)
+                .unwrap();
+        };
+        truncate("receipts.idx", INDEX_ENTRY_SIZE);
+        truncate("bodies.dat", 1);
+
+        let freezer = Freezer::open(tempdir.path()).unwrap();
+        assert_eq!(freezer.frozen(), 1);
+        assert_eq!(freezer.body(1), None);
+        freezer.append(&[7], &[8], &[9]).unwrap();
+        assert_eq!(freezer.header(1), Some(vec![7]));
+        assert_eq!(freezer.body(1), Some(vec![8]));
+    }
+}
@@ -0,0 +1,369 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Startup consistency check of the blockchain databases.
+//!
+//! An unclean shutdown can leave the best block pointer ahead of the data it points to. The
+//! check walks down from the best block to the first block whose header, body, details and
+//! state root are all present, and reverts the chain to it. The same checks are available as a
+//! full offline walk of the canonical chain. Blocks moved to the freezer are neither checked nor
+//! reverted.
+
+use std::fmt;
+use std::sync::Arc;
+use aion_types::H256;
+use blake2b::BLAKE2B_NULL_RLP;
+use journaldb::{self, Algorithm, JournalDB};
+use kvdb::{DBTransaction, KeyValueDB};
+use rlp::encode;
+use rlp_compress::{decompress, blocks_swapper};
+use blockchain::extras::{BlockDetails, BlockReceipts};
+use blockchain::freezer::frozen_blocks;
+use db::{self, Key, Readable, Writable};
+use encoded;
+use header::BlockNumber;
+
+/// Number of blocks below the best block searched for a consistent block.
+pub const MAX_ROLLBACK: BlockNumber = 1024;
+
+/// Key of the latest era in the state journal.
+const LATEST_ERA_KEY: [u8; 12] = [b'l', b'a', b's', b't', 0, 0, 0, 0, 0, 0, 0, 0];
+
+/// Outcome of the consistency check.
+#[derive(Debug, PartialEq)]
+pub enum Consistency {
+    /// There is no chain in the databases yet.
+    Empty,
+    /// The best block is consistent.
+    Consistent(BlockNumber),
+    /// The best block was inconsistent and the chain was reverted.
+    RolledBack {
+        /// Best block before the check.
+        from: BlockNumber,
+        /// Last consistent block, the new best block.
+        to: BlockNumber,
+    },
+}
+
+/// Part of a canonical block found missing by `verify`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MissingPart {
+    /// No canonical hash is recorded for the block number.
+    Hash,
+    /// Block header.
+    Header,
+    /// Block body.
+    Body,
+    /// Block details.
+    Details,
+    /// Block receipts.
+    Receipts,
+    /// State trie root, only checked for blocks whose state is not pruned.
+    State,
+}
+
+impl fmt::Display for MissingPart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let part = match *self {
+            MissingPart::Hash => "canonical hash",
+            MissingPart::Header => "header",
+            MissingPart::Body => "body",
+            MissingPart::Details => "details",
+            MissingPart::Receipts => "receipts",
+            MissingPart::State => "state root",
+        };
+        f.write_str(part)
+    }
+}
+
+/// Checks that the header, body, details and state root of the best block are present, and
+/// otherwise reverts the chain to the last block which has all of them.
+pub fn check_and_repair(db: &Arc<KeyValueDB>, pruning: Algorithm) -> Result<Consistency, String> {
+    let best_hash = match db
+        .get(db::COL_EXTRA, b"best")
+        .map_err(|e| format!("{}", e))?
+    {
+        Some(best) => H256::from_slice(&best),
+        None => return Ok(Consistency::Empty),
+    };
+    let state = journaldb::new(db.clone(), pruning, db::COL_STATE);
+
+    let best_details: Option<BlockDetails> = db.read(db::COL_EXTRA, &best_hash);
+    let best = match best_details {
+        Some(details) => details.number,
+        None => match header(&**db, &best_hash) {
+            Some(header) => header.number(),
+            None => state.latest_era().ok_or("best block not found, db crashed")?,
+        },
+    };
+    let best_is_canonical = canonical_hash(&**db, best) == Some(best_hash);
+    if best_is_canonical && is_consistent(&**db, &*state, best) {
+        return Ok(Consistency::Consistent(best));
+    }
+
+    let oldest = ::std::cmp::max(
+        best.saturating_sub(MAX_ROLLBACK),
+        ::std::cmp::max(state.earliest_era().unwrap_or(0), frozen_blocks(&**db)),
+    );
+    let to = (oldest..best)
+        .rev()
+        .find(|number| is_consistent(&**db, &*state, *number))
+        .ok_or_else(|| {
+            format!(
+                "no consistent block found in #{}..#{}, db crashed, please clean and resync",
+                oldest, best
+            )
+        })?;
+    warn!(target: "db", "Best block #{} {} is incomplete, reverting to #{}", best, best_hash, to);
+    revert(&**db, to, best)?;
+    Ok(Consistency::RolledBack {
+        from: best,
+        to: to,
+    })
+}
+
+/// Reverts the canonical chain from `latest` down to `to`, removing the blocks above `to`.
+/// Blocks which were only partially written are removed as far as they exist.
+pub fn revert(db: &KeyValueDB, to: BlockNumber, latest: BlockNumber) -> Result<(), String> {
+    let frozen = frozen_blocks(db);
+    if to < frozen {
+        return Err(format!(
+            "can not revert to #{}, blocks below #{} are in the freezer",
+            to, frozen
+        ));
+    }
+    let target_hash = canonical_hash(db, to).ok_or("can not found block , db may crashed")?;
+    let target_details: Option<BlockDetails> = db.read(db::COL_EXTRA, &target_hash);
+    let mut target_details = target_details.ok_or("can not found block , db may crashed")?;
+    target_details.children.clear();
+
+    let mut batch = DBTransaction::with_capacity(10000);
+    for blk in (to + 1..=latest).rev() {
+        let hash = match canonical_hash(db, blk) {
+            Some(hash) => hash,
+            None => continue,
+        };
+        if let Some(header) = header(db, &hash) {
+            batch.delete(db::COL_STATE, &header.state_root());
+        }
+        // delete col_headers && col_bodies
+        batch.delete(db::COL_HEADERS, &hash);
+        batch.delete(db::COL_BODIES, &hash);
+        // delete col_extra
+        batch.delete(db::COL_EXTRA, &hash);
+        batch.delete(db::COL_EXTRA, &Key::<BlockDetails>::key(&hash));
+        batch.delete(db::COL_EXTRA, &Key::<BlockReceipts>::key(&hash));
+        batch.delete(db::COL_EXTRA, &Key::<H256>::key(&blk));
+        // flush dbtransaction
+        if blk % 1000 == 0 {
+            info!(target:"revert","#{}", blk);
+            db.write(batch)
+                .map_err(|e| format!("db revert failed for: {:?}", e))?;
+            batch = DBTransaction::with_capacity(10000);
+        }
+    }
+
+    // revert last block detail and update new best hash
+    batch.write(db::COL_EXTRA, &target_hash, &target_details);
+    batch.put(db::COL_EXTRA, b"best", &target_hash);
+    // reset state
+    batch.put(db::COL_STATE, &LATEST_ERA_KEY, &encode(&to));
+    db.write(batch)
+        .map_err(|e| format!("db revert failed for: {:?}", e))?;
+    db.flush_synced()
+        .map_err(|e| format!("db revert failed for: {:?}", e))
+}
+
+/// Walks the canonical chain from `from` up to the best block and returns every missing part
+/// found. `progress` is called with each block number checked. Only the canonical hash and
+/// details of the blocks moved to the freezer are checked.
+pub fn verify<F>(
+    db: &Arc<KeyValueDB>,
+    pruning: Algorithm,
+    from: BlockNumber,
+    mut progress: F,
+) -> Result<Vec<(BlockNumber, MissingPart)>, String>
+where
+    F: FnMut(BlockNumber),
+{
+    let best_hash = match db
+        .get(db::COL_EXTRA, b"best")
+        .map_err(|e| format!("{}", e))?
+    {
+        Some(best) => H256::from_slice(&best),
+        None => return Ok(Vec::new()),
+    };
+    let best_details: Option<BlockDetails> = db.read(db::COL_EXTRA, &best_hash);
+    let best = match best_details {
+        Some(details) => details.number,
+        None => {
+            header(&**db, &best_hash)
+                .ok_or("best block not found, db crashed")?
+                .number()
+        }
+    };
+    let state = journaldb::new(db.clone(), pruning, db::COL_STATE);
+    let earliest_state = state.earliest_era().unwrap_or(0);
+    let frozen = frozen_blocks(&**db);
+
+    let mut missing = Vec::new();
+    for number in from..=best {
+        progress(number);
+        let hash = match canonical_hash(&**db, number) {
+            Some(hash) => hash,
+            None => {
+                missing.push((number, MissingPart::Hash));
+                continue;
+            }
+        };
+        let details: Option<BlockDetails> = db.read(db::COL_EXTRA, &hash);
+        if details.is_none() {
+            missing.push((number, MissingPart::Details));
+        }
+        if number < frozen {
+            continue;
+        }
+        match header(&**db, &hash) {
+            Some(header) => {
+                let state_root = header.state_root();
+                if number >= earliest_state
+                    && *state_root != BLAKE2B_NULL_RLP
+                    && !state.contains(state_root)
+                {
+                    missing.push((number, MissingPart::State));
+                }
+            }
+            None => missing.push((number, MissingPart::Header)),
+        }
+        if db.get(db::COL_BODIES, &hash).ok().and_then(|b| b).is_none() {
+            missing.push((number, MissingPart::Body));
+        }
+        // genesis has no receipts
+        let receipts: Option<BlockReceipts> = db.read(db::COL_EXTRA, &hash);
+        if number > 0 && receipts.is_none() {
+            missing.push((number, MissingPart::Receipts));
+        }
+    }
+    Ok(missing)
+}
+
+/// Hash of the canonical block at `number`.
+fn canonical_hash(db: &KeyValueDB, number: BlockNumber) -> Option<H256> {
+    db.read(db::COL_EXTRA, &number)
+}
+
+fn header(db: &KeyValueDB, hash: &H256) -> Option<::header::Header> {
+    db.get(db::COL_HEADERS, hash)
+        .ok()
+        .and_then(|header| header)
+        .map(|header| encoded::Header::new(decompress(&header, blocks_swapper()).into_vec()))
+        .map(|header| header.decode())
+}
+
+/// Whether the canonical block at `number` has its header, body, details and state root.
+fn is_consistent(db: &KeyValueDB, state: &JournalDB, number: BlockNumber) -> bool {
+    let hash = match canonical_hash(db, number) {
+        Some(hash) => hash,
+        None => return false,
+    };
+    let details: Option<BlockDetails> = db.read(db::COL_EXTRA, &hash);
+    let has_body = db.get(db::COL_BODIES, &hash).ok().and_then(|b| b).is_some();
+    match header(db, &hash) {
+        Some(header) => {
+            let state_root = header.state_root();
+            has_body
+                && details.is_some()
+                && (*state_root == BLAKE2B_NULL_RLP || state.contains(state_root))
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blockchain::{BlockChain, BlockProvider, Config};
+    use blockchain::generator::{BlockBuilder, BlockGenerator};
+    use kvdb::MockDbRepository;
+
+    #[test]
+    fn reverts_to_last_consistent_block() {
+        let genesis = BlockBuilder::genesis();
+        let blocks = genesis.add_blocks(3);
+        let generator = BlockGenerator::new(vec![blocks]);
+        let db: Arc<KeyValueDB> = Arc::new(MockDbRepository::init(
+            db::DB_NAMES.iter().map(|name| name.to_string()).collect(),
+        ));
+        let bc = BlockChain::new(Config::default(), &genesis.last().encoded(), db.clone());
+        let mut batch = DBTransaction::new();
+        for block in generator {
+            bc.insert_block(&mut batch, &block.encoded(), vec![]);
+            bc.commit();
+        }
+        db.write(batch).unwrap();
+        let best = bc.best_block_number();
+        let best_hash = bc.best_block_hash();
+
+        // lose the body of the best block
+        let mut batch = DBTransaction::new();
+        batch.delete(db::COL_BODIES, &best_hash);
+        db.write(batch).unwrap();
+
+        assert_eq!(
+            check_and_repair(&db, Algorithm::Archive),
+            Ok(Consistency::RolledBack {
+                from: best,
+                to: best - 1,
+            })
+        );
+        assert_eq!(
+            check_and_repair(&db, Algorithm::Archive),
+            Ok(Consistency::Consistent(best - 1))
+        );
+    }
+
+    #[test]
+    fn verify_reports_missing_parts() {
+        let genesis = BlockBuilder::genesis();
+        let blocks = genesis.add_blocks(3);
+        let generator = BlockGenerator::new(vec![blocks]);
+        let db: Arc<KeyValueDB> = Arc::new(MockDbRepository::init(
+            db::DB_NAMES.iter().map(|name| name.to_string()).collect(),
+        ));
+        let bc = BlockChain::new(Config::default(), &genesis.last().encoded(), db.clone());
+        let mut batch = DBTransaction::new();
+        for block in generator {
+            bc.insert_block(&mut batch, &block.encoded(), vec![]);
+            bc.commit();
+        }
+        db.write(batch).unwrap();
+        let hash = bc.block_hash(2).unwrap();
+
+        let mut batch = DBTransaction::new();
+        batch.delete(db::COL_BODIES, &hash);
+        db.write(batch).unwrap();
+
+        let mut checked = Vec::new();
+        let missing = verify(&db, Algorithm::Archive, 1, |number| checked.push(number)).unwrap();
+        assert_eq!(checked, vec![1, 2, 3]);
+        assert_eq!(missing, vec![(2, MissingPart::Body)]);
+    }
+}
@@ -0,0 +1,259 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Secondary index of the logs of canonical blocks.
+//!
+//! Maps the address emitting a log, alone and together with the first topic of the log, to
+//! the numbers of the blocks holding such logs, so that looking up the logs of a contract over
+//! a large range does not scan the blooms of every block. Block numbers are grouped by
+//! `GROUP_SIZE`: a key is the address, the optional topic and the big endian number of the
+//! group, and its value the sorted rlp list of the matching blocks of the group.
+//!
+//! The index covers the canonical blocks `first..next`, recorded with the hash of the block
+//! `next - 1` under `RANGE_KEY`. It is built in the background, forward as blocks are imported
+//! and backward to the genesis for the blocks imported before it was enabled. Blocks imported
+//! while it is disabled are indexed forward once it is enabled again. On a reorganization the
+//! range is rewound to the common ancestor. The entries of the retracted blocks are kept,
+//! lookups only return candidate blocks whose logs are matched afterwards.
+
+use std::cmp;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+use aion_types::{Address, H256};
+use byteorder::{BigEndian, ByteOrder};
+use parking_lot::Mutex;
+use kvdb::{DBTransaction, KeyValueDB};
+use rlp::{decode_list, encode_list, Rlp, RlpStream};
+use blockchain::{BlockChain, BlockProvider};
+use db;
+use header::BlockNumber;
+
+/// Number of consecutive blocks whose numbers are stored under the same key.
+pub const GROUP_SIZE: BlockNumber = 4096;
+
+/// Key of the indexed range of blocks.
+const RANGE_KEY: &'static [u8] = b"range";
+
+/// Indexed blocks and the hash of the last of them.
+struct IndexedRange {
+    first: BlockNumber,
+    next: BlockNumber,
+    head: H256,
+}
+
+fn read_range(db: &KeyValueDB) -> Option<IndexedRange> {
+    let bytes = db.get(db::COL_LOG_INDEX, RANGE_KEY).ok().and_then(|range| range)?;
+    let rlp = Rlp::new(&bytes);
+    Some(IndexedRange {
+        first: rlp.val_at(0),
+        next: rlp.val_at(1),
+        head: rlp.val_at(2),
+    })
+}
+
+fn write_range(batch: &mut DBTransaction, range: &IndexedRange) {
+    let mut stream = RlpStream::new_list(3);
+    stream
+        .append(&range.first)
+        .append(&range.next)
+        .append(&range.head);
+    batch.put(db::COL_LOG_INDEX, RANGE_KEY, &stream.out());
+}
+
+fn key(address: &Address, topic: Option<&H256>, group: BlockNumber) -> Vec<u8> {
+    let mut key = Vec::with_capacity(72);
+    key.extend_from_slice(address);
+    if let Some(topic) = topic {
+        key.extend_from_slice(topic);
+    }
+    let mut group_bytes = [0u8; 8];
+    BigEndian::write_u64(&mut group_bytes, group);
+    key.extend_from_slice(&group_bytes);
+    key
+}
+
+fn group_blocks(db: &KeyValueDB, key: &[u8]) -> Vec<BlockNumber> {
+    db.get(db::COL_LOG_INDEX, key)
+        .ok()
+        .and_then(|blocks| blocks)
+        .map_or_else(Vec::new, |blocks| decode_list(&blocks))
+}
+
+/// Index of the blocks holding the logs of an address, or of an address and first topic.
+pub struct LogIndex {
+    db: Arc<KeyValueDB>,
+    update_lock: Mutex<()>,
+}
+
+impl LogIndex {
+    /// Create the index stored in the log index column of `db`.
+    pub fn new(db: Arc<KeyValueDB>) -> LogIndex {
+        LogIndex {
+            db: db,
+            update_lock: Mutex::new(()),
+        }
+    }
+
+    /// Blocks covered by the index and the hash of the last of them. The last blocks may have
+    /// been retracted since they were indexed, if that hash is no longer canonical.
+    pub fn range(&self) -> Option<(Range<BlockNumber>, H256)> {
+        read_range(&*self.db).map(|range| (range.first..range.next, range.head))
+    }
+
+    /// Index at most `max_blocks` blocks, the new canonical blocks first and then the older
+    /// ones. Returns the number of blocks indexed, none if an update is already running.
+    pub fn update(&self, chain: &BlockChain, max_blocks: u64) -> Result<u64, String> {
+        let _lock = match self.update_lock.try_lock() {
+            Some(lock) => lock,
+            None => return Ok(0),
+        };
+        let best_number = chain.best_block_number();
+        let best_hash = match chain.block_hash(best_number) {
+            Some(hash) => hash,
+            None => return Ok(0),
+        };
+        let new_range = || {
+            IndexedRange {
+                first: best_number + 1,
+                next: best_number + 1,
+                head: best_hash,
+            }
+        };
+        let mut range = read_range(&*self.db).unwrap_or_else(&new_range);
+        let mut changed = false;
+
+        if chain.block_hash(range.next - 1) != Some(range.head) {
+            // the indexed head was retracted, restart from the common ancestor
+            let ancestor = chain.tree_route(range.head, best_hash).and_then(|route| {
+                chain
+                    .block_number(&route.ancestor)
+                    .map(|number| (number, route.ancestor))
+            });
+            match ancestor {
+                Some((number, hash)) => {
+                    range.next = number + 1;
+                    range.first = cmp::min(range.first, range.next);
+                    range.head = hash;
+                }
+                None => range = new_range(),
+            }
+            changed = true;
+        }
+
+        let mut entries: HashMap<Vec<u8>, Vec<BlockNumber>> = HashMap::new();
+        let mut indexed = 0;
+        while range.next <= best_number && indexed < max_blocks {
+            match Self::index_block(chain, range.next, &mut entries) {
+                Some(hash) => range.head = hash,
+                None => break,
+            }
+            range.next += 1;
+            indexed += 1;
+        }
+        while range.first > 0 && indexed < max_blocks {
+            if Self::index_block(chain, range.first - 1, &mut entries).is_none() {
+                break;
+            }
+            range.first -= 1;
+            indexed += 1;
+        }
+
+        if !changed && indexed == 0 {
+            return Ok(0);
+        }
+        let mut batch = DBTransaction::new();
+        for (key, mut blocks) in entries {
+            blocks.extend(group_blocks(&*self.db, &key));
+            blocks.sort();
+            blocks.dedup();
+            batch.put(db::COL_LOG_INDEX, &key, &encode_list::<BlockNumber, _>(&blocks));
+        }
+        write_range(&mut batch, &range);
+        self.db
+            .write(batch)
+            .map_err(|e| format!("Low level database error: {}", e))?;
+        Ok(indexed)
+    }
+
+    /// Add the logs of the canonical block `number` to `entries`. Returns the hash of the
+    /// block, or `None` if the block or its receipts are not available.
+    fn index_block(
+        chain: &BlockChain,
+        number: BlockNumber,
+        entries: &mut HashMap<Vec<u8>, Vec<BlockNumber>>,
+    ) -> Option<H256>
+    {
+        let hash = chain.block_hash(number)?;
+        // the genesis has no receipts
+        if number == 0 {
+            return Some(hash);
+        }
+        let receipts = chain.block_receipts(&hash)?;
+        let group = number / GROUP_SIZE;
+        for log in receipts.receipts.iter().flat_map(|receipt| receipt.logs()) {
+            entries
+                .entry(key(&log.address, None, group))
+                .or_insert_with(Vec::new)
+                .push(number);
+            if let Some(topic) = log.topics.get(0) {
+                entries
+                    .entry(key(&log.address, Some(topic), group))
+                    .or_insert_with(Vec::new)
+                    .push(number);
+            }
+        }
+        Some(hash)
+    }
+
+    /// Blocks between `from` and `to`, inclusive, which may hold logs of one of `addresses`,
+    /// with one of `topics` as first topic if given. The blocks must be covered by the index.
+    pub fn blocks(
+        &self,
+        addresses: &[Address],
+        topics: Option<&[H256]>,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> Vec<BlockNumber>
+    {
+        let mut keys = Vec::new();
+        for group in from / GROUP_SIZE..to / GROUP_SIZE + 1 {
+            for address in addresses {
+                match topics {
+                    Some(topics) => {
+                        for topic in topics {
+                            keys.push(key(address, Some(topic), group));
+                        }
+                    }
+                    None => keys.push(key(address, None, group)),
+                }
+            }
+        }
+        let mut blocks = keys
+            .iter()
+            .flat_map(|key| group_blocks(&*self.db, key))
+            .filter(|number| *number >= from && *number <= to)
+            .collect::<Vec<_>>();
+        blocks.sort();
+        blocks.dedup();
+        blocks
+    }
+}
@@ -28,15 +28,23 @@ mod blockchain;
 mod cache;
 mod config;
 mod extras;
+mod freezer;
 mod import_route;
+mod integrity;
+mod log_index;
 mod update;
 
 #[cfg(test)]
 pub mod generator;
 
-pub use self::blockchain::{BlockProvider, BlockChain};
+pub use self::blockchain::{BlockProvider, BlockChain, ReorgStats};
 pub use self::cache::CacheSize;
 pub use self::config::Config;
-pub use self::extras::{BlockReceipts, BlockDetails, TransactionAddress};
+pub use self::freezer::{frozen_blocks, Freezer, FREEZER_DIR};
+pub use self::extras::{BlockReceipts, BlockDetails, TransactionAddress, OrphanedBlock};
 pub use self::import_route::ImportRoute;
+pub use self::integrity::{
+    check_and_repair, revert, verify, Consistency, MissingPart, MAX_ROLLBACK,
+};
+pub use self::log_index::LogIndex;
 pub use types::tree_route::TreeRoute;
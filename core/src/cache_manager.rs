@@ -54,6 +54,12 @@ where T: Eq + Hash
         }
     }
 
+    /// Changes the preferred and maximum size of the cache, applied on the next collection.
+    pub fn set_cache_size(&mut self, pref_cache_size: usize, max_cache_size: usize) {
+        self.pref_cache_size = pref_cache_size;
+        self.max_cache_size = max_cache_size;
+    }
+
     pub fn note_used(&mut self, id: T) {
         if !self.cache_usage[0].contains(&id) {
             if let Some(c) = self
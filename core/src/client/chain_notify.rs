@@ -58,4 +58,9 @@ pub trait ChainNotify: Send + Sync {
     fn transactions_received(&self, _transactions: &[Bytes]) {
         // does nothing by default
     }
+
+    /// fires when new double-sign evidence is found or received. Evidence is RLP encoded.
+    fn double_sign_evidence(&self, _evidence: Bytes) {
+        // does nothing by default
+    }
 }
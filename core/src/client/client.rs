@@ -21,8 +21,10 @@
  ******************************************************************************/
 
 use super::super::transaction::UnverifiedTransaction;
+use std::cmp;
 use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::str::FromStr;
+use std::ops::Range;
 use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
 use std::sync::{Arc, Weak};
 use std::time::Duration;
@@ -33,22 +35,28 @@ use blake2b::blake2b;
 use bytes::Bytes;
 use journaldb;
 use kvdb::{DBTransaction, DBValue, KeyValueDB};
-use trie::{Trie, TrieFactory, TrieSpec};
+use trie::{self, Trie, TrieFactory, TrieSpec};
 use util_error::UtilError;
 
 // other
-use aion_types::{Address, H128, H256, H264, U256};
+use aion_types::{Address, H128, H256, U256, U512};
 use block::*;
-use blockchain::{BlockChain, BlockProvider, ImportRoute, TransactionAddress, TreeRoute};
+use blockchain::{
+    BlockChain, BlockProvider, ImportRoute, LogIndex, OrphanedBlock, ReorgStats, TransactionAddress,
+    TreeRoute,
+};
 use client::ancient_import::AncientVerifier;
 use client::Error as ClientError;
 use client::{
     BlockChainClient, BlockId, BlockImportError, CallAnalytics, ChainNotify, ClientConfig,
-    MiningBlockChainClient, ProvingBlockChainClient, PruningInfo, TransactionId,
+    DatabaseDurability, MemoryBudget, MemoryUsage, MiningBlockChainClient,
+    ProvingBlockChainClient, PruningInfo, StateOverride, TransactionId,
 };
 use encoded;
 use engines::{EpochTransition, EthEngine};
+use engines::pow_equihash_engine::calculate_stake;
 use error::{BlockError, CallError, ExecutionError, ImportError, ImportResult};
+use evidence::{DoubleSignEvidence, EvidenceError, EvidencePool, EVIDENCE_WINDOW};
 use executive::{contract_address, Executed, Executive};
 use factory::{Factories, VmFactory};
 use header::{BlockNumber, Header, Seal, SealType};
@@ -63,6 +71,7 @@ use service::ClientIoMessage;
 use spec::Spec;
 use state::{self, State};
 use state_db::StateDB;
+use state_diff::StateDiff;
 use transaction::{
     Action, LocalizedTransaction, PendingTransaction, SignedTransaction, Transaction,
 };
@@ -71,6 +80,7 @@ use verification;
 use verification::queue::BlockQueue;
 use verification::{PreverifiedBlock, Verifier};
 use views::BlockView;
+use vms::constants::{GAS_CALL_MAX, GAS_CREATE_MAX};
 use vms::{EnvInfo, LastHashes};
 
 // re-export
@@ -82,6 +92,10 @@ pub use verification::queue::QueueInfo as BlockQueueInfo;
 use_contract!(registry, "Registry", "res/contracts/registrar.json");
 
 const MIN_HISTORY_SIZE: u64 = 8;
+// blocks moved to the freezer at most on each tick
+const MAX_FROZEN_PER_TICK: u64 = 10_000;
+// blocks added to the log index at most on each tick
+const MAX_INDEXED_PER_TICK: u64 = 10_000;
 
 /// Report on the status of a client.
 #[derive(Default, Clone, Debug, Eq, PartialEq)]
@@ -143,6 +157,9 @@ pub struct Client {
     ancient_verifier: Mutex<Option<AncientVerifier>>,
     registrar: registry::Registry,
     registrar_address: Option<Address>,
+    evidence: RwLock<EvidencePool>,
+    memory_budget: RwLock<MemoryBudget>,
+    log_index: Option<LogIndex>,
 }
 
 impl Client {
@@ -168,7 +185,17 @@ impl Client {
             accountdb: Default::default(),
         };
 
-        let journal_db = journaldb::new(db.clone(), config.pruning, ::db::COL_STATE);
+        if config.pruning_checkpoints.is_some()
+            && config.pruning != journaldb::Algorithm::RefCounted
+        {
+            warn!(target: "client", "State checkpoints are only kept with refcounted pruning");
+        }
+        let journal_db = journaldb::new_with_checkpoints(
+            db.clone(),
+            config.pruning,
+            ::db::COL_STATE,
+            config.pruning_checkpoints,
+        );
         let mut state_db = StateDB::new(journal_db, config.state_cache_size);
         if state_db.journal_db().is_empty() {
             // Sets the correct state root.
@@ -226,6 +253,19 @@ impl Client {
             trace!(target: "client", "Found registrar at {}", addr);
         }
 
+        let memory_budget = MemoryBudget {
+            blockchain: config.blockchain.max_cache_size,
+            state: config.state_cache_size,
+            db: config.db_cache_size.map(|mb| mb * 1024 * 1024),
+            queue: config.queue.max_mem_use,
+            tx_queue: miner.tx_queue_memory_limit(),
+        };
+
+        let log_index = match config.log_index {
+            true => Some(LogIndex::new(db.clone())),
+            false => None,
+        };
+
         let client = Arc::new(Client {
             enabled: AtomicBool::new(true),
             chain: RwLock::new(chain),
@@ -246,6 +286,9 @@ impl Client {
             ancient_verifier: Mutex::new(None),
             registrar: registry::Registry::default(),
             registrar_address,
+            evidence: RwLock::new(EvidencePool::default()),
+            memory_budget: RwLock::new(memory_budget),
+            log_index: log_index,
         });
 
         // prune old states.
@@ -524,6 +567,7 @@ impl Client {
         }
 
         let max_blocks_to_import = 4;
+        let mut new_evidence = Vec::new();
         let (
             imported_blocks,
             import_results,
@@ -563,6 +607,9 @@ impl Client {
                         trace!(target: "block", "commit_block() header: {:?}", header.hash());
                         let route = self.commit_block(closed_block, &header, &block.bytes);
                         import_results.push(route);
+                        if let Some(evidence) = self.evidence.write().note_header(header) {
+                            new_evidence.push(evidence);
+                        }
 
                         self.report.write().accrue_block(&block);
                     }
@@ -573,6 +620,7 @@ impl Client {
 
             let imported = imported_blocks.len();
             let invalid_blocks = invalid_blocks.into_iter().collect::<Vec<H256>>();
+            self.evidence.write().prune(self.chain.read().best_block_number());
 
             if !invalid_blocks.is_empty() {
                 self.block_queue.mark_as_bad(&invalid_blocks);
@@ -616,10 +664,30 @@ impl Client {
             }
         }
 
-        self.db.read().flush().expect("DB flush failed.");
+        for evidence in new_evidence {
+            self.report_double_sign(evidence);
+        }
+
+        self.flush_db(imported > 0);
         imported
     }
 
+    /// Handle new double-sign evidence: pass it on to the network and have the miner hand it
+    /// to the double-sign contract, which slashes the offender.
+    fn report_double_sign(&self, evidence: DoubleSignEvidence) {
+        warn!(
+            target: "client",
+            "Staker {} signed two blocks at #{}: {} and {}",
+            evidence.offender(),
+            evidence.number(),
+            evidence.headers().0.hash(),
+            evidence.headers().1.hash()
+        );
+        let bytes = ::rlp::encode(&evidence).into_vec();
+        self.notify(|notify| notify.double_sign_evidence(bytes.clone()));
+        self.miner.report_double_sign(self, &evidence);
+    }
+
     /// Import a block with transaction receipts.
     /// The block is guaranteed to be the next best blocks in the first block sequence.
     /// Does no sealing or transaction validation.
@@ -903,9 +971,11 @@ impl Client {
 
         // prune all ancient eras until we're below the memory target,
         // but have at least the minimum number of states.
+        // refcounted pruning keeps exactly the history window regardless of memory.
         loop {
             let needs_pruning = state_db.journal_db().is_pruned()
-                && state_db.journal_db().journal_size() >= self.config.history_mem;
+                && (self.config.pruning == journaldb::Algorithm::RefCounted
+                    || state_db.journal_db().journal_size() >= self.config.history_mem);
 
             if !needs_pruning {
                 break;
@@ -974,8 +1044,16 @@ impl Client {
         self.block_header(id).and_then(|header| {
             let db = self.state_db.read().boxed_clone();
 
-            // early exit for pruned blocks
-            if db.is_pruned() && self.pruning_info().earliest_state > block_number {
+            // early exit for pruned blocks, unless kept as a checkpoint
+            let is_pruned = {
+                let journal_db = db.journal_db();
+                journal_db.is_pruned()
+                    && journal_db.earliest_era().unwrap_or(0) > block_number
+                    && journal_db
+                        .checkpoint_interval()
+                        .map_or(true, |interval| block_number % interval != 0)
+            };
+            if is_pruned {
                 return None;
             }
 
@@ -1033,13 +1111,48 @@ impl Client {
 
     /// Tick the client.
     // TODO: manage by real events.
-    pub fn tick(&self) { self.check_garbage(); }
+    pub fn tick(&self) {
+        self.check_garbage();
+        self.freeze_blocks();
+        self.index_logs();
+    }
 
     fn check_garbage(&self) {
         self.chain.read().collect_garbage();
         self.block_queue.collect_garbage();
     }
 
+    fn freeze_blocks(&self) {
+        let chain = self.chain.read().clone();
+        if let Err(e) = chain.freeze(MAX_FROZEN_PER_TICK) {
+            warn!(target: "client", "Failed to move old blocks to the freezer: {}", e);
+        }
+    }
+
+    fn index_logs(&self) {
+        if let Some(ref log_index) = self.log_index {
+            let chain = self.chain.read().clone();
+            match log_index.update(&chain, MAX_INDEXED_PER_TICK) {
+                Ok(0) => {}
+                Ok(indexed) => debug!(target: "client", "Indexed the logs of {} blocks", indexed),
+                Err(e) => warn!(target: "client", "Failed to index logs: {}", e),
+            }
+        }
+    }
+
+    /// End of the part of the indexed `range` which is still canonical. Blocks above the common
+    /// ancestor of the indexed `head` and the best block were retracted since they were indexed,
+    /// and the blocks replacing them are not indexed yet.
+    fn indexed_end(chain: &BlockChain, range: Range<BlockNumber>, head: H256) -> BlockNumber {
+        if chain.block_hash(range.end - 1) == Some(head) {
+            return range.end;
+        }
+        chain
+            .tree_route(head, chain.best_block_hash())
+            .and_then(|route| chain.block_number(&route.ancestor))
+            .map_or(range.start, |number| cmp::min(range.end, number + 1))
+    }
+
     /// Ask the client what the history parameter is.
     pub fn pruning_history(&self) -> u64 { self.history }
 
@@ -1147,6 +1260,17 @@ impl Client {
         }
     }
 
+    /// Flush buffered db writes. With the safe durability profile the writes are synced to disk
+    /// whenever the best block may have changed.
+    fn flush_db(&self, best_changed: bool) {
+        let db = self.db.read();
+        let result = match self.config.db_durability {
+            DatabaseDurability::Safe if best_changed => db.flush_synced(),
+            _ => db.flush(),
+        };
+        result.expect("DB flush failed.");
+    }
+
     /// revert database to blocknumber, only used in revert db
     pub fn revert_block(&self, to: BlockNumber) -> Result<(), String> {
         let state_db = self.state_db.read().boxed_clone();
@@ -1176,85 +1300,7 @@ impl Client {
             ));
         }
 
-        let target_block_header = self
-            .block(BlockId::Number(to))
-            .expect("can not found block , db may crashed")
-            .header()
-            .decode();
-        let mut batch = DBTransaction::with_capacity(10000);
-        let mut target_block_details = self
-            .chain
-            .read()
-            .block_details(&target_block_header.hash())
-            .expect("can not found block , db may crashed");
-        target_block_details.children.clear();
-
-        for blk in (to + 1..=latest).rev() {
-            // delete col_headers && col_boides
-            let hash = self
-                .block_hash(BlockId::Number(blk))
-                .expect("can not found block , db may crashed");
-            batch.delete(::db::COL_HEADERS, &hash);
-            batch.delete(::db::COL_BODIES, &hash);
-            // delete col_extra
-            let mut key_blk_detail = H264::default();
-            let mut key_blk_recipts = H264::default();
-            key_blk_detail[0] = 0u8;
-            key_blk_recipts[0] = 4u8;
-            (*key_blk_detail)[1..].clone_from_slice(&hash);
-            (*key_blk_recipts)[1..].clone_from_slice(&hash);
-            let mut blk_number = [0u8; 5];
-            blk_number[0] = 1u8;
-            blk_number[1] = (blk >> 24) as u8;
-            blk_number[2] = (blk >> 16) as u8;
-            blk_number[3] = (blk >> 8) as u8;
-            blk_number[4] = blk as u8;
-            batch.delete(::db::COL_EXTRA, &hash);
-            batch.delete(::db::COL_EXTRA, &key_blk_detail);
-            batch.delete(::db::COL_EXTRA, &key_blk_recipts);
-            batch.delete(::db::COL_EXTRA, &blk_number);
-            let header = self
-                .chain
-                .read()
-                .block_header(&hash)
-                .expect("can not found block , db may crashed");
-            let state_root = header.state_root();
-            batch.delete(::db::COL_STATE, &state_root);
-            // flush dbtransaction
-            if blk % 1000 == 0 {
-                info!(target:"revert","#{}", blk);
-                self.db
-                    .write()
-                    .write(batch.clone())
-                    .map_err(|e| format!("db revert failed for: {:?}", e))?;
-                batch.ops.clear();
-            }
-        }
-
-        let new_block = to;
-        let new_best_hash = self
-            .block_hash(BlockId::Number(new_block))
-            .expect("can not found block , db may crashed");
-        // revert last block detail
-        {
-            let details = target_block_details;
-            use db::Writable;
-            batch.write(::db::COL_EXTRA, &new_best_hash, &details);
-        }
-        // update new best hash
-        let new_best_hash = self
-            .block_hash(BlockId::Number(new_block))
-            .expect("can not found block , db may crashed");
-        batch.put(::db::COL_EXTRA, b"best", &new_best_hash);
-        // reset state
-        let latest_era_key = [b'l', b'a', b's', b't', 0, 0, 0, 0, 0, 0, 0, 0];
-        batch.put(::db::COL_STATE, &latest_era_key, &encode(&new_block));
-        self.db
-            .write()
-            .write(batch)
-            .map_err(|e| format!("db revert failed for: {:?}", e))?;
-
-        Ok(())
+        ::blockchain::revert(&**self.db.read(), to, latest)
     }
 }
 
@@ -1279,6 +1325,7 @@ impl BlockChainClient for Client {
     fn call_many(
         &self,
         transactions: &[(SignedTransaction, CallAnalytics)],
+        overrides: &StateOverride,
         block: BlockId,
     ) -> Result<Vec<Executed>, CallError>
     {
@@ -1287,6 +1334,7 @@ impl BlockChainClient for Client {
 
         // that's just a copy of the state.
         let mut state = self.state_at(block).ok_or(CallError::StatePruned)?;
+        apply_state_override(&mut state, overrides).map_err(ExecutionError::from)?;
         let mut results = Vec::with_capacity(transactions.len());
         let machine = self.engine.machine();
 
@@ -1300,42 +1348,91 @@ impl BlockChainClient for Client {
     }
 
     fn estimate_gas(&self, t: &SignedTransaction, block: BlockId) -> Result<U256, CallError> {
-        let (mut upper, max_upper, env_info) = {
-            let mut env_info = self.env_info(block).ok_or(CallError::StatePruned)?;
-            let init = env_info.gas_limit;
-            let max = init * U256::from(10);
-            env_info.gas_limit = max;
-            (init, max, env_info)
-        };
-
+        let env_info = self.env_info(block).ok_or(CallError::StatePruned)?;
         // that's just a copy of the state.
         let original_state = self.state_at(block).ok_or(CallError::StatePruned)?;
         let sender = t.sender();
+        let author = env_info.author;
+
+        let lower = t.gas_required();
+        // The transaction can't use more gas than allowed for its action type, nor more than fits
+        // in a block.
+        let max_gas = match t.action {
+            Action::Create => GAS_CREATE_MAX,
+            Action::Call(_) => GAS_CALL_MAX,
+        };
+        let mut upper = cmp::min(env_info.gas_limit, max_gas);
+        if upper < lower {
+            return Err(ExecutionError::NotEnoughBaseGas {
+                required: lower,
+                got: upper,
+            }
+            .into());
+        }
+        // Nor more than the sender can pay for.
+        if !t.gas_price.is_zero() {
+            let balance = original_state
+                .balance(&sender)
+                .map_err(ExecutionError::from)?;
+            let affordable = if balance > t.value {
+                (balance - t.value) / t.gas_price
+            } else {
+                U256::zero()
+            };
+            if affordable < lower {
+                return Err(ExecutionError::NotEnoughCash {
+                    required: lower.full_mul(t.gas_price) + U512::from(t.value),
+                    got: U512::from(balance),
+                }
+                .into());
+            }
+            upper = cmp::min(upper, affordable);
+        }
 
-        let cond = |gas| {
+        let execute = |gas: U256| -> Result<Executed, CallError> {
             let mut tx = t.as_unsigned().clone();
             tx.gas = gas;
             let tx = tx.fake_sign(sender);
 
             let mut state = original_state.clone();
-            Ok(Executive::new(&mut state, &env_info, self.engine.machine())
-                .transact_virtual(&tx, false)
-                .map(|r| r.exception.as_str() == "")
-                .unwrap_or(false))
+            let mut executed = Executive::new(&mut state, &env_info, self.engine.machine())
+                .transact_virtual(&tx, false)?;
+            executed.state_diff = Some(
+                state
+                    .diff_from(original_state.clone())
+                    .map_err(ExecutionError::from)?,
+            );
+            Ok(executed)
         };
 
-        if !cond(upper)? {
-            upper = max_upper;
-            if !cond(upper)? {
-                trace!(target: "estimate_gas", "estimate_gas failed with {}", upper);
-                let err = ExecutionError::Internal(format!(
-                    "Requires higher than upper limit of {}",
-                    upper
-                ));
-                return Err(err.into());
-            }
+        let reference = execute(upper)?;
+        if !reference.exception.is_empty() {
+            trace!(target: "estimate_gas", "estimate_gas failed with {}", upper);
+            return Err(CallError::Failed {
+                exception: reference.exception,
+                gas_used: reference.gas_used,
+                output: reference.output,
+            });
         }
-        let lower = t.gas_required();
+
+        // A gas limit is enough if the transaction succeeds and has the same effects as with the
+        // upper limit. Subcalls may fail with less gas while the transaction still succeeds.
+        let cond = |gas| -> Result<bool, CallError> {
+            let executed = match execute(gas) {
+                Ok(executed) => executed,
+                Err(CallError::Execution(_)) => return Ok(false),
+                Err(e) => return Err(e),
+            };
+            Ok(executed.exception.is_empty()
+                && executed.logs == reference.logs
+                && executed.output == reference.output
+                && same_state_changes(
+                    executed.state_diff.as_ref(),
+                    reference.state_diff.as_ref(),
+                    &[sender, author],
+                ))
+        };
+
         if cond(lower)? {
             trace!(target: "estimate_gas", "estimate_gas succeeded with {}", lower);
             return Ok(lower);
@@ -1359,7 +1456,8 @@ impl BlockChainClient for Client {
             Ok(upper)
         }
 
-        // binary chop to non-excepting call with gas somewhere between 21000 and block gas limit
+        // binary chop to non-excepting call with gas somewhere between the base gas and the upper
+        // limit
         trace!(target: "estimate_gas", "estimate_gas chopping {} .. {}", lower, upper);
         binary_chop(lower, upper, cond)
     }
@@ -1451,8 +1549,10 @@ impl BlockChainClient for Client {
     ) -> U256
     {
         let engine = &*self.engine;
+        // the difficulty is for the next block on top of the best block.
+        let number = self.chain.read().best_block_number() + 1;
         engine.calculate_difficulty(
-            1u8,
+            engine.machine().difficulty_version(number),
             parent_header.clone().map(|header| header.decode()).as_ref(),
             grand_parent_header
                 .clone()
@@ -1870,6 +1970,37 @@ impl BlockChainClient for Client {
 
     fn clear_bad(&self) { self.block_queue.clear_bad(); }
 
+    fn bad_blocks(&self) -> Vec<H256> { self.block_queue.bad_items() }
+
+    fn orphaned_blocks(&self, from: BlockNumber, to: BlockNumber) -> Vec<OrphanedBlock> {
+        self.chain.read().orphaned_blocks(from, to)
+    }
+
+    fn reorg_stats(&self) -> ReorgStats { self.chain.read().reorg_stats() }
+
+    fn import_evidence(&self, evidence: Bytes) -> Result<bool, EvidenceError> {
+        let evidence = DoubleSignEvidence::from_rlp(&evidence)?;
+        let best = self.chain.read().best_block_number();
+        // headers above the best block or signed without stake can be forged at no cost
+        if evidence.number() > best {
+            return Err(EvidenceError::FutureNumber(evidence.number(), best));
+        }
+        if calculate_stake(&evidence.offender(), &self.state()) == 0 {
+            return Err(EvidenceError::NoStake);
+        }
+        let is_new = {
+            let mut pool = self.evidence.write();
+            pool.prune(best);
+            evidence.number() + EVIDENCE_WINDOW >= best && pool.insert(evidence.clone())
+        };
+        if is_new {
+            self.report_double_sign(evidence);
+        }
+        Ok(is_new)
+    }
+
+    fn double_sign_evidence(&self) -> Vec<DoubleSignEvidence> { self.evidence.read().evidence() }
+
     fn chain_info(&self) -> BlockChainInfo {
         let mut chain_info = self.chain.read().chain_info();
         chain_info.pending_total_difficulty =
@@ -1891,19 +2022,54 @@ impl BlockChainClient for Client {
         };
 
         let chain = self.chain.read();
-        let blocks = filter
-            .bloom_possibilities()
-            .iter()
-            .map(move |bloom| chain.blocks_with_bloom(bloom, from, to))
-            .flat_map(|m| m)
-            // remove duplicate elements
-            .collect::<HashSet<u64>>()
-            .into_iter()
-            .collect::<Vec<u64>>();
+        let mut blocks = HashSet::new();
+        // parts of the range which are not in the log index are looked up in the blooms
+        let mut unindexed = vec![(from, to)];
+        let indexed = match (self.log_index.as_ref(), filter.address.as_ref()) {
+            (Some(log_index), Some(addresses)) if !addresses.is_empty() => {
+                log_index
+                    .range()
+                    .map(|(range, head)| range.start..Self::indexed_end(&chain, range, head))
+                    .filter(|range| range.start < range.end)
+                    .map(|range| (log_index, addresses, range))
+            }
+            _ => None,
+        };
+        if let Some((log_index, addresses, range)) = indexed {
+            let (start, end) = (cmp::max(from, range.start), cmp::min(to, range.end - 1));
+            if start <= end {
+                let topics = match filter.topics.get(0) {
+                    Some(&Some(ref topics)) if !topics.is_empty() => Some(&topics[..]),
+                    _ => None,
+                };
+                blocks.extend(log_index.blocks(addresses, topics, start, end));
+                unindexed.clear();
+                if from < start {
+                    unindexed.push((from, start - 1));
+                }
+                if end < to {
+                    unindexed.push((end + 1, to));
+                }
+            }
+        }
+        let blooms = filter.bloom_possibilities();
+        for (from, to) in unindexed {
+            for bloom in &blooms {
+                blocks.extend(chain.blocks_with_bloom(bloom, from, to));
+            }
+        }
 
-        self.chain
-            .read()
-            .logs(blocks, |entry| filter.matches(entry), filter.limit)
+        // the newest `offset` logs are collected too and dropped
+        let offset = filter.offset.unwrap_or(0);
+        let limit = filter.limit.map(|limit| limit.saturating_add(offset));
+        let mut logs = chain.logs(
+            blocks.into_iter().collect(),
+            |entry| filter.matches(entry),
+            limit,
+        );
+        let len = logs.len();
+        logs.truncate(len.saturating_sub(offset));
+        logs
     }
 
     fn last_hashes(&self) -> LastHashes {
@@ -1937,15 +2103,57 @@ impl BlockChainClient for Client {
     }
 
     fn pruning_info(&self) -> PruningInfo {
+        let state_db = self.state_db.read();
+        let journal_db = state_db.journal_db();
+        let earliest_state = journal_db.earliest_era().unwrap_or(0);
+        let retained = journal_db
+            .latest_era()
+            .map_or(0, |latest| (latest + 1).saturating_sub(earliest_state));
         PruningInfo {
             earliest_chain: self.chain.read().first_block_number().unwrap_or(1),
-            earliest_state: self
-                .state_db
-                .read()
-                .journal_db()
-                .earliest_era()
-                .unwrap_or(0),
+            earliest_state: earliest_state,
+            checkpoint_interval: journal_db.checkpoint_interval(),
+            state_size: self.db.read().disk_size(::db::COL_STATE),
+            pruning_backlog: if journal_db.is_pruned() {
+                retained.saturating_sub(self.history)
+            } else {
+                0
+            },
+        }
+    }
+
+    fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            budget: *self.memory_budget.read(),
+            blockchain: self.chain.read().cache_size().total(),
+            state: self.state_db.read().mem_used(),
+            queue: self.block_queue.queue_info().mem_used,
+            tx_queue: self.miner.tx_queue_memory_usage(),
+        }
+    }
+
+    fn set_memory_budget(&self, total: usize) -> MemoryBudget {
+        let mut current = self.memory_budget.write();
+        let budget = current.resized(total);
+        self.chain
+            .read()
+            .set_cache_size(budget.blockchain * 3 / 4, budget.blockchain);
+        self.state_db.write().set_cache_size(budget.state);
+        self.block_queue.set_max_mem_use(budget.queue);
+        if let Some(tx_queue) = budget.tx_queue {
+            self.miner.set_tx_queue_memory_limit(tx_queue);
         }
+        *current = budget;
+        self.check_garbage();
+        info!(
+            target: "client",
+            "Resized caches: blockchain {} MB, state {} MB, queue {} MB, transaction queue {} MB",
+            budget.blockchain / (1024 * 1024),
+            budget.state / (1024 * 1024),
+            budget.queue / (1024 * 1024),
+            budget.tx_queue.unwrap_or(0) / (1024 * 1024)
+        );
+        budget
     }
 
     fn call_contract(
@@ -2099,7 +2307,7 @@ impl MiningBlockChainClient for Client {
                 precise_time_ns() - start,
             );
         });
-        self.db.read().flush().expect("DB flush failed.");
+        self.flush_db(true);
         Ok(h)
     }
 
@@ -2192,6 +2400,57 @@ impl Drop for Client {
     fn drop(&mut self) { self.engine.stop(); }
 }
 
+/// Replace the fields of the overridden accounts in `state`.
+fn apply_state_override(
+    state: &mut State<StateDB>,
+    overrides: &StateOverride,
+) -> trie::Result<()>
+{
+    for (address, account) in overrides {
+        if let Some(ref code) = account.code {
+            state.reset_code(address, code.clone())?;
+        }
+        if let Some(balance) = account.balance {
+            state.set_balance(address, balance)?;
+        }
+        if let Some(nonce) = account.nonce {
+            state.set_nonce(address, nonce)?;
+        }
+        for (key, value) in &account.storage {
+            state.set_storage(address, *key, *value)?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether two executions of the same transaction changed the state the same way. Balances of
+/// the `payers` (sender and block author) depend on the gas limit and are not compared.
+fn same_state_changes(
+    a: Option<&StateDiff>,
+    b: Option<&StateDiff>,
+    payers: &[Address],
+) -> bool
+{
+    let (a, b) = match (a, b) {
+        (Some(a), Some(b)) => (a, b),
+        (a, b) => return a == b,
+    };
+    a.len() == b.len() && a.iter().zip(b.iter()).all(|(entry_a, entry_b)| {
+        let ((address_a, diff_a), (address_b, diff_b)) = (entry_a, entry_b);
+        if address_a != address_b {
+            return false;
+        }
+        if payers.contains(address_a) {
+            diff_a.nonce == diff_b.nonce
+                && diff_a.code == diff_b.code
+                && diff_a.storage == diff_b.storage
+                && diff_a.storage_dword == diff_b.storage_dword
+        } else {
+            diff_a == diff_b
+        }
+    })
+}
+
 /// Returns `LocalizedReceipt` given `LocalizedTransaction`
 /// and a vector of receipts from given block up to transaction index.
 fn transaction_receipt(
@@ -29,6 +29,7 @@ use kvdb::CompactionProfile;
 
 pub use std::time::Duration;
 pub use blockchain::Config as BlockChainConfig;
+pub use kvdb::DatabaseBackend;
 //pub use evm::VMType;
 pub use vms::VMType;
 
@@ -71,6 +72,31 @@ impl FromStr for DatabaseCompactionProfile {
     }
 }
 
+/// Client database durability profile
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DatabaseDurability {
+    /// Buffered writes, recent blocks may be lost on a crash
+    Fast,
+    /// WAL enabled and synced writes whenever the best block changes
+    Safe,
+}
+
+impl Default for DatabaseDurability {
+    fn default() -> Self { DatabaseDurability::Fast }
+}
+
+impl FromStr for DatabaseDurability {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fast" => Ok(DatabaseDurability::Fast),
+            "safe" => Ok(DatabaseDurability::Safe),
+            _ => Err("Invalid durability profile given. Expected fast/safe.".into()),
+        }
+    }
+}
+
 /// Client configuration. Includes configs for all sub-systems.
 #[derive(Debug, PartialEq, Default)]
 pub struct ClientConfig {
@@ -84,12 +110,18 @@ pub struct ClientConfig {
     pub fat_db: bool,
     /// The JournalDB ("pruning") algorithm to use.
     pub pruning: journaldb::Algorithm,
-    /// RocksDB column cache-size if not default
+    /// RocksDB block cache size in megabytes shared by all dbs, if not default
     pub db_cache_size: Option<usize>,
     /// State db compaction profile
     pub db_compaction: DatabaseCompactionProfile,
     /// Should db have WAL enabled?
     pub db_wal: bool,
+    /// Keep all dbs as column families of a single rocksdb?
+    pub db_column_families: bool,
+    /// Embedded storage engine of the dbs
+    pub db_backend: DatabaseBackend,
+    /// Database durability profile
+    pub db_durability: DatabaseDurability,
     /// The chain spec name
     pub spec_name: String,
     /// Type of block verifier used by client.
@@ -100,15 +132,19 @@ pub struct ClientConfig {
     pub jump_table_size: usize,
     /// Minimum state pruning history size.
     pub history: u64,
+    /// Interval of the blocks whose state is kept beyond the pruning history.
+    pub pruning_checkpoints: Option<u64>,
     /// Ideal memory usage for state pruning history.
     pub history_mem: usize,
     /// Check seal valididity on block import
     pub check_seal: bool,
+    /// Maintain an index of the logs by address and first topic?
+    pub log_index: bool,
 }
 
 #[cfg(test)]
 mod test {
-    use super::DatabaseCompactionProfile;
+    use super::{DatabaseCompactionProfile, DatabaseDurability};
 
     #[test]
     fn test_default_compaction_profile() {
@@ -124,4 +160,12 @@ mod test {
         assert_eq!(DatabaseCompactionProfile::SSD, "ssd".parse().unwrap());
         assert_eq!(DatabaseCompactionProfile::HDD, "hdd".parse().unwrap());
     }
+
+    #[test]
+    fn test_parsing_durability() {
+        assert_eq!(DatabaseDurability::default(), DatabaseDurability::Fast);
+        assert_eq!(DatabaseDurability::Fast, "fast".parse().unwrap());
+        assert_eq!(DatabaseDurability::Safe, "safe".parse().unwrap());
+        assert!("slow".parse::<DatabaseDurability>().is_err());
+    }
 }
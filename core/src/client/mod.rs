@@ -29,18 +29,23 @@ mod test_client;
 mod client;
 
 pub use self::client::*;
-pub use self::config::{ClientConfig, DatabaseCompactionProfile, BlockChainConfig, VMType};
+pub use self::config::{
+    ClientConfig, DatabaseBackend, DatabaseCompactionProfile, DatabaseDurability,
+    BlockChainConfig, VMType,
+};
 pub use self::error::Error;
 pub use self::test_client::{TestBlockChainClient, EachBlockWith};
 pub use self::chain_notify::ChainNotify;
-pub use self::traits::{BlockChainClient, MiningBlockChainClient, EngineClient};
+pub use self::traits::{BlockChainClient, BlockGasPrices, MiningBlockChainClient, EngineClient};
 
 pub use self::traits::ProvingBlockChainClient;
 
 pub use types::ids::*;
 pub use types::trace_filter::Filter as TraceFilter;
 pub use types::pruning_info::PruningInfo;
+pub use types::memory_budget::{MemoryBudget, MemoryUsage};
 pub use types::call_analytics::CallAnalytics;
+pub use types::state_override::{AccountOverride, StateOverride};
 
 pub use executive::{Executed, Executive};
 pub use vms::{EnvInfo, LastHashes};
@@ -40,11 +40,11 @@ use rlp::*;
 use key::{generate_keypair, public_to_address_ed25519};
 use tempdir::TempDir;
 use transaction::{self, Transaction, LocalizedTransaction, PendingTransaction, SignedTransaction, Action, DEFAULT_TRANSACTION_TYPE};
-use blockchain::{TreeRoute, BlockReceipts};
+use blockchain::{TreeRoute, BlockReceipts, OrphanedBlock, ReorgStats};
 use client::{
     BlockChainClient, MiningBlockChainClient, BlockChainInfo, BlockStatus, BlockId,
     TransactionId, LastHashes, CallAnalytics, BlockImportError,
-    ProvingBlockChainClient,
+    ProvingBlockChainClient, StateOverride,
 };
 use db::{COL_STATE, DB_NAMES};
 use header::{Header as BlockHeader, BlockNumber, SealType};
@@ -52,11 +52,13 @@ use filter::Filter;
 use log_entry::LocalizedLogEntry;
 use receipt::{Receipt, LocalizedReceipt};
 use error::ImportResult;
+use evidence::{DoubleSignEvidence, EvidenceError};
 use factory::VmFactory;
 use miner::{Miner, MinerService};
 use spec::Spec;
 use types::basic_account::BasicAccount;
 use types::pruning_info::PruningInfo;
+use types::memory_budget::{MemoryBudget, MemoryUsage};
 
 use verification::queue::QueueInfo;
 use block::{OpenBlock, SealedBlock, ClosedBlock};
@@ -453,6 +455,7 @@ impl BlockChainClient for TestBlockChainClient {
     fn call_many(
         &self,
         txs: &[(SignedTransaction, CallAnalytics)],
+        _overrides: &StateOverride,
         block: BlockId,
     ) -> Result<Vec<Executed>, CallError>
     {
@@ -598,6 +601,8 @@ impl BlockChainClient for TestBlockChainClient {
     fn logs(&self, filter: Filter) -> Vec<LocalizedLogEntry> {
         let mut logs = self.logs.read().clone();
         let len = logs.len();
+        logs.truncate(len.saturating_sub(filter.offset.unwrap_or(0)));
+        let len = logs.len();
         match filter.limit {
             Some(limit) if limit <= len => logs.split_off(len - limit),
             _ => logs,
@@ -838,6 +843,20 @@ impl BlockChainClient for TestBlockChainClient {
 
     fn clear_bad(&self) {}
 
+    fn bad_blocks(&self) -> Vec<H256> { Vec::new() }
+
+    fn orphaned_blocks(&self, _from: BlockNumber, _to: BlockNumber) -> Vec<OrphanedBlock> {
+        Vec::new()
+    }
+
+    fn reorg_stats(&self) -> ReorgStats { ReorgStats::default() }
+
+    fn import_evidence(&self, evidence: Bytes) -> Result<bool, EvidenceError> {
+        DoubleSignEvidence::from_rlp(&evidence).map(|_| true)
+    }
+
+    fn double_sign_evidence(&self) -> Vec<DoubleSignEvidence> { Vec::new() }
+
     fn additional_params(&self) -> BTreeMap<String, String> { Default::default() }
 
     fn chain_info(&self) -> BlockChainInfo {
@@ -886,9 +905,24 @@ impl BlockChainClient for TestBlockChainClient {
                 .as_ref()
                 .map(|x| best_num - x)
                 .unwrap_or(0),
+            checkpoint_interval: None,
+            state_size: None,
+            pruning_backlog: 0,
+        }
+    }
+
+    fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            budget: MemoryBudget::with_total(0),
+            blockchain: 0,
+            state: 0,
+            queue: 0,
+            tx_queue: 0,
         }
     }
 
+    fn set_memory_budget(&self, total: usize) -> MemoryBudget { MemoryBudget::with_total(total) }
+
     fn call_contract(
         &self,
         _id: BlockId,
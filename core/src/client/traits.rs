@@ -24,9 +24,10 @@ use std::collections::BTreeMap;
 use std::time::Duration;
 
 use block::{OpenBlock, SealedBlock, ClosedBlock};
-use blockchain::TreeRoute;
+use blockchain::{OrphanedBlock, ReorgStats, TreeRoute};
 use encoded;
 use error::{ImportResult, CallError, BlockImportError};
+use evidence::{DoubleSignEvidence, EvidenceError};
 use vms::LastHashes;
 use factory::VmFactory;
 use executive::Executed;
@@ -44,12 +45,29 @@ use kvdb::DBValue;
 use types::ids::*;
 use types::basic_account::BasicAccount;
 use types::call_analytics::CallAnalytics;
+use types::state_override::StateOverride;
 use types::blockchain_info::BlockChainInfo;
 use types::block_status::BlockStatus;
 use types::pruning_info::PruningInfo;
+use types::memory_budget::{MemoryBudget, MemoryUsage};
 
 use super::super::transaction::UnverifiedTransaction;
 
+/// Energy prices paid in a block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockGasPrices {
+    /// Block number.
+    pub number: BlockNumber,
+    /// How the block was sealed.
+    pub seal_type: SealType,
+    /// Energy used by the block.
+    pub gas_used: U256,
+    /// Energy limit of the block.
+    pub gas_limit: U256,
+    /// Sorted energy prices of the block's transactions.
+    pub gas_prices: ::stats::Corpus<U256>,
+}
+
 /// Blockchain database client. Owns and manages a blockchain and a block queue.
 pub trait BlockChainClient: Sync + Send {
     /// Get raw block header data by block id.
@@ -201,6 +219,23 @@ pub trait BlockChainClient: Sync + Send {
     /// Clear bad block queue.
     fn clear_bad(&self);
 
+    /// Get hashes of the blocks marked as bad by the block queue.
+    fn bad_blocks(&self) -> Vec<H256>;
+
+    /// Get the blocks retracted from the canonical chain with numbers in the given inclusive
+    /// range.
+    fn orphaned_blocks(&self, from: BlockNumber, to: BlockNumber) -> Vec<OrphanedBlock>;
+
+    /// Get the reorganisations of the canonical chain since the client started.
+    fn reorg_stats(&self) -> ReorgStats;
+
+    /// Import RLP encoded double-sign evidence received from a peer. Returns whether the
+    /// evidence was new to this client.
+    fn import_evidence(&self, evidence: Bytes) -> Result<bool, EvidenceError>;
+
+    /// Get the recent double-sign evidence known to this client.
+    fn double_sign_evidence(&self) -> Vec<DoubleSignEvidence>;
+
     /// Get blockchain information.
     fn chain_info(&self) -> BlockChainInfo;
 
@@ -245,11 +280,13 @@ pub trait BlockChainClient: Sync + Send {
         block: BlockId,
     ) -> Result<Executed, CallError>;
 
-    /// Makes multiple non-persistent but dependent transaction calls.
+    /// Makes multiple non-persistent but dependent transaction calls on top of the block state
+    /// with the given accounts overridden.
     /// Returns a vector of successes or a failure if any of the transaction fails.
     fn call_many(
         &self,
         txs: &[(SignedTransaction, CallAnalytics)],
+        overrides: &StateOverride,
         block: BlockId,
     ) -> Result<Vec<Executed>, CallError>;
 
@@ -316,6 +353,24 @@ pub trait BlockChainClient: Sync + Send {
         corpus.into()
     }
 
+    /// Energy prices paid by the transactions of a block, with the block's seal type and energy
+    /// usage.
+    fn block_gas_prices(&self, id: BlockId) -> Option<BlockGasPrices> {
+        let block = self.block(id)?;
+        let header = block.header_view();
+        Some(BlockGasPrices {
+            number: header.number(),
+            seal_type: header.seal_type().unwrap_or(SealType::Pow),
+            gas_used: header.gas_used(),
+            gas_limit: header.gas_limit(),
+            gas_prices: block
+                .transaction_views()
+                .iter()
+                .map(|t| t.gas_price())
+                .collect(),
+        })
+    }
+
     /// Get the chain spec name.
     fn spec_name(&self) -> String;
 
@@ -329,6 +384,13 @@ pub trait BlockChainClient: Sync + Send {
     /// Returns information about pruning/data availability.
     fn pruning_info(&self) -> PruningInfo;
 
+    /// Returns the memory used by the caches and their budget.
+    fn memory_usage(&self) -> MemoryUsage;
+
+    /// Splits `total` bytes between the caches and resizes them. The database cache keeps the
+    /// size it was opened with. Returns the new budget.
+    fn set_memory_budget(&self, total: usize) -> MemoryBudget;
+
     /// Like `call`, but with various defaults. Designed to be used for calling contracts.
     fn call_contract(&self, id: BlockId, address: Address, data: Bytes) -> Result<Bytes, String>;
 
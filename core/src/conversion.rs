@@ -0,0 +1,367 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Offline conversion of the databases between pruning methods.
+//!
+//! The chain dbs are copied as they are, while only the state of a single block is copied, by
+//! walking its account trie together with the storage trie and code of every account. Trie
+//! nodes are written after their children, so a node found in the target was copied with its
+//! whole subtree, which lets an interrupted copy resume where it stopped.
+
+use std::mem;
+use std::sync::Arc;
+use aion_types::H256;
+use blake2b::{BLAKE2B_EMPTY, BLAKE2B_NULL_RLP};
+use journaldb::{self, Algorithm};
+use kvdb::{DBTransaction, HashStore, KeyValueDB};
+use rlp::{self, Rlp, RlpStream};
+use trie::node::Node;
+use types::basic_account::BasicAccount;
+use account_db::combine_key;
+use db;
+use header::BlockNumber;
+use spec::Spec;
+use state_db::StateDB;
+
+/// Key of the conversion progress in the extras db of the target.
+const PROGRESS_KEY: &'static [u8] = b"conversion";
+
+/// Number of keys written per batch.
+const BATCH_KEYS: u64 = 10000;
+
+/// Phase of a conversion, recorded in the target so an interrupted conversion resumes.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Phase {
+    /// Copying the chain dbs.
+    Chain,
+    /// Copying the state of the conversion block.
+    State,
+    /// Re-executing the blocks above the conversion block.
+    Execute,
+}
+
+/// Block and phase of an interrupted conversion into `db`, if any.
+pub fn progress(db: &KeyValueDB) -> Result<Option<(BlockNumber, Phase)>, String> {
+    let value = match db
+        .get(db::COL_EXTRA, PROGRESS_KEY)
+        .map_err(|e| format!("{}", e))?
+    {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+    let rlp = Rlp::new(&value);
+    let phase = match rlp.val_at::<u8>(1) {
+        0 => Phase::Chain,
+        1 => Phase::State,
+        2 => Phase::Execute,
+        _ => return Err("invalid conversion progress in database".into()),
+    };
+    Ok(Some((rlp.val_at(0), phase)))
+}
+
+/// Records that the conversion at block `at` reached `phase`.
+pub fn set_progress(db: &KeyValueDB, at: BlockNumber, phase: Phase) -> Result<(), String> {
+    let mut stream = RlpStream::new_list(2);
+    stream.append(&at);
+    stream.append(&(phase as u8));
+    let mut batch = DBTransaction::new();
+    batch.put(db::COL_EXTRA, PROGRESS_KEY, &stream.out());
+    db.write(batch).map_err(|e| format!("{}", e))
+}
+
+/// Removes the progress of a finished conversion.
+pub fn clear_progress(db: &KeyValueDB) -> Result<(), String> {
+    let mut batch = DBTransaction::new();
+    batch.delete(db::COL_EXTRA, PROGRESS_KEY);
+    db.write(batch).map_err(|e| format!("{}", e))
+}
+
+/// Copies every db but the state from `source` to `target`. `progress` is called with the name
+/// of the db and the number of keys copied so far.
+pub fn copy_chain<F>(
+    source: &KeyValueDB,
+    target: &KeyValueDB,
+    mut progress: F,
+) -> Result<(), String>
+where
+    F: FnMut(&str, u64),
+{
+    for &name in db::DB_NAMES.iter().filter(|name| **name != db::COL_STATE) {
+        let mut batch = DBTransaction::new();
+        let mut copied = 0u64;
+        for (key, value) in source.iter(name) {
+            batch.put(name, &key, &value);
+            copied += 1;
+            if copied % BATCH_KEYS == 0 {
+                target.write(batch).map_err(|e| format!("{}", e))?;
+                batch = DBTransaction::new();
+                progress(name, copied);
+            }
+        }
+        target.write(batch).map_err(|e| format!("{}", e))?;
+        progress(name, copied);
+    }
+    Ok(())
+}
+
+/// Copies the state with the given root from `source` into the state db of `target`, and returns
+/// the number of nodes written. `progress` is called with the number of nodes written so far.
+pub fn copy_state<F>(
+    source: &HashStore,
+    target: &KeyValueDB,
+    root: &H256,
+    progress: F,
+) -> Result<u64, String>
+where
+    F: FnMut(u64),
+{
+    let mut copier = StateCopier {
+        source: source,
+        target: target,
+        batch: DBTransaction::new(),
+        pending: 0,
+        copied: 0,
+        progress: progress,
+    };
+    copier.copy_node(None, &mut Vec::new(), root)?;
+    copier.write()?;
+    Ok(copier.copied)
+}
+
+/// Records the state in the state db of `target` as the state of block `number`.
+pub fn journal_state(
+    target: &Arc<KeyValueDB>,
+    algorithm: Algorithm,
+    number: BlockNumber,
+    hash: &H256,
+) -> Result<(), String>
+{
+    let mut journal_db = journaldb::new(target.clone(), algorithm, db::COL_STATE);
+    let mut batch = DBTransaction::new();
+    journal_db
+        .journal_under(&mut batch, number, hash)
+        .map_err(|e| format!("{}", e))?;
+    target.write(batch).map_err(|e| format!("{}", e))
+}
+
+/// Builds the genesis state of `spec` in the state db of `target`, for targets converted from
+/// a database which no longer has it.
+pub fn write_genesis_state(
+    spec: &Spec,
+    target: &Arc<KeyValueDB>,
+    algorithm: Algorithm,
+) -> Result<(), String>
+{
+    let journal_db = journaldb::new(target.clone(), algorithm, db::COL_STATE);
+    let mut state_db = spec
+        .ensure_db_good(StateDB::new(journal_db, 0), &Default::default())
+        .map_err(|e| format!("{}", e))?;
+    let mut batch = DBTransaction::new();
+    state_db
+        .journal_under(&mut batch, 0, &spec.genesis_header().hash())
+        .map_err(|e| format!("{}", e))?;
+    target.write(batch).map_err(|e| format!("{}", e))
+}
+
+struct StateCopier<'a, F> {
+    source: &'a HashStore,
+    target: &'a KeyValueDB,
+    batch: DBTransaction,
+    pending: u64,
+    copied: u64,
+    progress: F,
+}
+
+impl<'a, F: FnMut(u64)> StateCopier<'a, F> {
+    /// Copies the node with the given hash after its subtree, unless it is already in the
+    /// target. Nodes of storage tries are keyed with the address hash of their account.
+    fn copy_node(
+        &mut self,
+        address_hash: Option<H256>,
+        path: &mut Vec<u8>,
+        hash: &H256,
+    ) -> Result<(), String>
+    {
+        if *hash == BLAKE2B_NULL_RLP {
+            return Ok(());
+        }
+        let key = match address_hash {
+            Some(ref address_hash) => combine_key(address_hash, hash),
+            None => *hash,
+        };
+        if self
+            .target
+            .get(db::COL_STATE, &key)
+            .map_err(|e| format!("{}", e))?
+            .is_some()
+        {
+            return Ok(());
+        }
+        let node = self
+            .source
+            .get(&key)
+            .ok_or_else(|| format!("state node {:x} not found", hash))?;
+        self.copy_children(address_hash, path, &node)?;
+        self.put(key, &node)
+    }
+
+    /// Copies the children of a node, and the storage and code of the accounts in its leaves.
+    /// `path` holds the nibbles of the key up to the node.
+    fn copy_children(
+        &mut self,
+        address_hash: Option<H256>,
+        path: &mut Vec<u8>,
+        node: &[u8],
+    ) -> Result<(), String>
+    {
+        let depth = path.len();
+        let res = match Node::decoded(node) {
+            Node::Empty => Ok(()),
+            Node::Leaf(_, _) if address_hash.is_some() => Ok(()),
+            Node::Leaf(partial, value) => {
+                path.extend((0..partial.len()).map(|i| partial.at(i)));
+                self.copy_account(path, value)
+            }
+            Node::Extension(partial, child) => {
+                path.extend((0..partial.len()).map(|i| partial.at(i)));
+                self.copy_child(address_hash, path, child)
+            }
+            Node::Branch(children, _) => {
+                let mut res = Ok(());
+                for (i, child) in children.iter().enumerate() {
+                    path.push(i as u8);
+                    res = self.copy_child(address_hash, path, child);
+                    path.pop();
+                    if res.is_err() {
+                        break;
+                    }
+                }
+                res
+            }
+        };
+        path.truncate(depth);
+        res
+    }
+
+    /// Copies a child referenced from a node, either by hash or inline.
+    fn copy_child(
+        &mut self,
+        address_hash: Option<H256>,
+        path: &mut Vec<u8>,
+        child: &[u8],
+    ) -> Result<(), String>
+    {
+        let rlp = Rlp::new(child);
+        if rlp.is_empty() {
+            Ok(())
+        } else if rlp.is_data() && rlp.size() == 32 {
+            self.copy_node(address_hash, path, &rlp.as_val())
+        } else {
+            self.copy_children(address_hash, path, child)
+        }
+    }
+
+    /// Copies the storage trie and code of the account stored at the key `path`.
+    fn copy_account(&mut self, path: &[u8], value: &[u8]) -> Result<(), String> {
+        if path.len() != 64 {
+            return Err(format!("invalid account key of {} nibbles", path.len()));
+        }
+        let address_hash = H256::from_slice(
+            &path
+                .chunks(2)
+                .map(|nibbles| nibbles[0] << 4 | nibbles[1])
+                .collect::<Vec<u8>>(),
+        );
+        let account: BasicAccount = rlp::decode(value);
+        self.copy_node(Some(address_hash), &mut Vec::new(), &account.storage_root)?;
+        if account.code_hash != BLAKE2B_EMPTY {
+            let key = combine_key(&address_hash, &account.code_hash);
+            let code = self
+                .source
+                .get(&key)
+                .ok_or_else(|| format!("code {:x} not found", account.code_hash))?;
+            self.put(key, &code)?;
+        }
+        Ok(())
+    }
+
+    fn put(&mut self, key: H256, value: &[u8]) -> Result<(), String> {
+        self.batch.put(db::COL_STATE, &key, value);
+        self.pending += 1;
+        self.copied += 1;
+        if self.pending == BATCH_KEYS {
+            self.write()?;
+            (self.progress)(self.copied);
+        }
+        Ok(())
+    }
+
+    fn write(&mut self) -> Result<(), String> {
+        let batch = mem::replace(&mut self.batch, DBTransaction::new());
+        self.pending = 0;
+        self.target.write(batch).map_err(|e| format!("{}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aion_types::{Address, U256};
+    use kvdb::{MemoryDBRepository, MockDbRepository};
+    use state::{CleanupMode, State};
+    use tests::helpers::get_temp_state;
+
+    #[test]
+    fn copies_state_with_storage_and_code() {
+        let a = Address::zero();
+        let (root, state_db) = {
+            let mut state = get_temp_state();
+            state
+                .add_balance(&a, &69.into(), CleanupMode::NoEmpty)
+                .unwrap();
+            state.set_storage(&a, 0xb.into(), 0xc.into()).unwrap();
+            state.init_code(&a, vec![1, 2, 3]).unwrap();
+            state.commit().unwrap();
+            state.drop()
+        };
+        let target: Arc<KeyValueDB> = Arc::new(MockDbRepository::init(
+            db::DB_NAMES.iter().map(|name| name.to_string()).collect(),
+        ));
+
+        let copied = copy_state(state_db.as_hashstore(), &*target, &root, |_| {}).unwrap();
+        assert!(copied > 0);
+        // everything is in place already
+        assert_eq!(copy_state(state_db.as_hashstore(), &*target, &root, |_| {}), Ok(0));
+
+        let journal_db = journaldb::new(target.clone(), Algorithm::Archive, db::COL_STATE);
+        let state = State::from_existing(
+            StateDB::new(journal_db, 0),
+            root,
+            U256::zero(),
+            Default::default(),
+            Arc::new(MemoryDBRepository::new()),
+        )
+        .unwrap();
+        assert_eq!(state.balance(&a).unwrap(), 69.into());
+        assert_eq!(state.storage_at(&a, &0xb.into()).unwrap(), 0xc.into());
+        assert_eq!(state.code(&a).unwrap(), Some(Arc::new(vec![1u8, 2, 3])));
+    }
+}
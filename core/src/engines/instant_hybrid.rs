@@ -0,0 +1,213 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+use aion_types::U256;
+use aion_machine::{LiveBlock, Transactions, WithBalances};
+use block::ExecutedBlock;
+use engines::{Engine, Seal};
+use error::{BlockError, Error};
+use header::{Header, SealType};
+use machine::EthereumMachine;
+use unexpected::OutOfBounds;
+
+/// Params for an instant hybrid engine.
+#[derive(Clone, Default)]
+pub struct InstantHybridParams {
+    /// base reward for a block.
+    pub block_reward: U256,
+    /// difficulty of every block.
+    pub difficulty: U256,
+    /// seconds after which an empty block is sealed, zero to only seal blocks with transactions.
+    pub period: u64,
+}
+
+impl From<::ajson::spec::InstantHybridParams> for InstantHybridParams {
+    fn from(p: ::ajson::spec::InstantHybridParams) -> Self {
+        InstantHybridParams {
+            block_reward: p.block_reward.map_or_else(Default::default, Into::into),
+            difficulty: p.difficulty.map_or(U256::one(), Into::into),
+            period: p.period.map_or(0, Into::into),
+        }
+    }
+}
+
+/// A development engine which seals blocks internally with trivially valid seals, alternating
+/// PoW and PoS blocks once PoS is active. Blocks are sealed when they have transactions or when
+/// the period elapsed since their parent.
+pub struct InstantHybrid {
+    params: InstantHybridParams,
+    machine: EthereumMachine,
+}
+
+impl InstantHybrid {
+    /// Returns new instance of InstantHybrid over the given state machine.
+    pub fn new(params: InstantHybridParams, machine: EthereumMachine) -> Self {
+        InstantHybrid {
+            params: params,
+            machine: machine,
+        }
+    }
+}
+
+impl Engine<EthereumMachine> for InstantHybrid {
+    fn name(&self) -> &str { "InstantHybrid" }
+
+    fn machine(&self) -> &EthereumMachine { &self.machine }
+
+    fn seal_fields(&self, _header: &Header) -> usize {
+        // nonce and solution for PoW blocks, seed and signature for PoS blocks.
+        2
+    }
+
+    fn seals_internally(&self) -> Option<bool> { Some(true) }
+
+    fn next_seal_type(&self, parent: &Header) -> Option<SealType> {
+        let number = parent.number() + 1;
+        match *parent.seal_type() {
+            Some(SealType::Pos) => Some(SealType::Pow),
+            _ if self.machine.is_seal_type_active(&SealType::Pos, number) => Some(SealType::Pos),
+            _ => Some(SealType::Pow),
+        }
+    }
+
+    fn generate_seal(&self, block: &ExecutedBlock, parent: &Header) -> Seal {
+        let header = LiveBlock::header(block);
+        let elapsed = header.timestamp().saturating_sub(parent.timestamp());
+        let period_elapsed = self.params.period > 0 && elapsed >= self.params.period;
+        if block.transactions().is_empty() && !period_elapsed {
+            return Seal::None;
+        }
+        match *header.seal_type() {
+            Some(SealType::Pos) => Seal::Regular(vec![vec![0u8; 64], vec![0u8; 96]]),
+            _ => Seal::Regular(vec![vec![0u8; 32], Vec::new()]),
+        }
+    }
+
+    fn populate_from_parent(
+        &self,
+        header: &mut Header,
+        _parent: Option<&Header>,
+        _grand_parent: Option<&Header>,
+    )
+    {
+        header.set_difficulty(self.params.difficulty);
+    }
+
+    fn calculate_difficulty(
+        &self,
+        _version: u8,
+        _parent: Option<&Header>,
+        _grand_parent: Option<&Header>,
+    ) -> U256
+    {
+        self.params.difficulty
+    }
+
+    fn on_close_block(&self, block: &mut ExecutedBlock) -> Result<(), Error> {
+        let reward = self.params.block_reward;
+        let author = *LiveBlock::header(&*block).author();
+        block.header_mut().set_reward(reward);
+        if reward == U256::zero() {
+            return Ok(());
+        }
+        self.machine.add_balance(block, &author, &reward)?;
+        self.machine.note_rewards(block, &[(author, reward)])
+    }
+
+    fn verify_local_seal(&self, _header: &Header) -> Result<(), Error> { Ok(()) }
+
+    fn verify_block_basic(&self, header: &Header) -> Result<(), Error> {
+        if let Some(ref seal_type) = *header.seal_type() {
+            if !self.machine.is_seal_type_active(seal_type, header.number()) {
+                return Err(BlockError::InactiveSealType(OutOfBounds {
+                    min: Some(self.machine.seal_type_activation(seal_type)),
+                    max: None,
+                    found: header.number(),
+                })
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use aion_types::{Address, U256};
+    use tests::helpers::*;
+    use spec::Spec;
+    use header::{Header, SealType};
+    use block::*;
+    use engines::Seal;
+    use kvdb::MemoryDBRepository;
+
+    #[test]
+    fn instant_hybrid_alternates_seal_types() {
+        let engine = Spec::new_instant_hybrid().engine;
+        let mut parent = Header::default();
+
+        assert_eq!(engine.next_seal_type(&parent), Some(SealType::Pos));
+        parent.set_seal_type(Some(SealType::Pos));
+        assert_eq!(engine.next_seal_type(&parent), Some(SealType::Pow));
+        parent.set_seal_type(Some(SealType::Pow));
+        assert_eq!(engine.next_seal_type(&parent), Some(SealType::Pos));
+    }
+
+    #[test]
+    fn instant_hybrid_can_seal_both_types() {
+        let spec = Spec::new_instant_hybrid();
+        let engine = &*spec.engine;
+        let genesis_header = spec.genesis_header();
+        let last_hashes = Arc::new(vec![genesis_header.hash()]);
+        for seal_type in &[SealType::Pow, SealType::Pos] {
+            let db = spec
+                .ensure_db_good(get_temp_state_db(), &Default::default())
+                .unwrap();
+            let b = OpenBlock::new(
+                engine,
+                Default::default(),
+                db,
+                &genesis_header,
+                None,
+                None,
+                seal_type,
+                last_hashes.clone(),
+                Address::default(),
+                (3141562.into(), 31415620.into()),
+                vec![],
+                false,
+                Arc::new(MemoryDBRepository::new()),
+            )
+            .unwrap();
+            let b = b.close_and_lock();
+            assert_eq!(b.header().difficulty(), &U256::one());
+            assert_eq!(b.header().reward(), &U256::from(0x0d));
+            match engine.generate_seal(b.block(), &genesis_header) {
+                Seal::Regular(seal) => {
+                    let sealed = b.try_seal(engine, seal).unwrap();
+                    assert!(engine.verify_block_basic(sealed.header()).is_ok());
+                }
+                _ => panic!("an empty block is sealed once the period elapsed"),
+            }
+        }
+    }
+}
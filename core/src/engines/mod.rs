@@ -22,6 +22,7 @@
 
 //! Consensus engine specification and basic implementations.
 
+mod instant_hybrid;
 mod instant_seal;
 mod null_engine;
 pub mod pow_equihash_engine;
@@ -29,6 +30,7 @@ pub mod pow_equihash_engine;
 pub mod epoch;
 
 pub use self::epoch::{EpochVerifier, Transition as EpochTransition};
+pub use self::instant_hybrid::InstantHybrid;
 pub use self::instant_seal::InstantSeal;
 pub use self::null_engine::NullEngine;
 pub use self::pow_equihash_engine::POWEquihashEngine;
@@ -41,7 +43,7 @@ use self::epoch::PendingTransition;
 
 use precompiled::builtin::BuiltinContract;
 use error::Error;
-use header::{Header, BlockNumber};
+use header::{Header, BlockNumber, SealType};
 use spec::CommonParams;
 use transaction::{UnverifiedTransaction, SignedTransaction};
 
@@ -199,6 +201,14 @@ pub trait Engine<M: Machine>: Sync + Send {
     /// Some(false) means that the node might seal internally but is not qualified now.
     fn seals_internally(&self) -> Option<bool> { None }
 
+    /// Equihash (n, k) parameters of the proof-of-work seals, if the engine is sealed by
+    /// Equihash solutions.
+    fn equihash_params(&self) -> Option<(u32, u32)> { None }
+
+    /// Seal type of the next block sealed internally on top of the given parent. `None` means
+    /// the default proof-of-work.
+    fn next_seal_type(&self, _parent: &M::Header) -> Option<SealType> { None }
+
     /// Attempt to seal the block internally.
     ///
     /// If `Some` is returned, then you get a valid seal.
@@ -33,6 +33,7 @@ use aion_types::{Address, H128, U128, H256};
 use blake2b::blake2b;
 use rustc_hex::FromHex;
 use num_bigint::BigUint;
+use precompiled::double_sign_contract::{double_sign_contract_address, slashed_key};
 
 pub trait GrantParentHeaderValidator {
     fn validate(
@@ -46,6 +47,7 @@ pub trait GrantParentHeaderValidator {
 
 pub struct DifficultyValidator<'a> {
     pub difficulty_calc: &'a DifficultyCalc,
+    pub difficulty_version: u8,
 }
 
 impl<'a> GrantParentHeaderValidator for DifficultyValidator<'a> {
@@ -58,9 +60,11 @@ impl<'a> GrantParentHeaderValidator for DifficultyValidator<'a> {
     ) -> Result<(), Error>
     {
         let difficulty = header.difficulty().clone();
-        let calc_difficulty = self
-            .difficulty_calc
-            .calculate_difficulty_v1(parent_header, grant_parent_header);
+        let calc_difficulty = self.difficulty_calc.calculate_difficulty(
+            self.difficulty_version,
+            parent_header,
+            grant_parent_header,
+        );
         if difficulty != calc_difficulty {
             Err(BlockError::InvalidDifficulty(Mismatch {
                 expected: calc_difficulty,
@@ -132,7 +136,7 @@ impl GrantParentHeaderValidator for POSValidator {
 
         let state = state.expect("State should exist.");
         // Verify block timestamp
-        let stake = self.calculate_stake(sender_from_seed, state);
+        let stake = calculate_stake(&sender_from_seed, &state);
         let hash_of_seed = blake2b(&seed[..]);
         let a = BigUint::parse_bytes(
             b"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
@@ -172,31 +176,41 @@ impl POSValidator {
 
         Ok(n.ln() + ln_256 * ((x.len() - 1) as f64))
     }
+}
 
-    fn calculate_stake(&self, address: Address, state: State<StateDB>) -> u64 {
-        let staking_registry = Address::from_slice(
-            "a00876be75b664de079b58e7acbf70ce315ba4aaa487f7ddf2abd5e0e1a8dff4"
-                .from_hex()
-                .unwrap()
-                .as_slice(),
-        );
-
-        let map_key = address.0;
-
-        let map_offset: [u8; 16] = [
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x06,
-        ];
-
-        let mut storage_key: [u8; 32] = [0; 32];
-        let mut digest = Keccak::new_keccak256();
-        digest.update(&map_key);
-        digest.update(&map_offset);
-        digest.finalize(&mut storage_key);
+/// Key of the stake of `staker` in the staking registry.
+pub fn stake_key(staker: &Address) -> H128 {
+    let map_offset: [u8; 16] = [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x06,
+    ];
+
+    let mut storage_key: [u8; 32] = [0; 32];
+    let mut digest = Keccak::new_keccak256();
+    digest.update(&staker.0);
+    digest.update(&map_offset);
+    digest.finalize(&mut storage_key);
+    H128::from(&storage_key[0..16])
+}
 
-        let stake = state
-            .storage_at(&staking_registry, &H128::from(&storage_key[0..16]))
-            .unwrap_or(H128::default());
-        U128::from(stake).as_u64()
+/// Stake of `address` in the staking registry, zero if it was slashed for double-signing.
+pub fn calculate_stake(address: &Address, state: &State<StateDB>) -> u64 {
+    // stakers slashed for double-signing have no stake left
+    let slashed = state
+        .storage_at(&double_sign_contract_address(), &slashed_key(address))
+        .unwrap_or(H128::default());
+    if !U128::from(slashed).is_zero() {
+        return 0;
     }
+
+    let staking_registry = Address::from_slice(
+        "a00876be75b664de079b58e7acbf70ce315ba4aaa487f7ddf2abd5e0e1a8dff4"
+            .from_hex()
+            .unwrap()
+            .as_slice(),
+    );
+    let stake = state
+        .storage_at(&staking_registry, &stake_key(address))
+        .unwrap_or(H128::default());
+    U128::from(stake).as_u64()
 }
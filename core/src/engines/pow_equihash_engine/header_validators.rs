@@ -19,7 +19,7 @@
  *
  ******************************************************************************/
 
-use header::{Header, V1};
+use header::{Header, HeaderVersion};
 use equihash::EquihashValidator;
 use blake2b::Blake2b;
 use aion_types::U256;
@@ -79,14 +79,17 @@ impl HeaderValidator for EnergyConsumedValidator {
         Ok(())
     }
 }
-pub struct VersionValidator;
+pub struct VersionValidator {
+    /// header version at the height of the header, as scheduled by the forks.
+    pub version: HeaderVersion,
+}
 impl HeaderValidator for VersionValidator {
     fn validate(&self, header: &Header) -> Result<(), Error> {
         let version = header.version();
-        if version != V1 {
-            error!(target: "equihash", "Invalid header version, found version {}, expected {}", version, V1);
+        if version != self.version {
+            error!(target: "equihash", "Invalid header version, found version {}, expected {}", version, self.version);
             return Err(BlockError::InvalidHeaderVersion(Mismatch {
-                expected: V1,
+                expected: self.version,
                 found: version,
             })
             .into());
@@ -28,9 +28,11 @@ use machine::EthereumMachine;
 use std::sync::Arc;
 use engines::Engine;
 use aion_types::U256;
-use header::{Header, SealType};
+use header::{BlockNumber, Header, SealType};
 use block::ExecutedBlock;
-use error::Error;
+use error::{BlockError, Error};
+use spec::forks;
+use unexpected::OutOfBounds;
 use std::cmp;
 use state::State;
 use state_db::StateDB;
@@ -51,6 +53,7 @@ use self::header_validators::{
     EquihashSolutionValidator
 };
 use self::grant_parent_header_validators::{GrantParentHeaderValidator, DifficultyValidator, POSValidator};
+pub use self::grant_parent_header_validators::{calculate_stake, stake_key};
 
 #[derive(Debug, PartialEq)]
 pub struct POWEquihashEngineParams {
@@ -64,6 +67,18 @@ pub struct POWEquihashEngineParams {
     pub block_time_lower_bound: u64,
     pub block_time_upper_bound: u64,
     pub minimum_difficulty: U256,
+    /// Block time targeted by the v1 difficulty adjustment, in seconds.
+    pub target_block_time: u64,
+    /// The v1 difficulty moves by at most `1 / adjustment_bound_divisor` of the parent difficulty.
+    pub adjustment_bound_divisor: U256,
+    /// Lower bound of the v1 difficulty.
+    pub adjustment_minimum_difficulty: U256,
+    /// Upper bound of the v1 difficulty.
+    pub adjustment_maximum_difficulty: U256,
+    /// Equihash n parameter of the proof-of-work solutions.
+    pub equihash_n: u32,
+    /// Equihash k parameter of the proof-of-work solutions.
+    pub equihash_k: u32,
 }
 
 impl From<ajson::spec::POWEquihashEngineParams> for POWEquihashEngineParams {
@@ -89,6 +104,18 @@ impl From<ajson::spec::POWEquihashEngineParams> for POWEquihashEngineParams {
             block_time_lower_bound: p.block_time_lower_bound.map_or(5u64, Into::into),
             block_time_upper_bound: p.block_time_upper_bound.map_or(15u64, Into::into),
             minimum_difficulty: p.minimum_difficulty.map_or(U256::from(16), Into::into),
+            target_block_time: p.target_block_time.map_or(14u64, Into::into),
+            adjustment_bound_divisor: p
+                .adjustment_bound_divisor
+                .map_or(U256::from(20), Into::into),
+            adjustment_minimum_difficulty: p
+                .adjustment_minimum_difficulty
+                .map_or(U256::one(), Into::into),
+            adjustment_maximum_difficulty: p
+                .adjustment_maximum_difficulty
+                .map_or(U256::max_value(), Into::into),
+            equihash_n: p.equihash_n.unwrap_or(210),
+            equihash_k: p.equihash_k.unwrap_or(9),
         }
     }
 }
@@ -99,6 +126,10 @@ pub struct DifficultyCalc {
     block_time_lower_bound: u64,
     block_time_upper_bound: u64,
     minimum_difficulty: U256,
+    target_block_time: u64,
+    adjustment_bound_divisor: U256,
+    adjustment_minimum_difficulty: U256,
+    adjustment_maximum_difficulty: U256,
 }
 
 impl DifficultyCalc {
@@ -108,6 +139,26 @@ impl DifficultyCalc {
             block_time_lower_bound: params.block_time_lower_bound,
             block_time_upper_bound: params.block_time_upper_bound,
             minimum_difficulty: params.minimum_difficulty,
+            target_block_time: params.target_block_time,
+            // a zero divisor would make every step infinite.
+            adjustment_bound_divisor: cmp::max(params.adjustment_bound_divisor, U256::one()),
+            adjustment_minimum_difficulty: params.adjustment_minimum_difficulty,
+            adjustment_maximum_difficulty: params.adjustment_maximum_difficulty,
+        }
+    }
+
+    /// Difficulty of a block with the given difficulty calculation version.
+    pub fn calculate_difficulty(
+        &self,
+        version: u8,
+        parent: Option<&Header>,
+        grand_parent: Option<&Header>,
+    ) -> U256
+    {
+        match version {
+            0u8 => self.calculate_difficulty_v0(parent, grand_parent),
+            1u8 => self.calculate_difficulty_v1(parent, grand_parent),
+            _ => unimplemented!(),
         }
     }
 
@@ -162,42 +213,56 @@ impl DifficultyCalc {
         output_difficulty
     }
 
+    /// Difficulty of a block from its two previous blocks of the same seal type.
+    ///
+    /// The difficulty moves by `parent_difficulty / adjustment_bound_divisor` (at least 1) towards
+    /// `target_block_time`: up when the parent came faster, down when it came slower. Equal or
+    /// out-of-order timestamps count as a fast block. The result stays within the adjustment
+    /// bounds.
     pub fn calculate_difficulty_v1(
         &self,
         parent: Option<&Header>,
         grand_parent: Option<&Header>,
     ) -> U256
     {
-        // If not parent pos block, return the initial difficulty
-        if parent.is_none() || grand_parent.is_none() {
-            return U256::from(1);
-        }
-        let parent = parent.expect("Parent unwrap tested before");
+        let (parent, grand_parent) = match (parent, grand_parent) {
+            (Some(parent), Some(grand_parent)) => (parent, grand_parent),
+            // Initial difficulty until there are two blocks to compare.
+            _ => return self.clamp_difficulty_v1(U256::one()),
+        };
         let parent_difficulty = parent.difficulty().clone();
-        let grand_parent = grand_parent.expect("Grand parent unwrap tested before");
-        let parent_timestamp = parent.timestamp();
-        let grand_parent_timestamp = grand_parent.timestamp();
-        let delta_time = parent_timestamp - grand_parent_timestamp;
-        assert!(delta_time > 0);
-
-        // NOTE: the computation below is in f64 (never use it in production)
-        let alpha = 0.05f64;
-        let barrier = 14u64;
-        let diff =  if delta_time > barrier {
-            cmp::min(
-                parent_difficulty.as_u64() - 1,
-                (parent_difficulty.as_u64() as f64 / (1f64 + alpha)) as u64,
-            )
-        } else if delta_time < barrier {
-            cmp::max(
-                parent_difficulty.as_u64() + 1,
-                (parent_difficulty.as_u64() as f64 * (1f64 + alpha)) as u64,
-            )
+        let delta_time = parent.timestamp().saturating_sub(grand_parent.timestamp());
+
+        let mut step = parent_difficulty / self.adjustment_bound_divisor;
+        if step.is_zero() {
+            step = U256::one();
+        }
+
+        let difficulty = if delta_time < self.target_block_time {
+            let (difficulty, overflow) = parent_difficulty.overflowing_add(step);
+            if overflow {
+                U256::max_value()
+            } else {
+                difficulty
+            }
+        } else if delta_time > self.target_block_time {
+            if parent_difficulty > step {
+                parent_difficulty - step
+            } else {
+                U256::zero()
+            }
         } else {
-            parent_difficulty.as_u64()
+            parent_difficulty
         };
 
-        U256::from(cmp::max(1u64, diff))
+        self.clamp_difficulty_v1(difficulty)
+    }
+
+    fn clamp_difficulty_v1(&self, difficulty: U256) -> U256 {
+        cmp::min(
+            cmp::max(difficulty, self.adjustment_minimum_difficulty),
+            self.adjustment_maximum_difficulty,
+        )
     }
 }
 
@@ -227,8 +292,12 @@ impl RewardsCalculator {
         }
     }
 
-    fn calculate_reward(&self, header: &Header) -> U256 {
-        let number = U256::from(header.number());
+    /// Reward of a block, the ramp-up bounds count blocks from `rampup_activation`.
+    fn calculate_reward(&self, header: &Header, rampup_activation: BlockNumber) -> U256 {
+        if header.number() < rampup_activation {
+            return self.lower_block_reward;
+        }
+        let number = U256::from(header.number() - rampup_activation);
         if number <= self.rampup_lower_bound {
             self.lower_block_reward
         } else if number <= self.rampup_upper_bound {
@@ -244,6 +313,8 @@ pub struct POWEquihashEngine {
     machine: EthereumMachine,
     rewards_calculator: RewardsCalculator,
     difficulty_calc: DifficultyCalc,
+    equihash_n: u32,
+    equihash_k: u32,
 }
 
 impl POWEquihashEngine {
@@ -255,16 +326,21 @@ impl POWEquihashEngine {
             machine,
             rewards_calculator,
             difficulty_calc,
+            equihash_n: params.equihash_n,
+            equihash_k: params.equihash_k,
         })
     }
 
     fn calculate_reward(&self, header: &Header) -> U256 {
-        self.rewards_calculator.calculate_reward(header)
+        let rampup_activation = self.machine.forks().activation(forks::REWARD_RAMPUP);
+        self.rewards_calculator
+            .calculate_reward(header, rampup_activation)
     }
 
+    /// Cheap checks of a header received from the network which do not depend on the chain
+    /// spec. The header version follows the forks of the spec and is checked on import.
     pub fn validate_block_header(header: &Header) -> Result<(), Error> {
         let mut block_header_validators: Vec<Box<HeaderValidator>> = Vec::with_capacity(4);
-        block_header_validators.push(Box::new(VersionValidator {}));
         block_header_validators.push(Box::new(EnergyConsumedValidator {}));
         if header.seal_type().clone() == Some(SealType::Pow) {
             block_header_validators.push(Box::new(POWValidator {}));
@@ -289,6 +365,8 @@ impl Engine<EthereumMachine> for Arc<POWEquihashEngine> {
         2
     }
 
+    fn equihash_params(&self) -> Option<(u32, u32)> { Some((self.equihash_n, self.equihash_k)) }
+
     fn populate_from_parent(
         &self,
         header: &mut Header,
@@ -296,7 +374,8 @@ impl Engine<EthereumMachine> for Arc<POWEquihashEngine> {
         grand_parent: Option<&Header>,
     )
     {
-        let difficulty = self.calculate_difficulty(1u8, parent, grand_parent);
+        let version = self.machine.difficulty_version(header.number());
+        let difficulty = self.calculate_difficulty(version, parent, grand_parent);
         header.set_difficulty(difficulty);
     }
 
@@ -307,17 +386,8 @@ impl Engine<EthereumMachine> for Arc<POWEquihashEngine> {
         grand_parent: Option<&Header>,
     ) -> U256
     {
-        match version {
-            0u8 => {
-                self.difficulty_calc
-                    .calculate_difficulty_v0(parent, grand_parent)
-            }
-            1u8 => {
-                self.difficulty_calc
-                    .calculate_difficulty_v1(parent, grand_parent)
-            }
-            _ => unimplemented!(),
-        }
+        self.difficulty_calc
+            .calculate_difficulty(version, parent, grand_parent)
     }
 
     fn on_close_block(&self, block: &mut ExecutedBlock) -> Result<(), Error> {
@@ -343,8 +413,20 @@ impl Engine<EthereumMachine> for Arc<POWEquihashEngine> {
     }
 
     fn verify_block_basic(&self, header: &Header) -> Result<(), Error> {
+        if let Some(ref seal_type) = *header.seal_type() {
+            if !self.machine.is_seal_type_active(seal_type, header.number()) {
+                return Err(BlockError::InactiveSealType(OutOfBounds {
+                    min: Some(self.machine.seal_type_activation(seal_type)),
+                    max: None,
+                    found: header.number(),
+                })
+                .into());
+            }
+        }
         let mut cheap_validators: Vec<Box<HeaderValidator>> = Vec::with_capacity(4);
-        cheap_validators.push(Box::new(VersionValidator {}));
+        cheap_validators.push(Box::new(VersionValidator {
+            version: self.machine.header_version(header.number()),
+        }));
         cheap_validators.push(Box::new(EnergyConsumedValidator {}));
         if header.seal_type().clone() == Some(SealType::Pow) {
             cheap_validators.push(Box::new(POWValidator {}));
@@ -368,7 +450,10 @@ impl Engine<EthereumMachine> for Arc<POWEquihashEngine> {
         }
         let mut costly_validators: Vec<Box<HeaderValidator>> = Vec::with_capacity(1);
         costly_validators.push(Box::new(EquihashSolutionValidator {
-            solution_validator: EquihashValidator::new(210, 9),
+            solution_validator: EquihashValidator::new(
+                self.equihash_n as i32,
+                self.equihash_k as i32,
+            ),
         }));
         for v in costly_validators.iter() {
             v.validate(header)?;
@@ -397,6 +482,7 @@ impl Engine<EthereumMachine> for Arc<POWEquihashEngine> {
         let mut grand_validators: Vec<Box<GrantParentHeaderValidator>> = Vec::with_capacity(1);
         grand_validators.push(Box::new(DifficultyValidator {
             difficulty_calc: &self.difficulty_calc,
+            difficulty_version: self.machine.difficulty_version(header.number()),
         }));
         if header.seal_type().clone() == Some(SealType::Pos) {
             grand_validators.push(Box::new(POSValidator {}));
@@ -416,6 +502,11 @@ mod tests {
     use super::RewardsCalculator;
     use super::POWEquihashEngineParams;
     use super::DifficultyCalc;
+    use super::grant_parent_header_validators::{DifficultyValidator, GrantParentHeaderValidator};
+    use super::SealType;
+    use equihash::EquihashSolver;
+    use error::{BlockError, Error};
+    use spec::Spec;
 
     #[test]
     fn test_calculate_rewards_number1() {
@@ -430,12 +521,18 @@ mod tests {
             block_time_lower_bound: 0u64,
             block_time_upper_bound: 0u64,
             minimum_difficulty: U256::zero(),
+            target_block_time: 14u64,
+            adjustment_bound_divisor: U256::from(20),
+            adjustment_minimum_difficulty: U256::one(),
+            adjustment_maximum_difficulty: U256::max_value(),
+            equihash_n: 210,
+            equihash_k: 9,
         };
         let calculator = RewardsCalculator::new(&params);
         let mut header = Header::default();
         header.set_number(1);
         assert_eq!(
-            calculator.calculate_reward(&header),
+            calculator.calculate_reward(&header, 0),
             U256::from(748997531261476163u64)
         );
     }
@@ -453,12 +550,18 @@ mod tests {
             block_time_lower_bound: 0u64,
             block_time_upper_bound: 0u64,
             minimum_difficulty: U256::zero(),
+            target_block_time: 14u64,
+            adjustment_bound_divisor: U256::from(20),
+            adjustment_minimum_difficulty: U256::one(),
+            adjustment_maximum_difficulty: U256::max_value(),
+            equihash_n: 210,
+            equihash_k: 9,
         };
         let calculator = RewardsCalculator::new(&params);
         let mut header = Header::default();
         header.set_number(10000);
         assert_eq!(
-            calculator.calculate_reward(&header),
+            calculator.calculate_reward(&header, 0),
             U256::from(777891039832365092u64)
         );
     }
@@ -476,12 +579,18 @@ mod tests {
             block_time_lower_bound: 0u64,
             block_time_upper_bound: 0u64,
             minimum_difficulty: U256::zero(),
+            target_block_time: 14u64,
+            adjustment_bound_divisor: U256::from(20),
+            adjustment_minimum_difficulty: U256::one(),
+            adjustment_maximum_difficulty: U256::max_value(),
+            equihash_n: 210,
+            equihash_k: 9,
         };
         let calculator = RewardsCalculator::new(&params);
         let mut header = Header::default();
         header.set_number(259200);
         assert_eq!(
-            calculator.calculate_reward(&header),
+            calculator.calculate_reward(&header, 0),
             U256::from(1497989283243258292u64)
         );
     }
@@ -499,12 +608,18 @@ mod tests {
             block_time_lower_bound: 0u64,
             block_time_upper_bound: 0u64,
             minimum_difficulty: U256::zero(),
+            target_block_time: 14u64,
+            adjustment_bound_divisor: U256::from(20),
+            adjustment_minimum_difficulty: U256::one(),
+            adjustment_maximum_difficulty: U256::max_value(),
+            equihash_n: 210,
+            equihash_k: 9,
         };
         let calculator = RewardsCalculator::new(&params);
         let mut header = Header::default();
         header.set_number(300000);
         assert_eq!(
-            calculator.calculate_reward(&header),
+            calculator.calculate_reward(&header, 0),
             U256::from(1497989283243310185u64)
         );
     }
@@ -522,10 +637,14 @@ mod tests {
             block_time_lower_bound: 5u64,
             block_time_upper_bound: 15u64,
             minimum_difficulty: U256::from(16),
+            target_block_time: 14u64,
+            adjustment_bound_divisor: U256::from(20),
+            adjustment_minimum_difficulty: U256::one(),
+            adjustment_maximum_difficulty: U256::max_value(),
+            equihash_n: 210,
+            equihash_k: 9,
         };
         let calculator = DifficultyCalc::new(&params);
-        let mut header = Header::default();
-        header.set_number(3);
         let mut parent_header = Header::default();
         parent_header.set_timestamp(1524538000u64);
         parent_header.set_difficulty(U256::from(1));
@@ -534,7 +653,7 @@ mod tests {
         grand_parent_header.set_timestamp(1524528000u64);
         grand_parent_header.set_number(1);
         let difficulty =
-            calculator.calculate_difficulty(&header, &parent_header, Some(&grand_parent_header));
+            calculator.calculate_difficulty_v0(Some(&parent_header), Some(&grand_parent_header));
         assert_eq!(difficulty, U256::from(16));
     }
     #[test]
@@ -550,10 +669,14 @@ mod tests {
             block_time_lower_bound: 5u64,
             block_time_upper_bound: 15u64,
             minimum_difficulty: U256::from(16),
+            target_block_time: 14u64,
+            adjustment_bound_divisor: U256::from(20),
+            adjustment_minimum_difficulty: U256::one(),
+            adjustment_maximum_difficulty: U256::max_value(),
+            equihash_n: 210,
+            equihash_k: 9,
         };
         let calculator = DifficultyCalc::new(&params);
-        let mut header = Header::default();
-        header.set_number(3);
         let mut parent_header = Header::default();
         parent_header.set_timestamp(1524528005u64);
         parent_header.set_number(2);
@@ -562,7 +685,7 @@ mod tests {
         grand_parent_header.set_timestamp(1524528000u64);
         grand_parent_header.set_number(1);
         let difficulty =
-            calculator.calculate_difficulty(&header, &parent_header, Some(&grand_parent_header));
+            calculator.calculate_difficulty_v0(Some(&parent_header), Some(&grand_parent_header));
         assert_eq!(difficulty, U256::from(2001));
     }
     #[test]
@@ -578,10 +701,14 @@ mod tests {
             block_time_lower_bound: 5u64,
             block_time_upper_bound: 15u64,
             minimum_difficulty: U256::from(16),
+            target_block_time: 14u64,
+            adjustment_bound_divisor: U256::from(20),
+            adjustment_minimum_difficulty: U256::one(),
+            adjustment_maximum_difficulty: U256::max_value(),
+            equihash_n: 210,
+            equihash_k: 9,
         };
         let calculator = DifficultyCalc::new(&params);
-        let mut header = Header::default();
-        header.set_number(3);
         let mut parent_header = Header::default();
         parent_header.set_timestamp(1524528010u64);
         parent_header.set_difficulty(U256::from(3000));
@@ -590,7 +717,7 @@ mod tests {
         grand_parent_header.set_timestamp(1524528000u64);
         grand_parent_header.set_number(1);
         let difficulty =
-            calculator.calculate_difficulty(&header, &parent_header, Some(&grand_parent_header));
+            calculator.calculate_difficulty_v0(Some(&parent_header), Some(&grand_parent_header));
         assert_eq!(difficulty, U256::from(3000));
     }
     #[test]
@@ -606,10 +733,14 @@ mod tests {
             block_time_lower_bound: 5u64,
             block_time_upper_bound: 15u64,
             minimum_difficulty: U256::from(16),
+            target_block_time: 14u64,
+            adjustment_bound_divisor: U256::from(20),
+            adjustment_minimum_difficulty: U256::one(),
+            adjustment_maximum_difficulty: U256::max_value(),
+            equihash_n: 210,
+            equihash_k: 9,
         };
         let calculator = DifficultyCalc::new(&params);
-        let mut header = Header::default();
-        header.set_number(3);
         let mut parent_header = Header::default();
         parent_header.set_timestamp(1524528020u64);
         parent_header.set_difficulty(U256::from(3000));
@@ -618,8 +749,194 @@ mod tests {
         grand_parent_header.set_timestamp(1524528000u64);
         grand_parent_header.set_number(1);
         let difficulty =
-            calculator.calculate_difficulty(&header, &parent_header, Some(&grand_parent_header));
+            calculator.calculate_difficulty_v0(Some(&parent_header), Some(&grand_parent_header));
         assert_eq!(difficulty, U256::from(2999));
     }
 
+    /// (parent difficulty, parent timestamp - grand parent timestamp, expected difficulty) with
+    /// the default v1 parameters.
+    const DIFFICULTY_V1_VECTORS: [(u64, i64, u64); 10] = [
+        (1000, 10, 1050),
+        (1000, 14, 1000),
+        (1000, 20, 950),
+        // equal timestamps
+        (1000, 0, 1050),
+        // out-of-order timestamps
+        (1000, -5, 1050),
+        (19, 10, 20),
+        (10, 20, 9),
+        (1, 20, 1),
+        (1, 1, 2),
+        (2_000_000_000_000, 100, 1_900_000_000_000),
+    ];
+
+    fn difficulty_v1_params() -> POWEquihashEngineParams {
+        POWEquihashEngineParams {
+            rampup_upper_bound: U256::zero(),
+            rampup_lower_bound: U256::zero(),
+            rampup_start_value: U256::zero(),
+            rampup_end_value: U256::zero(),
+            lower_block_reward: U256::zero(),
+            upper_block_reward: U256::zero(),
+            difficulty_bound_divisor: U256::from(2048),
+            block_time_lower_bound: 5u64,
+            block_time_upper_bound: 15u64,
+            minimum_difficulty: U256::from(16),
+            target_block_time: 14u64,
+            adjustment_bound_divisor: U256::from(20),
+            adjustment_minimum_difficulty: U256::one(),
+            adjustment_maximum_difficulty: U256::max_value(),
+            equihash_n: 210,
+            equihash_k: 9,
+        }
+    }
+
+    fn headers(parent_difficulty: u64, delta_time: i64) -> (Header, Header) {
+        let timestamp = 1524528000u64;
+        let mut parent_header = Header::default();
+        parent_header.set_number(2);
+        parent_header.set_timestamp((timestamp as i64 + delta_time) as u64);
+        parent_header.set_difficulty(U256::from(parent_difficulty));
+        let mut grand_parent_header = Header::default();
+        grand_parent_header.set_number(1);
+        grand_parent_header.set_timestamp(timestamp);
+        (parent_header, grand_parent_header)
+    }
+
+    #[test]
+    fn test_calculate_difficulty_v1_vectors() {
+        let calculator = DifficultyCalc::new(&difficulty_v1_params());
+        for &(parent_difficulty, delta_time, expected) in DIFFICULTY_V1_VECTORS.iter() {
+            let (parent, grand_parent) = headers(parent_difficulty, delta_time);
+            let difficulty = calculator.calculate_difficulty_v1(Some(&parent), Some(&grand_parent));
+            assert_eq!(
+                difficulty,
+                U256::from(expected),
+                "parent difficulty {}, delta time {}",
+                parent_difficulty,
+                delta_time
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_difficulty_v1_vectors() {
+        let calculator = DifficultyCalc::new(&difficulty_v1_params());
+        let validator = DifficultyValidator {
+            difficulty_calc: &calculator,
+            difficulty_version: 1,
+        };
+        for &(parent_difficulty, delta_time, expected) in DIFFICULTY_V1_VECTORS.iter() {
+            let (parent_header, grand_parent_header) = headers(parent_difficulty, delta_time);
+            let mut header = Header::default();
+            header.set_number(3);
+            header.set_difficulty(U256::from(expected));
+            assert!(
+                validator
+                    .validate(&header, Some(&parent_header), Some(&grand_parent_header), None)
+                    .is_ok()
+            );
+            header.set_difficulty(U256::from(expected + 1));
+            assert!(
+                validator
+                    .validate(&header, Some(&parent_header), Some(&grand_parent_header), None)
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_calculate_difficulty_v1_bounds() {
+        let mut params = difficulty_v1_params();
+        params.adjustment_minimum_difficulty = U256::from(100);
+        params.adjustment_maximum_difficulty = U256::from(1000);
+        let calculator = DifficultyCalc::new(&params);
+
+        assert_eq!(calculator.calculate_difficulty_v1(None, None), U256::from(100));
+        let (parent_header, grand_parent_header) = headers(990, 1);
+        assert_eq!(
+            calculator.calculate_difficulty_v1(Some(&parent_header), Some(&grand_parent_header)),
+            U256::from(1000)
+        );
+        let (parent_header, grand_parent_header) = headers(101, 100);
+        assert_eq!(
+            calculator.calculate_difficulty_v1(Some(&parent_header), Some(&grand_parent_header)),
+            U256::from(100)
+        );
+    }
+
+    #[test]
+    fn test_calculate_rewards_rampup_activation() {
+        let mut params = difficulty_v1_params();
+        params.rampup_upper_bound = U256::from(259200);
+        params.rampup_start_value = U256::from(748994641621655092u64);
+        params.rampup_end_value = U256::from(1497989283243310185u64);
+        params.lower_block_reward = U256::from(748994641621655092u64);
+        params.upper_block_reward = U256::from(1497989283243310185u64);
+        let calculator = RewardsCalculator::new(&params);
+        let mut header = Header::default();
+
+        header.set_number(50);
+        assert_eq!(
+            calculator.calculate_reward(&header, 100),
+            U256::from(748994641621655092u64)
+        );
+        header.set_number(10100);
+        assert_eq!(
+            calculator.calculate_reward(&header, 100),
+            U256::from(777891039832365092u64)
+        );
+    }
+
+    #[test]
+    fn test_verify_block_basic_header_version() {
+        let spec = Spec::new_test_forks();
+        let mut header = Header::default();
+        header.set_version(2);
+        match spec.engine.verify_block_basic(&header) {
+            Err(Error::Block(BlockError::InvalidHeaderVersion(_))) => {}
+            other => panic!("expected invalid header version, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_verify_block_basic_inactive_seal_type() {
+        let spec = Spec::new_test_forks();
+        let mut header = Header::default();
+        header.set_seal_type(Some(SealType::Pos));
+
+        header.set_number(199);
+        match spec.engine.verify_block_basic(&header) {
+            Err(Error::Block(BlockError::InactiveSealType(_))) => {}
+            other => panic!("expected inactive seal type, got {:?}", other),
+        }
+        header.set_number(200);
+        match spec.engine.verify_block_basic(&header) {
+            Err(Error::Block(BlockError::InactiveSealType(_))) => {
+                panic!("seal type should be active")
+            }
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn test_verify_block_solved_by_cpu_solver() {
+        let spec = Spec::new_pow_dev();
+        let (n, k) = spec.engine.equihash_params().unwrap();
+        assert_eq!((n, k), (48, 5));
+        let mut header = Header::default();
+        header.set_number(1);
+        header.set_seal_type(Some(SealType::Pow));
+
+        let (nonce, solution) = EquihashSolver::new(n, k)
+            .search(&header.mine_hash(), [0u8; 32], 2, |_, _| true, || false)
+            .unwrap();
+        header.set_seal(vec![nonce.to_vec(), solution.clone()]);
+        assert!(spec.engine.verify_block_unordered(&header).is_ok());
+
+        let mut other_nonce = nonce;
+        other_nonce[31] ^= 1;
+        header.set_seal(vec![other_nonce.to_vec(), solution]);
+        assert!(spec.engine.verify_block_unordered(&header).is_err());
+    }
 }
@@ -100,6 +100,8 @@ pub enum BlockError {
     InvalidPosTimestamp(u64, u64, u64),
     /// Invalid stake
     InvalidStake,
+    /// Blocks of this seal type are not accepted before the fork activating them.
+    InactiveSealType(OutOfBounds<BlockNumber>),
 }
 
 impl fmt::Display for BlockError {
@@ -148,6 +150,7 @@ impl fmt::Display for BlockError {
                 )
             }
             InvalidStake => format!("Invalid stake amount"),
+            InactiveSealType(ref oob) => format!("Seal type not active yet: {}", oob),
         };
 
         f.write_fmt(format_args!("Block error ({})", msg))
@@ -230,6 +233,8 @@ pub enum Error {
     Block(BlockError),
     /// Unknown engine given.
     UnknownEngineName(String),
+    /// Chain spec is inconsistent.
+    InvalidSpec(String),
     /// Error concerning EVM code execution.
     Execution(ExecutionError),
     /// Error concerning transaction processing.
@@ -268,6 +273,7 @@ impl fmt::Display for Error {
             Error::UnknownEngineName(ref name) => {
                 f.write_fmt(format_args!("Unknown engine name ({})", name))
             }
+            Error::InvalidSpec(ref msg) => f.write_fmt(format_args!("Invalid spec ({})", msg)),
             Error::PowHashInvalid => f.write_str("Invalid or out of date PoW hash."),
             Error::PowInvalid => f.write_str("Invalid nonce or mishash"),
             Error::Trie(ref err) => err.fmt(f),
@@ -48,6 +48,11 @@ pub fn new_foundation<'a, T: Into<SpecParams<'a>>>(params: T) -> Spec {
     load(params.into(), include_bytes!("../../res/aion/mainnet.json"))
 }
 
+/// Create a new development chain spec, sealing PoW and PoS blocks in turn without any work.
+pub fn new_dev<'a, T: Into<SpecParams<'a>>>(params: T) -> Spec {
+    load(params.into(), include_bytes!("../../res/instant_hybrid.json"))
+}
+
 #[cfg(test)]
 fn load_machine(b: &[u8]) -> EthereumMachine {
     Spec::load_machine(b).expect("chain spec is invalid")
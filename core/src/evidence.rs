@@ -0,0 +1,370 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Evidence of proof-of-stake misbehaviour.
+//!
+//! A staker double-signs when it seals two different blocks on top of the same parent from
+//! the same seed. Both headers carry the staker's ed25519 signature, so the pair can be
+//! checked by any node and handed to the double-sign contract, which slashes the staker.
+//! Blocks sealed again on top of another parent, e.g. after a reorganization of the PoW
+//! blocks, are not double-signing.
+
+use std::fmt;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use aion_types::{Address, H256};
+use blake2b::blake2b;
+use header::{BlockNumber, Header, SealType};
+use key::public_to_address_ed25519;
+use rcrypto::ed25519::verify;
+use rlp::{DecoderError, Encodable, RlpStream, UntrustedRlp};
+
+/// Errors concerning double-sign evidence.
+#[derive(Debug, PartialEq, Clone)]
+pub enum EvidenceError {
+    /// Evidence could not be decoded.
+    Decoder(DecoderError),
+    /// One of the headers is not sealed with proof-of-stake.
+    NotPosHeader,
+    /// Seal of one of the headers is malformed.
+    InvalidSeal,
+    /// Block signature of one of the headers does not verify.
+    InvalidSignature,
+    /// Headers are at different heights.
+    DifferentNumber(BlockNumber, BlockNumber),
+    /// Headers were sealed on top of different parents.
+    DifferentParent,
+    /// Headers were sealed from different seeds.
+    DifferentSeed,
+    /// Headers were signed by different keys.
+    DifferentSigner,
+    /// Both headers are the same block.
+    SameBlock,
+    /// Headers are above the best block.
+    FutureNumber(BlockNumber, BlockNumber),
+    /// Signer has no stake to be slashed.
+    NoStake,
+}
+
+impl fmt::Display for EvidenceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match *self {
+            EvidenceError::Decoder(ref err) => format!("undecodable evidence: {}", err),
+            EvidenceError::NotPosHeader => "header is not sealed with proof-of-stake".into(),
+            EvidenceError::InvalidSeal => "malformed proof-of-stake seal".into(),
+            EvidenceError::InvalidSignature => "invalid block signature".into(),
+            EvidenceError::DifferentNumber(a, b) => {
+                format!("headers at different heights: {} and {}", a, b)
+            }
+            EvidenceError::DifferentParent => "headers sealed on different parents".into(),
+            EvidenceError::DifferentSeed => "headers sealed from different seeds".into(),
+            EvidenceError::DifferentSigner => "headers signed by different keys".into(),
+            EvidenceError::SameBlock => "headers are the same block".into(),
+            EvidenceError::FutureNumber(number, best) => {
+                format!("headers at {} above the best block {}", number, best)
+            }
+            EvidenceError::NoStake => "signer has no stake".into(),
+        };
+
+        f.write_fmt(format_args!("Double-sign evidence error ({})", msg))
+    }
+}
+
+impl From<DecoderError> for EvidenceError {
+    fn from(err: DecoderError) -> Self { EvidenceError::Decoder(err) }
+}
+
+/// Two conflicting proof-of-stake headers signed by the same ed25519 key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DoubleSignEvidence {
+    first: Header,
+    second: Header,
+    signer: H256,
+}
+
+impl DoubleSignEvidence {
+    /// Check two headers against each other and build the evidence. The headers are
+    /// ordered by hash, so the same pair always gives the same evidence.
+    pub fn new(a: Header, b: Header) -> Result<Self, EvidenceError> {
+        if a.hash() == b.hash() {
+            return Err(EvidenceError::SameBlock);
+        }
+        if a.number() != b.number() {
+            return Err(EvidenceError::DifferentNumber(a.number(), b.number()));
+        }
+        let signer = signer_of(&a)?;
+        if signer != signer_of(&b)? {
+            return Err(EvidenceError::DifferentSigner);
+        }
+        if a.parent_hash() != b.parent_hash() {
+            return Err(EvidenceError::DifferentParent);
+        }
+        if a.seal()[0] != b.seal()[0] {
+            return Err(EvidenceError::DifferentSeed);
+        }
+
+        let (first, second) = if a.hash() < b.hash() {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        Ok(DoubleSignEvidence {
+            first: first,
+            second: second,
+            signer: signer,
+        })
+    }
+
+    /// Decode and check evidence received from the network.
+    pub fn from_rlp(bytes: &[u8]) -> Result<Self, EvidenceError> {
+        let rlp = UntrustedRlp::new(bytes);
+        if rlp.item_count()? != 2 {
+            return Err(EvidenceError::Decoder(DecoderError::RlpIncorrectListLen));
+        }
+        DoubleSignEvidence::new(rlp.val_at(0)?, rlp.val_at(1)?)
+    }
+
+    /// Block number both headers were sealed at.
+    pub fn number(&self) -> BlockNumber { self.first.number() }
+
+    /// Conflicting headers.
+    pub fn headers(&self) -> (&Header, &Header) { (&self.first, &self.second) }
+
+    /// Ed25519 public key that signed both headers.
+    pub fn signer(&self) -> &H256 { &self.signer }
+
+    /// Address of the misbehaving staker.
+    pub fn offender(&self) -> Address { public_to_address_ed25519(&self.signer) }
+
+    /// Identifier of this evidence (blake2b of its RLP).
+    pub fn hash(&self) -> H256 { blake2b(self.rlp_bytes()) }
+}
+
+impl Encodable for DoubleSignEvidence {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(2);
+        s.append(&self.first);
+        s.append(&self.second);
+    }
+}
+
+/// Returns the public key that signed a proof-of-stake header, after checking the
+/// block signature over its bare hash. The seed and the block must come from the same key.
+pub fn signer_of(header: &Header) -> Result<H256, EvidenceError> {
+    if header.seal_type() != &Some(SealType::Pos) {
+        return Err(EvidenceError::NotPosHeader);
+    }
+    let seal = header.seal();
+    if seal.len() != 2 || seal[0].len() != 96 || seal[1].len() != 96 {
+        return Err(EvidenceError::InvalidSeal);
+    }
+    let seed = &seal[0];
+    let signature = &seal[1];
+    if seed[..32] != signature[..32] {
+        return Err(EvidenceError::InvalidSeal);
+    }
+    if !verify(&header.bare_hash().0, &signature[..32], &signature[32..96]) {
+        return Err(EvidenceError::InvalidSignature);
+    }
+    Ok(H256::from(&signature[..32]))
+}
+
+/// Number of blocks below the best block for which signed headers and evidence are kept.
+pub const EVIDENCE_WINDOW: BlockNumber = 128;
+
+/// Recently signed proof-of-stake headers, used to catch double-signing, and the evidence
+/// found or received so far.
+#[derive(Default)]
+pub struct EvidencePool {
+    signed: HashMap<(BlockNumber, H256), Header>,
+    evidence: HashMap<H256, DoubleSignEvidence>,
+}
+
+impl EvidencePool {
+    /// Remember the signer of a proof-of-stake header. Returns new evidence if the signer
+    /// already signed another header at the same height from the same seed.
+    pub fn note_header(&mut self, header: &Header) -> Option<DoubleSignEvidence> {
+        let signer = match signer_of(header) {
+            Ok(signer) => signer,
+            Err(_) => return None,
+        };
+        let signed = match self.signed.entry((header.number(), signer)) {
+            Entry::Occupied(entry) => entry.get().clone(),
+            Entry::Vacant(entry) => {
+                entry.insert(header.clone());
+                return None;
+            }
+        };
+        match DoubleSignEvidence::new(signed, header.clone()) {
+            Ok(ref evidence) if self.insert(evidence.clone()) => Some(evidence.clone()),
+            _ => None,
+        }
+    }
+
+    /// Add evidence to the pool. Returns `false` if it was already known.
+    pub fn insert(&mut self, evidence: DoubleSignEvidence) -> bool {
+        match self.evidence.entry(evidence.hash()) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(evidence);
+                true
+            }
+        }
+    }
+
+    /// Evidence currently in the pool.
+    pub fn evidence(&self) -> Vec<DoubleSignEvidence> { self.evidence.values().cloned().collect() }
+
+    /// Forget headers and evidence older than `EVIDENCE_WINDOW` blocks below `best`.
+    pub fn prune(&mut self, best: BlockNumber) {
+        let oldest = best.saturating_sub(EVIDENCE_WINDOW);
+        self.signed.retain(|&(number, _), _| number >= oldest);
+        self.evidence.retain(|_, evidence| evidence.number() >= oldest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use header::{Header, SealType};
+    use rcrypto::ed25519::{keypair, signature};
+    use rlp;
+
+    fn pos_header(number: u64, timestamp: u64, seed_key: &[u8], key: &[u8]) -> Header {
+        let (seed_secret, seed_public) = keypair(seed_key);
+        let mut header = Header::new();
+        header.set_number(number);
+        header.set_timestamp(timestamp);
+        header.set_seal_type(Some(SealType::Pos));
+        let mut seed = seed_public.to_vec();
+        seed.extend_from_slice(&signature(b"parent seed", &seed_secret));
+        header.set_seal(vec![seed, vec![]]);
+        sign(header, key)
+    }
+
+    /// Replace the block signature of a PoS header, keeping its seed.
+    fn sign(mut header: Header, key: &[u8]) -> Header {
+        let (secret, public) = keypair(key);
+        let seed = header.seal()[0].clone();
+        let mut sig = public.to_vec();
+        sig.extend_from_slice(&signature(&header.bare_hash().0, &secret));
+        header.set_seal(vec![seed, sig]);
+        header
+    }
+
+    #[test]
+    fn accepts_conflicting_headers() {
+        let key = [1u8; 32];
+        let a = pos_header(10, 100, &key, &key);
+        let b = pos_header(10, 101, &key, &key);
+        let evidence = DoubleSignEvidence::new(a.clone(), b.clone()).unwrap();
+        assert_eq!(evidence.number(), 10);
+        assert_eq!(
+            evidence.offender(),
+            public_to_address_ed25519(&H256::from(&keypair(&key).1[..]))
+        );
+        // the pair is ordered, so both orders give the same evidence
+        let reversed = DoubleSignEvidence::new(b, a).unwrap();
+        assert_eq!(evidence.hash(), reversed.hash());
+
+        let decoded = DoubleSignEvidence::from_rlp(&rlp::encode(&evidence)).unwrap();
+        assert_eq!(decoded, evidence);
+    }
+
+    #[test]
+    fn rejects_innocent_headers() {
+        let key = [1u8; 32];
+        let other = [2u8; 32];
+        let a = pos_header(10, 100, &key, &key);
+        assert_eq!(
+            DoubleSignEvidence::new(a.clone(), a.clone()),
+            Err(EvidenceError::SameBlock)
+        );
+        assert_eq!(
+            DoubleSignEvidence::new(a.clone(), pos_header(11, 100, &key, &key)),
+            Err(EvidenceError::DifferentNumber(10, 11))
+        );
+        assert_eq!(
+            DoubleSignEvidence::new(a.clone(), pos_header(10, 101, &other, &other)),
+            Err(EvidenceError::DifferentSigner)
+        );
+        // seed signed by another key than the block
+        assert_eq!(
+            DoubleSignEvidence::new(a.clone(), pos_header(10, 101, &other, &key)),
+            Err(EvidenceError::InvalidSeal)
+        );
+
+        // same key and seed, but sealed again on another parent
+        let mut resealed = pos_header(10, 101, &key, &key);
+        resealed.set_parent_hash(H256::from(&[1u8; 32][..]));
+        let resealed = sign(resealed, &key);
+        assert_eq!(
+            DoubleSignEvidence::new(a.clone(), resealed),
+            Err(EvidenceError::DifferentParent)
+        );
+
+        // same key, but sealed on another seed chain
+        let mut reseeded = pos_header(10, 101, &key, &key);
+        let mut seal = reseeded.seal().to_vec();
+        seal[0][95] ^= 1;
+        reseeded.set_seal(seal);
+        assert_eq!(
+            DoubleSignEvidence::new(a.clone(), reseeded),
+            Err(EvidenceError::DifferentSeed)
+        );
+
+        let mut forged = pos_header(10, 101, &key, &key);
+        forged.set_timestamp(102);
+        assert_eq!(
+            DoubleSignEvidence::new(a.clone(), forged),
+            Err(EvidenceError::InvalidSignature)
+        );
+
+        let mut pow = a.clone();
+        pow.set_seal_type(Some(SealType::Pow));
+        assert_eq!(
+            DoubleSignEvidence::new(pow, a),
+            Err(EvidenceError::NotPosHeader)
+        );
+    }
+
+    #[test]
+    fn pool_catches_double_signing_once() {
+        let key = [1u8; 32];
+        let mut pool = EvidencePool::default();
+        assert!(pool.note_header(&pos_header(10, 100, &key, &key)).is_none());
+        assert!(pool.note_header(&pos_header(11, 100, &key, &key)).is_none());
+        assert!(
+            pool.note_header(&pos_header(10, 100, &[2u8; 32], &[2u8; 32]))
+                .is_none()
+        );
+
+        let evidence = pool.note_header(&pos_header(10, 101, &key, &key)).unwrap();
+        assert_eq!(evidence.number(), 10);
+        assert!(pool.note_header(&pos_header(10, 101, &key, &key)).is_none());
+        assert!(!pool.insert(evidence.clone()));
+        assert_eq!(pool.evidence(), vec![evidence]);
+
+        pool.prune(10 + EVIDENCE_WINDOW + 1);
+        assert!(pool.evidence().is_empty());
+        assert!(pool.note_header(&pos_header(10, 102, &key, &key)).is_none());
+    }
+}
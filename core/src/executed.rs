@@ -199,6 +199,15 @@ pub enum CallError {
     StateCorrupt,
     /// Error executing.
     Execution(ExecutionError),
+    /// The vm failed to execute the transaction, e.g. it was reverted or ran out of gas.
+    Failed {
+        /// Exception message reported by the vm.
+        exception: String,
+        /// Gas used by the failed execution.
+        gas_used: U256,
+        /// Output of the failed execution, carrying the revert reason if any.
+        output: Bytes,
+    },
 }
 
 impl From<ExecutionError> for CallError {
@@ -215,6 +224,10 @@ impl fmt::Display for CallError {
             Exceptional => "An exception happened in the execution".into(),
             StateCorrupt => "Stored state found to be corrupted.".into(),
             Execution(ref e) => format!("{}", e),
+            Failed {
+                ref exception,
+                ..
+            } => format!("Vm execution failed: {}", exception),
         };
 
         f.write_fmt(format_args!("Transaction execution error ({}).", msg))
@@ -107,21 +107,25 @@ extern crate tokio;
 pub mod account_provider;
 pub mod block;
 pub mod client;
+pub mod conversion;
 pub mod transaction;
 pub mod db;
 pub mod encoded;
 pub mod engines;
 pub mod error;
+pub mod evidence;
 pub mod ethereum;
 pub mod executed;
 pub mod header;
 pub mod machine;
+pub mod migrations;
 pub mod miner;
 pub mod pod_state;
 pub mod service;
 pub mod spec;
 pub mod state;
 pub mod state_db;
+pub mod state_dump;
 pub mod verification;
 pub mod views;
 
@@ -31,8 +31,8 @@ use precompiled::builtin::BuiltinContract;
 use client::BlockChainClient;
 use error::Error;
 use executive::Executive;
-use header::{BlockNumber, Header};
-use spec::CommonParams;
+use header::{BlockNumber, Header, HeaderVersion, SealType, V1};
+use spec::{forks, CommonParams, Forks};
 use state::{CleanupMode, Substate};
 use transaction::{self, SYSTEM_ADDRESS, UnverifiedTransaction, SignedTransaction};
 use tx_filter::TransactionFilter;
@@ -158,6 +158,35 @@ impl EthereumMachine {
     /// Get the general parameters of the chain.
     pub fn params(&self) -> &CommonParams { &self.params }
 
+    /// Activation blocks of the hard forks.
+    pub fn forks(&self) -> &Forks { &self.params.forks }
+
+    /// Version of the difficulty calculation for the given block.
+    pub fn difficulty_version(&self, block_number: BlockNumber) -> u8 {
+        if self.forks().is_active(forks::DIFFICULTY_V1, block_number) {
+            1
+        } else {
+            0
+        }
+    }
+
+    /// Version of the header of the given block. No fork has changed the header layout yet,
+    /// the forks that do go here.
+    pub fn header_version(&self, _block_number: BlockNumber) -> HeaderVersion { V1 }
+
+    /// First block accepting the given seal type.
+    pub fn seal_type_activation(&self, seal_type: &SealType) -> BlockNumber {
+        match *seal_type {
+            SealType::Pow => 0,
+            SealType::Pos => self.forks().activation(forks::UNITY),
+        }
+    }
+
+    /// Whether blocks with the given seal type are accepted at the given block.
+    pub fn is_seal_type_active(&self, seal_type: &SealType, block_number: BlockNumber) -> bool {
+        block_number >= self.seal_type_activation(seal_type)
+    }
+
     /// Builtin-contracts for the chain..
     pub fn builtins(&self) -> &BTreeMap<Address, Box<BuiltinContract>> { &*self.builtins }
 
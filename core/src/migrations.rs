@@ -0,0 +1,130 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Versioned schema migrations of the databases.
+//!
+//! The schema version of the databases is recorded in the extras db. Migrations are applied in
+//! order from the recorded version up to `CURRENT_SCHEMA_VERSION`, and the version is recorded
+//! after each of them so an interrupted run resumes at the first migration not applied.
+
+use byteorder::{BigEndian, ByteOrder};
+use kvdb::{DBTransaction, KeyValueDB};
+use db;
+
+/// Key of the schema version in the extras db.
+pub const SCHEMA_VERSION_KEY: &'static [u8] = b"schema_version";
+
+/// Schema version of the databases written by this version of the client.
+pub const CURRENT_SCHEMA_VERSION: u32 = 1;
+
+/// A migration of the databases from the previous schema version.
+pub trait Migration {
+    /// Schema version of the databases after the migration.
+    fn version(&self) -> u32;
+    /// Short description of the migration, for logs.
+    fn description(&self) -> &'static str;
+    /// Migrate the databases.
+    fn migrate(&self, db: &KeyValueDB) -> Result<(), String>;
+}
+
+/// Databases written before schema versions were recorded already have the layout of the
+/// first version.
+struct Unversioned;
+
+impl Migration for Unversioned {
+    fn version(&self) -> u32 { 1 }
+
+    fn description(&self) -> &'static str { "record the schema version" }
+
+    fn migrate(&self, _db: &KeyValueDB) -> Result<(), String> { Ok(()) }
+}
+
+/// All migrations, in version order.
+fn migrations() -> Vec<Box<Migration>> { vec![Box::new(Unversioned) as Box<Migration>] }
+
+/// Schema version recorded in the databases, 0 if none is.
+pub fn schema_version(db: &KeyValueDB) -> Result<u32, String> {
+    match db
+        .get(db::COL_EXTRA, SCHEMA_VERSION_KEY)
+        .map_err(|e| format!("{}", e))?
+    {
+        Some(ref version) if version.len() == 4 => Ok(BigEndian::read_u32(version)),
+        Some(_) => Err("invalid schema version in database".into()),
+        None => Ok(0),
+    }
+}
+
+fn set_schema_version(db: &KeyValueDB, version: u32) -> Result<(), String> {
+    let mut buf = [0u8; 4];
+    BigEndian::write_u32(&mut buf, version);
+    let mut batch = DBTransaction::new();
+    batch.put(db::COL_EXTRA, SCHEMA_VERSION_KEY, &buf);
+    db.write(batch).map_err(|e| format!("{}", e))?;
+    db.flush_synced().map_err(|e| format!("{}", e))
+}
+
+/// Applies the pending migrations and returns the version and description of each applied.
+/// Fails if the databases were written by a newer client.
+pub fn migrate(db: &KeyValueDB) -> Result<Vec<(u32, &'static str)>, String> {
+    let version = schema_version(db)?;
+    if version > CURRENT_SCHEMA_VERSION {
+        return Err(format!(
+            "database schema version {} is newer than the supported version {}, please upgrade",
+            version, CURRENT_SCHEMA_VERSION
+        ));
+    }
+    let mut applied = Vec::new();
+    for migration in migrations() {
+        if migration.version() <= version {
+            continue;
+        }
+        migration.migrate(db)?;
+        set_schema_version(db, migration.version())?;
+        applied.push((migration.version(), migration.description()));
+    }
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kvdb::MockDbRepository;
+
+    fn new_db() -> MockDbRepository {
+        MockDbRepository::init(db::DB_NAMES.iter().map(|name| name.to_string()).collect())
+    }
+
+    #[test]
+    fn migrates_unversioned_db_once() {
+        let db = new_db();
+        assert_eq!(schema_version(&db), Ok(0));
+        assert_eq!(migrate(&db), Ok(vec![(1, "record the schema version")]));
+        assert_eq!(schema_version(&db), Ok(CURRENT_SCHEMA_VERSION));
+        assert_eq!(migrate(&db), Ok(vec![]));
+    }
+
+    #[test]
+    fn refuses_newer_schema() {
+        let db = new_db();
+        set_schema_version(&db, CURRENT_SCHEMA_VERSION + 1).unwrap();
+        assert!(migrate(&db).is_err());
+    }
+}
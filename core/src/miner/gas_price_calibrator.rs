@@ -0,0 +1,139 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Calibration of the minimal energy price from the prices paid in recent blocks.
+
+use std::cmp;
+use std::time::{Duration, Instant};
+
+use aion_types::U256;
+use client::MiningBlockChainClient;
+
+/// Options of the minimal energy price calibration.
+#[derive(Debug, PartialEq, Clone)]
+pub struct GasPriceCalibratorOptions {
+    /// Number of blocks with transactions to take prices from.
+    pub blk_price_window: usize,
+    /// Maximal number of blocks traversed looking for prices.
+    pub max_blk_traverse: usize,
+    /// Percentile of the block prices used as minimal price.
+    pub gas_price_percentile: usize,
+    /// Minimal time between two calibrations.
+    pub recalibration_period: Duration,
+}
+
+/// Tracks the energy prices of recent blocks to update the minimal energy price.
+pub struct GasPriceCalibrator {
+    options: GasPriceCalibratorOptions,
+    next_calibration: Instant,
+}
+
+impl GasPriceCalibrator {
+    /// Creates a calibrator, the first calibration happens on the next call to `recalibrate`.
+    pub fn new(options: GasPriceCalibratorOptions) -> Self {
+        GasPriceCalibrator {
+            options: options,
+            next_calibration: Instant::now(),
+        }
+    }
+
+    /// Returns the new minimal energy price, bounded by `floor` and `ceil`, if a calibration is
+    /// due and the recent blocks have enough transactions. The price never goes below `floor`,
+    /// even if `ceil` does.
+    pub fn recalibrate(
+        &mut self,
+        client: &MiningBlockChainClient,
+        floor: U256,
+        ceil: U256,
+    ) -> Option<U256>
+    {
+        let now = Instant::now();
+        if now < self.next_calibration {
+            return None;
+        }
+        self.next_calibration = now + self.options.recalibration_period;
+
+        let corpus =
+            client.gas_price_corpus(self.options.blk_price_window, self.options.max_blk_traverse);
+        let price = *corpus.percentile(self.options.gas_price_percentile)?;
+        Some(cmp::max(cmp::min(price, ceil), floor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use aion_types::U256;
+    use client::{EachBlockWith, TestBlockChainClient};
+    use super::{GasPriceCalibrator, GasPriceCalibratorOptions};
+
+    fn calibrator(recalibration_period: Duration) -> GasPriceCalibrator {
+        GasPriceCalibrator::new(GasPriceCalibratorOptions {
+            blk_price_window: 5,
+            max_blk_traverse: 10,
+            gas_price_percentile: 50,
+            recalibration_period: recalibration_period,
+        })
+    }
+
+    #[test]
+    fn calibrates_within_bounds() {
+        let client = TestBlockChainClient::new();
+        client.add_blocks(10, EachBlockWith::Transaction);
+        // Transactions of the test client pay 200_000_000_000.
+        let price = U256::from(200_000_000_000u64);
+
+        let mut calibrator = calibrator(Duration::from_secs(0));
+        assert_eq!(calibrator.recalibrate(&client, 0.into(), U256::max_value()), Some(price));
+        assert_eq!(
+            calibrator.recalibrate(&client, 0.into(), 1000.into()),
+            Some(1000.into())
+        );
+        assert_eq!(
+            calibrator.recalibrate(&client, price * 2.into(), U256::max_value()),
+            Some(price * 2.into())
+        );
+        assert_eq!(
+            calibrator.recalibrate(&client, 2000.into(), 1000.into()),
+            Some(2000.into())
+        );
+    }
+
+    #[test]
+    fn waits_for_recalibration_period() {
+        let client = TestBlockChainClient::new();
+        client.add_blocks(10, EachBlockWith::Transaction);
+
+        let mut calibrator = calibrator(Duration::from_secs(3600));
+        assert!(calibrator.recalibrate(&client, 0.into(), U256::max_value()).is_some());
+        assert!(calibrator.recalibrate(&client, 0.into(), U256::max_value()).is_none());
+    }
+
+    #[test]
+    fn needs_enough_blocks_with_transactions() {
+        let client = TestBlockChainClient::new();
+        client.add_blocks(10, EachBlockWith::Nothing);
+
+        let mut calibrator = calibrator(Duration::from_secs(0));
+        assert!(calibrator.recalibrate(&client, 0.into(), U256::max_value()).is_none());
+    }
+}
@@ -0,0 +1,168 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Mines proof-of-work blocks on the CPU of the node, for devnets and tests.
+
+use std::mem;
+use std::sync::{Arc, Weak};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use aion_types::{H256, U256};
+use blake2b::Blake2b;
+use client::Client;
+use engines::EthEngine;
+use equihash::EquihashSolver;
+use miner::{Miner, MinerService, NotifyWork};
+use num_cpus;
+use parking_lot::Mutex;
+
+/// Configures the internal miner.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Options {
+    /// Enable to mine internally
+    pub enable: bool,
+    /// Number of solver threads, 0 for one per CPU
+    pub threads: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            enable: false,
+            threads: 0,
+        }
+    }
+}
+
+/// Solves the work of the miner with the CPU Equihash solver and submits the seals.
+pub struct InternalMiner {
+    miner: Weak<Miner>,
+    client: Weak<Client>,
+    solver: EquihashSolver,
+    threads: usize,
+    /// Abort flag of the search for the current work.
+    current: Mutex<Arc<AtomicBool>>,
+}
+
+impl NotifyWork for InternalMiner {
+    fn notify_work(&self, pow_hash: H256, target: H256) {
+        trace!(target: "miner", "Internal miner got work {}", pow_hash);
+
+        let abort = Arc::new(AtomicBool::new(false));
+        mem::replace(&mut *self.current.lock(), abort.clone()).store(true, Ordering::SeqCst);
+
+        let solver = self.solver.clone();
+        let threads = self.threads;
+        let miner = self.miner.clone();
+        let client = self.client.clone();
+        let spawned = thread::Builder::new()
+            .name("internal-miner".into())
+            .spawn(move || {
+                let boundary = U256::from(target);
+                let found = solver.search(
+                    &pow_hash,
+                    H256::random().0,
+                    threads,
+                    move |nonce, solution| meets_boundary(&pow_hash, nonce, solution, &boundary),
+                    move || abort.load(Ordering::SeqCst),
+                );
+                let (nonce, solution) = match found {
+                    Some(found) => found,
+                    None => return,
+                };
+                if let (Some(miner), Some(client)) = (miner.upgrade(), client.upgrade()) {
+                    let seal = vec![nonce.to_vec(), solution];
+                    if let Err(e) = miner.submit_seal(&*client, pow_hash, seal) {
+                        warn!(target: "miner", "Internally mined seal rejected: {:?}", e);
+                    }
+                }
+            });
+        if let Err(e) = spawned {
+            warn!(target: "miner", "Error while starting the internal miner: {:?}", e);
+        }
+    }
+}
+
+impl InternalMiner {
+    /// Start mining internally and register the internal miner in the miner.
+    pub fn register(
+        options: &Options,
+        engine: &EthEngine,
+        miner: Arc<Miner>,
+        client: Weak<Client>,
+    ) -> Result<(), String>
+    {
+        let (n, k) = engine.equihash_params().ok_or_else(|| {
+            format!(
+                "Engine {} does not seal blocks with Equihash solutions",
+                engine.name()
+            )
+        })?;
+        let threads = match options.threads {
+            0 => num_cpus::get(),
+            threads => threads,
+        };
+        info!(
+            target: "miner",
+            "Mining internally with Equihash n={}, k={} on {} threads",
+            n,
+            k,
+            threads
+        );
+
+        let internal_miner = InternalMiner {
+            miner: Arc::downgrade(&miner),
+            client: client,
+            solver: EquihashSolver::new(n, k),
+            threads: threads,
+            current: Mutex::new(Arc::new(AtomicBool::new(false))),
+        };
+        miner.push_notifier(Box::new(internal_miner) as Box<NotifyWork>);
+        Ok(())
+    }
+}
+
+impl Drop for InternalMiner {
+    fn drop(&mut self) { self.current.lock().store(true, Ordering::SeqCst); }
+}
+
+/// Whether the seal hash of the solution is below the boundary of the work.
+pub fn meets_boundary(pow_hash: &H256, nonce: &[u8], solution: &[u8], boundary: &U256) -> bool {
+    let mut input: Vec<u8> = Vec::with_capacity(32 + nonce.len() + solution.len());
+    input.extend_from_slice(pow_hash.as_ref());
+    input.extend_from_slice(nonce);
+    input.extend_from_slice(solution);
+    U256::from(Blake2b::hash_256(input.as_slice())) < *boundary
+}
+
+#[cfg(test)]
+mod tests {
+    use aion_types::{H256, U256};
+    use super::meets_boundary;
+
+    #[test]
+    fn meets_boundary_of_any_difficulty() {
+        let pow_hash = H256::from(1);
+        assert!(meets_boundary(&pow_hash, &[0u8; 32], &[0u8; 36], &U256::max_value()));
+        assert!(!meets_boundary(&pow_hash, &[0u8; 32], &[0u8; 36], &U256::zero()));
+    }
+}
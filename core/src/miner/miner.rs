@@ -20,7 +20,7 @@
  *
  ******************************************************************************/
 
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{self, Duration, Instant};
 use std::thread;
@@ -31,7 +31,15 @@ use ansi_term::Colour;
 use bytes::Bytes;
 use engines::{EthEngine, Seal};
 use error::*;
-use miner::{MinerService, MinerStatus, NotifyWork};
+use evidence::DoubleSignEvidence;
+use key::public_to_address_ed25519;
+use precompiled::double_sign_contract::double_sign_contract_address;
+use miner::{
+    GasPriceCalibrator, GasPriceCalibratorOptions, MinerService, MinerStatus, NotifyWork,
+    PendingBlockStatus, TransactionSelection,
+};
+use miner::tx_selection::BlockSelection;
+use futures::sync::oneshot;
 use parking_lot::{Mutex, RwLock};
 use transaction::banning_queue::{BanningTransactionQueue, Threshold};
 use transaction::local_transactions::{Status as LocalTransactionStatus, TxIoMessage};
@@ -42,7 +50,7 @@ use transaction::transaction_queue::{
 };
 use transaction::{
     Action, Condition as TransactionCondition, Error as TransactionError,
-    ImportResult as TransactionImportResult, PendingTransaction, SignedTransaction,
+    ImportResult as TransactionImportResult, PendingTransaction, SignedTransaction, Transaction,
     UnverifiedTransaction,
 };
 use using_queue::{GetAction, UsingQueue};
@@ -55,6 +63,9 @@ use receipt::{Receipt, RichReceipt};
 use spec::Spec;
 use state::State;
 
+/// Energy limit of the double-sign contract calls reporting double-sign evidence.
+const SLASH_GAS: u64 = 200_000;
+
 /// Different possible definitions for pending transaction set.
 #[derive(Debug, PartialEq)]
 pub enum PendingSet {
@@ -124,6 +135,11 @@ pub struct MinerOptions {
     pub maximal_gas_price: U256,
     /// maximal gas price of a new local transaction to be accepted by the miner/transaction queue when using dynamic gas price
     pub local_max_gas_price: U256,
+    /// Track the energy prices of recent blocks to raise the minimal gas price, which stays
+    /// between `minimal_gas_price` and `local_max_gas_price`.
+    pub gas_price_calibrator: Option<GasPriceCalibratorOptions>,
+    /// Policies selecting the pending transactions of new blocks.
+    pub tx_selection: TransactionSelection,
 }
 
 impl Default for MinerOptions {
@@ -147,6 +163,8 @@ impl Default for MinerOptions {
             minimal_gas_price: 10_000_000_000u64.into(),
             maximal_gas_price: 9_000_000_000_000_000_000u64.into(),
             local_max_gas_price: 100_000_000_000u64.into(),
+            gas_price_calibrator: None,
+            tx_selection: TransactionSelection::default(),
         }
     }
 }
@@ -154,6 +172,15 @@ impl Default for MinerOptions {
 struct SealingWork {
     queue: UsingQueue<ClosedBlock>,
     enabled: bool,
+    prepared: u64,
+    invalidated: u64,
+}
+
+/// Pending PoS blocks, kept apart from the PoW sealing work, one per staker.
+struct PosSealingWork {
+    blocks: HashMap<Address, (ClosedBlock, Instant)>,
+    prepared: u64,
+    invalidated: u64,
 }
 
 /// Keeps track of transactions using priority queue and holds currently mined block.
@@ -163,11 +190,14 @@ pub struct Miner {
     transaction_queue: Arc<RwLock<BanningTransactionQueue>>,
     transaction_listener: RwLock<Vec<Box<Fn(&[H256]) + Send + Sync>>>,
     sealing_work: Mutex<SealingWork>,
+    pos_sealing_work: Mutex<PosSealingWork>,
     next_allowed_reseal: Mutex<Instant>,
     next_mandatory_reseal: RwLock<Instant>,
     sealing_block_last_request: Mutex<u64>,
     // for sealing...
     options: MinerOptions,
+    minimal_gas_price: RwLock<U256>,
+    gas_price_calibrator: Mutex<Option<GasPriceCalibrator>>,
     gas_range_target: RwLock<(U256, U256)>,
     author: RwLock<Address>,
     extra_data: RwLock<Bytes>,
@@ -175,6 +205,11 @@ pub struct Miner {
     accounts: Option<Arc<AccountProvider>>,
     notifiers: RwLock<Vec<Box<NotifyWork>>>,
     tx_message: Mutex<IoChannel<TxIoMessage>>,
+    // hash of the latest sealing work, signalled to long-polling miners on change
+    last_work_hash: Mutex<Option<H256>>,
+    work_waiters: Mutex<Vec<oneshot::Sender<()>>>,
+    // ed25519 keypair used to report double-sign evidence
+    evidence_reporter: RwLock<Option<[u8; 64]>>,
 }
 
 impl Miner {
@@ -184,6 +219,16 @@ impl Miner {
         self.sealing_work.lock().enabled = true;
     }
 
+    /// Boundary of the sealing work with the given mine hash, `None` if the work is unknown or
+    /// out of date.
+    pub fn work_boundary(&self, mine_hash: &H256) -> Option<H256> {
+        self.sealing_work
+            .lock()
+            .queue
+            .find_used_if(|b| &b.header().mine_hash() == mine_hash)
+            .map(|b| b.header().boundary())
+    }
+
     /// Creates new instance of miner Arc.
     pub fn new(
         options: MinerOptions,
@@ -234,15 +279,51 @@ impl Miner {
             sealing_work: Mutex::new(SealingWork {
                 queue: UsingQueue::new(options.work_queue_size),
                 enabled: options.force_sealing || spec.engine.seals_internally().is_some(),
+                prepared: 0,
+                invalidated: 0,
+            }),
+            pos_sealing_work: Mutex::new(PosSealingWork {
+                blocks: HashMap::new(),
+                prepared: 0,
+                invalidated: 0,
             }),
             gas_range_target: RwLock::new((U256::zero(), U256::zero())),
             author: RwLock::new(Address::default()),
             extra_data: RwLock::new(Vec::new()),
+            minimal_gas_price: RwLock::new(options.minimal_gas_price),
+            gas_price_calibrator: Mutex::new(
+                options
+                    .gas_price_calibrator
+                    .clone()
+                    .map(GasPriceCalibrator::new),
+            ),
             options: options,
             accounts: accounts,
             engine: spec.engine.clone(),
             notifiers: RwLock::new(notifiers),
             tx_message: Mutex::new(message_channel),
+            last_work_hash: Mutex::new(None),
+            work_waiters: Mutex::new(Vec::new()),
+            evidence_reporter: RwLock::new(None),
+        }
+    }
+
+    /// Update the minimal gas price from the energy prices of recent blocks, if calibration is
+    /// enabled.
+    fn recalibrate_minimal_gas_price(&self, client: &MiningBlockChainClient) {
+        let price = match *self.gas_price_calibrator.lock() {
+            Some(ref mut calibrator) => {
+                calibrator.recalibrate(
+                    client,
+                    self.options.minimal_gas_price,
+                    self.options.local_max_gas_price,
+                )
+            }
+            None => None,
+        };
+        if let Some(price) = price {
+            debug!(target: "miner", "Minimal gas price calibrated to {}", price);
+            *self.minimal_gas_price.write() = price;
         }
     }
 
@@ -254,6 +335,22 @@ impl Miner {
         *self.tx_message.lock() = tx_message;
     }
 
+    /// Memory limit of the transaction queue, `None` if it is not limited.
+    pub fn tx_queue_memory_limit(&self) -> Option<usize> {
+        match self.transaction_queue.read().memory_limit() {
+            limit if limit == usize::max_value() => None,
+            limit => Some(limit),
+        }
+    }
+
+    /// Changes the memory limit of the transaction queue.
+    pub fn set_tx_queue_memory_limit(&self, limit: usize) {
+        self.transaction_queue.write().set_memory_limit(limit);
+    }
+
+    /// Memory used by the transactions in the transaction queue.
+    pub fn tx_queue_memory_usage(&self) -> usize { self.transaction_queue.read().memory_usage() }
+
     /// Creates new instance of miner with accounts and with given spec.
     pub fn with_spec_and_accounts(spec: &Spec, accounts: Option<Arc<AccountProvider>>) -> Miner {
         Miner::new_raw(
@@ -317,16 +414,27 @@ impl Miner {
         let chain_info = client.chain_info();
         let (transactions, mut open_block, original_work_hash) = {
             let transactions = {
-                self.transaction_queue.read().top_transactions_at(
+                let queue = self.transaction_queue.read();
+                let transactions = queue.top_transactions_at(
                     chain_info.best_block_number,
                     chain_info.best_block_timestamp,
-                )
+                );
+                self.options
+                    .tx_selection
+                    .order(transactions, &queue.local_senders())
             };
+            // PoS blocks are prepared apart from the PoW sealing work and never replace it.
+            let is_pos = seal_type == Some(&SealType::Pos);
             let mut sealing_work = self.sealing_work.lock();
-            let last_work_hash = sealing_work
-                .queue
-                .peek_last_ref()
-                .map(|pb| pb.block().header().hash());
+            let last_work_hash = match is_pos {
+                true => None,
+                false => {
+                    sealing_work
+                        .queue
+                        .peek_last_ref()
+                        .map(|pb| pb.block().header().hash())
+                }
+            };
             let best_hash = chain_info.best_block_hash;
 
             // check to see if last ClosedBlock in would_seals is actually same parent block.
@@ -335,10 +443,15 @@ impl Miner {
             //   if at least one was pushed successfully, close and enqueue new ClosedBlock;
             //   otherwise, leave everything alone.
             // otherwise, author a fresh block.
-            let mut open_block = match sealing_work
-                .queue
-                .pop_if(|b| b.block().header().parent_hash() == &best_hash)
-            {
+            let pending = match is_pos {
+                true => None,
+                false => {
+                    sealing_work
+                        .queue
+                        .pop_if(|b| b.block().header().parent_hash() == &best_hash)
+                }
+            };
+            let mut open_block = match pending {
                 // Do not reopen block to get a more accurate block timestamp
                 //
                 // Ideally, a new block should be created when
@@ -379,10 +492,21 @@ impl Miner {
 
         trace!(target: "block", "prepare_block: block_number: {:?}, parent_block: {:?}", block_number, client.best_block_header().number());
 
+        let mut selection = BlockSelection::new(
+            &self.options.tx_selection,
+            *open_block.block().header().gas_limit(),
+            self.bridge_address(block_number),
+        );
         let mut tx_count: usize = 0;
         let tx_total = transactions.len();
         for tx in transactions {
             let hash = tx.hash();
+            let gas_used = *open_block.block().header().gas_used();
+            if !selection.admits(&tx, &gas_used) {
+                debug!(target: "block", "Skipping transaction {:?} refused by the selection policies", hash);
+                continue;
+            }
+            let sender = tx.sender();
             let start = Instant::now();
             // Disable transaction permission verification for now.
             // TODO: remove this functionality or keep it?
@@ -463,6 +587,8 @@ impl Miner {
                            block_number, hash, e);
                 }
                 Ok(_) => {
+                    let energy_used = *open_block.block().header().gas_used() - gas_used;
+                    selection.on_pushed(sender, energy_used);
                     tx_count += 1;
                 } // imported ok
             }
@@ -488,6 +614,16 @@ impl Miner {
         (block, original_work_hash)
     }
 
+    /// Address of the token bridge contract active at the given block, if any.
+    fn bridge_address(&self, block_number: BlockNumber) -> Option<Address> {
+        self.engine
+            .machine()
+            .builtins()
+            .iter()
+            .find(|&(_, builtin)| builtin.name() == "atb" && builtin.is_active(block_number))
+            .map(|(address, _)| *address)
+    }
+
     /// Check is reseal is allowed and necessary.
     fn requires_reseal(&self, best_block: BlockNumber) -> bool {
         let has_local_transactions = self
@@ -617,6 +753,13 @@ impl Miner {
                 let target = block.block().header().boundary();
                 let is_new =
                     original_work_hash.map_or(true, |h| block.block().header().mine_hash() != h);
+                let outdated = sealing_work.queue.peek_last_ref().map_or(false, |b| {
+                    b.block().header().parent_hash() != block.block().header().parent_hash()
+                });
+                if outdated {
+                    sealing_work.invalidated += 1;
+                }
+                sealing_work.prepared += 1;
                 sealing_work.queue.push(block);
                 // If push notifications are enabled we assume all work items are used.
                 if !self.notifiers.read().is_empty() && is_new {
@@ -629,6 +772,15 @@ impl Miner {
             trace!(target: "block", "prepare_work: leaving (last={:?})", sealing_work.queue.peek_last_ref().map(|b| b.block().header().mine_hash()));
             (work, is_new)
         };
+        if let Some((pow_hash, _, _)) = work {
+            let mut last_work_hash = self.last_work_hash.lock();
+            if *last_work_hash != Some(pow_hash) {
+                *last_work_hash = Some(pow_hash);
+                for waiter in self.work_waiters.lock().drain(..) {
+                    let _ = waiter.send(());
+                }
+            }
+        }
         if is_new {
             work.map(|(pow_hash, target, _number)| {
                 for notifier in self.notifiers.read().iter() {
@@ -671,6 +823,110 @@ impl Miner {
         prepare_new
     }
 
+    /// Sets the ed25519 keypair (private key + public key) used to report double-sign evidence.
+    pub fn set_evidence_reporter(&self, keypair: [u8; 64]) {
+        *self.evidence_reporter.write() = Some(keypair);
+    }
+
+    /// Hands the given double-sign evidence to the double-sign contract, which slashes the
+    /// offender, in a transaction signed by the evidence reporter. Does nothing if there is no
+    /// reporter.
+    pub fn report_double_sign(
+        &self,
+        client: &MiningBlockChainClient,
+        evidence: &DoubleSignEvidence,
+    )
+    {
+        let keypair = match *self.evidence_reporter.read() {
+            Some(reporter) => reporter,
+            None => {
+                debug!(target: "miner", "No evidence reporter, double-sign of {} not reported", evidence.offender());
+                return;
+            }
+        };
+
+        let reporter = public_to_address_ed25519(&H256::from(&keypair[32..]));
+        let nonce = self
+            .last_nonce(&reporter)
+            .map(|nonce| nonce + U256::one())
+            .unwrap_or_else(|| client.latest_nonce(&reporter));
+        let transaction = Transaction::new(
+            nonce,
+            self.minimal_gas_price(),
+            SLASH_GAS.into(),
+            Action::Call(double_sign_contract_address()),
+            U256::zero(),
+            ::rlp::encode(evidence).into_vec(),
+        )
+        .sign(&keypair, None);
+        let hash = transaction.hash();
+
+        match self.import_own_transaction(client, PendingTransaction::new(transaction, None)) {
+            Ok(_) => {
+                info!(target: "miner", "Reported double-sign of {} in transaction {}", evidence.offender(), hash)
+            }
+            Err(e) => {
+                warn!(target: "miner", "Failed to report double-sign of {}: {:?}", evidence.offender(), e)
+            }
+        }
+    }
+
+    /// Returns the pending PoS block of the given staker. A new one is prepared if there is none
+    /// on top of the best block yet or the existing one is older than the prepare block interval.
+    pub fn pos_block(&self, client: &MiningBlockChainClient, author: Address) -> ClosedBlock {
+        let best_hash = client.chain_info().best_block_hash;
+        if let Some(&(ref block, ref prepared_at)) =
+            self.pos_sealing_work.lock().blocks.get(&author)
+        {
+            if block.header().parent_hash() == &best_hash
+                && prepared_at.elapsed() < self.prepare_block_interval()
+            {
+                return block.clone();
+            }
+        }
+        self.prepare_pos_block(client, author)
+    }
+
+    /// Prepares a new pending PoS block for the given staker, replacing its previous one.
+    fn prepare_pos_block(&self, client: &MiningBlockChainClient, author: Address) -> ClosedBlock {
+        let (block, _) = self.prepare_block(client, Some(&SealType::Pos), author);
+        let mut pos_sealing_work = self.pos_sealing_work.lock();
+        pos_sealing_work.prepared += 1;
+        let previous = pos_sealing_work
+            .blocks
+            .insert(author, (block.clone(), Instant::now()));
+        if let Some((previous, _)) = previous {
+            if previous.header().parent_hash() != block.header().parent_hash() {
+                pos_sealing_work.invalidated += 1;
+            }
+        }
+        block
+    }
+
+    /// Refreshes the pending PoS blocks of the stakers which requested one, if PoS is active on
+    /// top of the best block.
+    fn update_pos_sealing(&self, client: &MiningBlockChainClient) {
+        let next_number = client.chain_info().best_block_number + 1;
+        if !self
+            .engine
+            .machine()
+            .is_seal_type_active(&SealType::Pos, next_number)
+        {
+            return;
+        }
+        let authors: Vec<Address> = self
+            .pos_sealing_work
+            .lock()
+            .blocks
+            .keys()
+            .cloned()
+            .collect();
+        for author in authors {
+            trace!(target: "block", "update_pos_sealing: preparing a block for {:?}", author);
+            self.prepare_pos_block(client, author);
+        }
+    }
+
     /// Try to prepare a work.
     /// Create a new work if no work exists or update an existing work depending on the
     /// configurations and the current conditions.
@@ -892,16 +1148,47 @@ impl MinerService for Miner {
         self.update_sealing(client);
     }
 
+    fn work_changed(&self, work_hash: &H256) -> oneshot::Receiver<()> {
+        let (waiter, changed) = oneshot::channel();
+        let last_work_hash = self.last_work_hash.lock();
+        if *last_work_hash == Some(*work_hash) {
+            let mut waiters = self.work_waiters.lock();
+            // drops the waiters of long polls that gave up
+            waiters.retain(|waiter| !waiter.is_canceled());
+            waiters.push(waiter);
+        } else {
+            let _ = waiter.send(());
+        }
+        changed
+    }
+
     fn status(&self) -> MinerStatus {
         let status = self.transaction_queue.read().status();
         let sealing_work = self.sealing_work.lock();
+        let pos_sealing_work = self.pos_sealing_work.lock();
+        let transactions_in_pending_block = sealing_work
+            .queue
+            .peek_last_ref()
+            .map_or(0, |b| b.transactions().len());
         MinerStatus {
             transactions_in_pending_queue: status.pending,
             transactions_in_future_queue: status.future,
-            transactions_in_pending_block: sealing_work
-                .queue
-                .peek_last_ref()
-                .map_or(0, |b| b.transactions().len()),
+            transactions_in_pending_block: transactions_in_pending_block,
+            pow_pending: PendingBlockStatus {
+                prepared: sealing_work.prepared,
+                invalidated: sealing_work.invalidated,
+                transactions_in_pending_block: transactions_in_pending_block,
+            },
+            pos_pending: PendingBlockStatus {
+                prepared: pos_sealing_work.prepared,
+                invalidated: pos_sealing_work.invalidated,
+                transactions_in_pending_block: pos_sealing_work
+                    .blocks
+                    .values()
+                    .map(|&(ref b, _)| b.transactions().len())
+                    .max()
+                    .unwrap_or(0),
+            },
         }
     }
 
@@ -922,9 +1209,10 @@ impl MinerService for Miner {
 
     fn set_minimal_gas_price(&mut self, min_gas_price: U256) {
         self.options.minimal_gas_price = min_gas_price;
+        *self.minimal_gas_price.write() = min_gas_price;
     }
 
-    fn minimal_gas_price(&self) -> U256 { self.options.minimal_gas_price }
+    fn minimal_gas_price(&self) -> U256 { *self.minimal_gas_price.read() }
 
     fn set_maximal_gas_price(&mut self, max_gas_price: U256) {
         self.options.maximal_gas_price = max_gas_price;
@@ -1245,7 +1533,11 @@ impl MinerService for Miner {
             // | Make sure to release the locks before calling that method.             |
             // --------------------------------------------------------------------------
             trace!(target: "block", "update_sealing: preparing a block");
-            let (block, original_work_hash) = self.prepare_block(client, None, self.author());
+            let seal_type = self
+                .engine
+                .next_seal_type(&client.best_block_header().decode());
+            let (block, original_work_hash) =
+                self.prepare_block(client, seal_type.as_ref(), self.author());
             match self.engine.seals_internally() {
                 Some(true) => {
                     trace!(target: "block", "update_sealing: engine indicates internal sealing");
@@ -1262,6 +1554,7 @@ impl MinerService for Miner {
                 }
             }
         }
+        self.update_pos_sealing(client);
     }
 
     fn is_currently_sealing(&self) -> bool { self.sealing_work.lock().queue.is_in_use() }
@@ -1369,6 +1662,8 @@ impl MinerService for Miner {
                 .remove_old(&fetch_account, time);
         }
 
+        self.recalibrate_minimal_gas_price(client);
+
         // re-boradcast
         // let chain_info = client.chain_info();
         // let transactions = {
@@ -1439,6 +1734,7 @@ mod tests {
     use transaction::Action;
     use client::{BlockChainClient, EachBlockWith, TestBlockChainClient};
     use miner::MinerService;
+    use futures::Future;
     use tests::helpers::generate_dummy_client;
 
     #[test]
@@ -1473,7 +1769,93 @@ mod tests {
         assert!(miner.submit_seal(&client, res.unwrap(), vec![]).is_ok());
     }
 
-    fn miner() -> Miner {
+    #[test]
+    fn should_signal_work_change() {
+        // given
+        let client = TestBlockChainClient::default();
+        let miner = Miner::with_spec(&Spec::new_test());
+        let work = miner
+            .map_sealing_work(&client, |b| b.block().header().mine_hash())
+            .unwrap();
+
+        // when
+        let mut changed = miner.work_changed(&work);
+        let unchanged = changed.try_recv();
+        client.add_blocks(1, EachBlockWith::Nothing);
+        miner.update_sealing(&client);
+        let new_work = miner
+            .map_sealing_work(&client, |b| b.block().header().mine_hash())
+            .unwrap();
+
+        // then
+        assert_eq!(unchanged, Ok(None));
+        assert_eq!(changed.wait(), Ok(()));
+        assert!(new_work != work);
+        assert_eq!(miner.work_changed(&work).wait(), Ok(()));
+    }
+
+    #[test]
+    fn should_keep_pow_and_pos_pending_blocks_apart() {
+        // given
+        let client = TestBlockChainClient::default();
+        let miner = Miner::with_spec(&Spec::new_test());
+        let staker = Address::from(1);
+        let work = miner
+            .map_sealing_work(&client, |b| b.block().header().mine_hash())
+            .unwrap();
+
+        // when
+        let pos_block = miner.pos_block(&client, staker);
+        let cached = miner.pos_block(&client, staker);
+        let same_work = miner
+            .map_sealing_work(&client, |b| b.block().header().mine_hash())
+            .unwrap();
+        let before = miner.status();
+        client.add_blocks(1, EachBlockWith::Nothing);
+        miner.update_sealing(&client);
+        let next_pos_block = miner.pos_block(&client, staker);
+        let after = miner.status();
+
+        // then
+        assert_eq!(same_work, work);
+        assert_eq!(pos_block.header().hash(), cached.header().hash());
+        assert_eq!(before.pow_pending.prepared, 1);
+        assert_eq!(before.pos_pending.prepared, 1);
+        assert_eq!(before.pos_pending.invalidated, 0);
+        assert_eq!(next_pos_block.header().parent_hash(), &client.chain_info().best_block_hash);
+        assert_eq!(after.pow_pending.prepared, 2);
+        assert_eq!(after.pow_pending.invalidated, 1);
+        assert_eq!(after.pos_pending.invalidated, 1);
+    }
+
+    #[test]
+    fn should_refresh_pos_blocks_on_update_sealing() {
+        // given
+        let client = TestBlockChainClient::default();
+        let miner = Miner::with_spec(&Spec::new_test());
+        let staker = Address::from(1);
+        let pos_block = miner.pos_block(&client, staker);
+
+        // when
+        client.add_blocks(1, EachBlockWith::Nothing);
+        miner.update_sealing(&client);
+        let refreshed = miner.status();
+        let next_pos_block = miner.pos_block(&client, staker);
+        let after = miner.status();
+
+        // then
+        assert_eq!(refreshed.pos_pending.prepared, 2);
+        assert_eq!(refreshed.pos_pending.invalidated, 1);
+        assert!(next_pos_block.header().hash() != pos_block.header().hash());
+        assert_eq!(next_pos_block.header().parent_hash(), &client.chain_info().best_block_hash);
+        assert_eq!(next_pos_block.header().seal_type(), &Some(SealType::Pos));
+        // the block refreshed by update_sealing is handed out as is
+        assert_eq!(after.pos_pending.prepared, 2);
+    }
+
+    fn miner() -> Miner { miner_with_selection(TransactionSelection::default()) }
+
+    fn miner_with_selection(tx_selection: TransactionSelection) -> Miner {
         Arc::try_unwrap(Miner::new(
             MinerOptions {
                 force_sealing: false,
@@ -1493,6 +1875,8 @@ mod tests {
                 minimal_gas_price: 0u64.into(),
                 maximal_gas_price: 9_000_000_000_000_000_000u64.into(),
                 local_max_gas_price: 100_000_000_000u64.into(),
+                gas_price_calibrator: None,
+                tx_selection: tx_selection,
             },
             &Spec::new_test(),
             None, // accounts provider
@@ -1541,6 +1925,35 @@ mod tests {
         assert!(!miner.prepare_work_sealing(&client));
     }
 
+    #[test]
+    fn should_apply_transaction_selection_policies() {
+        // given
+        let client = TestBlockChainClient::default();
+        let unlimited = miner();
+        let capped = miner_with_selection(TransactionSelection {
+            max_sender_energy_percent: Some(0),
+            ..Default::default()
+        });
+        let transaction = transaction();
+
+        // when
+        for miner in &[&unlimited, &capped] {
+            let res = miner.import_own_transaction(
+                &client,
+                PendingTransaction::new(transaction.clone(), None),
+            );
+            assert_eq!(res.unwrap(), TransactionImportResult::Current);
+        }
+        let (unlimited_block, _) = unlimited.prepare_block(&client, None, Address::default());
+        let (capped_block, _) = capped.prepare_block(&client, None, Address::default());
+
+        // then
+        assert_eq!(unlimited_block.transactions().len(), 1);
+        assert_eq!(capped_block.transactions().len(), 0);
+        // refused transactions stay in the queue
+        assert_eq!(capped.pending_transactions().len(), 1);
+    }
+
     #[test]
     fn should_not_use_pending_block_if_best_block_is_higher() {
         // given
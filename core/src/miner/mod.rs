@@ -45,20 +45,28 @@
 //! }
 //! ```
 */
+mod gas_price_calibrator;
+mod internal_miner;
 mod miner;
 mod staker;
 mod stratum;
+mod tx_selection;
 pub mod external;
 
+pub use self::gas_price_calibrator::{GasPriceCalibrator, GasPriceCalibratorOptions};
+pub use self::internal_miner::{InternalMiner, Options as InternalMinerOptions};
 pub use self::miner::{Miner, MinerOptions, Banning, PendingSet};
 pub use self::staker::Staker;
 pub use self::stratum::{Stratum, Error as StratumError, Options as StratumOptions, NotifyWork};
+pub use self::tx_selection::{SelectionOrdering, TransactionSelection};
+pub use acore_stratum::{ShareDifficulty, WorkerStats as StratumWorkerStats};
 
 pub use transaction::local_transactions::Status as LocalTransactionStatus;
 
 use std::collections::BTreeMap;
 use aion_types::{H256, U256, Address};
 use bytes::Bytes;
+use futures::sync::oneshot;
 
 use block::ClosedBlock;
 use client::{MiningBlockChainClient};
@@ -166,6 +174,9 @@ pub trait MinerService: Send + Sync {
         seal: Vec<Bytes>,
     ) -> Result<(), Error>;
 
+    /// Returns a receiver notified once the sealing work is no longer the one of `work_hash`.
+    fn work_changed(&self, work_hash: &H256) -> oneshot::Receiver<()>;
+
     /// Get the sealing work package and if `Some`, apply some transform.
     fn map_sealing_work<F, T>(&self, chain: &MiningBlockChainClient, f: F) -> Option<T>
     where
@@ -232,4 +243,19 @@ pub struct MinerStatus {
     pub transactions_in_future_queue: usize,
     /// Number of transactions included in currently mined block
     pub transactions_in_pending_block: usize,
+    /// Pending PoW block metrics
+    pub pow_pending: PendingBlockStatus,
+    /// Pending PoS block metrics
+    pub pos_pending: PendingBlockStatus,
+}
+
+/// Metrics of the pending blocks prepared for one seal type
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PendingBlockStatus {
+    /// Number of pending blocks prepared
+    pub prepared: u64,
+    /// Number of pending blocks replaced because the chain moved to a new best block
+    pub invalidated: u64,
+    /// Number of transactions included in the largest current pending block
+    pub transactions_in_pending_block: usize,
 }
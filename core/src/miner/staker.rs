@@ -32,6 +32,7 @@ use engines::EthEngine;
 use rcrypto::ed25519::{keypair, signature};
 use spec::Spec;
 use header::SealType;
+use precompiled::double_sign_contract::{double_sign_contract_address, slashed_key};
 
 use super::Miner;
 use ansi_term::Colour;
@@ -61,6 +62,8 @@ pub enum Error {
     PosInvalid,
     /// Failed to import the block
     FailedToImport,
+    /// PoS blocks are not accepted yet at this height.
+    PosNotActive,
 }
 
 impl Staker {
@@ -83,6 +86,11 @@ impl Staker {
         }
     }
 
+    /// Use this staker's key to report double-sign evidence to the double-sign contract
+    pub fn report_evidence_with(&self, miner: &Miner) {
+        miner.set_evidence_reporter(self.keypair);
+    }
+
     /// Calculate the block producing time of this staker
     pub fn calc_produce_time(&self, client: &Client) -> u64 {
         let map_key = self.address.0;
@@ -106,6 +114,15 @@ impl Staker {
             )
             .unwrap_or(H128::default());
         let stake = U128::from(stake).as_u64();
+        // stakers slashed for double-signing have no stake left
+        let slashed = client
+            .storage_at(
+                &double_sign_contract_address(),
+                &slashed_key(&self.address),
+                BlockId::Latest,
+            )
+            .unwrap_or(H128::default());
+        let stake = if U128::from(slashed).is_zero() { stake } else { 0 };
 
         // timestamp and previous seed
         let parent_header = client.best_block_header_with_seal_type(&SealType::Pos);
@@ -150,8 +167,15 @@ impl Staker {
 
     /// Produce a PoS block
     pub fn produce_block(&self, miner: &Miner, client: &Client) -> Result<(), Error> {
-        // 1. create a PoS block template
-        let (raw_block, _) = miner.prepare_block(client, Some(&SealType::Pos), self.address);
+        // 1. get the PoS block template of this staker
+        let raw_block = miner.pos_block(client, self.address);
+        if !self
+            .engine
+            .machine()
+            .is_seal_type_active(&SealType::Pos, raw_block.header().number())
+        {
+            return Err(Error::PosNotActive);
+        }
         let parent_hash = raw_block.header().parent_hash().clone();
         let bare_hash = raw_block.header().bare_hash();
 
@@ -28,12 +28,15 @@ use std::fmt;
 
 use block::IsBlock;
 use client::Client;
-use aion_types::{H256, clean_0x};
+use aion_types::{H256, U256, clean_0x};
 use acore_stratum::{
-    JobDispatcher, PushWorkHandler,
+    JobDispatcher, PushWorkHandler, ShareDifficulty, ShareResult, WorkerStats,
     Stratum as StratumService, Error as StratumServiceError,
 };
+use equihash::EquihashValidator;
+use error::Error as MinerError;
 use miner::{self, Miner, MinerService};
+use super::internal_miner::meets_boundary;
 use dir::helpers::replace_home_and_local;
 use dir::{default_data_path,default_local_path,CHAINS_PATH};
 
@@ -59,6 +62,8 @@ pub struct Options {
     pub port: u16,
     /// Secret for peers
     pub secret: Option<H256>,
+    /// Share difficulty of the workers, shares are checked against the block target if none
+    pub share_difficulty: Option<ShareDifficulty>,
 }
 
 impl Default for Options {
@@ -73,6 +78,7 @@ impl Default for Options {
             listen_addr: "127.0.0.1".to_string(),
             port: 8008,
             secret: None,
+            share_difficulty: None,
         }
     }
 }
@@ -179,6 +185,58 @@ impl JobDispatcher for StratumJobDispatcher {
             }
         })
     }
+
+    fn submit_share(&self, payload: Vec<String>, share_target: Option<H256>) -> ShareResult {
+        let payload = match SubmitPayload::from_args(payload) {
+            Ok(payload) => payload,
+            Err(e) => return ShareResult::Invalid(e.to_string()),
+        };
+        let (client, miner) = match (self.client.upgrade(), self.miner.upgrade()) {
+            (Some(client), Some(miner)) => (client, miner),
+            _ => return ShareResult::Stale,
+        };
+        let boundary = match miner.work_boundary(&payload.pow_hash) {
+            Some(boundary) => U256::from(boundary),
+            None => return ShareResult::Stale,
+        };
+
+        if let Some((n, k)) = client.engine().equihash_params() {
+            let validator = EquihashValidator::new(n as i32, k as i32);
+            if !validator.is_valid_solution(
+                &payload.solution,
+                payload.pow_hash.as_ref(),
+                payload.nonce.as_ref(),
+            ) {
+                return ShareResult::Invalid("invalid equihash solution".into());
+            }
+        }
+
+        if meets_boundary(&payload.pow_hash, &payload.nonce, &payload.solution, &boundary) {
+            let seal = vec![payload.nonce.to_vec(), payload.solution.to_vec()];
+            return match miner.submit_seal(&*client, payload.pow_hash, seal) {
+                Ok(_) => ShareResult::Block,
+                Err(MinerError::PowHashInvalid) => ShareResult::Stale,
+                Err(e) => {
+                    warn!(target: "stratum", "submit_seal error: {:?}", e);
+                    ShareResult::Invalid(e.to_string())
+                }
+            };
+        }
+
+        match share_target {
+            Some(ref target)
+                if meets_boundary(
+                    &payload.pow_hash,
+                    &payload.nonce,
+                    &payload.solution,
+                    &U256::from(*target),
+                ) =>
+            {
+                ShareResult::Accepted
+            }
+            _ => ShareResult::Invalid("share target not met".into()),
+        }
+    }
 }
 
 impl StratumJobDispatcher {
@@ -216,6 +274,7 @@ impl StratumJobDispatcher {
 }
 
 /// Wrapper for dedicated stratum service
+#[derive(Clone)]
 pub struct Stratum {
     dispatcher: Arc<StratumJobDispatcher>,
     service: Arc<StratumService>,
@@ -264,6 +323,7 @@ impl Stratum {
             &SocketAddr::new(options.listen_addr.parse::<IpAddr>()?, options.port),
             dispatcher.clone(),
             options.secret.clone(),
+            options.share_difficulty.clone(),
         )?;
 
         Ok(Stratum {
@@ -272,10 +332,18 @@ impl Stratum {
         })
     }
 
+    /// Share accounting of the workers
+    pub fn worker_stats(&self) -> Vec<WorkerStats> { self.service.worker_stats() }
+
     /// Start STRATUM job dispatcher and register it in the miner
-    pub fn register(cfg: &Options, miner: Arc<Miner>, client: Weak<Client>) -> Result<(), Error> {
+    pub fn register(
+        cfg: &Options,
+        miner: Arc<Miner>,
+        client: Weak<Client>,
+    ) -> Result<Stratum, Error>
+    {
         let stratum = miner::Stratum::start(cfg, Arc::downgrade(&miner.clone()), client)?;
-        miner.push_notifier(Box::new(stratum) as Box<NotifyWork>);
-        Ok(())
+        miner.push_notifier(Box::new(stratum.clone()) as Box<NotifyWork>);
+        Ok(stratum)
     }
 }
@@ -0,0 +1,372 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+//! Selection of the pending transactions included in a new block.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+use aion_types::{Address, U256};
+use rlp;
+use transaction::{Action, SignedTransaction};
+
+/// Order in which the pending transactions are considered for a new block.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SelectionOrdering {
+    /// Order of the transaction queue.
+    Queue,
+    /// Highest fee (energy price times energy limit) per byte of the transaction first.
+    EnergyPricePerByte,
+}
+
+/// Policies selecting the pending transactions of a new block. Transactions of a sender always
+/// keep their nonce order.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TransactionSelection {
+    /// Order of the transactions.
+    pub ordering: SelectionOrdering,
+    /// Whether transactions of local senders go first.
+    pub prioritize_local: bool,
+    /// Senders whose transactions go first.
+    pub priority_senders: HashSet<Address>,
+    /// Maximal percentage of the block energy limit the transactions of a sender can use.
+    pub max_sender_energy_percent: Option<u64>,
+    /// Percentage of the block energy limit only bridge transactions can use.
+    pub bridge_energy_reserve_percent: u64,
+}
+
+impl Default for TransactionSelection {
+    fn default() -> Self {
+        TransactionSelection {
+            ordering: SelectionOrdering::Queue,
+            prioritize_local: false,
+            priority_senders: HashSet::new(),
+            max_sender_energy_percent: None,
+            bridge_energy_reserve_percent: 0,
+        }
+    }
+}
+
+/// Next transaction of a sender, ordered by priority then score then queue position.
+#[derive(PartialEq, Eq)]
+struct Candidate {
+    priority: bool,
+    score: U256,
+    position: usize,
+    sender: Address,
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Candidate) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then(self.score.cmp(&other.score))
+            .then(other.position.cmp(&self.position))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Candidate) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl TransactionSelection {
+    /// Orders the transactions given in queue order, keeping the order of the transactions of
+    /// each sender.
+    pub fn order(
+        &self,
+        transactions: Vec<SignedTransaction>,
+        local_senders: &HashSet<Address>,
+    ) -> Vec<SignedTransaction>
+    {
+        if self.ordering == SelectionOrdering::Queue
+            && !self.prioritize_local
+            && self.priority_senders.is_empty()
+        {
+            return transactions;
+        }
+
+        let total = transactions.len();
+        let mut by_sender: HashMap<Address, VecDeque<(usize, SignedTransaction)>> = HashMap::new();
+        for (position, tx) in transactions.into_iter().enumerate() {
+            by_sender
+                .entry(tx.sender())
+                .or_insert_with(VecDeque::new)
+                .push_back((position, tx));
+        }
+
+        let mut heads = BinaryHeap::new();
+        for (sender, txs) in by_sender.iter() {
+            if let Some(&(position, ref tx)) = txs.front() {
+                heads.push(self.candidate(*sender, position, tx, local_senders));
+            }
+        }
+
+        let mut ordered = Vec::with_capacity(total);
+        while let Some(head) = heads.pop() {
+            let txs = by_sender
+                .get_mut(&head.sender)
+                .expect("candidates are senders of by_sender; qed");
+            let (_, tx) = txs
+                .pop_front()
+                .expect("a candidate is the front of its sender; qed");
+            ordered.push(tx);
+            if let Some(&(position, ref tx)) = txs.front() {
+                heads.push(self.candidate(head.sender, position, tx, local_senders));
+            }
+        }
+        ordered
+    }
+
+    fn candidate(
+        &self,
+        sender: Address,
+        position: usize,
+        tx: &SignedTransaction,
+        local_senders: &HashSet<Address>,
+    ) -> Candidate
+    {
+        let priority = (self.prioritize_local && local_senders.contains(&sender))
+            || self.priority_senders.contains(&sender);
+        let score = match self.ordering {
+            SelectionOrdering::Queue => U256::zero(),
+            SelectionOrdering::EnergyPricePerByte => {
+                let size = rlp::encode(tx).len() as u64;
+                tx.gas_price.saturating_mul(tx.gas) / U256::from(size)
+            }
+        };
+        Candidate {
+            priority: priority,
+            score: score,
+            position: position,
+            sender: sender,
+        }
+    }
+}
+
+/// Applies the energy limits of the selection policies while a block is filled.
+pub struct BlockSelection {
+    energy_limit: U256,
+    max_sender_energy: Option<U256>,
+    bridge_reserve: U256,
+    bridge: Option<Address>,
+    sender_energy: HashMap<Address, U256>,
+    skipped_senders: HashSet<Address>,
+}
+
+impl BlockSelection {
+    /// Starts the selection of a block with the given energy limit and bridge contract address.
+    pub fn new(
+        policy: &TransactionSelection,
+        energy_limit: U256,
+        bridge: Option<Address>,
+    ) -> BlockSelection
+    {
+        let percent = |p: u64| energy_limit / U256::from(100) * U256::from(p);
+        BlockSelection {
+            energy_limit: energy_limit,
+            max_sender_energy: policy.max_sender_energy_percent.map(|p| percent(p)),
+            bridge_reserve: match bridge {
+                Some(_) => percent(policy.bridge_energy_reserve_percent),
+                None => U256::zero(),
+            },
+            bridge: bridge,
+            sender_energy: HashMap::new(),
+            skipped_senders: HashSet::new(),
+        }
+    }
+
+    /// Whether the transaction can be pushed in a block which already used `energy_used`.
+    /// Once a transaction of a sender is refused, the later ones are as well.
+    pub fn admits(&mut self, tx: &SignedTransaction, energy_used: &U256) -> bool {
+        let sender = tx.sender();
+        if self.skipped_senders.contains(&sender) {
+            return false;
+        }
+        let sender_energy = self
+            .sender_energy
+            .get(&sender)
+            .cloned()
+            .unwrap_or_else(U256::zero);
+        let over_sender_limit = self
+            .max_sender_energy
+            .map_or(false, |max| sender_energy.saturating_add(tx.gas) > max);
+        let is_bridge = match (self.bridge, &tx.action) {
+            (Some(bridge), &Action::Call(ref to)) => *to == bridge,
+            _ => false,
+        };
+        let non_bridge_limit = self.energy_limit.saturating_sub(self.bridge_reserve);
+        let over_reserve = !is_bridge && energy_used.saturating_add(tx.gas) > non_bridge_limit;
+        if over_sender_limit || over_reserve {
+            self.skipped_senders.insert(sender);
+            return false;
+        }
+        true
+    }
+
+    /// Accounts the energy used by a transaction of the sender pushed in the block.
+    pub fn on_pushed(&mut self, sender: Address, energy_used: U256) {
+        *self
+            .sender_energy
+            .entry(sender)
+            .or_insert_with(U256::zero) += energy_used;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use aion_types::{Address, U256};
+    use key::{generate_keypair, Ed25519KeyPair};
+    use transaction::{Action, SignedTransaction, Transaction, DEFAULT_TRANSACTION_TYPE};
+    use super::{BlockSelection, SelectionOrdering, TransactionSelection};
+
+    fn transaction(
+        keypair: &Ed25519KeyPair,
+        nonce: u64,
+        gas_price: u64,
+        data: Vec<u8>,
+        action: Action,
+    ) -> SignedTransaction
+    {
+        Transaction {
+            action: action,
+            value: U256::zero(),
+            data: data,
+            gas: U256::from(100_000),
+            gas_price: gas_price.into(),
+            nonce: nonce.into(),
+            transaction_type: DEFAULT_TRANSACTION_TYPE,
+            nonce_bytes: Vec::new(),
+            gas_price_bytes: Vec::new(),
+            gas_bytes: Vec::new(),
+            value_bytes: Vec::new(),
+        }
+        .sign(keypair.secret(), None)
+    }
+
+    fn nonces(transactions: &[SignedTransaction], keypair: &Ed25519KeyPair) -> Vec<U256> {
+        transactions
+            .iter()
+            .filter(|tx| tx.sender() == keypair.address())
+            .map(|tx| tx.nonce)
+            .collect()
+    }
+
+    #[test]
+    fn keeps_queue_order_by_default() {
+        let a = generate_keypair();
+        let b = generate_keypair();
+        let txs = vec![
+            transaction(&a, 0, 1, vec![], Action::Create),
+            transaction(&b, 0, 10, vec![], Action::Create),
+        ];
+        let hashes: Vec<_> = txs.iter().map(|tx| tx.hash()).collect();
+
+        let ordered = TransactionSelection::default().order(txs, &HashSet::new());
+        assert_eq!(ordered.iter().map(|tx| tx.hash()).collect::<Vec<_>>(), hashes);
+    }
+
+    #[test]
+    fn orders_by_energy_price_per_byte_keeping_nonces() {
+        let a = generate_keypair();
+        let b = generate_keypair();
+        // same price, the large transaction of `a` pays less per byte
+        let txs = vec![
+            transaction(&a, 0, 10, vec![0u8; 1024], Action::Create),
+            transaction(&a, 1, 10, vec![], Action::Create),
+            transaction(&b, 0, 10, vec![], Action::Create),
+        ];
+        let selection = TransactionSelection {
+            ordering: SelectionOrdering::EnergyPricePerByte,
+            ..Default::default()
+        };
+
+        let ordered = selection.order(txs, &HashSet::new());
+        assert_eq!(ordered[0].sender(), b.address());
+        assert_eq!(nonces(&ordered, &a), vec![U256::from(0), U256::from(1)]);
+    }
+
+    #[test]
+    fn prioritizes_local_and_whitelisted_senders() {
+        let a = generate_keypair();
+        let b = generate_keypair();
+        let c = generate_keypair();
+        let txs = vec![
+            transaction(&a, 0, 100, vec![], Action::Create),
+            transaction(&b, 0, 1, vec![], Action::Create),
+            transaction(&c, 0, 1, vec![], Action::Create),
+        ];
+        let mut priority_senders = HashSet::new();
+        priority_senders.insert(c.address());
+        let mut local_senders = HashSet::new();
+        local_senders.insert(b.address());
+        let selection = TransactionSelection {
+            prioritize_local: true,
+            priority_senders: priority_senders,
+            ..Default::default()
+        };
+
+        let ordered = selection.order(txs, &local_senders);
+        let senders: Vec<Address> = ordered.iter().map(|tx| tx.sender()).collect();
+        assert_eq!(senders, vec![b.address(), c.address(), a.address()]);
+    }
+
+    #[test]
+    fn caps_energy_of_a_sender() {
+        let a = generate_keypair();
+        let b = generate_keypair();
+        let selection = TransactionSelection {
+            max_sender_energy_percent: Some(50),
+            ..Default::default()
+        };
+        let mut block = BlockSelection::new(&selection, U256::from(300_000), None);
+
+        let first = transaction(&a, 0, 1, vec![], Action::Create);
+        assert!(block.admits(&first, &U256::zero()));
+        block.on_pushed(first.sender(), U256::from(100_000));
+        // 200_000 would exceed half of the block
+        let second = transaction(&a, 1, 1, vec![], Action::Create);
+        assert!(!block.admits(&second, &U256::from(100_000)));
+        assert!(block.admits(&transaction(&b, 0, 1, vec![], Action::Create), &U256::from(100_000)));
+    }
+
+    #[test]
+    fn reserves_energy_for_bridge_transactions() {
+        let a = generate_keypair();
+        let b = generate_keypair();
+        let bridge = Address::from(0x200);
+        let selection = TransactionSelection {
+            bridge_energy_reserve_percent: 50,
+            ..Default::default()
+        };
+        let mut block = BlockSelection::new(&selection, U256::from(300_000), Some(bridge));
+
+        let to_bridge = transaction(&a, 0, 1, vec![], Action::Call(bridge));
+        let create = transaction(&b, 0, 1, vec![], Action::Create);
+        assert!(block.admits(&create, &U256::from(50_000)));
+        assert!(!block.admits(&create, &U256::from(50_001)));
+        assert!(block.admits(&to_bridge, &U256::from(200_000)));
+
+        // no bridge contract, no reserve
+        let mut block = BlockSelection::new(&selection, U256::from(300_000), None);
+        assert!(block.admits(&create, &U256::from(200_000)));
+    }
+}
@@ -80,18 +80,7 @@ impl PodAccount {
         let mut stream = RlpStream::new_list(4);
         stream.append(&self.nonce);
         stream.append(&self.balance);
-        stream.append(&sec_trie_root(
-            self.storage
-                .iter()
-                .map(|(k, v)| (k, rlp::encode(&U128::from(&**v)))),
-        ));
-        if !self.storage_dword.is_empty() {
-            stream.append(&sec_trie_root(
-                self.storage_dword
-                    .iter()
-                    .map(|(k, v)| (k, rlp::encode(&U256::from(&**v)))),
-            ));
-        }
+        stream.append(&sec_trie_root(self.storage_items()));
         stream.append(&blake2b(&self.code.as_ref().unwrap_or(&vec![])));
         stream.out()
     }
@@ -106,17 +95,26 @@ impl PodAccount {
         }
         let mut r = H256::new();
         let mut t = factory.create(db, &mut r);
-        for (k, v) in &self.storage {
-            if let Err(e) = t.insert(k, &rlp::encode(&U128::from(&**v))) {
-                warn!(target:"db","Encountered potential DB corruption: {}", e);
-            }
-        }
-        for (k, v) in &self.storage_dword {
-            if let Err(e) = t.insert(k, &rlp::encode(&U256::from(&**v))) {
+        for (k, v) in self.storage_items() {
+            if let Err(e) = t.insert(&k, &v) {
                 warn!(target:"db","Encountered potential DB corruption: {}", e);
             }
         }
     }
+
+    /// Entries of the storage trie, encoded as the state does: double word values are kept in
+    /// the same trie as 256-bit hashes.
+    fn storage_items(&self) -> Vec<(H128, Bytes)> {
+        self.storage
+            .iter()
+            .map(|(k, v)| (*k, rlp::encode(&U128::from(&**v)).into_vec()))
+            .chain(
+                self.storage_dword
+                    .iter()
+                    .map(|(k, v)| (*k, rlp::encode(v).into_vec())),
+            )
+            .collect()
+    }
 }
 
 impl From<ajson::blockchain::Account> for PodAccount {
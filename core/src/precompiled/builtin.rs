@@ -21,10 +21,13 @@
 
 use aion_types::{U256, Address, H128, H256};
 use ajson;
+use error::Error;
+use spec::{forks, Forks};
 use super::total_currency_contract::TotalCurrencyContract;
 use super::edverify_contract::EDVerifyContract;
 use super::blake2b_hash_contract::Blake2bHashContract;
 use super::tx_hash_contract::TxHashContract;
+use super::double_sign_contract::DoubleSignContract;
 use super::atb::token_bridge_contract::TokenBridgeContract;
 use std::fmt;
 use state::{State, Substate, Backend as StateBackend,CleanupMode};
@@ -54,6 +57,7 @@ pub fn builtin_contract(params: BuiltinParams) -> Box<BuiltinContract> {
         "ed_verify" => Box::new(EDVerifyContract::new(params)) as Box<BuiltinContract>,
         "tx_hash" => Box::new(TxHashContract::new(params)) as Box<BuiltinContract>,
         "blake2b_hash" => Box::new(Blake2bHashContract::new(params)) as Box<BuiltinContract>,
+        "double_sign" => Box::new(DoubleSignContract::new(params)) as Box<BuiltinContract>,
         _ => panic!("invalid builtin name: {}", name),
     }
 }
@@ -88,16 +92,26 @@ impl fmt::Display for BuiltinParams {
     }
 }
 
-impl From<ajson::spec::Builtin> for BuiltinParams {
-    fn from(b: ajson::spec::Builtin) -> Self {
-        let params = BuiltinParams {
+impl BuiltinParams {
+    /// Builtin parameters from the spec, `activate_at_fork` is looked up in the spec forks.
+    pub fn new(b: ajson::spec::Builtin, spec_forks: &Forks) -> Result<Self, Error> {
+        let activate_at = match b.activate_at_fork {
+            Some(ref fork) if !forks::is_known(fork) => {
+                return Err(Error::InvalidSpec(format!(
+                    "builtin {} is activated by unknown fork {}",
+                    b.name, fork
+                )));
+            }
+            Some(ref fork) => spec_forks.activation(fork),
+            None => b.activate_at.map(Into::into).unwrap_or(0),
+        };
+        Ok(BuiltinParams {
             name: b.name.clone(),
-            activate_at: b.activate_at.map(Into::into).unwrap_or(0),
+            activate_at: activate_at,
             deactivate_at: b.deactivate_at.map(Into::into),
             owner_address: b.owner_address.map(|a| a.into()),
             contract_address: b.address.map(|a| a.into()),
-        };
-        params
+        })
     }
 }
 
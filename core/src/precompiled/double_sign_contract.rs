@@ -0,0 +1,152 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Double-sign slashing contract.
+//!
+//! Takes RLP encoded double-sign evidence as input, checks it and records the offending staker
+//! as slashed. The proof-of-stake engine ignores the stake of slashed stakers, so they can no
+//! longer seal blocks in time.
+
+use super::builtin::{BuiltinParams, BuiltinContract, BuiltinExt};
+use aion_types::{Address, H128, U128, U256};
+use blake2b::blake2b;
+use evidence::DoubleSignEvidence;
+use rustc_hex::FromHex;
+use vms::vm::ExecutionResult;
+use vms::{ReturnData, EvmStatusCode};
+
+/// Address of the double-sign contract in the specs supporting proof-of-stake.
+const CONTRACT_ADDRESS: &'static str =
+    "0000000000000000000000000000000000000000000000000000000000000300";
+
+/// Address of the double-sign contract.
+pub fn double_sign_contract_address() -> Address {
+    Address::from_slice(&CONTRACT_ADDRESS.from_hex().expect("valid hex address"))
+}
+
+/// Storage key, in the double-sign contract, of the slashed flag of a staker.
+pub fn slashed_key(staker: &Address) -> H128 { H128::from(&blake2b(staker)[..16]) }
+
+pub struct DoubleSignContract {
+    /// contract name
+    name: String,
+    /// block number from which this contract is supported
+    activate_at: u64,
+}
+
+impl DoubleSignContract {
+    pub fn new(params: BuiltinParams) -> Self {
+        DoubleSignContract {
+            name: params.name,
+            activate_at: params.activate_at,
+        }
+    }
+
+    fn fail(&self, err_msg: String) -> ExecutionResult {
+        ExecutionResult {
+            gas_left: U256::zero(),
+            status_code: EvmStatusCode::Failure,
+            return_data: ReturnData::empty(),
+            exception: err_msg,
+        }
+    }
+}
+
+impl BuiltinContract for DoubleSignContract {
+    // two signature checks and a storage write
+    fn cost(&self, _input: &[u8]) -> U256 { U256::from(30000) }
+
+    fn is_active(&self, at: u64) -> bool { at >= self.activate_at }
+
+    fn name(&self) -> &str { &self.name }
+
+    /// @param input RLP of the double-sign evidence
+    //  @return the address of the slashed staker
+    fn execute(&self, ext: &mut BuiltinExt, input: &[u8]) -> ExecutionResult {
+        let evidence = match DoubleSignEvidence::from_rlp(input) {
+            Ok(evidence) => evidence,
+            Err(e) => return self.fail(format!("{}", e)),
+        };
+        let offender = evidence.offender();
+        let key = slashed_key(&offender);
+        if !U128::from(ext.storage_at(&key)).is_zero() {
+            return self.fail(format!("staker {} is already slashed", offender));
+        }
+        ext.set_storage(key, H128::from(U128::one()));
+        ExecutionResult {
+            gas_left: U256::zero(),
+            status_code: EvmStatusCode::Success,
+            return_data: ReturnData::new(offender.to_vec(), 0, offender.len()),
+            exception: String::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{slashed_key, DoubleSignContract};
+    use precompiled::builtin::{BuiltinParams, BuiltinExtImpl, BuiltinContext, BuiltinContract};
+    use tests::helpers::{double_sign_evidence, get_temp_state};
+    use state::Substate;
+    use vms::EvmStatusCode;
+    use aion_types::{Address, H128, H256, U128};
+    use rlp;
+
+    #[test]
+    fn slashes_offender_once() {
+        let contract = DoubleSignContract::new(BuiltinParams {
+            activate_at: 0,
+            deactivate_at: None,
+            name: String::from("double_sign"),
+            owner_address: None,
+            contract_address: None,
+        });
+        let evidence = double_sign_evidence(&[1u8; 32]);
+        let offender = evidence.offender();
+        let state = &mut get_temp_state();
+        let substate = &mut Substate::new();
+        let mut ext = BuiltinExtImpl::new(
+            state,
+            BuiltinContext {
+                sender: Address::zero(),
+                address: Address::zero(),
+                tx_hash: H256::zero(),
+                origin_tx_hash: H256::zero(),
+            },
+            substate,
+        );
+
+        let result = contract.execute(&mut ext, &[0xc0]);
+        assert_eq!(result.status_code, EvmStatusCode::Failure);
+
+        let input = rlp::encode(&evidence).into_vec();
+        let result = contract.execute(&mut ext, &input);
+        assert_eq!(result.status_code, EvmStatusCode::Success);
+        assert_eq!(&result.return_data.mem[..], &offender[..]);
+        assert_eq!(
+            ext.storage_at(&slashed_key(&offender)),
+            H128::from(U128::one())
+        );
+
+        let result = contract.execute(&mut ext, &input);
+        assert_eq!(result.status_code, EvmStatusCode::Failure);
+    }
+}
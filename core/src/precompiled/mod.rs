@@ -26,3 +26,4 @@ mod precompiled_utilities;
 mod edverify_contract;
 mod blake2b_hash_contract;
 mod tx_hash_contract;
+pub mod double_sign_contract;
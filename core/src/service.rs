@@ -32,17 +32,19 @@ use tokio::timer::Interval;
 use tokio::prelude::{Future, Stream};
 use ansi_term::Colour;
 use bytes::Bytes;
-use client::{ChainNotify, Client, ClientConfig, MiningBlockChainClient};
+use blockchain::{check_and_repair, Consistency, FREEZER_DIR};
+use migrations;
+use client::{
+    ChainNotify, Client, ClientConfig, DatabaseBackend, DatabaseDurability, MiningBlockChainClient,
+};
 use db;
 use error::*;
 use io::*;
 use kvdb::KeyValueDB;
-use kvdb::{DatabaseConfig, RepositoryConfig, DbRepository, DBTransaction, Error as DbError};
+use kvdb::{ColumnDbRepository, DatabaseConfig, RepositoryConfig, DbRepository, Error as DbError};
 use miner::Miner;
 use spec::Spec;
 use stop_guard::StopGuard;
-use aion_types::{H256, U256};
-use rlp::*;
 
 /// Message type for external and internal events
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -57,6 +59,9 @@ pub enum ClientIoMessage {
     NewMessage(Bytes),
 }
 
+/// Directory of the single rocksdb holding all dbs as column families.
+const COLUMN_FAMILIES_DIR: &'static str = "columns";
+
 /// Run the miner
 pub fn run_miner(executor: TaskExecutor, client: Arc<Client>) -> oneshot::Sender<()> {
     let (close, shutdown_signal) = oneshot::channel();
@@ -74,18 +79,48 @@ pub fn run_miner(executor: TaskExecutor, client: Arc<Client>) -> oneshot::Sender
     close
 }
 
+/// Open the dbs of the client at `client_path`, without starting the client.
+pub fn open_database(config: &ClientConfig, client_path: &Path) -> Result<Arc<KeyValueDB>, Error> {
+    let mut db_config = DatabaseConfig::default();
+    db_config.wal = config.db_wal;
+    if config.db_durability == DatabaseDurability::Safe && !config.db_wal {
+        warn!(target: "run", "Safe database durability requires WAL, ignoring --disable-wal");
+        db_config.wal = true;
+    }
+    db_config.backend = config.db_backend;
+    // separate dbs split the block cache budget, column families share a single cache
+    let cache_size = config.db_cache_size.map(|size| size * 1024 * 1024);
+    if let Some(cache_size) = cache_size {
+        db_config.block_cache_budget = Some(cache_size / db::DB_NAMES.len());
+    }
+    let mut db_configs = Vec::new();
+    for db_name in db::DB_NAMES.to_vec() {
+        let db_path = client_path.join(db_name);
+        db_config.compact_options = config.db_compaction.compaction_profile(&db_path);
+        db_configs.push(RepositoryConfig {
+            db_name: db_name.into(),
+            db_config: db_config.clone(),
+            db_path: db_path.to_string_lossy().into(),
+        });
+    }
+    if let Some(cache_size) = cache_size {
+        db_config.block_cache_budget = Some(cache_size);
+    }
+    ClientService::open_dbs(config, client_path, db_config, db_configs)
+}
+
 /// Client service setup. Creates and registers client and network services with the IO subsystem.
 pub struct ClientService {
     io_service: Arc<IoService<ClientIoMessage>>,
     client: Arc<Client>,
-    database: Arc<DbRepository>,
+    database: Arc<KeyValueDB>,
     _stop_guard: StopGuard,
 }
 
 impl ClientService {
     /// Start the `ClientService`.
     pub fn start(
-        config: ClientConfig,
+        mut config: ClientConfig,
         spec: &Spec,
         client_path: &Path,
         _ipc_path: &Path,
@@ -101,26 +136,40 @@ impl ClientService {
             Colour::Yellow.bold().paint(spec.engine.name())
         );
 
-        let mut db_config = DatabaseConfig::default();
-        db_config.wal = config.db_wal;
-        db_config.block_cache_size = config.db_cache_size.unwrap_or(1024) as u64;
-        let mut db_configs = Vec::new();
-        for db_name in db::DB_NAMES.to_vec() {
-            let db_path = client_path.join(db_name);
-            db_config.compact_options = config.db_compaction.compaction_profile(&db_path);
-            db_configs.push(RepositoryConfig {
-                db_name: db_name.into(),
-                db_config: db_config.clone(),
-                db_path: db_path.to_string_lossy().into(),
-            });
+        let dbs = open_database(&config, client_path)?;
+
+        for (version, description) in migrations::migrate(&*dbs)
+            .map_err(|e| Error::Database(DbError::Other(e)))?
+        {
+            info!(
+                target: "run",
+                "Migrated database to schema version {}: {}",
+                version,
+                description
+            );
         }
-        let dbs = DbRepository::init(db_configs)?;
-        let dbs = Arc::new(dbs);
 
-        // correct dbs
-        ClientService::correct_db(dbs.clone())
-            .map_err(|_e| Error::Database(DbError::Other(format!("db is not correct"))))?;
+        match check_and_repair(&dbs, config.pruning)
+            .map_err(|e| Error::Database(DbError::Other(e)))?
+        {
+            Consistency::RolledBack {
+                from,
+                to,
+            } => {
+                warn!(
+                    target: "run",
+                    "Database was inconsistent, reverted from #{} to #{}",
+                    from,
+                    to
+                )
+            }
+            Consistency::Consistent(best) => {
+                debug!(target: "run", "Database consistent at #{}", best)
+            }
+            Consistency::Empty => {}
+        }
 
+        config.blockchain.freezer_path = Some(client_path.join(FREEZER_DIR));
         let client = Client::new(config, &spec, dbs.clone(), miner, io_service.channel())?;
 
         let client_io = Arc::new(ClientIoHandler {
@@ -161,86 +210,43 @@ impl ClientService {
     /// Get a handle to the database.
     pub fn db(&self) -> Arc<KeyValueDB> { self.database.clone() }
 
-    /// check db if correct
-    fn correct_db(dbs: Arc<KeyValueDB>) -> Result<(), String> {
-        use db::Readable;
-        use blockchain::BlockDetails;
-        // get best block hash
-        let best_block_hash = dbs.get(db::COL_EXTRA, b"best").expect("EXTRA db not found");
-        match best_block_hash {
-            None => {
-                // new db , nothing to do ;
-                return Ok(());
-            }
-            Some(best) => {
-                let best_block_hash = H256::from_slice(&best);
-                let best_block_detail: BlockDetails = dbs
-                    .read(db::COL_EXTRA, &best_block_hash)
-                    .expect("db crashed");
-                let best_block_number = best_block_detail.number;
-                let mut loop_end = if best_block_number > 10 {
-                    10
-                } else {
-                    best_block_number as i64
-                };
-                let mut cur_blk_hash = best_block_hash;
-                loop {
-                    // check header and bodies whether exist in db
-                    let header = dbs
-                        .get(db::COL_HEADERS, &cur_blk_hash)
-                        .expect("HEADERS db not found");
-                    let bodies = dbs
-                        .get(db::COL_BODIES, &cur_blk_hash)
-                        .expect("BODIES db not found");
-                    if header.is_none() || bodies.is_none() {
-                        break;
-                    }
-                    loop_end -= 1;
-                    if loop_end < 0 {
-                        break;
-                    }
-                    // check it's parent
-                    let cur_blk_detail: Option<BlockDetails> =
-                        dbs.read(db::COL_EXTRA, &cur_blk_hash);
-                    if cur_blk_detail.is_none() {
-                        return Err(format!("db crashed"));
-                    }
-                    let cur_blk_detail = cur_blk_detail.expect("db crashed");
-                    cur_blk_hash = cur_blk_detail.parent;
-                }
-                if loop_end > 0 {
-                    // reset db
-                    let mut batch = DBTransaction::new();
-                    let cur_blk_detail: BlockDetails =
-                        dbs.read(db::COL_EXTRA, &cur_blk_hash).expect("db crashed");
-                    let parent = cur_blk_detail.parent;
-                    let parent_header_bytes = dbs
-                        .get(db::COL_HEADERS, &parent)
-                        .expect("HEADERS db not found")
-                        .expect("db crashed")
-                        .to_vec();
-                    let parent_header = ::encoded::Header::new(parent_header_bytes).decode();
-                    let parent_number = parent_header.number();
-                    batch.put(db::COL_EXTRA, b"best", &parent);
-                    // NOTE: the following code is problematic, fix in production.
-                    let new_parent_block_detail = BlockDetails {
-                        number: parent_number,
-                        total_pow_difficulty: U256::from(*parent_header.difficulty()),
-                        total_pos_difficulty: U256::from(*parent_header.difficulty()),
-                        parent: H256::from(*parent_header.parent_hash()),
-                        children: vec![],
-                        import_timestamp: 0,
-                    };
-                    // reset state db
-                    let latest_era_key = [b'l', b'a', b's', b't', 0, 0, 0, 0, 0, 0, 0, 0];
-                    batch.put(db::COL_STATE, &latest_era_key, &encode(&parent_number));
-                    use db::Writable;
-                    batch.write(db::COL_EXTRA, &parent, &new_parent_block_detail);
-                    let _ = dbs.write(batch);
-                }
-                return Ok(());
+    /// Open the dbs, as column families of a single rocksdb if enabled or already in use, or
+    /// one rocksdb per db otherwise. The per-db layout is migrated when column families are
+    /// enabled for the first time.
+    fn open_dbs(
+        config: &ClientConfig,
+        client_path: &Path,
+        mut db_config: DatabaseConfig,
+        db_configs: Vec<RepositoryConfig>,
+    ) -> Result<Arc<KeyValueDB>, Error>
+    {
+        let columns_path = client_path.join(COLUMN_FAMILIES_DIR);
+        if db_config.backend != DatabaseBackend::RocksDb {
+            if config.db_column_families || columns_path.exists() {
+                warn!(
+                    target: "run",
+                    "Column families require the rocksdb backend, ignoring them for {}",
+                    db_config.backend
+                );
             }
+            return Ok(Arc::new(DbRepository::init(db_configs)?));
+        }
+        if !config.db_column_families && !columns_path.exists() {
+            return Ok(Arc::new(DbRepository::init(db_configs)?));
+        }
+
+        db_config.compact_options = config.db_compaction.compaction_profile(&columns_path);
+        let path = columns_path.to_string_lossy().into_owned();
+        if !columns_path.exists() && ColumnDbRepository::has_legacy_layout(&db_configs) {
+            info!(target: "run", "Migrating databases to column families in {}", path);
+            return Ok(Arc::new(ColumnDbRepository::migrate(
+                &db_configs,
+                db_config,
+                &path,
+            )?));
         }
+        let columns = db::DB_NAMES.iter().map(|name| name.to_string()).collect();
+        Ok(Arc::new(ColumnDbRepository::init(columns, db_config, &path)?))
     }
 }
 
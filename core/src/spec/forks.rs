@@ -0,0 +1,150 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Hard fork activation blocks.
+
+use std::collections::BTreeMap;
+
+use ajson;
+use error::Error;
+use header::BlockNumber;
+
+/// Difficulty follows the v1 adjustment instead of v0.
+pub const DIFFICULTY_V1: &'static str = "difficultyV1";
+/// Proof-of-stake blocks are accepted.
+pub const UNITY: &'static str = "unity";
+/// The block reward ramp-up schedule starts, its bounds count blocks from the activation.
+pub const REWARD_RAMPUP: &'static str = "rewardRampup";
+
+/// Hard forks known to this client.
+const KNOWN_FORKS: [&'static str; 3] = [DIFFICULTY_V1, UNITY, REWARD_RAMPUP];
+
+/// Whether `name` is a hard fork known to this client.
+pub fn is_known(name: &str) -> bool { KNOWN_FORKS.contains(&name) }
+
+/// Activation blocks of the hard forks, by name.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Forks(BTreeMap<String, BlockNumber>);
+
+impl Forks {
+    /// Activation block of a fork named in the spec.
+    pub fn get(&self, name: &str) -> Option<BlockNumber> { self.0.get(name).cloned() }
+
+    /// Activation block of a fork, forks missing from the spec are active from genesis.
+    pub fn activation(&self, name: &str) -> BlockNumber { self.get(name).unwrap_or(0) }
+
+    /// Whether a fork is active at the given block.
+    pub fn is_active(&self, name: &str, number: BlockNumber) -> bool {
+        number >= self.activation(name)
+    }
+
+    /// Checks that the spec only schedules known forks, a misspelled fork would otherwise be
+    /// ignored and the fork it was meant for active from genesis.
+    pub fn verify(&self) -> Result<(), Error> {
+        match self.0.keys().find(|name| !is_known(name)) {
+            Some(name) => Err(Error::InvalidSpec(format!("unknown fork {}", name))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl From<BTreeMap<String, BlockNumber>> for Forks {
+    fn from(forks: BTreeMap<String, BlockNumber>) -> Self { Forks(forks) }
+}
+
+impl From<Option<BTreeMap<String, ajson::uint::Uint>>> for Forks {
+    fn from(forks: Option<BTreeMap<String, ajson::uint::Uint>>) -> Self {
+        Forks(
+            forks
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(name, number)| (name, number.into()))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use aion_types::Address;
+    use header::SealType;
+    use spec::Spec;
+    use super::{Forks, UNITY, DIFFICULTY_V1, REWARD_RAMPUP};
+
+    #[test]
+    fn forks_activation() {
+        let mut activations = BTreeMap::new();
+        activations.insert(UNITY.to_owned(), 100);
+        let forks = Forks::from(activations);
+
+        assert_eq!(forks.get(UNITY), Some(100));
+        assert!(!forks.is_active(UNITY, 99));
+        assert!(forks.is_active(UNITY, 100));
+        // forks missing from the spec are always active
+        assert_eq!(forks.get(DIFFICULTY_V1), None);
+        assert!(forks.is_active(DIFFICULTY_V1, 0));
+    }
+
+    #[test]
+    fn spec_forks_activation() {
+        let spec = Spec::new_test_forks();
+        let machine = spec.engine.machine();
+
+        assert_eq!(machine.difficulty_version(99), 0);
+        assert_eq!(machine.difficulty_version(100), 1);
+        assert!(machine.is_seal_type_active(&SealType::Pow, 0));
+        assert!(!machine.is_seal_type_active(&SealType::Pos, 199));
+        assert!(machine.is_seal_type_active(&SealType::Pos, 200));
+        assert_eq!(machine.forks().activation(REWARD_RAMPUP), 300);
+
+        let blake2b = Address::from(0x11);
+        assert!(machine.builtin(&blake2b, 199).is_none());
+        assert!(machine.builtin(&blake2b, 200).is_some());
+    }
+
+    #[test]
+    fn spec_with_unknown_forks_is_invalid() {
+        let mut activations = BTreeMap::new();
+        activations.insert(UNITY.to_owned(), 100);
+        assert!(Forks::from(activations.clone()).verify().is_ok());
+        activations.insert("untiy".to_owned(), 100);
+        assert!(Forks::from(activations).verify().is_err());
+
+        let json = include_str!("../../res/forks_test.json");
+        let misspelled = json.replace("\"unity\": 200", "\"untiy\": 200");
+        assert!(Spec::load(&::std::env::temp_dir(), misspelled.as_bytes()).is_err());
+        let misspelled = json.replace(
+            "\"activate_at_fork\": \"unity\"",
+            "\"activate_at_fork\": \"untiy\"",
+        );
+        assert!(Spec::load(&::std::env::temp_dir(), misspelled.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn default_spec_forks_active_from_genesis() {
+        let spec = Spec::new_test();
+        let machine = spec.engine.machine();
+
+        assert_eq!(machine.difficulty_version(0), 1);
+        assert!(machine.is_seal_type_active(&SealType::Pos, 0));
+    }
+}
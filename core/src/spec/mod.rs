@@ -22,9 +22,11 @@
 
 //! Blockchain params.
 
+pub mod forks;
 mod genesis;
 mod seal;
 pub mod spec;
 
+pub use self::forks::Forks;
 pub use self::genesis::Genesis;
 pub use self::spec::{Spec, SpecParams, CommonParams};
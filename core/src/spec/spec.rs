@@ -37,18 +37,21 @@ use parking_lot::RwLock;
 use rlp::{Rlp, RlpStream};
 use vms::{CallType, ActionValue, ActionParams, ParamsType, EnvInfo};
 
-use precompiled::builtin::{BuiltinContract, builtin_contract};
-use engines::{POWEquihashEngine, EthEngine, NullEngine, InstantSeal};
+use precompiled::builtin::{BuiltinContract, BuiltinParams, builtin_contract};
+use engines::{POWEquihashEngine, EthEngine, NullEngine, InstantSeal, InstantHybrid};
+use engines::pow_equihash_engine::POWEquihashEngineParams;
+use equihash;
 use error::Error;
 use executive::Executive;
 use factory::Factories;
 use header::{Header, SealType};
 use machine::EthereumMachine;
 use pod_state::PodState;
-use spec::Genesis;
+use spec::{Forks, Genesis};
 use spec::seal::Generic as GenericSeal;
 use state::backend::Basic as BasicBackend;
 use state::{Backend, State, Substate};
+use state_dump;
 
 // helper for formatting errors.
 fn fmt_err<F: ::std::fmt::Display>(f: F) -> String { format!("Spec json is invalid: {}", f) }
@@ -67,6 +70,8 @@ pub struct CommonParams {
     pub registrar: Address,
     /// Transaction permission managing contract address.
     pub transaction_permission_contract: Option<Address>,
+    /// Activation blocks of the hard forks.
+    pub forks: Forks,
 }
 
 impl From<ajson::spec::Params> for CommonParams {
@@ -78,6 +83,7 @@ impl From<ajson::spec::Params> for CommonParams {
             gas_limit_bound_divisor: p.gas_limit_bound_divisor.into(),
             registrar: p.registrar.map_or_else(Address::new, Into::into),
             transaction_permission_contract: p.transaction_permission_contract.map(Into::into),
+            forks: p.forks.into(),
         }
     }
 }
@@ -174,33 +180,51 @@ impl Clone for Spec {
     }
 }
 
-fn load_machine_from(s: ajson::spec::Spec) -> EthereumMachine {
-    let builtins = s
-        .accounts
-        .builtins()
-        .into_iter()
-        .map(|p| (p.0.into(), builtin_contract(From::from(p.1))))
-        .collect();
+/// Builtin contracts of the spec accounts.
+fn load_builtins(
+    accounts: &ajson::spec::State,
+    forks: &Forks,
+) -> Result<BTreeMap<Address, Box<BuiltinContract>>, Error>
+{
+    let mut builtins = BTreeMap::new();
+    for (address, builtin) in accounts.builtins() {
+        let params = BuiltinParams::new(builtin, forks)?;
+        builtins.insert(address.into(), builtin_contract(params));
+    }
+    Ok(builtins)
+}
+
+fn load_machine_from(s: ajson::spec::Spec) -> Result<EthereumMachine, Error> {
     let params = CommonParams::from(s.params);
+    params.forks.verify()?;
+    let builtins = load_builtins(&s.accounts, &params.forks)?;
 
-    Spec::machine(&s.engine, params, builtins)
+    Ok(Spec::machine(&s.engine, params, builtins))
 }
 
 /// Load from JSON object.
 fn load_from(spec_params: SpecParams, s: ajson::spec::Spec) -> Result<Spec, Error> {
-    let builtins = s
+    let params = CommonParams::from(s.params);
+    params.forks.verify()?;
+    let builtins = load_builtins(&s.accounts, &params.forks)?;
+    let g = Genesis::from(s.genesis);
+    let GenericSeal(seal_rlp) = g.seal.into();
+    let constructors = s
         .accounts
-        .builtins()
+        .constructors()
         .into_iter()
-        .map(|p| (p.0.into(), builtin_contract(From::from(p.1))))
+        .map(|(a, c)| (a.into(), c.into()))
         .collect();
-    let g = Genesis::from(s.genesis);
-    let GenericSeal(seal_rlp) = g.seal.into();
-    let params = CommonParams::from(s.params);
+    let mut genesis_state: PodState = s.accounts.into();
+    if let Some(ref path) = s.state_dump {
+        let mut accounts = genesis_state.drain();
+        accounts.extend(state_dump::load(Path::new(path))?);
+        genesis_state = PodState::from(accounts);
+    }
 
     let mut s = Spec {
         name: s.name.clone().into(),
-        engine: Spec::engine(spec_params, s.engine, params, builtins),
+        engine: Spec::engine(spec_params, s.engine, params, builtins)?,
         data_dir: s.data_dir.unwrap_or(s.name).into(),
         parent_hash: g.parent_hash,
         transactions_root: g.transactions_root,
@@ -212,14 +236,9 @@ fn load_from(spec_params: SpecParams, s: ajson::spec::Spec) -> Result<Spec, Erro
         timestamp: g.timestamp,
         extra_data: g.extra_data,
         seal_rlp: seal_rlp,
-        constructors: s
-            .accounts
-            .constructors()
-            .into_iter()
-            .map(|(a, c)| (a.into(), c.into()))
-            .collect(),
+        constructors: constructors,
         state_root_memo: RwLock::new(Default::default()), // will be overwritten right after.
-        genesis_state: s.accounts.into(),
+        genesis_state: genesis_state,
     };
 
     // use memoized state root if provided.
@@ -268,22 +287,29 @@ impl Spec {
         engine_spec: ajson::spec::Engine,
         params: CommonParams,
         builtins: BTreeMap<Address, Box<BuiltinContract>>,
-    ) -> Arc<EthEngine>
+    ) -> Result<Arc<EthEngine>, Error>
     {
         let machine = Self::machine(&engine_spec, params, builtins);
 
-        match engine_spec {
+        Ok(match engine_spec {
             ajson::spec::Engine::POWEquihashEngine(pow_equihash_engine) => {
-                Arc::new(POWEquihashEngine::new(
-                    pow_equihash_engine.params.into(),
-                    machine,
-                ))
+                let params: POWEquihashEngineParams = pow_equihash_engine.params.into();
+                if !equihash::is_valid_params(params.equihash_n, params.equihash_k) {
+                    return Err(Error::InvalidSpec(format!(
+                        "unsupported equihash parameters n={}, k={}",
+                        params.equihash_n, params.equihash_k
+                    )));
+                }
+                Arc::new(POWEquihashEngine::new(params, machine))
             }
             ajson::spec::Engine::Null(null) => {
                 Arc::new(NullEngine::new(null.params.into(), machine))
             }
             ajson::spec::Engine::InstantSeal => Arc::new(InstantSeal::new(machine)),
-        }
+            ajson::spec::Engine::InstantHybrid(instant_hybrid) => {
+                Arc::new(InstantHybrid::new(instant_hybrid.params.into(), machine))
+            }
+        })
     }
 
     // given a pre-constructor state, run all the given constructors and produce a new state and
@@ -472,7 +498,7 @@ impl Spec {
     pub fn load_machine<R: Read>(reader: R) -> Result<EthereumMachine, String> {
         ajson::spec::Spec::load(reader)
             .map_err(fmt_err)
-            .map(load_machine_from)
+            .and_then(|x| load_machine_from(x).map_err(fmt_err))
     }
 
     /// Loads spec from json file. Provide factories for executing contracts and ensuring
@@ -558,6 +584,18 @@ impl Spec {
     /// Create a new Spec with InstantSeal consensus which does internal sealing (not requiring
     /// work).
     pub fn new_instant() -> Spec { load_bundled!("instant_seal") }
+
+    /// Create a new Spec with InstantHybrid consensus which internally seals PoW and PoS blocks
+    /// in turn.
+    pub fn new_instant_hybrid() -> Spec { load_bundled!("instant_hybrid") }
+
+    /// Create a new Spec with POWEquihashEngine consensus whose hard forks activate at
+    /// different heights.
+    pub fn new_test_forks() -> Spec { load_bundled!("forks_test") }
+
+    /// Create a new Spec with POWEquihashEngine consensus and Equihash parameters small enough
+    /// to be mined by the node itself.
+    pub fn new_pow_dev() -> Spec { load_bundled!("pow_dev") }
 }
 
 #[cfg(test)]
@@ -584,4 +622,11 @@ mod tests {
         );
     }
 
+    #[test]
+    fn spec_with_unsupported_equihash_params_is_invalid() {
+        let json = include_str!("../../res/pow_dev.json");
+        assert!(Spec::load(&::std::env::temp_dir(), json.as_bytes()).is_ok());
+        let unsupported = json.replace("\"equihashK\": 5", "\"equihashK\": 0");
+        assert!(Spec::load(&::std::env::temp_dir(), unsupported.as_bytes()).is_err());
+    }
 }
@@ -449,6 +449,12 @@ impl Account {
     /// Increment the nonce of the account by one.
     pub fn inc_nonce(&mut self) { self.nonce = self.nonce + U256::from(1u8); }
 
+    /// Set account nonce.
+    pub fn set_nonce(&mut self, nonce: U256) { self.nonce = nonce; }
+
+    /// Set account balance.
+    pub fn set_balance(&mut self, balance: U256) { self.balance = balance; }
+
     /// Increase account balance.
     pub fn add_balance(&mut self, x: &U256) { self.balance = self.balance + *x; }
 
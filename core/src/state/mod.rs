@@ -783,6 +783,16 @@ impl<B: Backend> State<B> {
         self.require(a, false).map(|mut x| x.inc_nonce())
     }
 
+    /// Set the nonce of account `a`.
+    pub fn set_nonce(&mut self, a: &Address, nonce: U256) -> trie::Result<()> {
+        self.require(a, false).map(|mut x| x.set_nonce(nonce))
+    }
+
+    /// Set the balance of account `a`.
+    pub fn set_balance(&mut self, a: &Address, balance: U256) -> trie::Result<()> {
+        self.require(a, false).map(|mut x| x.set_balance(balance))
+    }
+
     /// Mutate storage of account `a` so that it is `value` for `key`.
     pub fn set_storage(&mut self, a: &Address, key: H128, value: H128) -> trie::Result<()> {
         trace!(target: "state", "set_storage({}:{:x} to {:x})", a, key, value);
@@ -402,6 +402,21 @@ impl StateDB {
     /// Query how much memory is set aside for the accounts cache (in bytes).
     pub fn cache_size(&self) -> usize { self.cache_size }
 
+    /// Resize the account and code caches shared by all instances to `cache_size` bytes,
+    /// dropping the least recently used entries which no longer fit.
+    pub fn set_cache_size(&mut self, cache_size: usize) {
+        let acc_cache_size = cache_size * ACCOUNT_CACHE_RATIO / 100;
+        let cache_items = acc_cache_size / ::std::mem::size_of::<Option<Account>>();
+        self.account_cache
+            .lock()
+            .accounts
+            .set_capacity(cache_items);
+        self.code_cache
+            .lock()
+            .set_max_size(cache_size - acc_cache_size);
+        self.cache_size = cache_size;
+    }
+
     /// Check if the account can be returned from cache by matching current block parent hash against canonical
     /// state and filtering out account modified in later blocks.
     fn is_allowed(
@@ -0,0 +1,194 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Streaming dump of the state at a block, and its loader to seed a genesis state.
+//!
+//! The dump is JSON, one account per line, each line being a single entry of the `accounts`
+//! object of a chain spec:
+//!
+//! ```text
+//! {"0xa0..01":{"balance":"0x1","nonce":"0x0","code":"0x","storage":{},"storage_dword":{}}}
+//! ```
+//!
+//! Dumping needs the preimages of the trie keys, so the state must be kept in a fat db.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use aion_types::{Address, H256};
+use ajson;
+use blake2b::{blake2b, BLAKE2B_EMPTY};
+use kvdb::HashStore;
+use rlp::{self, Rlp};
+use rustc_hex::ToHex;
+use trie::{FatDB, Trie};
+use types::basic_account::BasicAccount;
+use account_db::AccountDB;
+use pod_account::PodAccount;
+
+/// Write the accounts of the fat state trie at `root` to `out`, one line per account.
+/// `progress` is called with the number of accounts written so far. Returns the number of
+/// accounts written.
+pub fn export<W, F>(
+    db: &HashStore,
+    root: &H256,
+    out: &mut W,
+    mut progress: F,
+) -> Result<u64, String>
+where
+    W: Write,
+    F: FnMut(u64),
+{
+    let trie = FatDB::new(db, root).map_err(|e| format!("{}", e))?;
+    let mut accounts = 0;
+    for item in trie.iter().map_err(|e| format!("{}", e))? {
+        let (address, value) = item.map_err(|e| format!("{}", e))?;
+        let account: BasicAccount = rlp::decode(&value);
+        let line = account_line(db, &address, &account)?;
+        out.write_all(line.as_bytes())
+            .map_err(|e| format!("Couldn't write to stream. Cause: {}", e))?;
+        accounts += 1;
+        progress(accounts);
+    }
+    out.flush().map_err(|e| format!("Couldn't write to stream. Cause: {}", e))?;
+    Ok(accounts)
+}
+
+/// JSON line of an account, with its code and storage.
+fn account_line(db: &HashStore, address: &[u8], account: &BasicAccount) -> Result<String, String> {
+    let account_db = AccountDB::from_hash(db, blake2b(address));
+    let code = if account.code_hash == BLAKE2B_EMPTY {
+        Vec::new()
+    } else {
+        account_db
+            .get(&account.code_hash)
+            .ok_or_else(|| format!("Missing code of account 0x{}", address.to_hex()))?
+            .into_vec()
+    };
+
+    // 128-bit values are stored as integers and double word values as 256-bit hashes, in the
+    // same storage trie.
+    let mut storage = Vec::new();
+    let mut storage_dword = Vec::new();
+    let trie = FatDB::new(&account_db, &account.storage_root).map_err(|e| format!("{}", e))?;
+    for item in trie.iter().map_err(|e| format!("{}", e))? {
+        let (key, value) = item.map_err(|e| format!("{}", e))?;
+        let value = Rlp::new(&value).data().to_vec();
+        let entry = format!("\"0x{}\":\"0x{}\"", key.to_hex(), value.to_hex());
+        if value.len() == 32 {
+            storage_dword.push(entry);
+        } else {
+            storage.push(entry);
+        }
+    }
+
+    Ok(format!(
+        "{{\"0x{}\":{{\"balance\":\"0x{:x}\",\"nonce\":\"0x{:x}\",\"code\":\"0x{}\",\
+         \"storage\":{{{}}},\"storage_dword\":{{{}}}}}}}\n",
+        address.to_hex(),
+        account.balance,
+        account.nonce,
+        code.to_hex(),
+        storage.join(","),
+        storage_dword.join(",")
+    ))
+}
+
+/// Read the accounts of the dump at `path`.
+pub fn load(path: &Path) -> io::Result<BTreeMap<Address, PodAccount>> {
+    let file = File::open(path).map_err(|e| {
+        io::Error::new(e.kind(), format!("Cannot open state dump {}: {}", path.display(), e))
+    })?;
+    let mut accounts = BTreeMap::new();
+    for (number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let state = ajson::spec::State::load(line.as_bytes()).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid state dump {} at line {}: {}", path.display(), number + 1, e),
+            )
+        })?;
+        accounts.extend(
+            state
+                .into_iter()
+                .map(|(address, account)| (address.into(), PodAccount::from(account))),
+        );
+    }
+    Ok(accounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::sync::Arc;
+    use aion_types::{H128, U256};
+    use tempdir::TempDir;
+    use kvdb::MemoryDBRepository;
+    use trie::{TrieFactory, TrieSpec};
+    use factory::Factories;
+    use pod_state::PodState;
+    use state::{CleanupMode, State};
+    use tests::helpers::get_temp_state_db;
+
+    #[test]
+    fn exported_state_loads_with_same_root() {
+        let factories = Factories {
+            trie: TrieFactory::new(TrieSpec::Fat),
+            ..Default::default()
+        };
+        let mut state = State::new(
+            get_temp_state_db(),
+            U256::zero(),
+            factories,
+            Arc::new(MemoryDBRepository::new()),
+        );
+        let a = Address::from(1);
+        let b = Address::from(2);
+        state
+            .add_balance(&a, &69.into(), CleanupMode::NoEmpty)
+            .unwrap();
+        state.set_storage(&a, 0xb.into(), 0xc.into()).unwrap();
+        state.set_storage_dword(&a, 0xd.into(), 0xe.into()).unwrap();
+        state.init_code(&a, vec![1, 2, 3]).unwrap();
+        state.inc_nonce(&b).unwrap();
+        state.commit().unwrap();
+        let (root, state_db) = state.drop();
+
+        let mut dump = Vec::new();
+        let accounts = export(state_db.as_hashstore(), &root, &mut dump, |_| {}).unwrap();
+        assert_eq!(accounts, 2);
+
+        let tempdir = TempDir::new("").unwrap();
+        let path = tempdir.path().join("state.json");
+        fs::write(&path, &dump).unwrap();
+        let loaded = PodState::from(load(&path).unwrap());
+        let account = &loaded.get()[&a];
+        assert_eq!(account.code, Some(vec![1u8, 2, 3]));
+        assert_eq!(account.storage[&H128::from(0xb)], H128::from(0xc));
+        assert_eq!(account.storage_dword[&H128::from(0xd)], H256::from(0xe));
+        assert_eq!(loaded.root(), root);
+    }
+}
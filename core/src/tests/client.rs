@@ -23,13 +23,25 @@
 use std::str::FromStr;
 use std::sync::Arc;
 use io::IoChannel;
-use client::{BlockChainClient, MiningBlockChainClient, Client, ClientConfig, BlockId};
+use client::{
+    AccountOverride, BlockChainClient, BlockId, CallAnalytics, Client, ClientConfig,
+    MiningBlockChainClient, StateOverride,
+};
 use state::{self, State, CleanupMode};
 use executive::Executive;
-use block::IsBlock;
+use block::{IsBlock, OpenBlock};
+use blockchain::generator::{BlockBuilder, BlockOptions};
+use blockchain::{BlockChain, Config as BlockChainConfig, LogIndex};
+use bytes::Bytes;
+use engines::pow_equihash_engine::stake_key;
+use evidence::{DoubleSignEvidence, EvidenceError};
+use header::SealType;
+use precompiled::double_sign_contract::double_sign_contract_address;
+use rcrypto::ed25519::keypair;
+use rlp;
 use tests::helpers::*;
 use types::filter::Filter;
-use aion_types::{Address, U256};
+use aion_types::{Address, H128, H256, U256};
 use kvdb::{DatabaseConfig, DbRepository, RepositoryConfig};
 use miner::Miner;
 use spec::Spec;
@@ -38,7 +50,11 @@ use key::Ed25519Secret;
 use transaction::{PendingTransaction, Transaction, Action, Condition};
 use miner::MinerService;
 use tempdir::TempDir;
-use kvdb::MemoryDBRepository;
+use error::{CallError, ExecutionError};
+use rustc_hex::{FromHex, ToHex};
+use kvdb::{DBTransaction, MemoryDBRepository};
+use log_entry::LogEntry;
+use receipt::{Receipt, SimpleReceipt};
 
 #[test]
 fn imports_from_empty() {
@@ -164,6 +180,7 @@ fn returns_logs() {
         address: None,
         topics: vec![],
         limit: None,
+        offset: None,
     });
     assert_eq!(logs.len(), 0);
 }
@@ -178,6 +195,7 @@ fn returns_logs_with_limit() {
         address: None,
         topics: vec![],
         limit: None,
+        offset: None,
     });
     assert_eq!(logs.len(), 0);
 }
@@ -210,6 +228,22 @@ fn can_collect_garbage() {
     assert!(client.blockchain_cache_info().blocks < 100 * 1024);
 }
 
+#[test]
+fn can_resize_caches() {
+    let client = generate_dummy_client(10);
+    let budget = client.set_memory_budget(100 * 1024 * 1024);
+    assert_eq!(budget.state, 60 * 1024 * 1024);
+    assert_eq!(budget.db, None);
+    assert_eq!(budget.total(), 100 * 1024 * 1024);
+    assert_eq!(budget.tx_queue, Some(5 * 1024 * 1024));
+
+    let usage = client.memory_usage();
+    assert_eq!(usage.budget, budget);
+    assert_eq!(client.queue_info().max_mem_use, budget.queue);
+    assert_eq!(client.miner().tx_queue_memory_limit(), budget.tx_queue);
+    assert!(usage.blockchain <= budget.blockchain);
+}
+
 #[test]
 fn empty_gas_price_histogram() {
     let client = generate_dummy_client_with_data(20, 0, slice_into![]);
@@ -436,3 +470,406 @@ fn transaction_proof() {
     assert_eq!(state.balance(&Address::default()).unwrap(), 5.into());
     assert_eq!(state.balance(&address).unwrap(), 95.into());
 }
+
+fn estimate_transaction(
+    action: Action,
+    data: Vec<u8>,
+    value: U256,
+    gas_price: U256,
+) -> Transaction
+{
+    Transaction {
+        nonce: 0.into(),
+        gas_price: gas_price,
+        gas: 0.into(),
+        action: action,
+        value: value,
+        data: data,
+        nonce_bytes: Vec::new(),
+        gas_price_bytes: Vec::new(),
+        gas_bytes: Vec::new(),
+        value_bytes: Vec::new(),
+        transaction_type: 0x01,
+    }
+}
+
+#[test]
+fn estimate_gas_of_transfer() {
+    let client = generate_dummy_client(0);
+    let sender = Address::from(1);
+    // not a precompiled contract of the test spec
+    let transaction =
+        estimate_transaction(Action::Call(Address::from(3)), vec![], 0.into(), 0.into())
+            .fake_sign(sender);
+
+    assert_eq!(
+        client.estimate_gas(&transaction, BlockId::Latest).unwrap(),
+        21000.into()
+    );
+}
+
+#[test]
+fn estimate_gas_of_token_bridge_call() {
+    let client = generate_dummy_client(0);
+    let sender = Address::from(1);
+    // token bridge precompiled contract of the test spec, which costs 21000 to call
+    let bridge = Address::from(2);
+    // owner()
+    let data = "8da5cb5b".from_hex().unwrap();
+    let transaction = estimate_transaction(Action::Call(bridge), data, 0.into(), 0.into());
+    let estimate = client
+        .estimate_gas(&transaction.clone().fake_sign(sender), BlockId::Latest)
+        .unwrap();
+    assert_eq!(estimate, transaction.gas_required() + 21000.into());
+
+    let call = |gas: U256| {
+        let mut transaction = transaction.clone();
+        transaction.gas = gas;
+        client
+            .call(
+                &transaction.fake_sign(sender),
+                Default::default(),
+                BlockId::Latest,
+            )
+            .unwrap()
+    };
+    assert_eq!(call(estimate).exception, "");
+    assert_ne!(call(estimate - 1.into()).exception, "");
+
+    // changeOwner(address), only allowed to the owner
+    let data = "a6f9dae1".from_hex().unwrap();
+    let transaction =
+        estimate_transaction(Action::Call(bridge), data, 0.into(), 0.into()).fake_sign(sender);
+    match client.estimate_gas(&transaction, BlockId::Latest) {
+        Err(CallError::Failed {
+            exception,
+            ..
+        }) => assert_eq!(exception, "invalid sender"),
+        other => panic!("Unexpected estimate: {:?}", other),
+    }
+}
+
+#[test]
+fn estimate_gas_of_contract_creation() {
+    let client = generate_dummy_client(0);
+    let sender = Address::from(1);
+    // vms/solidity/sols/internal_transactions.sol
+    let code = "60506040525b5b61000b565b6104f88061001a6000396000f30060506040523615610054576000356c01000000000000000000000000900463ffffffff1680631e4198e01461008f5780636d73ac71146100af578063cc8066c8146100f8578063efc81a8c1461012d57610054565b5b7f656718b7d7f0803b58a7a46a3a5ca0a26696492f223276e7a227baba40fb95b7346040518082815260100191505060405180910390a15b005b6100ad6004808080601001359035909160200190919290505061015e565b005b34156100bb5760006000fd5b6100e2600480808060100135903590916020019091929080359060100190919050506101c8565b6040518082815260100191505060405180910390f35b34156101045760006000fd5b61012b60048080806010013590359091602001909192908035906010019091905050610295565b005b34156101395760006000fd5b610141610300565b604051808383825281601001526020019250505060405180910390f35b81816108fc34908115029060405160006040518083038185898989f1945050505050151561018c5760006000fd5b7f281a259dfd2e4aaf4447339f7e35909b8a423be045a738057d6c3c01e8d1f5a2346040518082815260100191505060405180910390a15b5050565b60006000600060008686925092506002838363f65a554b886000604051601001526040518263ffffffff166c01000000000000000000000000028152600401808281526010019150506010604051808303816000888881813b151561022d5760006000fd5b5af1151561023b5760006000fd5b50505050604051805190601001500190507ff56ebbc311e11d9790970c4f650e868d7c17a8c35e5c268477d6933ae010d1f4826040518082815260100191505060405180910390a180935061028b565b5050509392505050565b82826108fc83908115029060405160006040518083038185898989f194505050505015156102c35760006000fd5b7f3f418b40de968f04f1770399699cdfb8221fb37431187bf6ba88c8ef1cde63de826040518082815260100191505060405180910390a15b505050565b600060006000600061031061037e565b604051809103906000f080158215161561032a5760006000fd5b915091507f6092db4a9f98e713a99420bc27f1f1cdfcfd435af45397aeb05ffbee8d567d8e8383604051808383825281601001526020019250505060405180910390a1818193509350610378565b50509091565b60405161013e8061038f833901905600605060405234156100105760006000fd5b610015565b61011a806100246000396000f300605060405236156030576000356c01000000000000000000000000900463ffffffff168063f65a554b14606b576030565b3415603b5760006000fd5b5b7f6684c6fb8e464ba954e17ed3f5aed6e2d49231ce285b7770f422d1f52cef503b60405160405180910390a15b005b341560765760006000fd5b608a600480803590601001909190505060a0565b6040518082815260100191505060405180910390f35b600060006001830190507f3bc83dc4da931c34301105d9c2aff52e35bb96133cd1cf0a835faa9bb607422c826040518082815260100191505060405180910390a180915060e8565b509190505600a165627a7a72305820ec84292d19105cb4d6f311689eed4db3cb4e4251249a9cf06f071e1746e74de60029a165627a7a723058209d38411c6f215aa8daa7dd8150890ea7576a1b0e117cedffc3c002a95810948e0029".from_hex().unwrap();
+    let transaction = estimate_transaction(Action::Create, code, 0.into(), 0.into());
+    let estimate = client
+        .estimate_gas(&transaction.clone().fake_sign(sender), BlockId::Latest)
+        .unwrap();
+    assert!(estimate > transaction.gas_required());
+
+    let call = |gas: U256| {
+        let mut transaction = transaction.clone();
+        transaction.gas = gas;
+        client
+            .call(
+                &transaction.fake_sign(sender),
+                Default::default(),
+                BlockId::Latest,
+            )
+            .unwrap()
+    };
+    // the estimate is the minimal gas the contract can be created with
+    assert_eq!(call(estimate).exception, "");
+    assert_ne!(call(estimate - 1.into()).exception, "");
+}
+
+#[test]
+fn estimate_gas_fails_with_vm_error() {
+    let client = generate_dummy_client(0);
+    let sender = Address::from(1);
+    // contract with a non payable constructor, reverts when created with value
+    let code = "605060405234156100105760006000fd5b610015565b610199806100246000396000f30060506040526000356c01000000000000000000000000900463ffffffff1680632d7df21a146100335761002d565b60006000fd5b341561003f5760006000fd5b6100666004808080601001359035909160200190919290803590601001909190505061007c565b6040518082815260100191505060405180910390f35b6000600060007f66fa32225b641331dff20698cd66d310b3149e86d875926af7ea2f2a9079e80b856040518082815260100191505060405180910390a18585915091506001841115156100d55783925061016456610163565b60018282632d7df21a898960018a036000604051601001526040518463ffffffff166c010000000000000000000000000281526004018084848252816010015260200182815260100193505050506010604051808303816000888881813b151561013f5760006000fd5b5af1151561014d5760006000fd5b5050505060405180519060100150019250610164565b5b505093925050505600a165627a7a72305820c4755a8b960e01280a2c8d85fae255d08e1be318b2c2685a948e7b42660c2f5c0029".from_hex().unwrap();
+    let transaction =
+        estimate_transaction(Action::Create, code, 1.into(), 0.into()).fake_sign(sender);
+
+    match client.estimate_gas(&transaction, BlockId::Latest) {
+        Err(CallError::Failed {
+            exception,
+            ..
+        }) => assert_eq!(exception, "Revert"),
+        other => panic!("Unexpected estimate: {:?}", other),
+    }
+}
+
+#[test]
+fn estimate_gas_is_bounded_by_sender_balance() {
+    let client = generate_dummy_client(0);
+    let sender = Address::from(1);
+    let transaction =
+        estimate_transaction(Action::Call(Address::from(2)), vec![], 0.into(), 1.into())
+            .fake_sign(sender);
+
+    match client.estimate_gas(&transaction, BlockId::Latest) {
+        Err(CallError::Execution(ExecutionError::NotEnoughCash {
+            ..
+        })) => {}
+        other => panic!("Unexpected estimate: {:?}", other),
+    }
+}
+
+#[test]
+fn call_many_applies_state_override() {
+    let client = generate_dummy_client(0);
+    let sender = Address::from(1);
+    // not a precompiled contract of the test spec
+    let contract = Address::from(3);
+    // Returns the value of storage slot 1.
+    let code = "60015460005260106000f3".from_hex().unwrap();
+    let mut overrides = StateOverride::new();
+    let mut account = AccountOverride::default();
+    account.nonce = Some(5.into());
+    account.code = Some(code);
+    account.storage.insert(H128::from(1), H128::from(42));
+    overrides.insert(contract, account);
+
+    let mut transaction =
+        estimate_transaction(Action::Call(contract), vec![], 0.into(), 0.into());
+    transaction.gas = 100_000.into();
+    let transactions = vec![
+        (transaction.clone().fake_sign(sender), CallAnalytics::default()),
+        (transaction.fake_sign(sender), CallAnalytics::default()),
+    ];
+
+    let results = client
+        .call_many(&transactions, &overrides, BlockId::Latest)
+        .unwrap();
+    assert_eq!(results.len(), 2);
+    for executed in &results {
+        assert_eq!(executed.exception, "");
+        assert_eq!(executed.output, H128::from(42).to_vec());
+    }
+    // Calls are executed on top of each other.
+    assert_eq!(results[1].cumulative_gas_used, results[0].gas_used * 2);
+
+    // The client state is left untouched.
+    assert_eq!(client.code(&contract, BlockId::Latest), Some(None));
+}
+
+#[test]
+fn block_gas_prices_of_empty_blocks() {
+    let client = generate_dummy_client(3);
+
+    let prices = client.block_gas_prices(BlockId::Number(2)).unwrap();
+    assert_eq!(prices.number, 2);
+    assert!(prices.gas_prices.is_empty());
+    assert!(client.block_gas_prices(BlockId::Number(4)).is_none());
+}
+
+/// Proof-of-stake block on top of the genesis of `spec`, sealed with the ed25519 `key`.
+fn pos_block(spec: &Spec, timestamp: u64, key: &[u8]) -> Bytes {
+    let engine = &*spec.engine;
+    let genesis_header = spec.genesis_header();
+    let db = spec
+        .ensure_db_good(get_temp_state_db(), &Default::default())
+        .unwrap();
+    let mut b = OpenBlock::new(
+        engine,
+        Default::default(),
+        db,
+        &genesis_header,
+        None,
+        None,
+        &SealType::Pos,
+        Arc::new(vec![genesis_header.hash()]),
+        Address::default(),
+        (3141562.into(), 31415620.into()),
+        vec![],
+        false,
+        Arc::new(MemoryDBRepository::new()),
+    )
+    .unwrap();
+    b.set_timestamp(timestamp);
+    let b = b.close_and_lock();
+    let seal = pos_seal(b.header(), key);
+    b.seal(engine, seal).unwrap().rlp_bytes()
+}
+
+/// Checks the miner queued a single report of `evidence` to the double-sign contract.
+fn assert_reported(client: &Client, evidence: &DoubleSignEvidence) {
+    let reports = client.miner().pending_transactions();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].action, Action::Call(double_sign_contract_address()));
+    assert_eq!(reports[0].data, rlp::encode(evidence).into_vec());
+}
+
+#[test]
+fn reports_double_signed_blocks() {
+    let spec = Spec::new_instant_hybrid();
+    let client = generate_dummy_client_with_spec_and_data(Spec::new_instant_hybrid, 0, 0, &[]);
+    client.miner().set_evidence_reporter(keypair(&[2u8; 32]).0);
+
+    let key = [1u8; 32];
+    client.import_block(pos_block(&spec, 10, &key)).unwrap();
+    client.import_block(pos_block(&spec, 11, &key)).unwrap();
+    client.flush_queue();
+
+    let evidence = client.double_sign_evidence();
+    assert_eq!(evidence.len(), 1);
+    assert_eq!(evidence[0].number(), 1);
+    assert_eq!(evidence[0].offender(), double_sign_evidence(&key).offender());
+    assert_reported(&client, &evidence[0]);
+}
+
+/// Hybrid spec in which the ed25519 `key` has stake in the staking registry.
+fn instant_hybrid_with_stake(key: &[u8]) -> Spec {
+    let staker = double_sign_evidence(key).offender();
+    let registry = format!(
+        "\"accounts\": {{ \"{}\": {{ \"balance\": \"0\", \"storage\": {{ \"0x{}\": \"0x1\" }} }},",
+        "a00876be75b664de079b58e7acbf70ce315ba4aaa487f7ddf2abd5e0e1a8dff4",
+        stake_key(&staker)[..].to_hex()
+    );
+    let json = include_str!("../../res/instant_hybrid.json").replace("\"accounts\": {", &registry);
+    Spec::load(&::std::env::temp_dir(), json.as_bytes()).expect("valid spec; qed")
+}
+
+#[test]
+fn reports_gossiped_evidence_once() {
+    let key = [1u8; 32];
+    let spec = instant_hybrid_with_stake(&key);
+    let client =
+        generate_dummy_client_with_spec_and_data(|| instant_hybrid_with_stake(&key), 0, 0, &[]);
+    client.miner().set_evidence_reporter(keypair(&[2u8; 32]).0);
+    client.import_block(pos_block(&spec, 10, &[3u8; 32])).unwrap();
+    client.flush_queue();
+
+    let evidence = double_sign_evidence(&key);
+    let bytes = rlp::encode(&evidence).into_vec();
+    assert_eq!(client.import_evidence(bytes.clone()), Ok(true));
+    assert_eq!(client.import_evidence(bytes), Ok(false));
+    assert_eq!(client.double_sign_evidence(), vec![evidence.clone()]);
+    assert_reported(&client, &evidence);
+}
+
+#[test]
+fn rejects_costless_gossiped_evidence() {
+    let spec = Spec::new_instant_hybrid();
+    let client = generate_dummy_client_with_spec_and_data(Spec::new_instant_hybrid, 0, 0, &[]);
+    client.miner().set_evidence_reporter(keypair(&[2u8; 32]).0);
+    let evidence = rlp::encode(&double_sign_evidence(&[1u8; 32])).into_vec();
+
+    // above the best block
+    assert_eq!(
+        client.import_evidence(evidence.clone()),
+        Err(EvidenceError::FutureNumber(1, 0))
+    );
+    client.import_block(pos_block(&spec, 10, &[3u8; 32])).unwrap();
+    client.flush_queue();
+    // signed by a key without stake
+    assert_eq!(client.import_evidence(evidence), Err(EvidenceError::NoStake));
+
+    assert!(client.double_sign_evidence().is_empty());
+    assert!(client.miner().pending_transactions().is_empty());
+}
+
+#[test]
+fn logs_of_indexed_and_unindexed_blocks() {
+    let secret = Ed25519Secret::from_str("7ea8af7d0982509cd815096d35bc3a295f57b2a078e4e25731e3ea977b9544626702b86f33072a55f46003b1e3e242eb18556be54c5ab12044c3c20829e0abb5").unwrap();
+    let address = Address::from(1);
+    let log = LogEntry {
+        address: address,
+        topics: vec![],
+        data: vec![],
+    };
+    let receipt = Receipt {
+        simple_receipt: SimpleReceipt {
+            state_root: H256::default(),
+            log_bloom: log.bloom(),
+            logs: vec![log.clone()],
+        },
+        gas_used: 21_000.into(),
+        transaction_fee: U256::zero(),
+        output: Bytes::default(),
+        error_message: String::default(),
+    };
+    let with_log = |parent: &BlockBuilder, nonce: u64, difficulty: u64| {
+        let transaction = Transaction {
+            nonce: nonce.into(),
+            gas_price: 0.into(),
+            gas: 21_000.into(),
+            action: Action::Call(address),
+            value: 0.into(),
+            data: Vec::new(),
+            nonce_bytes: Vec::new(),
+            gas_price_bytes: Vec::new(),
+            gas_bytes: Vec::new(),
+            value_bytes: Vec::new(),
+            transaction_type: ::transaction::DEFAULT_TRANSACTION_TYPE,
+        }
+        .sign(&secret, None);
+        parent.add_block_with(|| {
+            BlockOptions {
+                difficulty: difficulty.into(),
+                bloom: log.bloom(),
+                transactions: vec![transaction.clone()],
+            }
+        })
+    };
+
+    let genesis = BlockBuilder::genesis();
+    let b1 = with_log(&genesis, 0, 10);
+    let b2 = with_log(&b1, 1, 10);
+    let b3 = b2.add_block();
+    let fork3 = with_log(&b2, 2, 20);
+    let fork4 = with_log(&fork3, 3, 10);
+
+    let db = new_db();
+    {
+        let chain = BlockChain::new(
+            BlockChainConfig::default(),
+            &genesis.last().encoded(),
+            db.clone(),
+        );
+        let insert = |block: &BlockBuilder, receipts: Vec<Receipt>| {
+            let mut batch = DBTransaction::new();
+            chain.insert_block(&mut batch, &block.last().encoded(), receipts);
+            db.write(batch).unwrap();
+            chain.commit();
+        };
+        insert(&b1, vec![receipt.clone()]);
+        insert(&b2, vec![receipt.clone()]);
+        insert(&b3, vec![]);
+        LogIndex::new(db.clone()).update(&chain, 10).unwrap();
+        // the indexed block 3 is retracted by blocks with logs
+        insert(&fork3, vec![receipt.clone()]);
+        insert(&fork4, vec![receipt.clone()]);
+    }
+
+    let spec = Spec::new_test();
+    let mut config = ClientConfig::default();
+    config.log_index = true;
+    let client = Client::new(
+        config,
+        &spec,
+        db,
+        Arc::new(Miner::with_spec(&spec)),
+        IoChannel::disconnected(),
+    )
+    .unwrap();
+    let filter = |limit: Option<usize>, offset: Option<usize>| {
+        Filter {
+            from_block: BlockId::Earliest,
+            to_block: BlockId::Latest,
+            address: Some(vec![address]),
+            topics: vec![],
+            limit: limit,
+            offset: offset,
+        }
+    };
+    let numbers = |filter: Filter| {
+        client
+            .logs(filter)
+            .into_iter()
+            .map(|log| log.block_number)
+            .collect::<Vec<_>>()
+    };
+
+    // blocks 1 and 2 are found in the index, the blocks above it in the blooms
+    assert_eq!(numbers(filter(None, None)), vec![1, 2, 3, 4]);
+    // the newest `offset` logs are skipped
+    assert_eq!(numbers(filter(Some(2), Some(1))), vec![2, 3]);
+    assert_eq!(numbers(filter(None, Some(3))), vec![1]);
+}
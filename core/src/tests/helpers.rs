@@ -40,6 +40,9 @@ use state::*;
 use std::sync::Arc;
 use kvdb::{MockDbRepository, DBTransaction, KeyValueDB, MemoryDBRepository};
 use db;
+use evidence::DoubleSignEvidence;
+use header::SealType;
+use rcrypto::ed25519::{keypair, signature};
 
 // TODO: move everything over to get_null_spec.
 pub fn get_test_spec() -> Spec { Spec::new_test() }
@@ -86,6 +89,34 @@ fn create_unverifiable_block(order: u32, parent_hash: H256) -> Bytes {
     create_test_block(&create_unverifiable_block_header(order, parent_hash))
 }
 
+/// PoS seal of a header signed with the ed25519 `key`. The seed only depends on the parent,
+/// so headers sealed on the same parent share it.
+pub fn pos_seal(header: &Header, key: &[u8]) -> Vec<Bytes> {
+    let (secret, public) = keypair(key);
+    let mut seed = public.to_vec();
+    seed.extend_from_slice(&signature(&header.parent_hash().0, &secret));
+    let mut sig = public.to_vec();
+    sig.extend_from_slice(&signature(&header.bare_hash().0, &secret));
+    vec![seed, sig]
+}
+
+/// Evidence of the staker with the ed25519 `key` sealing two blocks at height 1.
+pub fn double_sign_evidence(key: &[u8]) -> DoubleSignEvidence {
+    let headers: Vec<Header> = (0..2)
+        .map(|timestamp| {
+            let mut header = Header::new();
+            header.set_number(1);
+            header.set_timestamp(timestamp);
+            header.set_seal_type(Some(SealType::Pos));
+            let seal = pos_seal(&header, key);
+            header.set_seal(seal);
+            header
+        })
+        .collect();
+    DoubleSignEvidence::new(headers[0].clone(), headers[1].clone())
+        .expect("headers conflict; qed")
+}
+
 pub fn create_test_block_with_data(header: &Header, transactions: &[SignedTransaction]) -> Bytes {
     let mut rlp = RlpStream::new_list(2);
     rlp.append(header);
@@ -304,7 +335,7 @@ pub fn get_test_client_with_blocks(blocks: Vec<Bytes>) -> Arc<Client> {
     client
 }
 
-fn new_db() -> Arc<KeyValueDB> {
+pub fn new_db() -> Arc<KeyValueDB> {
     let mut db_configs = Vec::new();
     for db_name in db::DB_NAMES.to_vec() {
         db_configs.push(db_name.into());
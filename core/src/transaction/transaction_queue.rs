@@ -688,6 +688,24 @@ impl TransactionQueue {
         }
     }
 
+    /// Returns the memory limit of the current and future transactions.
+    pub fn memory_limit(&self) -> usize { self.current.memory_limit }
+
+    /// Returns the memory used by the current and future transactions.
+    pub fn memory_usage(&self) -> usize { self.current.memory_usage + self.future.memory_usage }
+
+    /// Changes the memory limit, dropping the lowest priority transactions that no longer fit.
+    pub fn set_memory_limit(&mut self, limit: usize) {
+        self.current.memory_limit = limit;
+        self.future.memory_limit = limit;
+        self.future
+            .enforce_limit(&mut self.by_hash, &mut self.local_transactions);
+        let removed = self
+            .current
+            .enforce_limit(&mut self.by_hash, &mut self.local_transactions);
+        self.update_last_nonces(&removed);
+    }
+
     /// Add signed transaction to queue to be verified and imported.
     ///
     /// NOTE details_provider methods should be cheap to compute
@@ -1186,6 +1204,17 @@ impl TransactionQueue {
             .any(|tx| tx.origin == TransactionOrigin::Local)
     }
 
+    /// Returns senders of the pending local transactions
+    pub fn local_senders(&self) -> HashSet<Address> {
+        self.current
+            .by_priority
+            .iter()
+            .filter(|tx| tx.origin.is_local())
+            .filter_map(|tx| self.by_hash.get(&tx.hash))
+            .map(|tx| tx.sender())
+            .collect()
+    }
+
     /// Finds transaction in the queue by hash (if any)
     pub fn find(&self, hash: &H256) -> Option<PendingTransaction> {
         self.by_hash.get(hash).map(|tx| {
@@ -153,7 +153,7 @@ pub struct VerificationQueue<K: Kind> {
     processing: RwLock<HashMap<H256, U256>>, // hash to difficulty
     ticks_since_adjustment: AtomicUsize,
     max_queue_size: usize,
-    max_mem_use: usize,
+    max_mem_use: AtomicUsize,
     scale_verifiers: bool,
     verifier_handles: Vec<JoinHandle<()>>,
     state: Arc<(Mutex<State>, Condvar)>,
@@ -292,7 +292,7 @@ impl<K: Kind> VerificationQueue<K> {
             empty: empty,
             ticks_since_adjustment: AtomicUsize::new(0),
             max_queue_size: cmp::max(config.max_queue_size, MIN_QUEUE_LIMIT),
-            max_mem_use: cmp::max(config.max_mem_use, MIN_MEM_LIMIT),
+            max_mem_use: AtomicUsize::new(cmp::max(config.max_mem_use, MIN_MEM_LIMIT)),
             scale_verifiers: scale_verifiers,
             verifier_handles: verifier_handles,
             state: state,
@@ -493,6 +493,9 @@ impl<K: Kind> VerificationQueue<K> {
     /// Clear bad items in the queue.
     pub fn clear_bad(&self) { self.verification.bad.lock().clear(); }
 
+    /// Get hashes of the items known to be bad.
+    pub fn bad_items(&self) -> Vec<H256> { self.verification.bad.lock().iter().cloned().collect() }
+
     /// Wait for unverified queue to be empty
     pub fn flush(&self) {
         let mut lock = self.verification.empty.lock().unwrap();
@@ -679,11 +682,18 @@ impl<K: Kind> VerificationQueue<K> {
             verifying_queue_size: verifying_len,
             verified_queue_size: verified_len,
             max_queue_size: self.max_queue_size,
-            max_mem_use: self.max_mem_use,
+            max_mem_use: self.max_mem_use.load(AtomicOrdering::Relaxed),
             mem_used: unverified_bytes + verifying_bytes + verified_bytes,
         }
     }
 
+    /// Set the maximum heap memory to use. Items already queued are kept, only new items are
+    /// refused while the queue is above the limit.
+    pub fn set_max_mem_use(&self, max_mem_use: usize) {
+        self.max_mem_use
+            .store(cmp::max(max_mem_use, MIN_MEM_LIMIT), AtomicOrdering::Relaxed);
+    }
+
     /// Get the total difficulty of all the blocks in the queue.
     pub fn total_difficulty(&self) -> U256 { self.total_difficulty.read().clone() }
 
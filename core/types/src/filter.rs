@@ -53,6 +53,12 @@ pub struct Filter {
     /// If None, return all logs
     /// If specified, should only return *last* `n` logs.
     pub limit: Option<usize>,
+
+    /// Logs offset
+    ///
+    /// If specified, the *last* `n` logs are skipped before applying the limit, to page
+    /// through the logs from the most recent ones.
+    pub offset: Option<usize>,
 }
 
 impl Clone for Filter {
@@ -68,6 +74,7 @@ impl Clone for Filter {
             address: self.address.clone(),
             topics: topics[..].to_vec(),
             limit: self.limit,
+            offset: self.offset,
         }
     }
 }
@@ -141,6 +148,7 @@ mod tests {
             address: None,
             topics: vec![None, None, None, None],
             limit: None,
+            offset: None,
         };
 
         let possibilities = none_filter.bloom_possibilities();
@@ -166,6 +174,7 @@ mod tests {
                 None,
             ],
             limit: None,
+            offset: None,
         };
 
         let possibilities = filter.bloom_possibilities();
@@ -191,6 +200,7 @@ mod tests {
                 None,
             ],
             limit: None,
+            offset: None,
         };
 
         let possibilities = filter.bloom_possibilities();
@@ -221,6 +231,7 @@ mod tests {
                 None,
             ],
             limit: None,
+            offset: None,
         };
 
         // number of possibilites should be equal 2 * 2 * 2 * 1 = 8
@@ -248,6 +259,7 @@ mod tests {
                 None,
             ],
             limit: None,
+            offset: None,
         };
 
         let entry0 = LogEntry {
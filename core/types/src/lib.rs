@@ -44,11 +44,13 @@ pub mod call_analytics;
 pub mod filter;
 pub mod ids;
 pub mod log_entry;
+pub mod memory_budget;
 pub mod pruning_info;
 pub mod receipt;
 pub mod restoration_status;
 pub mod security_level;
 pub mod state_diff;
+pub mod state_override;
 pub mod trace_filter;
 pub mod tree_route;
 pub mod verification_queue_info;
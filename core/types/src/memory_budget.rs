@@ -0,0 +1,159 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Memory budget of the caches of the client and their usage.
+
+use std::cmp;
+
+const MB: usize = 1024 * 1024;
+/// Smallest budget of the blockchain caches.
+const MIN_BLOCKCHAIN: usize = 4 * MB;
+/// Smallest budget of the database cache.
+const MIN_DB: usize = 8 * MB;
+/// Smallest budget of the block verification queue.
+const MIN_QUEUE: usize = 16 * MB;
+/// Smallest budget of the transaction queue.
+const MIN_TX_QUEUE: usize = 2 * MB;
+
+/// Memory budget of the caches of the client, in bytes.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct MemoryBudget {
+    /// Blockchain caches of blocks, details, receipts and blooms.
+    pub blockchain: usize,
+    /// State account and code caches.
+    pub state: usize,
+    /// Block cache of the databases, `None` if they use their default size.
+    pub db: Option<usize>,
+    /// Block verification queue.
+    pub queue: usize,
+    /// Transaction queue, `None` if it is not limited.
+    pub tx_queue: Option<usize>,
+}
+
+impl MemoryBudget {
+    /// Splits `total` bytes between the caches, none of them getting less than its minimum
+    /// unless the minimums alone exceed `total`, in which case they are scaled down to fit.
+    pub fn with_total(total: usize) -> Self {
+        let mut blockchain = cmp::max(total / 5, MIN_BLOCKCHAIN);
+        let mut db = cmp::max(total / 4, MIN_DB);
+        let mut queue = cmp::max(total * 3 / 20, MIN_QUEUE);
+        let mut tx_queue = cmp::max(total / 20, MIN_TX_QUEUE);
+        let reserved = blockchain + db + queue + tx_queue;
+        if reserved > total {
+            let scale = |size: usize| (size as u64 * total as u64 / reserved as u64) as usize;
+            blockchain = scale(blockchain);
+            db = scale(db);
+            queue = scale(queue);
+            tx_queue = scale(tx_queue);
+        }
+        MemoryBudget {
+            blockchain: blockchain,
+            // the state cache has no minimum and gets what is left
+            state: total - blockchain - db - queue - tx_queue,
+            db: Some(db),
+            queue: queue,
+            tx_queue: Some(tx_queue),
+        }
+    }
+
+    /// Splits `total` bytes between the caches that can be resized at runtime, keeping the
+    /// database block cache of this budget, which is only sized when the databases are opened.
+    pub fn resized(&self, total: usize) -> Self {
+        let db = self.db.unwrap_or(0);
+        let mut budget = MemoryBudget::with_total(total.saturating_sub(db));
+        // the share reserved for the databases goes to the state cache instead
+        budget.state += budget.db.unwrap_or(0);
+        budget.db = self.db;
+        budget
+    }
+
+    /// Sum of the budgets of all caches.
+    pub fn total(&self) -> usize {
+        self.blockchain
+            + self.state
+            + self.db.unwrap_or(0)
+            + self.queue
+            + self.tx_queue.unwrap_or(0)
+    }
+}
+
+/// Memory used by the caches of the client, in bytes.
+#[derive(Debug, Clone)]
+pub struct MemoryUsage {
+    /// Budget the caches are kept within.
+    pub budget: MemoryBudget,
+    /// Used by the blockchain caches.
+    pub blockchain: usize,
+    /// Used by the state caches and the uncommitted state journal.
+    pub state: usize,
+    /// Used by the block verification queue.
+    pub queue: usize,
+    /// Used by the transactions in the transaction queue.
+    pub tx_queue: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MemoryBudget, MB};
+
+    #[test]
+    fn splits_total_between_caches() {
+        let budget = MemoryBudget::with_total(400 * MB);
+        assert_eq!(budget.blockchain, 80 * MB);
+        assert_eq!(budget.state, 140 * MB);
+        assert_eq!(budget.db, Some(100 * MB));
+        assert_eq!(budget.queue, 60 * MB);
+        assert_eq!(budget.tx_queue, Some(20 * MB));
+        assert_eq!(budget.total(), 400 * MB);
+
+        let budget = MemoryBudget::with_total(40 * MB);
+        assert_eq!(budget.blockchain, 8 * MB);
+        assert_eq!(budget.db, Some(10 * MB));
+        assert_eq!(budget.queue, 16 * MB);
+        assert_eq!(budget.tx_queue, Some(2 * MB));
+        assert_eq!(budget.state, 4 * MB);
+        assert_eq!(budget.total(), 40 * MB);
+    }
+
+    #[test]
+    fn keeps_db_cache_when_resized() {
+        let budget = MemoryBudget::with_total(400 * MB).resized(200 * MB);
+        assert_eq!(budget.db, Some(100 * MB));
+        assert_eq!(budget.blockchain, 20 * MB);
+        assert_eq!(budget.queue, 16 * MB);
+        assert_eq!(budget.tx_queue, Some(5 * MB));
+        assert_eq!(budget.total(), 200 * MB);
+
+        let mut budget = MemoryBudget::with_total(400 * MB);
+        budget.db = None;
+        assert_eq!(budget.resized(100 * MB).total(), 100 * MB);
+    }
+
+    #[test]
+    fn scales_minimums_down_to_total() {
+        let budget = MemoryBudget::with_total(20 * MB);
+        assert!(budget.blockchain < 4 * MB);
+        assert!(budget.db.unwrap() < 8 * MB);
+        assert!(budget.queue < 16 * MB);
+        assert!(budget.tx_queue.unwrap() < 2 * MB);
+        assert!(budget.total() <= 20 * MB);
+    }
+}
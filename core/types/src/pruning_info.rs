@@ -33,4 +33,10 @@ pub struct PruningInfo {
     pub earliest_chain: u64,
     /// The first block where state requests may be served.
     pub earliest_state: u64,
+    /// Interval of the earlier blocks whose state is kept, if any.
+    pub checkpoint_interval: Option<u64>,
+    /// Size of the state database on disk in bytes, if known.
+    pub state_size: Option<u64>,
+    /// Number of eras past the history window still waiting to be pruned.
+    pub pruning_backlog: u64,
 }
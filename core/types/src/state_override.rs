@@ -0,0 +1,43 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! State overrides for simulated calls.
+
+use std::collections::BTreeMap;
+
+use aion_types::{Address, H128, U256};
+use bytes::Bytes;
+
+/// Account fields replaced before simulating calls. Fields left empty keep their value.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AccountOverride {
+    /// Balance of the account.
+    pub balance: Option<U256>,
+    /// Nonce of the account.
+    pub nonce: Option<U256>,
+    /// Code of the account.
+    pub code: Option<Bytes>,
+    /// Storage slots of the account, other slots are kept.
+    pub storage: BTreeMap<H128, H128>,
+}
+
+/// Accounts to override, by address.
+pub type StateOverride = BTreeMap<Address, AccountOverride>;
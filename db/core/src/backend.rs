@@ -0,0 +1,136 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Embedded storage engines of the dbs of a `DbRepository`.
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use dbconfigs::DatabaseConfig;
+use rockskvdb::Rockskvdb;
+#[cfg(feature = "lmdb")]
+use lmdbkvdb::Lmdbkvdb;
+use traits::KeyValueEngine;
+
+/// Embedded storage engine of a db.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DatabaseBackend {
+    /// RocksDB, a log-structured merge tree.
+    RocksDb,
+    /// LMDB, a copy-on-write B+tree. Only available with the `lmdb` feature.
+    Lmdb,
+}
+
+impl Default for DatabaseBackend {
+    fn default() -> Self { DatabaseBackend::RocksDb }
+}
+
+impl FromStr for DatabaseBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rocksdb" => Ok(DatabaseBackend::RocksDb),
+            "lmdb" => Ok(DatabaseBackend::Lmdb),
+            x => Err(format!("Invalid database backend: {}", x)),
+        }
+    }
+}
+
+impl fmt::Display for DatabaseBackend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            DatabaseBackend::RocksDb => "rocksdb",
+            DatabaseBackend::Lmdb => "lmdb",
+        };
+        f.write_str(name)
+    }
+}
+
+impl DatabaseBackend {
+    /// Backends compiled in.
+    pub fn available() -> Vec<DatabaseBackend> {
+        let mut backends = vec![DatabaseBackend::RocksDb];
+        if cfg!(feature = "lmdb") {
+            backends.push(DatabaseBackend::Lmdb);
+        }
+        backends
+    }
+
+    /// Backend which created the db at `path`, None if there is no db yet.
+    pub fn detect(path: &Path) -> Option<DatabaseBackend> {
+        if path.join("CURRENT").exists() {
+            Some(DatabaseBackend::RocksDb)
+        } else if path.join("data.mdb").exists() {
+            Some(DatabaseBackend::Lmdb)
+        } else {
+            None
+        }
+    }
+}
+
+/// Open the db at `path` with the backend selected in `config`. Creates it if it does not exist.
+pub fn open(config: &DatabaseConfig, path: &str) -> Result<Box<KeyValueEngine>, String> {
+    match DatabaseBackend::detect(Path::new(path)) {
+        Some(backend) if backend != config.backend => {
+            return Err(format!(
+                "db {} was created with the {} backend, not {}",
+                path, backend, config.backend
+            ));
+        }
+        _ => {}
+    }
+    match config.backend {
+        DatabaseBackend::RocksDb => Ok(Box::new(Rockskvdb::open(config, path)?)),
+        #[cfg(feature = "lmdb")]
+        DatabaseBackend::Lmdb => Ok(Box::new(Lmdbkvdb::open(config, path)?)),
+        #[cfg(not(feature = "lmdb"))]
+        DatabaseBackend::Lmdb => {
+            Err("lmdb backend is not supported, build with the lmdb feature".into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn parses_backends() {
+        assert_eq!("rocksdb".parse(), Ok(DatabaseBackend::RocksDb));
+        assert_eq!("lmdb".parse(), Ok(DatabaseBackend::Lmdb));
+        assert!("leveldb".parse::<DatabaseBackend>().is_err());
+    }
+
+    #[test]
+    fn refuses_db_of_other_backend() {
+        let path = "./temp/backend_detect";
+        {
+            open(&DatabaseConfig::default(), path).unwrap();
+        }
+        assert_eq!(DatabaseBackend::detect(Path::new(path)), Some(DatabaseBackend::RocksDb));
+        let mut config = DatabaseConfig::default();
+        config.backend = DatabaseBackend::Lmdb;
+        assert!(open(&config, path).is_err());
+        let _ = fs::remove_dir_all(path);
+    }
+}
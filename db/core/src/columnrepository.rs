@@ -0,0 +1,453 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Repository keeping every db as a column family of a single rocksdb instance.
+//!
+//! Unlike `DbRepository`, which opens one rocksdb per db and applies a transaction db by db,
+//! each `DBTransaction` is committed here as one `WriteBatch`, so a crash can never leave a
+//! transaction half written across dbs. This holds as long as the write-ahead log is enabled.
+
+use std::fs;
+use std::path::Path;
+use parking_lot::RwLock;
+use parity_rocksdb::{
+    DB, BlockBasedOptions, Cache, Column, Direction, IteratorMode, Options, ReadOptions,
+    WriteBatch, WriteOptions, Writable,
+};
+
+use super::{Result, DBValue};
+use traits::{KeyValueDAO, KeyValueDB};
+use dbconfigs::{DatabaseConfig, RepositoryConfig};
+use dbtransaction::{DBTransaction, DBOp};
+use error::Error;
+use rockskvdb::Rockskvdb;
+
+/// Number of pairs copied per write batch when migrating from the per-db layout.
+const MIGRATION_BATCH_SIZE: usize = 10_000;
+
+/// db repository backed by a single rocksdb with one column family per db.
+pub struct ColumnDbRepository {
+    /// rocksdb instance, `None` while closed.
+    db: RwLock<Option<DB>>,
+    /// names of the column families.
+    columns: Vec<String>,
+    /// path of the rocksdb instance.
+    path: String,
+    /// db config, useful in reopening db.
+    config: DatabaseConfig,
+    write_options: WriteOptions,
+    sync_write_options: WriteOptions,
+    read_options: ReadOptions,
+}
+
+impl ColumnDbRepository {
+    /// Open the repository at `path` with a column family for each of `columns`. Missing column
+    /// families are created.
+    pub fn init(
+        columns: Vec<String>,
+        config: DatabaseConfig,
+        path: &str,
+    ) -> Result<ColumnDbRepository>
+    {
+        if !config.wal {
+            warn!(target: "db", "Write-ahead log disabled, commits across columns are not crash safe");
+        }
+
+        let mut read_options = ReadOptions::new();
+        read_options.set_verify_checksums(false);
+        let mut write_options = WriteOptions::new();
+        if !config.wal {
+            write_options.disable_wal(true);
+        }
+        let sync_write_options = Rockskvdb::sync_write_options(config.wal);
+
+        let db = ColumnDbRepository::open(&columns, &config, path).map_err(|e| {
+            Error::OpenError {
+                name: path.into(),
+                desc: e,
+            }
+        })?;
+        Ok(ColumnDbRepository {
+            db: RwLock::new(Some(db)),
+            columns: columns,
+            path: path.into(),
+            config: config,
+            write_options: write_options,
+            sync_write_options: sync_write_options,
+            read_options: read_options,
+        })
+    }
+
+    /// Copy the dbs of the per-db layout described by `configs` into a new repository at
+    /// `path`. The copy is made in a temporary directory which is moved to `path` once
+    /// complete, so an interrupted migration is simply started over. The old dbs are left
+    /// untouched.
+    pub fn migrate(
+        configs: &[RepositoryConfig],
+        config: DatabaseConfig,
+        path: &str,
+    ) -> Result<ColumnDbRepository>
+    {
+        let columns = configs
+            .iter()
+            .map(|c| c.db_name.clone())
+            .collect::<Vec<_>>();
+        let temp_path = format!("{}.migrating", path);
+        let _ = fs::remove_dir_all(&temp_path);
+        {
+            let target = ColumnDbRepository::init(columns.clone(), config.clone(), &temp_path)?;
+            for source in configs {
+                if !Path::new(&source.db_path).exists() {
+                    continue;
+                }
+                let db = Rockskvdb::open(&source.db_config, &source.db_path).map_err(|e| {
+                    Error::OpenError {
+                        name: source.db_name.clone(),
+                        desc: e,
+                    }
+                })?;
+                let target_db = target.db.read();
+                let target_db = target_db.as_ref().expect("target was just opened; qed");
+                let column = target_db
+                    .cf_handle(&source.db_name)
+                    .ok_or_else(|| Error::NotFound(source.db_name.clone()))?;
+                let mut copied = 0;
+                let mut batch = WriteBatch::new();
+                for (key, value) in db.iter() {
+                    batch.put_cf(column, &key, &value).map_err(Error::Other)?;
+                    copied += 1;
+                    if copied % MIGRATION_BATCH_SIZE == 0 {
+                        target_db
+                            .write_opt(batch, &target.write_options)
+                            .map_err(Error::Other)?;
+                        batch = WriteBatch::new();
+                    }
+                }
+                target_db
+                    .write_opt(batch, &target.write_options)
+                    .map_err(Error::Other)?;
+                info!(target: "db", "Migrated {} pairs of db {}", copied, source.db_name);
+            }
+        }
+        fs::rename(&temp_path, path).map_err(|e| Error::Other(format!("{}", e)))?;
+        info!(target: "db", "Migration to {} complete, the old dbs are no longer used", path);
+        ColumnDbRepository::init(columns, config, path)
+    }
+
+    /// Whether any db of the per-db layout described by `configs` exists on disk.
+    pub fn has_legacy_layout(configs: &[RepositoryConfig]) -> bool {
+        configs.iter().any(|c| Path::new(&c.db_path).exists())
+    }
+
+    fn open(
+        columns: &[String],
+        config: &DatabaseConfig,
+        path: &str,
+    ) -> ::std::result::Result<DB, String>
+    {
+        let mut block_opts = BlockBasedOptions::new();
+        block_opts.set_block_size(config.block_size);
+        block_opts.set_cache(Cache::new(config.block_cache_bytes()));
+        let opts = Rockskvdb::parse_options(config, &block_opts)?;
+
+        let names = columns.iter().map(|c| c.as_str()).collect::<Vec<_>>();
+        let mut column_opts = Vec::with_capacity(columns.len());
+        for _ in columns {
+            column_opts.push(Rockskvdb::parse_options(config, &block_opts)?);
+        }
+        match DB::open_cf(&opts, path, &names, &column_opts) {
+            Ok(db) => Ok(db),
+            Err(ref s) if s.starts_with("Invalid argument: Column family not found") => {
+                // new db or new columns, create the missing ones
+                let mut db = DB::open(&opts, path)?;
+                for name in names {
+                    if db.cf_handle(name).is_none() {
+                        db.create_cf(name, &Rockskvdb::parse_options(config, &block_opts)?)?;
+                    }
+                }
+                Ok(db)
+            }
+            Err(ref s) if s.starts_with("Corruption:") => {
+                warn!(target:"db","DB corrupted: {}, attempting repair", s);
+                DB::repair(&opts, path)?;
+                DB::open_cf(&opts, path, &names, &column_opts)
+            }
+            Err(s) => Err(s),
+        }
+    }
+
+    /// flush overlay to disk, nothing is buffered.
+    fn flush(&self) -> Result<()> { Ok(()) }
+
+    /// sync previous writes to disk, by making a synced empty write which syncs the whole
+    /// write-ahead log.
+    fn flush_synced(&self) -> Result<()> {
+        match *self.db.read() {
+            Some(ref db) => {
+                db.write_opt(WriteBatch::new(), &self.sync_write_options)
+                    .map_err(|e| {
+                        Error::FlushError {
+                            name: self.path.clone(),
+                            desc: e,
+                        }
+                    })
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// compact the whole key range of the column of a db.
+    fn compact(&self, db_name: &str) -> Result<()> {
+        let db = self.db.read();
+        let db = db.as_ref().ok_or_else(|| Error::NotFound(db_name.into()))?;
+        let column = db
+            .cf_handle(db_name)
+            .ok_or_else(|| Error::NotFound(db_name.into()))?;
+        let first = db
+            .iterator_cf_opt(column, IteratorMode::Start, &self.read_options)
+            .map_err(Error::Other)?
+            .next();
+        let last = db
+            .iterator_cf_opt(column, IteratorMode::End, &self.read_options)
+            .map_err(Error::Other)?
+            .next();
+        if let (Some((start, _)), Some((end, _))) = (first, last) {
+            db.compact_range_cf(column, &start, &end);
+        }
+        Ok(())
+    }
+
+    /// close the rocksdb instance
+    fn close_all(&mut self) { *self.db.write() = None; }
+
+    /// reopen the rocksdb instance
+    fn open_all(&mut self) {
+        self.close_all();
+        if let Ok(db) = ColumnDbRepository::open(&self.columns, &self.config, &self.path) {
+            *self.db.write() = Some(db);
+        }
+    }
+
+    fn column(&self, db: &DB, db_name: &str) -> Option<Column> {
+        match db.cf_handle(db_name) {
+            Some(column) => Some(column),
+            None => {
+                error!(target:"db","db:{} not found",db_name);
+                None
+            }
+        }
+    }
+}
+
+impl KeyValueDB for ColumnDbRepository {
+    fn get(&self, db_name: &str, key: &[u8]) -> Result<Option<DBValue>> {
+        let db = self.db.read();
+        let db = db.as_ref().ok_or_else(|| Error::NotFound(db_name.into()))?;
+        let column = db
+            .cf_handle(db_name)
+            .ok_or_else(|| Error::NotFound(db_name.into()))?;
+        let res = db
+            .get_cf_opt(column, key, &self.read_options)
+            .map(|r| r.map(|v| DBValue::from_slice(&v)))
+            .map_err(Error::Other)?;
+        trace!(target:"db", "db:{}, Get key = {:?}, value = {:?}", db_name, key, res);
+        Ok(res)
+    }
+
+    fn keys(&self) -> Option<Vec<String>> { Some(self.columns.clone()) }
+
+    fn write_buffered(&self, transaction: DBTransaction) {
+        let db = self.db.read();
+        let db = match db.as_ref() {
+            Some(db) => db,
+            None => {
+                error!(target:"db","db:{} is closed",self.path);
+                return;
+            }
+        };
+        let batch = WriteBatch::new();
+        for db_name in transaction.dbs() {
+            let column = match self.column(db, db_name) {
+                Some(column) => column,
+                None => continue,
+            };
+            for op in transaction.get_vec(db_name).unwrap_or_else(Vec::new) {
+                let res = match op {
+                    DBOp::Delete {
+                        key,
+                    } => {
+                        trace!(target:"db", "db:{}, Delete key = {:?}", db_name, key);
+                        batch.delete_cf(column, &key)
+                    }
+                    DBOp::Insert {
+                        key,
+                        value,
+                    } => {
+                        trace!(target:"db", "db:{}, Put key = {:?}, value = {:?}", db_name, key, value);
+                        batch.put_cf(column, &key, &value)
+                    }
+                };
+                if let Err(e) = res {
+                    error!(target:"db","db:{} batch error: {}",db_name,e);
+                }
+            }
+        }
+        if let Err(e) = db.write_opt(batch, &self.write_options) {
+            error!(target:"db","db:{} write error: {}",self.path,e);
+        }
+    }
+
+    fn iter(&self, db_name: &'static str) -> Box<Iterator<Item = (Box<[u8]>, Box<[u8]>)>> {
+        let db = self.db.read();
+        match db.as_ref().and_then(|db| self.column(db, db_name).map(|c| (db, c))) {
+            Some((db, column)) => {
+                match db.iterator_cf_opt(column, IteratorMode::Start, &self.read_options) {
+                    Ok(iter) => Box::new(iter),
+                    Err(_) => Box::new(None.into_iter()),
+                }
+            }
+            None => Box::new(None.into_iter()),
+        }
+    }
+
+    fn get_by_prefix(&self, db_name: &'static str, prefix: &[u8]) -> Option<Box<[u8]>> {
+        self.iter_from_prefix_unchecked(db_name, prefix)
+            .next()
+            .and_then(|(k, v)| {
+                if k.len() >= prefix.len() && k[0..prefix.len()] == prefix[..] {
+                    Some(v)
+                } else {
+                    None
+                }
+            })
+    }
+
+    fn iter_from_prefix<'a>(
+        &'a self,
+        db_name: &str,
+        prefix: &'static [u8],
+    ) -> Box<Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a>
+    {
+        self.iter_from_prefix_unchecked(db_name, prefix)
+    }
+
+    fn flush(&self) -> Result<()> { ColumnDbRepository::flush(self) }
+
+    fn flush_synced(&self) -> Result<()> { ColumnDbRepository::flush_synced(self) }
+
+    fn compact(&self, db_name: &str) -> Result<()> { ColumnDbRepository::compact(self, db_name) }
+
+    #[cfg(test)]
+    fn close_all(&mut self) { ColumnDbRepository::close_all(self); }
+    #[cfg(test)]
+    fn open_all(&mut self) { ColumnDbRepository::open_all(self); }
+}
+
+impl ColumnDbRepository {
+    fn iter_from_prefix_unchecked(
+        &self,
+        db_name: &str,
+        prefix: &[u8],
+    ) -> Box<Iterator<Item = (Box<[u8]>, Box<[u8]>)>>
+    {
+        let db = self.db.read();
+        match db.as_ref().and_then(|db| self.column(db, db_name).map(|c| (db, c))) {
+            Some((db, column)) => {
+                match db.iterator_cf_opt(
+                    column,
+                    IteratorMode::From(prefix, Direction::Forward),
+                    &self.read_options,
+                ) {
+                    Ok(iter) => Box::new(iter),
+                    Err(_) => Box::new(None.into_iter()),
+                }
+            }
+            None => Box::new(None.into_iter()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn columns() -> Vec<String> { vec!["headers".into(), "extra".into()] }
+
+    #[test]
+    fn commits_across_columns() {
+        let path = "./temp/testdb_columns";
+        {
+            let db =
+                ColumnDbRepository::init(columns(), DatabaseConfig::default(), path).unwrap();
+            let mut batch = DBTransaction::new();
+            batch.put("headers", b"1", b"header");
+            batch.put("extra", b"1", b"details");
+            batch.put("extra", b"2", b"details");
+            batch.delete("extra", b"2");
+            db.write(batch).unwrap();
+
+            assert_eq!(db.get("headers", b"1").unwrap().unwrap().to_vec(), b"header");
+            assert_eq!(db.get("extra", b"1").unwrap().unwrap().to_vec(), b"details");
+            assert_eq!(db.get("extra", b"2").unwrap(), None);
+            assert_eq!(db.get("headers", b"2").unwrap(), None);
+            assert!(db.get("state", b"1").is_err());
+            assert_eq!(db.iter("extra").count(), 1);
+        }
+        {
+            let db =
+                ColumnDbRepository::init(columns(), DatabaseConfig::default(), path).unwrap();
+            assert_eq!(db.get("headers", b"1").unwrap().unwrap().to_vec(), b"header");
+        }
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn migrates_per_db_layout() {
+        let path = "./temp/testdb_migrated";
+        let configs = columns()
+            .into_iter()
+            .map(|name| {
+                RepositoryConfig {
+                    db_path: format!("./temp/testdb_legacy/{}", name),
+                    db_name: name,
+                    db_config: DatabaseConfig::default(),
+                }
+            })
+            .collect::<Vec<_>>();
+        {
+            let mut db = Rockskvdb::open(&configs[0].db_config, &configs[0].db_path).unwrap();
+            db.put(b"1", &DBValue::from_slice(b"header"));
+        }
+        assert!(ColumnDbRepository::has_legacy_layout(&configs));
+
+        {
+            let db =
+                ColumnDbRepository::migrate(&configs, DatabaseConfig::default(), path).unwrap();
+            assert_eq!(db.get("headers", b"1").unwrap().unwrap().to_vec(), b"header");
+            assert_eq!(db.iter("extra").count(), 0);
+        }
+        assert!(!Path::new(&format!("{}.migrating", path)).exists());
+
+        let _ = fs::remove_dir_all(path);
+        let _ = fs::remove_dir_all("./temp/testdb_legacy");
+    }
+}
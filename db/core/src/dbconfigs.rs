@@ -19,9 +19,11 @@
  *
  ******************************************************************************/
 
+use std::cmp;
 #[cfg(target_os = "linux")]
 use std::path::PathBuf;
 use std::path::Path;
+use backend::DatabaseBackend;
 /// A single db in dbrepository ' config
 #[derive(Clone, Debug)]
 pub struct RepositoryConfig {
@@ -33,13 +35,17 @@ pub struct RepositoryConfig {
     pub db_path: String,
 }
 
-/// rocksdb config
+/// db config, most of the options only apply to rocksdb
 #[derive(Clone, Debug)]
 pub struct DatabaseConfig {
+    /// Embedded storage engine.
+    pub backend: DatabaseBackend,
     /// How many files rocksdb can open at one time.
     pub max_open_files: i32,
-    /// Memory budget for block based cache size (MB).
+    /// Memory budget for block based cache size (bytes).
     pub memory_budget: usize,
+    /// Size of the block cache alone (bytes), `memory_budget` is used when unset.
+    pub block_cache_budget: Option<usize>,
     /// Block based cache size (MB).
     pub block_size: usize,
     /// Compact options.
@@ -79,8 +85,10 @@ pub struct DatabaseConfig {
 impl Default for DatabaseConfig {
     fn default() -> DatabaseConfig {
         DatabaseConfig {
+            backend: DatabaseBackend::default(),
             max_open_files: 4096,
             memory_budget: 128 * 1024 * 1024,
+            block_cache_budget: None,
             block_size: 16 * 1024,
             compact_options: CompactionProfile::default(),
             use_fsync: false,
@@ -102,6 +110,13 @@ impl Default for DatabaseConfig {
     }
 }
 
+impl DatabaseConfig {
+    /// Bytes given to the block cache.
+    pub fn block_cache_bytes(&self) -> usize {
+        cmp::max(8, self.block_cache_budget.unwrap_or(self.memory_budget))
+    }
+}
+
 #[cfg(target_os = "linux")]
 use regex::Regex;
 #[cfg(target_os = "linux")]
@@ -19,19 +19,21 @@
  *
  ******************************************************************************/
 
-use rockskvdb;
+use backend;
 use mockkvdb;
 use std::collections::{HashMap,BTreeMap};
+use std::fs;
+use std::path::Path;
 use parking_lot::RwLock;
 
 use super::{Result, DBValue};
-use traits::{KeyValueDAO, KeyValueDB};
+use traits::{KeyValueDAO, KeyValueDB, KeyValueEngine};
 use dbconfigs::RepositoryConfig;
 use dbtransaction::{DBTransaction, DBOp};
 use error::Error;
 use MemoryDB;
 
-type DB = rockskvdb::Rockskvdb;
+type DB = Box<KeyValueEngine>;
 type DbName = String;
 type MockDb = mockkvdb::Mockkvdb;
 
@@ -66,6 +68,12 @@ impl MemoryDBRepository {
     }
     fn flush(&self) -> Result<()> { Ok(()) }
 
+    fn flush_synced(&self) -> Result<()> { Ok(()) }
+
+    fn disk_size(&self, _db_name: &str) -> Option<u64> { None }
+
+    fn compact(&self, _db_name: &str) -> Result<()> { Ok(()) }
+
     #[cfg(test)]
     fn close_all(&mut self) {}
 
@@ -82,7 +90,7 @@ impl DbRepository {
         let mut dbs = BTreeMap::new();
         let mut db_names = vec![];
         for config in configs {
-            match rockskvdb::Rockskvdb::open(&config.db_config, &config.db_path) {
+            match backend::open(&config.db_config, &config.db_path) {
                 Ok(db) => {
                     dbs.insert(config.db_name.clone(), RwLock::new(db));
                     db_names.push(config.db_name.clone());
@@ -108,7 +116,7 @@ impl DbRepository {
             match self.dbs.get(&*db_name) {
                 Some(db) => {
                     let mut db = db.write();
-                    db.flush().map_err(|e| {
+                    db.flush_opt(false).map_err(|e| {
                         Error::FlushError {
                             name: db_name,
                             desc: e,
@@ -120,6 +128,41 @@ impl DbRepository {
         }
         Ok(())
     }
+    /// flush overlay to disk and sync it, in flush priority order.
+    fn flush_synced(&self) -> Result<()> {
+        for db_name in self.db_priority.clone() {
+            if let Some(db) = self.dbs.get(&*db_name) {
+                db.write().flush_opt(true).map_err(|e| {
+                    Error::FlushError {
+                        name: db_name,
+                        desc: e,
+                    }
+                })?;
+            }
+        }
+        Ok(())
+    }
+    /// size of the files of the db on disk.
+    fn disk_size(&self, db_name: &str) -> Option<u64> {
+        self.configs
+            .iter()
+            .find(|config| config.db_name == db_name)
+            .map(|config| dir_size(Path::new(&config.db_path)))
+    }
+    /// compact the whole key range of the db.
+    fn compact(&self, db_name: &str) -> Result<()> {
+        match self.dbs.get(db_name) {
+            Some(db) => {
+                db.write().compact().map_err(|e| {
+                    Error::FlushError {
+                        name: db_name.into(),
+                        desc: e,
+                    }
+                })
+            }
+            None => Err(Error::NotFound(db_name.into())),
+        }
+    }
     /// close all dbs
     fn close_all(&mut self) {
         self.db_priority.clear();
@@ -130,7 +173,7 @@ impl DbRepository {
         self.close_all();
         let configs = self.configs.clone();
         for config in configs {
-            match rockskvdb::Rockskvdb::open(&config.db_config, &config.db_path) {
+            match backend::open(&config.db_config, &config.db_path) {
                 Ok(db) => {
                     self.dbs.insert(config.db_name.clone(), RwLock::new(db));
                     self.db_priority.push(config.db_name.clone());
@@ -141,6 +184,20 @@ impl DbRepository {
     }
 }
 
+/// Total size of the files in a directory, in bytes.
+fn dir_size(path: &Path) -> u64 {
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| entry.metadata().ok())
+                .filter(|metadata| metadata.is_file())
+                .map(|metadata| metadata.len())
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
 impl Drop for DbRepository {
     /// flush all dbs before drop.
     fn drop(&mut self) { let _ = self.flush(); }
@@ -162,6 +219,12 @@ impl MockDbRepository {
     }
     /// flush all db
     fn flush(&self) -> Result<()> { Ok(()) }
+    /// flush and sync all db
+    fn flush_synced(&self) -> Result<()> { Ok(()) }
+    /// mock dbs are not on disk
+    fn disk_size(&self, _db_name: &str) -> Option<u64> { None }
+    /// nothing to compact in mock dbs
+    fn compact(&self, _db_name: &str) -> Result<()> { Ok(()) }
     /// close all dbs
     fn close_all(&mut self) { self.dbs.clear(); }
     /// reopen all dbs
@@ -278,6 +341,12 @@ macro_rules! impl_keyvaluedb {
 
             fn flush(&self) -> Result<()> { $name::flush(self) }
 
+            fn flush_synced(&self) -> Result<()> { $name::flush_synced(self) }
+
+            fn disk_size(&self, db_name: &str) -> Option<u64> { $name::disk_size(self, db_name) }
+
+            fn compact(&self, db_name: &str) -> Result<()> { $name::compact(self, db_name) }
+
             #[cfg(test)]
             fn close_all(&mut self) { $name::close_all(self); }
             #[cfg(test)]
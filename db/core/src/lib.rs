@@ -37,10 +37,16 @@ extern crate interleaved_ordered;
 extern crate heapsize;
 extern crate plain_hasher;
 extern crate rand;
+#[cfg(feature = "lmdb")]
+extern crate lmdb;
 mod dbrepository;
+mod columnrepository;
 mod dbtransaction;
 mod mockkvdb;
 mod rockskvdb;
+#[cfg(feature = "lmdb")]
+mod lmdbkvdb;
+mod backend;
 mod memorydb;
 mod traits;
 mod error;
@@ -48,10 +54,14 @@ mod dbconfigs;
 
 use elastic_array::{ElasticArray32, ElasticArray128};
 pub use dbrepository::{DbRepository, MockDbRepository, MemoryDBRepository};
+pub use columnrepository::ColumnDbRepository;
 pub use dbtransaction::{DBOp, DBTransaction};
 pub use mockkvdb::Mockkvdb;
 pub use rockskvdb::Rockskvdb;
-pub use traits::{HashStore, AsHashStore, KeyValueDB};
+#[cfg(feature = "lmdb")]
+pub use lmdbkvdb::Lmdbkvdb;
+pub use backend::DatabaseBackend;
+pub use traits::{HashStore, AsHashStore, KeyValueDB, KeyValueEngine};
 pub use memorydb::MemoryDB;
 pub use error::Error;
 pub use dbconfigs::{DatabaseConfig, CompactionProfile, RepositoryConfig};
@@ -0,0 +1,302 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! LMDB db, an alternative to rocksdb. LMDB is a copy-on-write B+tree: it keeps no log and
+//! never compacts, at the price of rewriting whole pages on every commit.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use interleaved_ordered::interleave_ordered;
+use lmdb::{
+    Cursor, Database, Environment, EnvironmentFlags, Error as LmdbError, Transaction, WriteFlags,
+};
+use dbconfigs::DatabaseConfig;
+use traits::{KeyValueDAO, KeyValueEngine};
+use super::{Key, DBValue};
+
+/// Maximum size of a db. The address space is reserved, the file grows with the data.
+#[cfg(target_pointer_width = "64")]
+const MAP_SIZE: usize = 1 << 40;
+#[cfg(not(target_pointer_width = "64"))]
+const MAP_SIZE: usize = 1 << 30;
+
+/// Number of entries read per read transaction by iterators.
+const ITER_CHUNK: usize = 1024;
+
+enum KeyState {
+    Insert(DBValue),
+    Delete,
+}
+
+pub struct Lmdbkvdb {
+    env: Arc<Environment>,
+    db: Database,
+    overlay: HashMap<Key, KeyState>,
+}
+
+impl Lmdbkvdb {
+    /// Open database directory. Creates if it does not exist.
+    pub fn open(config: &DatabaseConfig, path: &str) -> Result<Self, String> {
+        fs::create_dir_all(path).map_err(|e| format!("{}", e))?;
+        // without the wal, commits are synced on flush only, like rocksdb
+        let flags = if config.wal {
+            EnvironmentFlags::empty()
+        } else {
+            EnvironmentFlags::NO_SYNC
+        };
+        let env = Environment::new()
+            .set_flags(flags)
+            .set_map_size(MAP_SIZE)
+            .open(Path::new(path))
+            .map_err(|e| format!("{}", e))?;
+        let db = env.open_db(None).map_err(|e| format!("{}", e))?;
+        Ok(Lmdbkvdb {
+            env: Arc::new(env),
+            db: db,
+            overlay: HashMap::new(),
+        })
+    }
+
+    pub fn flush(&mut self) -> Result<(), String> { self.flush_opt(false) }
+
+    /// Commit the overlay in a single write transaction, and wait for the environment to be
+    /// synced to disk if `sync`.
+    pub fn flush_opt(&mut self, sync: bool) -> Result<(), String> {
+        if !self.overlay.is_empty() {
+            let mut txn = self.env.begin_rw_txn().map_err(|e| format!("{}", e))?;
+            for (key, keystate) in self.overlay.drain() {
+                let key: &[u8] = &key;
+                let res = match keystate {
+                    KeyState::Insert(value) => {
+                        let value: &[u8] = &value;
+                        txn.put(self.db, &key, &value, WriteFlags::empty())
+                    }
+                    KeyState::Delete => {
+                        match txn.del(self.db, &key, None) {
+                            Err(LmdbError::NotFound) => Ok(()),
+                            res => res,
+                        }
+                    }
+                };
+                res.map_err(|e| format!("{}", e))?;
+            }
+            txn.commit().map_err(|e| format!("{}", e))?;
+        }
+        if sync {
+            self.env.sync(true).map_err(|e| format!("{}", e))?;
+        }
+        Ok(())
+    }
+
+    fn iter_from(&self, from: &[u8]) -> LmdbkvdbIterator {
+        LmdbkvdbIterator {
+            env: self.env.clone(),
+            db: self.db,
+            from: from.to_vec(),
+            skip_from: false,
+            done: false,
+            chunk: VecDeque::new(),
+        }
+    }
+}
+
+/// Iterator reading the db in chunks, each in its own read transaction, so that no
+/// transaction is held open while iterating.
+pub struct LmdbkvdbIterator {
+    env: Arc<Environment>,
+    db: Database,
+    /// Key the next chunk starts at.
+    from: Vec<u8>,
+    /// Whether `from` was already returned with the previous chunk.
+    skip_from: bool,
+    done: bool,
+    chunk: VecDeque<(Box<[u8]>, Box<[u8]>)>,
+}
+
+impl LmdbkvdbIterator {
+    fn read_chunk(&mut self) -> Result<(), LmdbError> {
+        let txn = self.env.begin_ro_txn()?;
+        {
+            let mut cursor = txn.open_ro_cursor(self.db)?;
+            let iter = if self.from.is_empty() {
+                cursor.iter_start()
+            } else {
+                cursor.iter_from(&self.from)
+            };
+            for (key, value) in iter {
+                if self.skip_from && key == &self.from[..] {
+                    continue;
+                }
+                self.chunk.push_back((key.into(), value.into()));
+                if self.chunk.len() == ITER_CHUNK {
+                    break;
+                }
+            }
+        }
+        match self.chunk.back() {
+            Some(&(ref key, _)) if self.chunk.len() == ITER_CHUNK => {
+                self.from = key.to_vec();
+                self.skip_from = true;
+            }
+            _ => self.done = true,
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for LmdbkvdbIterator {
+    type Item = (Box<[u8]>, Box<[u8]>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.chunk.is_empty() && !self.done {
+            if let Err(e) = self.read_chunk() {
+                error!(target:"db","lmdb iterator error: {}", e);
+                self.done = true;
+            }
+        }
+        self.chunk.pop_front()
+    }
+}
+
+impl KeyValueDAO for Lmdbkvdb {
+    fn get(&self, k: &[u8]) -> Option<DBValue> {
+        match self.overlay.get(k) {
+            Some(KeyState::Insert(ref value)) => Some(value.clone()),
+            Some(KeyState::Delete) => None,
+            None => {
+                let txn = self.env.begin_ro_txn().ok()?;
+                let value = txn.get(self.db, &k).ok().map(DBValue::from_slice);
+                value
+            }
+        }
+    }
+
+    fn put(&mut self, k: &[u8], v: &DBValue) -> Option<DBValue> {
+        let mut ekey = Key::new();
+        ekey.append_slice(k);
+        self.overlay.insert(ekey, KeyState::Insert(v.clone()));
+        if self.overlay.len() > 10000 {
+            let _ = self.flush();
+        }
+        Some(v.clone())
+    }
+
+    fn delete(&mut self, k: &[u8]) -> Option<DBValue> {
+        let mut ekey = Key::new();
+        ekey.append_slice(k);
+        self.overlay.insert(ekey, KeyState::Delete);
+        if self.overlay.len() > 10000 {
+            let _ = self.flush();
+        }
+        // ignore the result
+        Some(DBValue::from_slice(k))
+    }
+
+    fn iter(&self) -> Box<Iterator<Item = (Box<[u8]>, Box<[u8]>)>> {
+        let mut overlay_data = self
+            .overlay
+            .iter()
+            .filter_map(|(k, v)| {
+                match *v {
+                    KeyState::Insert(ref value) => {
+                        Some((
+                            k.clone().to_vec().into_boxed_slice(),
+                            value.clone().to_vec().into_boxed_slice(),
+                        ))
+                    }
+                    KeyState::Delete => None,
+                }
+            })
+            .collect::<Vec<_>>();
+        overlay_data.sort();
+        Box::new(interleave_ordered(overlay_data, self.iter_from(&[])))
+    }
+
+    fn get_by_prefix(&self, prefix: &[u8]) -> Option<Box<[u8]>> {
+        match self.iter_from(prefix).next() {
+            Some((k, v)) => {
+                if k.len() >= prefix.len() && k[0..prefix.len()] == prefix[..] {
+                    Some(v)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn iter_from_prefix(&self, prefix: &[u8]) -> Box<Iterator<Item = (Box<[u8]>, Box<[u8]>)>> {
+        Box::new(self.iter_from(prefix))
+    }
+}
+
+impl KeyValueEngine for Lmdbkvdb {
+    fn flush_opt(&mut self, sync: bool) -> Result<(), String> { Lmdbkvdb::flush_opt(self, sync) }
+}
+
+impl Drop for Lmdbkvdb {
+    fn drop(&mut self) { let _ = self.flush(); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crud_test() {
+        let path = "./temp/lmdb_crud";
+        {
+            let mut db = Lmdbkvdb::open(&DatabaseConfig::default(), path).unwrap();
+
+            db.put(&[1], &DBValue::from_slice(&[1]));
+            db.put(&[2], &DBValue::from_slice(&[2]));
+            assert_eq!(db.get(&[1]).unwrap(), DBValue::from_slice(&[1]));
+            db.flush().unwrap();
+            assert_eq!(db.get(&[2]).unwrap(), DBValue::from_slice(&[2]));
+
+            db.put(&[1], &DBValue::from_slice(&[3]));
+            db.delete(&[2]);
+            db.flush().unwrap();
+            assert_eq!(db.get(&[1]).unwrap(), DBValue::from_slice(&[3]));
+            assert_eq!(db.get(&[2]), None);
+        }
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn iterates_across_chunks() {
+        let path = "./temp/lmdb_iter";
+        {
+            let mut db = Lmdbkvdb::open(&DatabaseConfig::default(), path).unwrap();
+            let count = ITER_CHUNK * 2 + 1;
+            for i in 0..count {
+                let key = [(i >> 8) as u8, i as u8];
+                db.put(&key, &DBValue::from_slice(&key));
+            }
+            db.flush().unwrap();
+            assert_eq!(db.iter().count(), count);
+            assert_eq!(db.iter_from_prefix(&[4]).count(), count - 4 * 256);
+        }
+        let _ = fs::remove_dir_all(path);
+    }
+}
@@ -20,7 +20,7 @@
  ******************************************************************************/
 
 use std::cmp;
-use traits::KeyValueDAO;
+use traits::{KeyValueDAO, KeyValueEngine};
 use parity_rocksdb::{
     DB, Options, BlockBasedOptions, Cache, ReadOptions, IteratorMode, Direction, WriteOptions, WriteBatch, DBIterator, Writable, DBCompactionStyle
 };
@@ -49,6 +49,9 @@ impl<'a> Iterator for RockskvdbIterator<'a> {
 pub struct Rockskvdb {
     db: DB,
     write_options: WriteOptions,
+    /// write options waiting for the write-ahead log to be synced, same as `write_options`
+    /// when the log is disabled.
+    sync_write_options: WriteOptions,
     read_options: ReadOptions,
     block_cache_options: BlockBasedOptions,
     overlay: HashMap<Key, KeyState>,
@@ -59,6 +62,7 @@ impl Rockskvdb {
         Rockskvdb {
             db: DB::open_default("./temp/testdb").expect("open default rocksdb failed"),
             write_options: WriteOptions::new(),
+            sync_write_options: Rockskvdb::sync_write_options(true),
             read_options: ReadOptions::new(),
             block_cache_options: BlockBasedOptions::new(),
             overlay: HashMap::new(),
@@ -71,8 +75,7 @@ impl Rockskvdb {
 
         {
             block_opts.set_block_size(config.block_size);
-            let cache_size = cmp::max(8, config.memory_budget);
-            let cache = Cache::new(cache_size);
+            let cache = Cache::new(config.block_cache_bytes());
             block_opts.set_cache(cache);
         }
 
@@ -83,6 +86,7 @@ impl Rockskvdb {
         if !config.wal {
             write_opts.disable_wal(true);
         }
+        let sync_write_opts = Rockskvdb::sync_write_options(config.wal);
 
         match Rockskvdb::parse_options(&config, &block_opts) {
             Ok(opts) => {
@@ -91,6 +95,7 @@ impl Rockskvdb {
                         Ok(Rockskvdb {
                             db: t,
                             write_options: write_opts,
+                            sync_write_options: sync_write_opts,
                             read_options: read_opts,
                             block_cache_options: block_opts,
                             overlay: HashMap::new(),
@@ -107,6 +112,7 @@ impl Rockskvdb {
                         Ok(Rockskvdb {
                             db: DB::open(&opts, path)?,
                             write_options: write_opts,
+                            sync_write_options: sync_write_opts,
                             read_options: read_opts,
                             block_cache_options: block_opts,
                             overlay: HashMap::new(),
@@ -118,7 +124,10 @@ impl Rockskvdb {
             Err(e) => return Err(e.into()),
         }
     }
-    pub fn flush(&mut self) -> Result<(), String> {
+    pub fn flush(&mut self) -> Result<(), String> { self.flush_opt(false) }
+
+    /// Flush the overlay, and wait for the write-ahead log to be synced to disk if `sync`.
+    pub fn flush_opt(&mut self, sync: bool) -> Result<(), String> {
         let batch = WriteBatch::new();
         for (ref key, ref keystate) in self.overlay.drain() {
             match (key, keystate) {
@@ -130,11 +139,44 @@ impl Rockskvdb {
                 }
             }
         }
-        self.db.write_opt(batch, &self.write_options)?;
+        if sync {
+            self.db.write_opt(batch, &self.sync_write_options)?;
+        } else {
+            self.db.write_opt(batch, &self.write_options)?;
+        }
         self.overlay.clear();
         Ok(())
     }
-    fn parse_options(
+    /// Flush the overlay, then compact the whole key range.
+    pub fn compact(&mut self) -> Result<(), String> {
+        self.flush()?;
+        let first = self
+            .db
+            .iterator_opt(IteratorMode::Start, &self.read_options)
+            .next();
+        let last = self
+            .db
+            .iterator_opt(IteratorMode::End, &self.read_options)
+            .next();
+        if let (Some((start, _)), Some((end, _))) = (first, last) {
+            self.db.compact_range(&start, &end);
+        }
+        Ok(())
+    }
+    /// Write options which wait for the write-ahead log to be synced to disk. Syncing needs
+    /// the log, so without it writes are not synced.
+    pub fn sync_write_options(wal: bool) -> WriteOptions {
+        let mut opts = WriteOptions::new();
+        if wal {
+            opts.set_sync(true);
+        } else {
+            opts.disable_wal(true);
+        }
+        opts
+    }
+
+    /// Build rocksdb options from the db config.
+    pub fn parse_options(
         config: &DatabaseConfig,
         block_cache_config: &BlockBasedOptions,
     ) -> Result<Options, String>
@@ -282,6 +324,11 @@ impl KeyValueDAO for Rockskvdb {
         ))
     }
 }
+impl KeyValueEngine for Rockskvdb {
+    fn flush_opt(&mut self, sync: bool) -> Result<(), String> { Rockskvdb::flush_opt(self, sync) }
+
+    fn compact(&mut self) -> Result<(), String> { Rockskvdb::compact(self) }
+}
 impl Drop for Rockskvdb {
     fn drop(&mut self) { let _ = self.flush(); }
 }
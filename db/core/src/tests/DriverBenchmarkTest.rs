@@ -20,7 +20,9 @@
  ******************************************************************************/
 
 #![allow(dead_code)]
-use super::super::{DBTransaction, DbRepository, KeyValueDB, RepositoryConfig, DatabaseConfig};
+use super::super::{
+    DBTransaction, DbRepository, KeyValueDB, RepositoryConfig, DatabaseBackend, DatabaseConfig,
+};
 use rand;
 use std::fs;
 use std::path::Path;
@@ -302,10 +304,20 @@ fn print_environment() {
     println!("CPUCache: {}", cacheSize);
 }
 
-fn new_bench(dbpath: String, keyCount: u32, valueSizeBytes: u32) -> BenchmarkTest {
+fn new_bench(
+    backend: DatabaseBackend,
+    name: &str,
+    keyCount: u32,
+    valueSizeBytes: u32,
+) -> BenchmarkTest
+{
+    // one dir per backend, so that the benchmarks of different backends can run in parallel
+    let dbpath = format!("./temp/{}/{}", backend, name);
+    let mut db_config = DatabaseConfig::default();
+    db_config.backend = backend;
     let dbrepository_configs = vec![RepositoryConfig {
         db_name: DB_NAME.into(),
-        db_config: DatabaseConfig::default(),
+        db_config: db_config,
         db_path: dbpath.clone(),
     }];
     let db = DbRepository::init(dbrepository_configs).unwrap();
@@ -319,128 +331,96 @@ fn new_bench(dbpath: String, keyCount: u32, valueSizeBytes: u32) -> BenchmarkTes
 // ====================== Unit tests =============================
 // ---------------------------------------------------------------
 
-#[test]
-fn benchtest_fillSequentialKeys() {
-    for _i in 0..TEST_REPEAT {
-        let mut bench = new_bench("./temp/fsk".into(), 1000_000, 100);
-        if _i == 0 {
-            bench.print_header();
-        }
-        bench.start();
-        bench.write(Order::SEQUENTIAL, 1000_000, 100, 1);
-        bench.stop("benchtest_fillSequentialKeys".into(), 1000_000, 100, 1);
-    }
-}
+/// Benchmarks of a backend, run with e.g. `cargo test -p db --release rocksdb::benchtest`.
+macro_rules! benchtests {
+    ($backend_mod:ident, $backend:expr) => {
+        mod $backend_mod {
+            use super::*;
 
-#[test]
-fn benchtest_fillSequentialBatch1K() {
-    for _i in 0..TEST_REPEAT {
-        let mut bench = new_bench("./temp/fskb1k".into(), 1000_000, 100);
-        bench.start();
-        bench.write(Order::SEQUENTIAL, 1000_000, 100, 1000);
-        bench.stop(
-            "benchtest_fillSequentialBatch1K".into(),
-            1000_000,
-            100,
-            1000,
-        );
-    }
-}
-
-#[test]
-fn benchtest_fillRandomkeys() {
-    for _i in 0..TEST_REPEAT {
-        let mut bench = new_bench("./temp/frk".into(), 1000_000, 100);
-        bench.start();
-        bench.write(Order::RANDOM, 1000_000, 100, 1);
-        bench.stop("benchtest_fillRandomkeys".into(), 1000_000, 100, 1);
-    }
-}
+            #[test]
+            fn benchtest_fillSequentialKeys() {
+                for _i in 0..TEST_REPEAT {
+                    let mut bench = new_bench($backend, "fsk", 1000_000, 100);
+                    if _i == 0 {
+                        bench.print_header();
+                    }
+                    bench.start();
+                    bench.write(Order::SEQUENTIAL, 1000_000, 100, 1);
+                    bench.stop("benchtest_fillSequentialKeys".into(), 1000_000, 100, 1);
+                }
+            }
 
-#[test]
-fn benchtest_fillRandomBatch1K() {
-    for _i in 0..TEST_REPEAT {
-        let mut bench = new_bench("./temp/frkb1k".into(), 1000_000, 100);
-        bench.start();
-        bench.write(Order::RANDOM, 1000_000, 100, 1000);
-        bench.stop("benchtest_fillRandomBatch1K".into(), 1000_000, 100, 1000);
-    }
-}
+            #[test]
+            fn benchtest_fillSequentialBatch1K() {
+                for _i in 0..TEST_REPEAT {
+                    let mut bench = new_bench($backend, "fskb1k", 1000_000, 100);
+                    bench.start();
+                    bench.write(Order::SEQUENTIAL, 1000_000, 100, 1000);
+                    bench.stop(
+                        "benchtest_fillSequentialBatch1K".into(),
+                        1000_000,
+                        100,
+                        1000,
+                    );
+                }
+            }
 
-#[test]
-fn benchtest_fillRandomValue10K() {
-    for _i in 0..TEST_REPEAT {
-        let mut bench = new_bench("./temp/frv10k".into(), 10_000, 100_000);
-        bench.start();
-        bench.write(Order::RANDOM, 10_000, 100_000, 1);
-        bench.stop("benchtest_fillRandomValue10K".into(), 10_000, 100_000, 1);
-    }
-}
+            #[test]
+            fn benchtest_fillRandomkeys() {
+                for _i in 0..TEST_REPEAT {
+                    let mut bench = new_bench($backend, "frk", 1000_000, 100);
+                    bench.start();
+                    bench.write(Order::RANDOM, 1000_000, 100, 1);
+                    bench.stop("benchtest_fillRandomkeys".into(), 1000_000, 100, 1);
+                }
+            }
 
-#[test]
-fn benchtest_overwriteRandom() {
-    for _i in 0..TEST_REPEAT {
-        let mut bench = new_bench("./temp/or".into(), 1000_000, 100);
-        bench.write(Order::SEQUENTIAL, 1000_000, 100, 1);
-        {
-            let db = bench.db.clone();
-            let mut db = db.write().unwrap();
-            db.close_all();
-        }
-        let _filesizeinitial = get_directory_size_bytes(Path::new("./temp/or")) as i64;
-        {
-            let db = bench.db.clone();
-            let mut db = db.write().unwrap();
-            db.open_all();
-        }
-        bench.start();
-        bench.overwrite(Order::RANDOM, 1000_000, 100, 1);
-        bench.stop("benchtest_overwriteRandom".into(), 1000_000, 100, 1);
-    }
-}
+            #[test]
+            fn benchtest_fillRandomBatch1K() {
+                for _i in 0..TEST_REPEAT {
+                    let mut bench = new_bench($backend, "frkb1k", 1000_000, 100);
+                    bench.start();
+                    bench.write(Order::RANDOM, 1000_000, 100, 1000);
+                    bench.stop("benchtest_fillRandomBatch1K".into(), 1000_000, 100, 1000);
+                }
+            }
 
-#[test]
-fn benchtest_readSequential() {
-    for _i in 0..TEST_REPEAT {
-        let mut bench = new_bench("./temp/rs".into(), 1000_000, 100);
-        bench.write(Order::SEQUENTIAL, 1000_000, 100, 1);
-        bench.start();
-        let keyCount: u64 = 1000_000;
-        let mut byteCount: u64 = 0;
-        {
-            let db = bench.db.clone();
-            let db = db.read().unwrap();
-            for k in 0..keyCount {
-                let key = formatNumber(k);
-                let value = db.get(DB_NAME, &key).unwrap().unwrap();
-                byteCount += key.len() as u64 + value.len() as u64;
-                bench.finishedSingleOp();
+            #[test]
+            fn benchtest_fillRandomValue10K() {
+                for _i in 0..TEST_REPEAT {
+                    let mut bench = new_bench($backend, "frv10k", 10_000, 100_000);
+                    bench.start();
+                    bench.write(Order::RANDOM, 10_000, 100_000, 1);
+                    bench.stop("benchtest_fillRandomValue10K".into(), 10_000, 100_000, 1);
+                }
             }
-        }
-        bench.byteCount = byteCount;
-        bench.stop("benchtest_readSequential".into(), 1000_000, 100, 1);
-    }
-}
 
-#[test]
-fn benchtest_readRandom() {
-    for _i in 0..TEST_REPEAT {
-        let mut bench = new_bench("./temp/rr".into(), 1000_000, 100);
-        bench.write(Order::SEQUENTIAL, 1000_000, 100, 1);
-        bench.start();
-        let keyCount: u64 = 1000_000;
-        let mut byteCount: u64 = 0;
-        {
-            let db = bench.db.clone();
-            let db = db.read().unwrap();
-            for _k in 0..keyCount {
-                let key = formatNumber(rand::random::<u64>() % keyCount);
-                let value = db.get(DB_NAME, &key).unwrap().unwrap();
-                byteCount += key.len() as u64 + value.len() as u64;
-                bench.finishedSingleOp();
+            #[test]
+            fn benchtest_overwriteRandom() {
+                for _i in 0..TEST_REPEAT {
+                    let mut bench = new_bench($backend, "or", 1000_000, 100);
+                    bench.write(Order::SEQUENTIAL, 1000_000, 100, 1);
+                    {
+                        let db = bench.db.clone();
+                        let mut db = db.write().unwrap();
+                        db.close_all();
+                    }
+                    let _filesizeinitial =
+                        get_directory_size_bytes(Path::new(&bench.dbpath)) as i64;
+                    {
+                        let db = bench.db.clone();
+                        let mut db = db.write().unwrap();
+                        db.open_all();
+                    }
+                    bench.start();
+                    bench.overwrite(Order::RANDOM, 1000_000, 100, 1);
+                    bench.stop("benchtest_overwriteRandom".into(), 1000_000, 100, 1);
+                }
             }
         }
-        bench.byteCount = byteCount;
-        bench.stop("benchtest_readRandom".into(), 1000_000, 100, 1);
-    }
+    };
 }
+
+benchtests!(rocksdb, DatabaseBackend::RocksDb);
+#[cfg(feature = "lmdb")]
+benchtests!(lmdb, DatabaseBackend::Lmdb);
@@ -39,6 +39,13 @@ pub trait KeyValueDAO: Sync + Send {
         prefix: &'static [u8],
     ) -> Box<Iterator<Item = (Box<[u8]>, Box<[u8]>)>>;
 }
+/// embedded storage engine of a single db.
+pub trait KeyValueEngine: KeyValueDAO + Send + Sync {
+    /// Write the buffered changes, and wait until they are synced to disk if `sync`.
+    fn flush_opt(&mut self, sync: bool) -> ::std::result::Result<(), String>;
+    /// Compact the whole key range.
+    fn compact(&mut self) -> ::std::result::Result<(), String> { Ok(()) }
+}
 /// db repository operation.
 pub trait KeyValueDB: Sync + Send {
     /// Get the value by key from the specified db
@@ -53,6 +60,12 @@ pub trait KeyValueDB: Sync + Send {
     fn write_buffered(&self, transaction: DBTransaction);
     /// Flush db
     fn flush(&self) -> Result<()> { Ok(()) }
+    /// Flush db and wait until the written data is synced to disk
+    fn flush_synced(&self) -> Result<()> { self.flush() }
+    /// Size of the specified db on disk in bytes, None if unknown
+    fn disk_size(&self, _db_name: &str) -> Option<u64> { None }
+    /// Compact the whole key range of the specified db
+    fn compact(&self, _db_name: &str) -> Result<()> { Ok(()) }
     /// Return a specified db' iterator
     fn iter(&self, db_name: &'static str) -> Box<Iterator<Item = (Box<[u8]>, Box<[u8]>)>>;
     /// Get value by partial key. Prefix size should match configured prefix size. Only searches flushed values.
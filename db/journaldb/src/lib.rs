@@ -45,6 +45,7 @@ use db as kvdb;
 mod traits;
 mod archivedb;
 mod overlayrecentdb;
+mod refcounteddb;
 
 /// Export the `JournalDB` trait.
 pub use self::traits::JournalDB;
@@ -60,6 +61,12 @@ pub enum Algorithm {
     /// Inserts go into memory overlay, which is tried for key fetches. Memory overlay gets
     /// flushed in backing only at end of recent history.
     OverlayRecent,
+    /// Reference counted nodes; recent history lasts for a particular number of blocks and
+    /// the states of sparse checkpoints may be kept beyond it.
+    ///
+    /// Nodes are written to disk at once; removals are journalled and applied when their era
+    /// leaves the recent history.
+    RefCounted,
 }
 
 impl Default for Algorithm {
@@ -73,6 +80,7 @@ impl str::FromStr for Algorithm {
         match s {
             "archive" => Ok(Algorithm::Archive),
             "fast" => Ok(Algorithm::OverlayRecent),
+            "refcounted" => Ok(Algorithm::RefCounted),
             e => Err(format!("Invalid algorithm: {}", e)),
         }
     }
@@ -84,6 +92,7 @@ impl Algorithm {
         match *self {
             Algorithm::Archive => "archive",
             Algorithm::OverlayRecent => "fast",
+            Algorithm::RefCounted => "refcounted",
         }
     }
 
@@ -92,6 +101,7 @@ impl Algorithm {
         match *self {
             Algorithm::Archive => "archive",
             Algorithm::OverlayRecent => "overlayrecent",
+            Algorithm::RefCounted => "refcounted",
         }
     }
 
@@ -99,7 +109,13 @@ impl Algorithm {
     pub fn is_stable(&self) -> bool { true }
 
     /// Returns all algorithm types.
-    pub fn all_types() -> Vec<Algorithm> { vec![Algorithm::Archive, Algorithm::OverlayRecent] }
+    pub fn all_types() -> Vec<Algorithm> {
+        vec![
+            Algorithm::Archive,
+            Algorithm::OverlayRecent,
+            Algorithm::RefCounted,
+        ]
+    }
 }
 
 impl fmt::Display for Algorithm {
@@ -112,12 +128,31 @@ pub fn new(
     algorithm: Algorithm,
     db_name: &'static str,
 ) -> Box<JournalDB>
+{
+    new_with_checkpoints(backing, algorithm, db_name, None)
+}
+
+/// Create a new `JournalDB` trait object keeping the state of every `checkpoint_interval`-th
+/// block if the algorithm supports checkpoints.
+pub fn new_with_checkpoints(
+    backing: Arc<::kvdb::KeyValueDB>,
+    algorithm: Algorithm,
+    db_name: &'static str,
+    checkpoint_interval: Option<u64>,
+) -> Box<JournalDB>
 {
     match algorithm {
         Algorithm::Archive => Box::new(archivedb::ArchiveDB::new(backing, db_name)),
         Algorithm::OverlayRecent => {
             Box::new(overlayrecentdb::OverlayRecentDB::new(backing, db_name))
         }
+        Algorithm::RefCounted => {
+            Box::new(refcounteddb::RefCountedDB::new(
+                backing,
+                db_name,
+                checkpoint_interval,
+            ))
+        }
     }
 }
 
@@ -133,18 +168,21 @@ mod tests {
     fn test_journal_algorithm_parsing() {
         assert_eq!(Algorithm::Archive, "archive".parse().unwrap());
         assert_eq!(Algorithm::OverlayRecent, "fast".parse().unwrap());
+        assert_eq!(Algorithm::RefCounted, "refcounted".parse().unwrap());
     }
 
     #[test]
     fn test_journal_algorithm_printing() {
         assert_eq!(Algorithm::Archive.to_string(), "archive".to_owned());
         assert_eq!(Algorithm::OverlayRecent.to_string(), "fast".to_owned());
+        assert_eq!(Algorithm::RefCounted.to_string(), "refcounted".to_owned());
     }
 
     #[test]
     fn test_journal_algorithm_is_stable() {
         assert!(Algorithm::Archive.is_stable());
         assert!(Algorithm::OverlayRecent.is_stable());
+        assert!(Algorithm::RefCounted.is_stable());
     }
 
     #[test]
@@ -157,15 +195,18 @@ mod tests {
         // compiling should fail if some cases are not covered
         let mut archive = 0;
         let mut overlayrecent = 0;
+        let mut refcounted = 0;
 
         for a in &Algorithm::all_types() {
             match *a {
                 Algorithm::Archive => archive += 1,
                 Algorithm::OverlayRecent => overlayrecent += 1,
+                Algorithm::RefCounted => refcounted += 1,
             }
         }
 
         assert_eq!(archive, 1);
         assert_eq!(overlayrecent, 1);
+        assert_eq!(refcounted, 1);
     }
 }
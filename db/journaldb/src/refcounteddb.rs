@@ -0,0 +1,608 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Disk-backed, reference counted `JournalDB` implementation.
+
+use std::cmp;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::sync::Arc;
+use parking_lot::RwLock;
+use heapsize::HeapSizeOf;
+use rlp::*;
+use super::{DB_PREFIX_LEN, LATEST_ERA_KEY};
+use traits::JournalDB;
+use kvdb::{KeyValueDB, DBTransaction, HashStore, DBValue, MemoryDB};
+use aion_types::H256;
+use error::UtilError;
+use bytes::Bytes;
+
+/// Implementation of the `JournalDB` trait for a disk-backed database with reference counted
+/// nodes.
+///
+/// Inserted nodes are written to disk at once, together with the number of references to them.
+/// Removals are only journalled. When an era becomes ancient and is marked canonical, the
+/// removals of its canonical record and the insertions of the other records release their
+/// references, and nodes which are no longer referenced are deleted. Recent history is thus
+/// kept for exactly as many eras as the client keeps in the journal.
+///
+/// With a checkpoint interval, the state of every block whose number is a multiple of the
+/// interval is kept as well: an unreferenced node is only deleted if no checkpoint lies between
+/// the era it was inserted in and the era it was released in.
+pub struct RefCountedDB {
+    overlay: MemoryDB,
+    backing: Arc<KeyValueDB>,
+    journal: Arc<RwLock<RefJournal>>,
+    checkpoint_interval: Option<u64>,
+    db_name: &'static str,
+}
+
+#[derive(Default, PartialEq, Debug)]
+struct RefJournal {
+    entries: HashMap<u64, Vec<JournalEntry>>,
+    // Reference counts written to batches which may not be in the backing db yet.
+    pending: HashMap<H256, Option<RefCount>>,
+    latest_era: Option<u64>,
+    earliest_era: Option<u64>,
+}
+
+#[derive(PartialEq, Debug)]
+struct JournalEntry {
+    id: H256,
+    insertions: Vec<H256>,
+    deletions: Vec<H256>,
+}
+
+impl HeapSizeOf for JournalEntry {
+    fn heap_size_of_children(&self) -> usize {
+        self.insertions.heap_size_of_children() + self.deletions.heap_size_of_children()
+    }
+}
+
+/// Number of references to a node and the earliest era it was inserted in.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct RefCount {
+    refs: u32,
+    era: u64,
+}
+
+impl HeapSizeOf for RefCount {
+    fn heap_size_of_children(&self) -> usize { 0 }
+}
+
+const PADDING: [u8; 10] = [0u8; 10];
+
+/// Suffix of the key holding the reference count of a node.
+const REF_SUFFIX: &'static [u8] = b"rc";
+
+fn journal_key(era: u64, index: usize) -> Vec<u8> {
+    let mut r = RlpStream::new_list(3);
+    r.append(&era);
+    r.append(&index);
+    r.append(&&PADDING[..]);
+    r.drain().into_vec()
+}
+
+fn ref_key(key: &H256) -> Vec<u8> {
+    let mut k = key.to_vec();
+    k.extend_from_slice(REF_SUFFIX);
+    k
+}
+
+impl RefCountedDB {
+    /// Create a new instance from a key-value db, keeping the state of every
+    /// `checkpoint_interval`-th block if given.
+    pub fn new(
+        backing: Arc<KeyValueDB>,
+        db_name: &'static str,
+        checkpoint_interval: Option<u64>,
+    ) -> RefCountedDB
+    {
+        let journal = RefCountedDB::read_journal(&*backing, db_name);
+        RefCountedDB {
+            overlay: MemoryDB::new(),
+            backing: backing,
+            journal: Arc::new(RwLock::new(journal)),
+            checkpoint_interval: checkpoint_interval.filter(|interval| *interval > 0),
+            db_name: db_name,
+        }
+    }
+
+    #[cfg(test)]
+    fn can_reconstruct_journal(&self) -> bool {
+        let reconstructed = Self::read_journal(&*self.backing, self.db_name);
+        let journal = self.journal.read();
+        journal.entries == reconstructed.entries
+            && journal.latest_era == reconstructed.latest_era
+            && journal.earliest_era == reconstructed.earliest_era
+    }
+
+    fn payload(&self, key: &H256) -> Option<DBValue> {
+        self.backing
+            .get(self.db_name, key)
+            .expect("Low-level database error. Some issue with your hard disk?")
+    }
+
+    fn read_journal(db: &KeyValueDB, db_name: &str) -> RefJournal {
+        let mut journal = RefJournal::default();
+        if let Some(val) = db
+            .get(db_name, &LATEST_ERA_KEY)
+            .expect("Low-level database error.")
+        {
+            let mut era = decode::<u64>(&val);
+            journal.latest_era = Some(era);
+            loop {
+                let mut index = 0usize;
+                while let Some(rlp_data) = db
+                    .get(db_name, &journal_key(era, index))
+                    .expect("Low-level database error.")
+                {
+                    let rlp = Rlp::new(&rlp_data);
+                    journal
+                        .entries
+                        .entry(era)
+                        .or_insert_with(Vec::new)
+                        .push(JournalEntry {
+                            id: rlp.val_at(0),
+                            insertions: rlp.list_at(1),
+                            deletions: rlp.list_at(2),
+                        });
+                    index += 1;
+                    journal.earliest_era = Some(era);
+                }
+                if index == 0 || era == 0 {
+                    break;
+                }
+                era -= 1;
+            }
+        }
+        trace!(
+            target: "journaldb",
+            "Recovered {} journal eras, earliest {:?}",
+            journal.entries.len(),
+            journal.earliest_era
+        );
+        journal
+    }
+
+    fn ref_count(&self, journal: &RefJournal, key: &H256) -> Option<RefCount> {
+        if let Some(rc) = journal.pending.get(key) {
+            return *rc;
+        }
+        self.backing
+            .get(self.db_name, &ref_key(key))
+            .expect("Low-level database error. Some issue with your hard disk?")
+            .map(|val| {
+                let rlp = Rlp::new(&val);
+                RefCount {
+                    refs: rlp.val_at(0),
+                    era: rlp.val_at(1),
+                }
+            })
+    }
+
+    fn set_ref_count(
+        &self,
+        journal: &mut RefJournal,
+        batch: &mut DBTransaction,
+        key: &H256,
+        rc: RefCount,
+    )
+    {
+        let mut r = RlpStream::new_list(2);
+        r.append(&rc.refs);
+        r.append(&rc.era);
+        batch.put_vec(self.db_name, &ref_key(key), r.out());
+        journal.pending.insert(key.clone(), Some(rc));
+    }
+
+    /// Add `refs` references to a node inserted in `era`, writing the node if it is new.
+    fn retain(
+        &self,
+        journal: &mut RefJournal,
+        batch: &mut DBTransaction,
+        key: &H256,
+        value: &DBValue,
+        refs: u32,
+        era: u64,
+    )
+    {
+        let rc = match self.ref_count(journal, key) {
+            Some(rc) => {
+                RefCount {
+                    refs: rc.refs + refs,
+                    era: cmp::min(rc.era, era),
+                }
+            }
+            None => {
+                batch.put(self.db_name, key, value);
+                RefCount {
+                    refs: refs,
+                    era: era,
+                }
+            }
+        };
+        self.set_ref_count(journal, batch, key, rc);
+    }
+
+    /// Release a reference to a node in `era`. Returns whether the node was deleted.
+    fn release(
+        &self,
+        journal: &mut RefJournal,
+        batch: &mut DBTransaction,
+        key: &H256,
+        era: u64,
+    ) -> bool
+    {
+        match self.ref_count(journal, key) {
+            Some(mut rc) if rc.refs > 1 => {
+                rc.refs -= 1;
+                self.set_ref_count(journal, batch, key, rc);
+                false
+            }
+            Some(mut rc) if rc.refs == 1 => {
+                if self.is_checkpointed(rc.era, era) {
+                    rc.refs = 0;
+                    self.set_ref_count(journal, batch, key, rc);
+                    false
+                } else {
+                    batch.delete(self.db_name, key);
+                    batch.delete(self.db_name, &ref_key(key));
+                    journal.pending.insert(key.clone(), None);
+                    true
+                }
+            }
+            _ => {
+                warn!(target: "journaldb", "Releasing unreferenced node {}", key);
+                false
+            }
+        }
+    }
+
+    /// Whether a node referenced from era `inserted` until era `released` belongs to the state
+    /// of a checkpoint.
+    fn is_checkpointed(&self, inserted: u64, released: u64) -> bool {
+        match self.checkpoint_interval {
+            Some(interval) if released > 0 => (released - 1) / interval * interval >= inserted,
+            _ => false,
+        }
+    }
+}
+
+impl Clone for RefCountedDB {
+    fn clone(&self) -> RefCountedDB {
+        RefCountedDB {
+            overlay: self.overlay.clone(),
+            backing: self.backing.clone(),
+            journal: self.journal.clone(),
+            checkpoint_interval: self.checkpoint_interval,
+            db_name: self.db_name,
+        }
+    }
+}
+
+impl JournalDB for RefCountedDB {
+    fn boxed_clone(&self) -> Box<JournalDB> { Box::new(self.clone()) }
+
+    fn mem_used(&self) -> usize {
+        let journal = self.journal.read();
+        self.overlay.mem_used()
+            + journal.entries.heap_size_of_children()
+            + journal.pending.heap_size_of_children()
+    }
+
+    fn journal_size(&self) -> usize { self.journal.read().entries.heap_size_of_children() }
+
+    fn is_empty(&self) -> bool { self.journal.read().latest_era.is_none() }
+
+    fn latest_era(&self) -> Option<u64> { self.journal.read().latest_era }
+
+    fn earliest_era(&self) -> Option<u64> { self.journal.read().earliest_era }
+
+    fn checkpoint_interval(&self) -> Option<u64> { self.checkpoint_interval }
+
+    fn journal_under(
+        &mut self,
+        batch: &mut DBTransaction,
+        now: u64,
+        id: &H256,
+    ) -> Result<u32, UtilError>
+    {
+        trace!(target: "journaldb", "entry: #{} ({})", now, id);
+
+        let changes = self.overlay.drain();
+        let journal = self.journal.clone();
+        let mut journal = journal.write();
+
+        // flush previous changes
+        journal.pending.clear();
+
+        let mut insertions = Vec::new();
+        let mut deletions = Vec::new();
+        for (key, (value, rc)) in changes {
+            if rc > 0 {
+                self.retain(&mut journal, batch, &key, &value, rc as u32, now);
+                insertions.extend((0..rc).map(|_| key.clone()));
+            } else if rc < 0 {
+                deletions.extend((rc..0).map(|_| key.clone()));
+            }
+        }
+        let ops = insertions.len() + deletions.len();
+
+        let index = journal.entries.get(&now).map_or(0, |j| j.len());
+        let mut r = RlpStream::new_list(3);
+        r.append(id);
+        r.append_list(&insertions);
+        r.append_list(&deletions);
+        batch.put_vec(self.db_name, &journal_key(now, index), r.out());
+
+        if journal.latest_era.map_or(true, |e| now > e) {
+            batch.put_vec(self.db_name, &LATEST_ERA_KEY, encode(&now).into_vec());
+            journal.latest_era = Some(now);
+        }
+        if journal.earliest_era.map_or(true, |e| e > now) {
+            journal.earliest_era = Some(now);
+        }
+
+        journal
+            .entries
+            .entry(now)
+            .or_insert_with(Vec::new)
+            .push(JournalEntry {
+                id: id.clone(),
+                insertions: insertions,
+                deletions: deletions,
+            });
+        Ok(ops as u32)
+    }
+
+    fn mark_canonical(
+        &mut self,
+        batch: &mut DBTransaction,
+        end_era: u64,
+        canon_id: &H256,
+    ) -> Result<u32, UtilError>
+    {
+        trace!(target: "journaldb", "canonical: #{} ({})", end_era, canon_id);
+
+        let journal = self.journal.clone();
+        let mut journal = journal.write();
+
+        let mut ops = 0;
+        let records = journal.entries.remove(&end_era).unwrap_or_default();
+        for (index, record) in records.into_iter().enumerate() {
+            batch.delete(self.db_name, &journal_key(end_era, index));
+            // the canonical record's removals take effect, other records are undone.
+            let released = if record.id == *canon_id {
+                record.deletions
+            } else {
+                record.insertions
+            };
+            for key in released {
+                if self.release(&mut journal, batch, &key, end_era) {
+                    ops += 1;
+                }
+            }
+        }
+
+        if !journal.entries.is_empty() {
+            journal.earliest_era = Some(end_era + 1);
+        }
+
+        Ok(ops)
+    }
+
+    fn inject(&mut self, batch: &mut DBTransaction) -> Result<u32, UtilError> {
+        let changes = self.overlay.drain();
+        let journal = self.journal.clone();
+        let mut journal = journal.write();
+        let era = journal.latest_era.unwrap_or(0);
+
+        let mut ops = 0;
+        for (key, (value, rc)) in changes {
+            if rc > 0 {
+                self.retain(&mut journal, batch, &key, &value, rc as u32, era);
+                ops += 1;
+            } else if rc < 0 {
+                for _ in rc..0 {
+                    self.release(&mut journal, batch, &key, era);
+                }
+                ops += 1;
+            }
+        }
+
+        Ok(ops)
+    }
+
+    fn state(&self, id: &H256) -> Option<Bytes> {
+        self.backing
+            .get_by_prefix(self.db_name, &id[0..DB_PREFIX_LEN])
+            .map(|b| b.into_vec())
+    }
+
+    fn backing(&self) -> &Arc<KeyValueDB> { &self.backing }
+
+    fn flush(&self) { self.journal.write().pending.clear(); }
+
+    fn consolidate(&mut self, with: MemoryDB) { self.overlay.consolidate(with); }
+}
+
+impl HashStore for RefCountedDB {
+    fn keys(&self) -> HashMap<H256, i32> {
+        let mut ret: HashMap<H256, i32> = self
+            .backing
+            .iter(self.db_name)
+            .filter(|&(ref key, _)| key.len() == 32)
+            .map(|(key, _)| (H256::from_slice(&*key), 1))
+            .collect();
+
+        for (key, refs) in self.overlay.keys() {
+            match ret.entry(key) {
+                Entry::Occupied(mut entry) => {
+                    *entry.get_mut() += refs;
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(refs);
+                }
+            }
+        }
+        ret
+    }
+
+    fn get(&self, key: &H256) -> Option<DBValue> {
+        if let Some((d, rc)) = self.overlay.raw(key) {
+            if rc > 0 {
+                return Some(d);
+            }
+        }
+        self.payload(key)
+    }
+
+    fn contains(&self, key: &H256) -> bool { self.get(key).is_some() }
+
+    fn insert(&mut self, value: &[u8]) -> H256 { self.overlay.insert(value) }
+
+    fn emplace(&mut self, key: H256, value: DBValue) { self.overlay.emplace(key, value); }
+
+    fn remove(&mut self, key: &H256) { self.overlay.remove(key); }
+}
+
+#[cfg(test)]
+mod tests {
+    use blake2b::blake2b;
+    use kvdb::{HashStore, MockDbRepository};
+    use super::*;
+    use JournalDB;
+
+    fn new_db(checkpoint_interval: Option<u64>) -> RefCountedDB {
+        let backing = Arc::new(MockDbRepository::init(vec!["test".into()]));
+        RefCountedDB::new(backing, "test", checkpoint_interval)
+    }
+
+    #[test]
+    fn long_history() {
+        // history is 3
+        let mut jdb = new_db(None);
+        let h = jdb.insert(b"foo");
+        jdb.commit_batch(0, &blake2b(b"0"), None).unwrap();
+        assert!(jdb.contains(&h));
+        jdb.remove(&h);
+        jdb.commit_batch(1, &blake2b(b"1"), None).unwrap();
+        assert!(jdb.contains(&h));
+        jdb.commit_batch(2, &blake2b(b"2"), None).unwrap();
+        assert!(jdb.contains(&h));
+        jdb.commit_batch(3, &blake2b(b"3"), Some((0, blake2b(b"0"))))
+            .unwrap();
+        assert!(jdb.contains(&h));
+        jdb.commit_batch(4, &blake2b(b"4"), Some((1, blake2b(b"1"))))
+            .unwrap();
+        assert!(!jdb.contains(&h));
+        assert!(jdb.can_reconstruct_journal());
+        assert_eq!(jdb.earliest_era(), Some(2));
+    }
+
+    #[test]
+    fn complex_with_fork() {
+        // history is 1
+        let mut jdb = new_db(None);
+
+        let foo = jdb.insert(b"foo");
+        let bar = jdb.insert(b"bar");
+        jdb.commit_batch(0, &blake2b(b"0"), None).unwrap();
+
+        jdb.remove(&foo);
+        let baz = jdb.insert(b"baz");
+        jdb.commit_batch(1, &blake2b(b"1a"), Some((0, blake2b(b"0"))))
+            .unwrap();
+
+        jdb.remove(&bar);
+        jdb.commit_batch(1, &blake2b(b"1b"), Some((0, blake2b(b"0"))))
+            .unwrap();
+        assert!(jdb.can_reconstruct_journal());
+
+        jdb.commit_batch(2, &blake2b(b"2b"), Some((1, blake2b(b"1b"))))
+            .unwrap();
+        assert!(jdb.contains(&foo));
+        assert!(!jdb.contains(&bar));
+        assert!(!jdb.contains(&baz));
+        assert!(jdb.can_reconstruct_journal());
+    }
+
+    #[test]
+    fn keeps_checkpoint_state() {
+        // history is 1, checkpoint every 2 blocks
+        let mut jdb = new_db(Some(2));
+
+        let foo = jdb.insert(b"foo");
+        jdb.commit_batch(1, &blake2b(b"1"), None).unwrap();
+        let bar = jdb.insert(b"bar");
+        jdb.commit_batch(2, &blake2b(b"2"), Some((1, blake2b(b"1"))))
+            .unwrap();
+        jdb.remove(&foo);
+        jdb.remove(&bar);
+        jdb.commit_batch(3, &blake2b(b"3"), Some((2, blake2b(b"2"))))
+            .unwrap();
+        jdb.commit_batch(4, &blake2b(b"4"), Some((3, blake2b(b"3"))))
+            .unwrap();
+
+        // both were part of the state of block 2
+        assert!(jdb.contains(&foo));
+        assert!(jdb.contains(&bar));
+
+        let baz = jdb.insert(b"baz");
+        jdb.commit_batch(5, &blake2b(b"5"), Some((4, blake2b(b"4"))))
+            .unwrap();
+        jdb.remove(&baz);
+        jdb.commit_batch(6, &blake2b(b"6"), Some((5, blake2b(b"5"))))
+            .unwrap();
+        jdb.commit_batch(7, &blake2b(b"7"), Some((6, blake2b(b"6"))))
+            .unwrap();
+
+        // only part of the state of block 5
+        assert!(!jdb.contains(&baz));
+        assert!(jdb.can_reconstruct_journal());
+    }
+
+    #[test]
+    fn reopen_keeps_references() {
+        let backing = Arc::new(MockDbRepository::init(vec!["test".into()]));
+        let foo = {
+            let mut jdb = RefCountedDB::new(backing.clone(), "test", None);
+            let foo = jdb.insert(b"foo");
+            jdb.commit_batch(0, &blake2b(b"0"), None).unwrap();
+            jdb.insert(b"foo");
+            jdb.commit_batch(1, &blake2b(b"1"), None).unwrap();
+            foo
+        };
+
+        let mut jdb = RefCountedDB::new(backing, "test", None);
+        assert_eq!(jdb.latest_era(), Some(1));
+        jdb.remove(&foo);
+        jdb.commit_batch(2, &blake2b(b"2"), Some((0, blake2b(b"0"))))
+            .unwrap();
+        jdb.commit_batch(3, &blake2b(b"3"), Some((1, blake2b(b"1"))))
+            .unwrap();
+        jdb.commit_batch(4, &blake2b(b"4"), Some((2, blake2b(b"2"))))
+            .unwrap();
+        // inserted twice, removed once
+        assert!(jdb.contains(&foo));
+    }
+}
@@ -51,6 +51,9 @@ pub trait JournalDB: HashStore {
     /// Get the latest era in the DB. None if there isn't yet any data in there.
     fn latest_era(&self) -> Option<u64>;
 
+    /// Interval of the blocks whose state is kept when the rest of history is pruned.
+    fn checkpoint_interval(&self) -> Option<u64> { None }
+
     /// Journal recent database operations as being associated with a given era and id.
     // TODO: give the overlay to this function so journaldbs don't manage the overlays themeselves.
     fn journal_under(
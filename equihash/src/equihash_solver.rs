@@ -0,0 +1,357 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! CPU solver for parametric (n, k) Equihash.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use blake2b::Blake2b;
+use bytes::{i32_to_bytes, i32_to_bytes_le, Bytes};
+
+/// A partial solution: the xor of the hashes of its indices.
+struct Row {
+    hash: Vec<u8>,
+    indices: Vec<u32>,
+}
+
+/// Equihash solver following Wagner's algorithm.
+#[derive(Debug, Clone)]
+pub struct EquihashSolver {
+    n: u32,
+    k: u32,
+    collision_bit_length: u32,
+    indices_per_hash_output: u32,
+    indices_hash_length: usize,
+    hash_output: usize,
+}
+
+impl EquihashSolver {
+    /// Creates a solver for the given parameters.
+    ///
+    /// Panics if the parameters are not supported, see `is_valid_params`.
+    pub fn new(n: u32, k: u32) -> EquihashSolver {
+        assert!(
+            super::is_valid_params(n, k),
+            "unsupported equihash parameters n={}, k={}",
+            n,
+            k
+        );
+        let indices_per_hash_output = 512 / n;
+        let indices_hash_length = ((n + 7) / 8) as usize;
+        EquihashSolver {
+            n,
+            k,
+            collision_bit_length: n / (k + 1),
+            indices_per_hash_output,
+            indices_hash_length,
+            hash_output: indices_per_hash_output as usize * indices_hash_length,
+        }
+    }
+
+    /// Finds all the solutions of the given header and nonce, in minimal encoding.
+    pub fn solve(&self, block_header: &[u8], nonce: &[u8]) -> Vec<Bytes> {
+        let mut rows = self.initial_rows(block_header, nonce);
+
+        // all rounds but the last collide on the next collision_bit_length bits
+        for round in 1..self.k {
+            let start = (round - 1) * self.collision_bit_length;
+            let max_rows = rows.len() * 2;
+            rows = self.collide(rows, start, self.collision_bit_length, max_rows);
+        }
+
+        // the last round collides on all the remaining bits
+        let start = (self.k - 1) * self.collision_bit_length;
+        let rows = self.collide(rows, start, self.n - start, usize::max_value());
+
+        let mut solutions: Vec<Bytes> = rows
+            .into_iter()
+            .filter(|row| is_zero(&row.hash, self.n))
+            .map(|row| self.get_minimal_from_indices(&row.indices))
+            .collect();
+        solutions.sort();
+        solutions.dedup();
+        solutions
+    }
+
+    /// Searches nonces on `threads` threads until `accept` returns true for a solution or `abort`
+    /// returns true. Nonces are counted up from `nonce_seed`.
+    pub fn search<A, S>(
+        &self,
+        block_header: &[u8],
+        nonce_seed: [u8; 32],
+        threads: usize,
+        accept: A,
+        abort: S,
+    ) -> Option<([u8; 32], Bytes)>
+    where
+        A: Fn(&[u8], &[u8]) -> bool + Send + Sync + 'static,
+        S: Fn() -> bool + Send + Sync + 'static,
+    {
+        let threads = ::std::cmp::max(threads, 1);
+        let accept = Arc::new(accept);
+        let abort = Arc::new(abort);
+        let found = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = mpsc::channel();
+
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                let solver = self.clone();
+                let block_header = block_header.to_vec();
+                let accept = accept.clone();
+                let abort = abort.clone();
+                let found = found.clone();
+                let tx = tx.clone();
+                thread::spawn(move || {
+                    let mut counter = t as u64;
+                    while !found.load(Ordering::SeqCst) && !abort() {
+                        let nonce = nonce_at(&nonce_seed, counter);
+                        for solution in solver.solve(&block_header, &nonce) {
+                            if accept(&nonce, &solution) {
+                                found.store(true, Ordering::SeqCst);
+                                let _ = tx.send((nonce, solution));
+                                return;
+                            }
+                        }
+                        counter = counter.wrapping_add(threads as u64);
+                    }
+                })
+            })
+            .collect();
+        drop(tx);
+
+        let result = rx.recv().ok();
+        found.store(true, Ordering::SeqCst);
+        for handle in handles {
+            let _ = handle.join();
+        }
+        result
+    }
+
+    fn initial_rows(&self, block_header: &[u8], nonce: &[u8]) -> Vec<Row> {
+        let mut personalization: Vec<u8> = Vec::with_capacity(16);
+        personalization.extend_from_slice("AION0PoW".as_bytes());
+        personalization.extend_from_slice(&i32_to_bytes_le(self.n as i32));
+        personalization.extend_from_slice(&i32_to_bytes_le(self.k as i32));
+
+        let mut param = [0u8; 64];
+        param[0] = self.hash_output as u8;
+        param[2] = 1;
+        param[3] = 1;
+        param[48..64].copy_from_slice(&personalization[..16]);
+
+        let init_size = 1u32 << (self.collision_bit_length + 1);
+        let mut rows = Vec::with_capacity(init_size as usize);
+        let mut blakehash = [0u8; 64];
+        for index in 0..init_size {
+            let offset = index % self.indices_per_hash_output;
+            if offset == 0 {
+                let mut blake2b = Blake2b::with_params(&param);
+                blake2b.update(block_header);
+                blake2b.update(nonce);
+                let leb = ((index / self.indices_per_hash_output) as i32).to_le();
+                blake2b.update(&i32_to_bytes(leb));
+                blake2b.finalize(&mut blakehash[..self.hash_output]);
+            }
+            let s = offset as usize * self.indices_hash_length;
+            rows.push(Row {
+                hash: blakehash[s..s + self.indices_hash_length].to_vec(),
+                indices: vec![index],
+            });
+        }
+        rows
+    }
+
+    /// Pairs up the rows agreeing on `len` bits from bit `start`, keeping at most `max_rows`.
+    fn collide(&self, mut rows: Vec<Row>, start: u32, len: u32, max_rows: usize) -> Vec<Row> {
+        rows.sort_by_key(|row| get_bits(&row.hash, start, len));
+
+        let mut next = Vec::new();
+        let mut i = 0;
+        while i < rows.len() && next.len() < max_rows {
+            let key = get_bits(&rows[i].hash, start, len);
+            let mut j = i + 1;
+            while j < rows.len() && get_bits(&rows[j].hash, start, len) == key {
+                j += 1;
+            }
+            for a in i..j {
+                for b in (a + 1)..j {
+                    if let Some(row) = join(&rows[a], &rows[b]) {
+                        next.push(row);
+                    }
+                }
+            }
+            i = j;
+        }
+        next
+    }
+
+    fn get_minimal_from_indices(&self, indices: &[u32]) -> Bytes {
+        let bit_len = self.collision_bit_length + 1;
+        let mut minimal = Vec::with_capacity(indices.len() * bit_len as usize / 8);
+        let mut acc_value: u64 = 0;
+        let mut acc_bits: u32 = 0;
+        for index in indices {
+            acc_value = (acc_value << bit_len) | *index as u64;
+            acc_bits += bit_len;
+            while acc_bits >= 8 {
+                acc_bits -= 8;
+                minimal.push((acc_value >> acc_bits) as u8);
+            }
+            acc_value &= (1 << acc_bits) - 1;
+        }
+        minimal
+    }
+}
+
+/// Joins two rows with distinct indices, the row with the smaller first index goes first.
+fn join(a: &Row, b: &Row) -> Option<Row> {
+    let (left, right) = if a.indices[0] < b.indices[0] {
+        (a, b)
+    } else {
+        (b, a)
+    };
+    let mut indices = Vec::with_capacity(left.indices.len() * 2);
+    indices.extend_from_slice(&left.indices);
+    indices.extend_from_slice(&right.indices);
+
+    let mut sorted = indices.clone();
+    sorted.sort();
+    if sorted.windows(2).any(|w| w[0] == w[1]) {
+        return None;
+    }
+
+    let hash = left
+        .hash
+        .iter()
+        .zip(right.hash.iter())
+        .map(|(x, y)| x ^ y)
+        .collect();
+    Some(Row {
+        hash: hash,
+        indices: indices,
+    })
+}
+
+/// Big-endian value of `len` (at most 64) bits of `hash`, starting from bit `start`.
+fn get_bits(hash: &[u8], start: u32, len: u32) -> u64 {
+    let mut value = 0u64;
+    for bit in start..(start + len) {
+        let byte = hash[(bit / 8) as usize];
+        value = (value << 1) | ((byte >> (7 - bit % 8)) & 1) as u64;
+    }
+    value
+}
+
+fn is_zero(hash: &[u8], bits: u32) -> bool {
+    let mut start = 0;
+    while start < bits {
+        let len = ::std::cmp::min(64, bits - start);
+        if get_bits(hash, start, len) != 0 {
+            return false;
+        }
+        start += len;
+    }
+    true
+}
+
+/// The nonce `counter` steps after `seed`, counting in the first 8 bytes as a little-endian
+/// integer.
+fn nonce_at(seed: &[u8; 32], counter: u64) -> [u8; 32] {
+    let mut nonce = *seed;
+    let mut low = 0u64;
+    for i in 0..8 {
+        low |= (seed[i] as u64) << (8 * i);
+    }
+    let low = low.wrapping_add(counter);
+    for i in 0..8 {
+        nonce[i] = (low >> (8 * i)) as u8;
+    }
+    nonce
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EquihashSolver, nonce_at};
+    use equihash_validator::EquihashValidator;
+    use is_valid_params;
+
+    #[test]
+    fn test_is_valid_params() {
+        assert!(is_valid_params(210, 9));
+        assert!(is_valid_params(48, 5));
+        assert!(!is_valid_params(211, 9));
+        assert!(!is_valid_params(48, 0));
+        assert!(!is_valid_params(16, 1));
+    }
+
+    #[test]
+    fn test_nonce_at() {
+        let mut seed = [0u8; 32];
+        seed[0] = 0xff;
+        seed[31] = 7;
+        let nonce = nonce_at(&seed, 2);
+        assert_eq!(&nonce[..2], &[1u8, 1u8]);
+        assert_eq!(nonce[31], 7);
+    }
+
+    #[test]
+    fn test_solve_is_valid_solution() {
+        let solver = EquihashSolver::new(48, 5);
+        let validator = EquihashValidator::new(48, 5);
+        let header = [0u8; 32];
+
+        let mut found = 0;
+        for i in 0..16u64 {
+            let nonce = nonce_at(&[0u8; 32], i);
+            for solution in solver.solve(&header, &nonce) {
+                assert_eq!(solution.len(), 36);
+                assert!(validator.is_valid_solution(&solution, &header, &nonce));
+                assert!(!validator.is_valid_solution(&solution, &[1u8; 32], &nonce));
+                found += 1;
+            }
+        }
+        assert!(found > 0);
+    }
+
+    #[test]
+    fn test_search() {
+        let solver = EquihashSolver::new(48, 5);
+        let validator = EquihashValidator::new(48, 5);
+        let header = [3u8; 32];
+
+        let (nonce, solution) = solver
+            .search(&header, [0u8; 32], 4, |_, _| true, || false)
+            .unwrap();
+        assert!(validator.is_valid_solution(&solution, &header, &nonce));
+    }
+
+    #[test]
+    fn test_search_abort() {
+        let solver = EquihashSolver::new(48, 5);
+        assert!(
+            solver
+                .search(&[0u8; 32], [0u8; 32], 2, |_, _| false, || true)
+                .is_none()
+        );
+    }
+}
@@ -25,14 +25,13 @@ use bytes::bytes_to_i32s;
 use bytes::i32_to_bytes;
 use bytes::i32_to_bytes_le;
 use blake2b::Blake2b;
-use std::ptr;
 
 pub struct EquihashValidator {
     n: i32,
     k: i32,
-    //    indices_per_hash_output: i32,
+    indices_per_hash_output: i32,
     indices_hash_length: usize,
-    //    hash_output: i32,
+    hash_output: i32,
     collision_bit_length: i32,
     solution_width: i32,
 }
@@ -41,20 +40,20 @@ impl EquihashValidator {
     pub fn new(n: i32, k: i32) -> EquihashValidator {
         let indices_per_hash_output = 512 / n;
         let indices_hash_length = (n + 7) / 8;
-        //        let hash_output = indices_per_hash_output * indices_hash_length;
+        let hash_output = indices_per_hash_output * indices_hash_length;
         let collision_bit_length = n / (k + 1);
         let solution_width = (1 << k) * (collision_bit_length + 1) / 8;
         debug!(target: "equihash", "equihash validator - solution_width={}", solution_width);
         debug!(target: "equihash", "equihash validator - collision_bit_length={}", collision_bit_length);
-        //        trace!(target: "equihash", "equihash validator - hash_output={}", hash_output);
+        debug!(target: "equihash", "equihash validator - hash_output={}", hash_output);
         debug!(target: "equihash", "equihash validator - indices_hash_length={}", indices_hash_length);
         debug!(target: "equihash", "equihash validator - indices_per_hash_output={}", indices_per_hash_output);
         EquihashValidator {
             n,
             k,
-            //            indices_per_hash_output,
+            indices_per_hash_output,
             indices_hash_length: indices_hash_length as usize,
-            //            hash_output,
+            hash_output,
             collision_bit_length,
             solution_width,
         }
@@ -125,17 +124,17 @@ impl EquihashValidator {
         nonce: &[u8],
         indices: &[i32],
         header: &[u8],
-    ) -> Vec<[u8; 27]>
+    ) -> Vec<Vec<u8>>
     {
-        let hashesperblake: i32 = 2;
+        let hashesperblake: i32 = self.indices_per_hash_output;
 
         let mut param = [0u8; 64];
-        param[0] = 54;
+        param[0] = self.hash_output as u8;
         param[2] = 1;
         param[3] = 1;
         param[48..64].copy_from_slice(&personalization[..16]);
 
-        let mut out: Vec<[u8; 27]> = Vec::new();
+        let mut out: Vec<Vec<u8>> = Vec::new();
         let indice_len = indices.len();
         for i in 0..indice_len {
             let mut blake2b = Blake2b::with_params(&param);
@@ -143,20 +142,11 @@ impl EquihashValidator {
             blake2b.update(nonce);
             let leb: i32 = (indices[i] / hashesperblake).to_le();
             blake2b.update(&i32_to_bytes(leb));
-            let mut blakehash = [0u8; 54];
-            blake2b.finalize(&mut blakehash);
-
-            unsafe {
-                let mut index_hash: [u8; 27] = [0u8; 27];
-
-                let s = ((indices[i] % hashesperblake) * (self.n + 7) / 8) as usize;
-                ptr::copy_nonoverlapping(
-                    blakehash[s..].as_ptr(),
-                    index_hash.as_mut_ptr(),
-                    ((self.n + 7) / 8) as usize,
-                );
-                out.push(index_hash);
-            }
+            let mut blakehash = [0u8; 64];
+            blake2b.finalize(&mut blakehash[..self.hash_output as usize]);
+
+            let s = (indices[i] % hashesperblake) as usize * self.indices_hash_length;
+            out.push(blakehash[s..s + self.indices_hash_length].to_vec());
         }
 
         out
@@ -168,7 +158,7 @@ impl EquihashValidator {
         index: i32,
         hash: &mut [u8],
         round: i32,
-        hashes: &Vec<[u8; 27]>,
+        hashes: &Vec<Vec<u8>>,
     ) -> bool
     {
         if round == 0 {
@@ -181,8 +171,8 @@ impl EquihashValidator {
             return false;
         }
 
-        let mut hash0 = hashes[index as usize];
-        let mut hash1 = hashes[index1 as usize];
+        let mut hash0 = hashes[index as usize].clone();
+        let mut hash1 = hashes[index1 as usize].clone();
         let verify0 = self.verify(&indices, index, &mut hash0, round - 1, &hashes);
         if !verify0 {
             error!(target: "equihash", "Solution validation failed - unable to verify left subtree");
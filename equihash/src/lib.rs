@@ -25,10 +25,26 @@ extern crate blake2b;
 extern crate log;
 extern crate rustc_hex as hex;
 
+mod equihash_solver;
 mod equihash_validator;
 
+pub use equihash_solver::EquihashSolver;
 pub use equihash_validator::EquihashValidator;
 
+/// Whether (n, k) are parameters the solver and the validator support: the hash splits into k + 1
+/// collision segments, the indices of a solution pack into whole bytes and a blake2b digest holds
+/// at least one hash.
+pub fn is_valid_params(n: u32, k: u32) -> bool {
+    if k == 0 || k >= 32 || n == 0 || n > 512 || n % (k + 1) != 0 {
+        return false;
+    }
+    let collision_bit_length = n / (k + 1);
+    let indices_per_hash_output = 512 / n;
+    collision_bit_length + 1 < 32
+        && ((1u64 << k) * (collision_bit_length as u64 + 1)) % 8 == 0
+        && indices_per_hash_output * ((n + 7) / 8) <= 64
+}
+
 fn extend_array(input: &[u8], output: &mut [u8], bit_len: i32, byte_pad: i32) {
     let out_width: i32 = (bit_len + 7) / 8 + byte_pad;
     let bit_len_mask: i32 = (1 << bit_len) - 1;
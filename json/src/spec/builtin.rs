@@ -32,6 +32,8 @@ pub struct Builtin {
     pub name: String,
     /// Activation block.
     pub activate_at: Option<Uint>,
+    /// Name of the fork activating the builtin, replaces `activate_at`.
+    pub activate_at_fork: Option<String>,
     /// Deactivation block.
     pub deactivate_at: Option<Uint>,
     /// Owner address.
@@ -71,4 +73,16 @@ mod tests {
         assert_eq!(deserialized.name, "late_start");
         assert_eq!(deserialized.activate_at, Some(Uint(100000.into())));
     }
+
+    #[test]
+    fn activate_at_fork() {
+        let s = r#"{
+            "name": "late_start",
+            "activate_at_fork": "unity"
+        }"#;
+
+        let deserialized: Builtin = serde_json::from_str(s).unwrap();
+        assert!(deserialized.activate_at.is_none());
+        assert_eq!(deserialized.activate_at_fork, Some("unity".to_owned()));
+    }
 }
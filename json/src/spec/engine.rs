@@ -22,7 +22,7 @@
 
 //! Engine deserialization.
 
-use super::{POWEquihashEngine, NullEngine};
+use super::{POWEquihashEngine, NullEngine, InstantHybrid};
 
 /// Engine deserialization.
 #[derive(Debug, PartialEq, Deserialize)]
@@ -35,6 +35,9 @@ pub enum Engine {
     /// Instantly sealing engine.
     #[serde(rename = "instantSeal")]
     InstantSeal,
+    /// Instantly sealing engine alternating PoW and PoS blocks, for development.
+    #[serde(rename = "instantHybrid")]
+    InstantHybrid(InstantHybrid),
 }
 
 #[cfg(test)]
@@ -67,5 +70,17 @@ mod tests {
             Engine::InstantSeal => {} // instant seal is unit tested in its own file.
             _ => panic!(),
         };
+
+        let s = r#"{
+            "instantHybrid": {
+                "params": {}
+            }
+        }"#;
+
+        let deserialized: Engine = serde_json::from_str(s).unwrap();
+        match deserialized {
+            Engine::InstantHybrid(_) => {} // instant hybrid is unit tested in its own file.
+            _ => panic!(),
+        };
     }
 }
@@ -0,0 +1,70 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Instant hybrid engine params deserialization.
+
+use uint::Uint;
+
+/// Instant hybrid engine params deserialization.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct InstantHybridParams {
+    /// Block reward.
+    #[serde(rename = "blockReward")]
+    pub block_reward: Option<Uint>,
+    /// Difficulty of every block.
+    pub difficulty: Option<Uint>,
+    /// Seconds after which an empty block is sealed, blocks are only sealed for transactions
+    /// if zero or missing.
+    pub period: Option<Uint>,
+}
+
+/// Instant hybrid engine descriptor
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct InstantHybrid {
+    /// Instant hybrid engine params.
+    pub params: InstantHybridParams,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use uint::Uint;
+    use aion_types::U256;
+    use super::*;
+
+    #[test]
+    fn instant_hybrid_deserialization() {
+        let s = r#"{
+            "params": {
+                "blockReward": "0x0d",
+                "period": 5
+            }
+        }"#;
+
+        let deserialized: InstantHybrid = serde_json::from_str(s).unwrap();
+        assert_eq!(
+            deserialized.params.block_reward,
+            Some(Uint(U256::from(0x0d)))
+        );
+        assert_eq!(deserialized.params.difficulty, None);
+        assert_eq!(deserialized.params.period, Some(Uint(U256::from(5))));
+    }
+}
@@ -22,6 +22,8 @@
 
 //! Spec params deserialization.
 
+use std::collections::BTreeMap;
+
 use uint::{self, Uint};
 use hash::Address;
 
@@ -43,6 +45,8 @@ pub struct Params {
     /// Transaction permission contract address.
     #[serde(rename = "transactionPermissionContract")]
     pub transaction_permission_contract: Option<Address>,
+    /// Activation block numbers of hard forks, by name.
+    pub forks: Option<BTreeMap<String, Uint>>,
 }
 
 #[cfg(test)]
@@ -64,6 +68,26 @@ mod tests {
         assert_eq!(deserialized.maximum_extra_data_size, Uint(U256::from(0x20)));
         assert_eq!(deserialized.min_gas_limit, Uint(U256::from(0x1388)));
         assert_eq!(deserialized.gas_limit_bound_divisor, Uint(U256::from(0x20)));
+        assert!(deserialized.forks.is_none());
+    }
+
+    #[test]
+    fn params_forks_deserialization() {
+        let s = r#"{
+            "maximumExtraDataSize": "0x20",
+            "minGasLimit": "0x1388",
+            "gasLimitBoundDivisor": "0x20",
+            "forks": {
+                "unity": "0x64",
+                "difficultyV1": 200
+            }
+        }"#;
+
+        let deserialized: Params = serde_json::from_str(s).unwrap();
+        let forks = deserialized.forks.unwrap();
+        assert_eq!(forks.len(), 2);
+        assert_eq!(forks["unity"], Uint(U256::from(100)));
+        assert_eq!(forks["difficultyV1"], Uint(U256::from(200)));
     }
 
     #[test]
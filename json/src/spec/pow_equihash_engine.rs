@@ -43,6 +43,18 @@ pub struct POWEquihashEngineParams {
     pub block_time_upper_bound: Option<u64>,
     #[serde(rename = "minimumDifficulty")]
     pub minimum_difficulty: Option<Uint>,
+    #[serde(rename = "targetBlockTime")]
+    pub target_block_time: Option<u64>,
+    #[serde(rename = "adjustmentBoundDivisor")]
+    pub adjustment_bound_divisor: Option<Uint>,
+    #[serde(rename = "adjustmentMinimumDifficulty")]
+    pub adjustment_minimum_difficulty: Option<Uint>,
+    #[serde(rename = "adjustmentMaximumDifficulty")]
+    pub adjustment_maximum_difficulty: Option<Uint>,
+    #[serde(rename = "equihashN")]
+    pub equihash_n: Option<u32>,
+    #[serde(rename = "equihashK")]
+    pub equihash_k: Option<u32>,
 }
 
 /// pow equihash engine deserialization
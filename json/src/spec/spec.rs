@@ -43,6 +43,9 @@ pub struct Spec {
     pub genesis: Genesis,
     /// Genesis state.
     pub accounts: State,
+    /// Path of a state dump whose accounts are added to the genesis state.
+    #[serde(rename = "stateDump")]
+    pub state_dump: Option<String>,
 }
 
 impl Spec {
@@ -23,6 +23,9 @@
 //! Blockchain test state deserializer.
 
 use std::collections::BTreeMap;
+use std::io::Read;
+use serde_json;
+use serde_json::Error;
 use hash::Address;
 use bytes::Bytes;
 use spec::{Account, Builtin};
@@ -32,6 +35,12 @@ use spec::{Account, Builtin};
 pub struct State(BTreeMap<Address, Account>);
 
 impl State {
+    /// Loads state from json.
+    pub fn load<R>(reader: R) -> Result<Self, Error>
+    where R: Read {
+        serde_json::from_reader(reader)
+    }
+
     /// Returns all builtins.
     pub fn builtins(&self) -> BTreeMap<Address, Builtin> {
         self.0
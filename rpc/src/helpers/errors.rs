@@ -25,11 +25,17 @@
 #![allow(dead_code)]
 use std::fmt;
 
+use abi::decode_revert_reason;
 use acore::account_provider::{SignError as AccountError};
+use acore::client::PruningInfo;
 use acore::error::{Error as EthcoreError, CallError};
+use aion_types::U256;
 use jsonrpc_core::{futures, Error, ErrorCode, Value};
 use rlp::DecoderError;
+use rustc_hex::ToHex;
+use serde_json::Map;
 use acore::transaction::Error as TransactionError;
+use vms::EvmStatusCode;
 
 mod codes {
     // NOTE [ToDr] Codes from [-32099, -32000]
@@ -160,6 +166,39 @@ pub fn state_pruned() -> Error {
     }
 }
 
+pub fn state_pruned_at(pruning: &PruningInfo) -> Error {
+    let checkpoints = match pruning.checkpoint_interval {
+        Some(interval) => format!(", or at a multiple of {}", interval),
+        None => String::new(),
+    };
+    Error {
+        code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+        message: format!(
+            "The state of this block has been pruned. State is queryable from block #{}{}.",
+            pruning.earliest_state, checkpoints
+        ),
+        data: Some(Value::from(pruning.earliest_state)),
+    }
+}
+
+pub fn state_not_listable() -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+        message: "Listing the state requires fat DB and the state of the requested block. Run \
+                  with --fat-db=on and --pruning=archive."
+            .into(),
+        data: None,
+    }
+}
+
+pub fn logger<T: fmt::Debug>(error: T) -> Error {
+    Error {
+        code: ErrorCode::InvalidParams,
+        message: "Couldn't set log levels.".into(),
+        data: Some(Value::String(format!("{:?}", error))),
+    }
+}
+
 pub fn state_corrupt() -> Error { internal("State corrupt", "") }
 
 pub fn exceptional() -> Error {
@@ -170,6 +209,34 @@ pub fn exceptional() -> Error {
     }
 }
 
+/// Error for a call the vm failed to execute, with the vm status, the decoded revert reason if
+/// any, the gas used and the raw output as data.
+pub fn vm_failure(exception: &str, gas_used: U256, output: &[u8]) -> Error {
+    let status = EvmStatusCode::from_exception(exception).unwrap_or(EvmStatusCode::Failure);
+    let reason = match status {
+        EvmStatusCode::Revert => decode_revert_reason(output),
+        _ => None,
+    };
+    let message = match reason {
+        Some(ref reason) => format!("The execution was reverted: {}", reason),
+        None => format!("The execution failed due to an exception: {}", exception),
+    };
+
+    let mut data = Map::new();
+    data.insert("status".into(), Value::String(status.name().into()));
+    if let Some(reason) = reason {
+        data.insert("reason".into(), Value::String(reason));
+    }
+    data.insert("nrgUsed".into(), Value::String(format!("{:#x}", gas_used)));
+    data.insert("output".into(), Value::String(format!("0x{}", output.to_hex())));
+
+    Error {
+        code: ErrorCode::ServerError(codes::EXCEPTION_ERROR),
+        message: message,
+        data: Some(Value::Object(data)),
+    }
+}
+
 pub fn no_work() -> Error {
     Error {
         code: ErrorCode::ServerError(codes::NO_WORK),
@@ -431,6 +498,11 @@ pub fn call(error: CallError) -> Error {
         CallError::StateCorrupt => state_corrupt(),
         CallError::Exceptional => exceptional(),
         CallError::Execution(e) => execution(e),
+        CallError::Failed {
+            exception,
+            gas_used,
+            output,
+        } => vm_failure(&exception, gas_used, &output),
         CallError::TransactionNotFound => {
             internal(
                 "{}, this should not be the case with eth_call, most likely a bug.",
@@ -36,7 +36,7 @@ mod subscribers;
 
 pub use self::dispatch::{Dispatcher, FullDispatcher};
 pub use self::poll_manager::PollManager;
-pub use self::poll_filter::{PollFilter, limit_logs};
+pub use self::poll_filter::{PollFilter, limit_logs, DEFAULT_LIMIT};
 pub use self::requests::{
     TransactionRequest, FilledTransactionRequest, ConfirmationRequest, ConfirmationPayload, CallRequest,
 };
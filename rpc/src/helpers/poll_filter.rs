@@ -29,7 +29,7 @@ use types::{Filter, Log};
 pub type BlockNumber = u64;
 
 /// default logs limit.
-const DEFAULT_LIMIT: usize = 1000;
+pub const DEFAULT_LIMIT: usize = 1000;
 
 /// Filter state.
 #[derive(Clone)]
@@ -0,0 +1,130 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Admin rpc implementation.
+use std::sync::Arc;
+
+use acore::client::BlockChainClient;
+use jsonrpc_core::Result;
+use sync::sync::{NetworkManager, SyncProvider};
+use version::version;
+
+use helpers::errors;
+use traits::Admin;
+use types::{MemoryUsage, NodeInfo, PeerInfo, PeerNetworkInfo, Peers};
+
+/// Admin rpc implementation.
+pub struct AdminClient<C, S: ?Sized, N: ?Sized> {
+    client: Arc<C>,
+    sync: Arc<S>,
+    network: Arc<N>,
+}
+
+impl<C, S: ?Sized, N: ?Sized> AdminClient<C, S, N>
+where
+    C: BlockChainClient,
+    S: SyncProvider,
+    N: NetworkManager,
+{
+    /// Creates new AdminClient.
+    pub fn new(client: &Arc<C>, sync: &Arc<S>, network: &Arc<N>) -> Self {
+        AdminClient {
+            client: client.clone(),
+            sync: sync.clone(),
+            network: network.clone(),
+        }
+    }
+}
+
+impl<C, S: ?Sized, N: ?Sized> Admin for AdminClient<C, S, N>
+where
+    C: BlockChainClient + 'static,
+    S: SyncProvider + 'static,
+    N: NetworkManager + 'static,
+{
+    fn peers(&self) -> Result<Peers> {
+        let network_config = self.network.network_config();
+        let sync_peers = self.sync.peers();
+        let active = sync_peers.iter().filter(|peer| peer.is_active).count();
+        let peers: Vec<PeerInfo> = sync_peers
+            .into_iter()
+            .map(|peer| {
+                PeerInfo {
+                    id: peer.id,
+                    name: peer.revision,
+                    caps: Vec::new(),
+                    network: PeerNetworkInfo {
+                        remote_address: peer.addr,
+                        local_address: network_config.local_node.clone(),
+                    },
+                    mode: peer.mode,
+                    highest_block_number: peer.highest_block_number,
+                    total_difficulty: peer.total_difficulty.into(),
+                }
+            })
+            .collect();
+
+        Ok(Peers {
+            active: active,
+            connected: peers.len(),
+            max: network_config.max_peers,
+            peers: peers,
+        })
+    }
+
+    fn add_peer(&self, node: String) -> Result<bool> {
+        self.network
+            .add_node(&node)
+            .map(|_| true)
+            .map_err(|e| errors::invalid_params("node", e))
+    }
+
+    fn remove_peer(&self, node_id: String) -> Result<bool> { Ok(self.network.remove_node(&node_id)) }
+
+    fn node_info(&self) -> Result<NodeInfo> {
+        let chain_info = self.client.chain_info();
+        let network_config = self.network.network_config();
+        Ok(NodeInfo {
+            enode: self.sync.enode(),
+            version: version(),
+            net_id: network_config.net_id,
+            max_peers: network_config.max_peers,
+            best_block_number: chain_info.best_block_number,
+            best_block_hash: chain_info.best_block_hash.into(),
+            total_difficulty: chain_info.total_difficulty.into(),
+            genesis_hash: chain_info.genesis_hash.into(),
+        })
+    }
+
+    fn memory_usage(&self) -> Result<MemoryUsage> { Ok(self.client.memory_usage().into()) }
+
+    fn set_cache_size(&self, size: u64) -> Result<MemoryUsage> {
+        if size == 0 {
+            return Err(errors::invalid_params("size", "cache size must be positive"));
+        }
+        let bytes = size
+            .checked_mul(1024 * 1024)
+            .filter(|bytes| *bytes <= usize::max_value() as u64)
+            .ok_or_else(|| errors::invalid_params("size", "cache size is too large"))?;
+        self.client.set_memory_budget(bytes as usize);
+        Ok(self.client.memory_usage().into())
+    }
+}
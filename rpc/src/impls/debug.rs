@@ -0,0 +1,226 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Debug rpc implementation.
+use std::cmp;
+use std::sync::Arc;
+
+use acore::client::{BlockChainClient, BlockId};
+use acore::header::SealType;
+use aion_types::{Address, H128};
+use jsonrpc_core::{Error, Result};
+use jsonrpc_macros::Trailing;
+use logger;
+
+use helpers::errors;
+use traits::Debug;
+use types::{
+    AccountDump, BlockDump, BlockNumber, Bytes, H256 as RpcH256, OrphanedBlock, PruningStats,
+    ReorgStats,
+};
+
+/// Max number of accounts returned by a single `debug_dumpBlock` call.
+const MAX_DUMP_ACCOUNTS: u64 = 1000;
+/// Number of storage keys fetched from the state at once.
+const STORAGE_PAGE_SIZE: u64 = 1024;
+/// Max number of block heights searched by a single `debug_getOrphanedBlocks` call.
+const MAX_ORPHANED_BLOCKS_RANGE: u64 = 10000;
+
+/// Debug rpc implementation.
+pub struct DebugClient<C> {
+    client: Arc<C>,
+}
+
+impl<C> DebugClient<C>
+where C: BlockChainClient
+{
+    /// Creates new DebugClient.
+    pub fn new(client: &Arc<C>) -> Self {
+        DebugClient {
+            client: client.clone(),
+        }
+    }
+
+    fn state_pruned(&self) -> Error { errors::state_pruned_at(&self.client.pruning_info()) }
+
+    fn dump_account(&self, address: &Address, id: BlockId) -> Result<AccountDump> {
+        let client = &self.client;
+        let balance = client.balance(address, id).ok_or_else(|| self.state_pruned())?;
+        let nonce = client.nonce(address, id).ok_or_else(|| self.state_pruned())?;
+        let code = client.code(address, id).ok_or_else(|| self.state_pruned())?;
+
+        let mut account = AccountDump {
+            balance: balance.into(),
+            nonce: nonce.into(),
+            code: code.map_or_else(Bytes::default, Bytes::new),
+            storage: Default::default(),
+        };
+
+        let mut after: Option<H128> = None;
+        loop {
+            let keys = client
+                .list_storage(id, address, after.as_ref(), STORAGE_PAGE_SIZE)
+                .ok_or_else(errors::state_not_listable)?;
+            for key in &keys {
+                let value = client
+                    .storage_at(address, key, id)
+                    .ok_or_else(|| self.state_pruned())?;
+                account.storage.insert((*key).into(), value.into());
+            }
+            if (keys.len() as u64) < STORAGE_PAGE_SIZE {
+                break;
+            }
+            after = keys.last().cloned();
+        }
+
+        Ok(account)
+    }
+
+    fn block_number(&self, number: BlockNumber) -> Result<u64> {
+        let id = match number {
+            BlockNumber::Pending => BlockId::Latest,
+            number => number.into(),
+        };
+        self.client.block_number(id).ok_or_else(errors::unknown_block)
+    }
+}
+
+impl<C> Debug for DebugClient<C>
+where C: BlockChainClient + 'static
+{
+    fn set_log_level(&self, levels: String) -> Result<bool> {
+        logger::set_log_levels(&levels)
+            .map(|_| true)
+            .map_err(errors::logger)
+    }
+
+    fn clear_queue(&self) -> Result<bool> {
+        self.client.clear_queue();
+        Ok(true)
+    }
+
+    fn clear_bad(&self) -> Result<bool> {
+        self.client.clear_bad();
+        Ok(true)
+    }
+
+    fn bad_blocks(&self) -> Result<Vec<RpcH256>> {
+        Ok(self
+            .client
+            .bad_blocks()
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    fn dump_block(
+        &self,
+        count: u64,
+        after: Option<RpcH256>,
+        num: Trailing<BlockNumber>,
+    ) -> Result<BlockDump>
+    {
+        let id: BlockId = num.unwrap_or_default().into();
+        let header = self
+            .client
+            .block_header(id)
+            .ok_or_else(errors::unknown_block)?;
+        let after: Option<Address> = after.map(Into::into);
+        let count = cmp::min(count, MAX_DUMP_ACCOUNTS);
+
+        // Ask for one more account to know whether the dump is complete.
+        let mut addresses = self
+            .client
+            .list_accounts(id, after.as_ref(), count + 1)
+            .ok_or_else(errors::state_not_listable)?;
+        let next = if addresses.len() as u64 > count {
+            addresses.truncate(count as usize);
+            addresses.last().cloned().map(Into::into)
+        } else {
+            None
+        };
+
+        let mut dump = BlockDump {
+            root: header.state_root().into(),
+            accounts: Default::default(),
+            next: next,
+        };
+        for address in &addresses {
+            let account = self.dump_account(address, id)?;
+            dump.accounts.insert((*address).into(), account);
+        }
+
+        Ok(dump)
+    }
+
+    fn orphaned_blocks(&self, from: BlockNumber, to: BlockNumber) -> Result<Vec<OrphanedBlock>> {
+        let from = self.block_number(from)?;
+        let to = self.block_number(to)?;
+        if to < from || to - from >= MAX_ORPHANED_BLOCKS_RANGE {
+            return Err(errors::invalid_params(
+                "block range",
+                format!("at most {} blocks, from {} to {}", MAX_ORPHANED_BLOCKS_RANGE, from, to),
+            ));
+        }
+
+        Ok(self
+            .client
+            .orphaned_blocks(from, to)
+            .into_iter()
+            .map(|orphan| {
+                OrphanedBlock {
+                    hash: orphan.hash.into(),
+                    number: orphan.number.into(),
+                    miner: orphan.author.into(),
+                    seal_type: match orphan.seal_type {
+                        SealType::Pow => "pow".into(),
+                        SealType::Pos => "pos".into(),
+                    },
+                    difficulty: orphan.difficulty.into(),
+                    reorg_depth: orphan.reorg_depth.into(),
+                    new_best: orphan.new_best.into(),
+                }
+            })
+            .collect())
+    }
+
+    fn reorg_stats(&self) -> Result<ReorgStats> {
+        let stats = self.client.reorg_stats();
+        Ok(ReorgStats {
+            reorgs: stats.reorgs.into(),
+            last_depth: stats.last_depth.into(),
+            max_depth: stats.max_depth.into(),
+            orphaned_pow: stats.orphaned_pow.into(),
+            orphaned_pos: stats.orphaned_pos.into(),
+        })
+    }
+
+    fn pruning_stats(&self) -> Result<PruningStats> {
+        let info = self.client.pruning_info();
+        Ok(PruningStats {
+            earliest_chain: info.earliest_chain.into(),
+            earliest_state: info.earliest_state.into(),
+            checkpoint_interval: info.checkpoint_interval.map(Into::into),
+            state_size: info.state_size.map(Into::into),
+            pruning_backlog: info.pruning_backlog.into(),
+        })
+    }
+}
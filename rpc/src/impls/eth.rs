@@ -28,7 +28,7 @@ use std::thread;
 use std::time;
 
 use rlp::UntrustedRlp;
-use aion_types::{H256, H128, U128, Address};
+use aion_types::{H256, H128, U128, U256, Address};
 use serde_json::{self, Value};
 use serde_json::map::Map;
 use dispatch::DynamicGasPrice;
@@ -36,6 +36,7 @@ use dispatch::DynamicGasPrice;
 use sync::sync::SyncProvider;
 use acore::account_provider::AccountProvider;
 use acore::client::{MiningBlockChainClient, BlockId, TransactionId};
+use acore::error::CallError;
 use acore::filter::Filter as EthcoreFilter;
 use acore::header::{BlockNumber as EthBlockNumber, SealType};
 use acore::log_entry::LogEntry;
@@ -44,22 +45,29 @@ use acore::miner::external::ExternalMinerService;
 use acore::transaction::SignedTransaction;
 use acore::blockchain::BlockReceipts;
 use solidity::compile;
+use stats::Corpus;
 
-use jsonrpc_core::{BoxFuture, Result};
+use jsonrpc_core::{BoxFuture, Error, Result};
 use jsonrpc_core::futures::future;
 use jsonrpc_macros::Trailing;
 
-use helpers::{errors, limit_logs, fake_sign};
+use helpers::{errors, limit_logs, fake_sign, DEFAULT_LIMIT};
 use helpers::dispatch::{FullDispatcher, default_gas_price};
 use helpers::accounts::unwrap_provider;
 use traits::{Eth, Pb};
 use types::{
     Block, BlockTransactions, BlockNumber, Bytes, SyncStatus,
-    Transaction, CallRequest, Index, Filter, Log, Receipt, Work,
+    Transaction, CallRequest, CallResult, StateOverride, Index, Filter, Log, Receipt, Work,
     H64 as RpcH64, H256 as RpcH256, U256 as RpcU256, U128 as RpcU128, H128 as RpcH128,
-    Contract, ContractInfo, Abi, AbiIO, SyncInfo, AcitvePeerInfo, PbSyncInfo, SimpleReceipt, SimpleReceiptLog
+    Contract, ContractInfo, Abi, AbiIO, SyncInfo, AcitvePeerInfo, PbSyncInfo, SimpleReceipt, SimpleReceiptLog,
+    BlockFees, FeeHistory,
 };
 
+/// Max number of blocks returned by a single `aion_feeHistory` call.
+const MAX_FEE_HISTORY_BLOCKS: u64 = 1024;
+/// Price percentiles of `aion_feeHistory` when none are given: slow, normal and fast.
+const DEFAULT_FEE_HISTORY_PERCENTILES: [usize; 3] = [10, 50, 90];
+
 // const EXTRA_INFO_PROOF: &'static str = "Object exists in in blockchain (fetched earlier), extra_info is always available if object exists; qed";
 
 /// Eth rpc implementation.
@@ -106,6 +114,16 @@ where
         }
     }
 
+    /// Error for a block whose state is no longer available.
+    fn state_pruned(&self) -> Error { errors::state_pruned_at(&self.client.pruning_info()) }
+
+    fn call_error(&self, error: CallError) -> Error {
+        match error {
+            CallError::StatePruned => self.state_pruned(),
+            error => errors::call(error),
+        }
+    }
+
     /// Attempt to get the `Arc<AccountProvider>`, errors if provider was not
     /// set.
     #[allow(dead_code)]
@@ -220,6 +238,21 @@ where M: MinerService {
     result
 }
 
+/// Given percentiles of a sorted price corpus, empty if the corpus is.
+fn price_percentiles(corpus: &Corpus<U256>, percentiles: &[usize]) -> Vec<RpcU256> {
+    if corpus.is_empty() {
+        return Vec::new();
+    }
+    percentiles
+        .iter()
+        .map(|p| {
+            // Low percentiles of small corpora fall below the first price.
+            let price = corpus.percentile(*p).unwrap_or(&corpus[0]);
+            RpcU256::from(*price)
+        })
+        .collect()
+}
+
 fn check_known<C>(client: &C, number: BlockNumber) -> Result<()>
 where C: MiningBlockChainClient {
     use acore::block_status::BlockStatus;
@@ -279,6 +312,65 @@ where
         )))
     }
 
+    fn fee_history(
+        &self,
+        block_count: RpcU256,
+        newest: BlockNumber,
+        percentiles: Trailing<Vec<usize>>,
+    ) -> Result<FeeHistory>
+    {
+        let percentiles = percentiles
+            .unwrap_or_else(|| DEFAULT_FEE_HISTORY_PERCENTILES.to_vec());
+        if let Some(p) = percentiles.iter().find(|p| **p > 100) {
+            return Err(errors::invalid_params("percentiles", p));
+        }
+        let newest = match newest {
+            BlockNumber::Pending => BlockId::Latest,
+            number => number.into(),
+        };
+        let newest = self
+            .client
+            .block_number(newest)
+            .ok_or_else(errors::unknown_block)?;
+        let block_count: U256 = block_count.into();
+        let block_count = ::std::cmp::min(block_count, MAX_FEE_HISTORY_BLOCKS.into()).low_u64();
+        let oldest = newest + 1 - ::std::cmp::min(block_count, newest + 1);
+
+        let mut history = FeeHistory::default();
+        history.oldest_block = oldest.into();
+        let mut pow_prices = Vec::new();
+        let mut pos_prices = Vec::new();
+        for number in oldest..(newest + 1) {
+            let block = self
+                .client
+                .block_gas_prices(BlockId::Number(number))
+                .ok_or_else(errors::unknown_block)?;
+            let (seal_type, seal_fees, seal_prices) = match block.seal_type {
+                SealType::Pow => ("pow", &mut history.pow, &mut pow_prices),
+                SealType::Pos => ("pos", &mut history.pos, &mut pos_prices),
+            };
+            seal_fees.blocks += 1;
+            seal_fees.transactions += block.gas_prices.len() as u64;
+            seal_prices.extend(block.gas_prices.iter().cloned());
+
+            let gas_used_ratio = if block.gas_limit.is_zero() {
+                0.0
+            } else {
+                block.gas_used.low_u64() as f64 / block.gas_limit.low_u64() as f64
+            };
+            history.blocks.push(BlockFees {
+                number: number.into(),
+                seal_type: seal_type.into(),
+                gas_used_ratio: gas_used_ratio,
+                prices: price_percentiles(&block.gas_prices, &percentiles),
+            });
+        }
+        history.pow.prices = price_percentiles(&pow_prices.into(), &percentiles);
+        history.pos.prices = price_percentiles(&pos_prices.into(), &percentiles);
+
+        Ok(history)
+    }
+
     // ATTENTION: modified this api to return all block hases with POW seal type. Only for Unity POC.
     fn accounts(&self) -> Result<Vec<RpcH256>> {
         let hases = self.client.block_hashes_with_seal(SealType::Pow);
@@ -306,7 +398,7 @@ where
         try_bf!(check_known(&*self.client, id.clone()));
         let res = match self.client.balance(&address, id.into()) {
             Some(balance) => Ok(balance.into()),
-            None => Err(errors::state_pruned()),
+            None => Err(self.state_pruned()),
         };
 
         Box::new(future::done(res))
@@ -330,7 +422,7 @@ where
             .storage_at(&address, &H128::from(position), id.into())
         {
             Some(s) => Ok(s.into()),
-            None => Err(errors::state_pruned()),
+            None => Err(self.state_pruned()),
         };
 
         Box::new(future::done(res))
@@ -360,7 +452,7 @@ where
                 try_bf!(check_known(&*self.client, id.clone()));
                 match self.client.nonce(&address, id.into()) {
                     Some(nonce) => Ok(nonce.into()),
-                    None => Err(errors::state_pruned()),
+                    None => Err(self.state_pruned()),
                 }
             }
         };
@@ -389,7 +481,7 @@ where
 
         let res = match self.client.code(&address, id.into()) {
             Some(code) => Ok(code.map_or_else(Bytes::default, Bytes::new)),
-            None => Err(errors::state_pruned()),
+            None => Err(self.state_pruned()),
         };
 
         Box::new(future::done(res))
@@ -449,8 +541,12 @@ where
     fn compilers(&self) -> Result<Vec<String>> { Ok(vec![String::from("solidity")]) }
 
     fn logs(&self, filter: Filter) -> BoxFuture<Vec<Log>> {
-        let include_pending = filter.to_block == Some(BlockNumber::Pending);
-        let filter: EthcoreFilter = filter.into();
+        // pending logs are the most recent ones, skipped by any page with an offset
+        let include_pending =
+            filter.to_block == Some(BlockNumber::Pending) && filter.offset.unwrap_or(0) == 0;
+        let mut filter: EthcoreFilter = filter.into();
+        // let the client stop collecting logs once the page is full
+        filter.limit = Some(filter.limit.unwrap_or(DEFAULT_LIMIT));
         let mut logs = self
             .client
             .logs(filter.clone())
@@ -511,9 +607,43 @@ where
         let num = num.unwrap_or_default();
         let result = self.client.call(&signed, Default::default(), num.into());
 
-        Box::new(future::done(
-            result.map(|b| b.output.into()).map_err(errors::call),
-        ))
+        Box::new(future::done(result.map_err(|e| self.call_error(e)).and_then(
+            |executed| {
+                match executed.exception.as_str() {
+                    "" => Ok(executed.output.into()),
+                    exception => {
+                        Err(errors::vm_failure(
+                            exception,
+                            executed.gas_used,
+                            &executed.output,
+                        ))
+                    }
+                }
+            },
+        )))
+    }
+
+    fn call_many(
+        &self,
+        requests: Vec<CallRequest>,
+        num: BlockNumber,
+        overrides: Trailing<StateOverride>,
+    ) -> BoxFuture<Vec<CallResult>>
+    {
+        let mut transactions = Vec::with_capacity(requests.len());
+        for request in requests {
+            let signed = try_bf!(fake_sign::sign_call(CallRequest::into(request)));
+            transactions.push((signed, Default::default()));
+        }
+        let overrides = overrides.unwrap_or_default().into();
+
+        let result = self
+            .client
+            .call_many(&transactions, &overrides, num.into())
+            .map(|executed| executed.into_iter().map(Into::into).collect())
+            .map_err(|e| self.call_error(e));
+
+        Box::new(future::done(result))
     }
 
     fn estimate_gas(&self, request: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<RpcU256> {
@@ -523,7 +653,7 @@ where
             self.client
                 .estimate_gas(&signed, num.unwrap_or_default().into())
                 .map(Into::into)
-                .map_err(errors::call),
+                .map_err(|e| self.call_error(e)),
         ))
     }
 
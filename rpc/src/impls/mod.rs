@@ -30,6 +30,8 @@ macro_rules! try_bf {
     };
 }
 
+mod admin;
+mod debug;
 #[macro_use]
 mod eth;
 mod eth_filter;
@@ -42,6 +44,8 @@ mod stratum;
 mod web3;
 mod ping;
 
+pub use self::admin::AdminClient;
+pub use self::debug::DebugClient;
 pub use self::eth::EthClient;
 pub use self::eth_filter::EthFilterClient;
 pub use self::eth_pubsub::EthPubSubClient;
@@ -33,15 +33,18 @@ use jsonrpc_macros::Trailing;
 use aion_types::{H256, U256};
 use acore::block::IsBlock;
 use acore::client::{MiningBlockChainClient, BlockId};
-use acore::miner::MinerService;
+use acore::miner::{MinerService, Stratum as StratumService};
 use acore::account_provider::AccountProvider;
-use jsonrpc_core::{Error, Result};
+use jsonrpc_core::{BoxFuture, Error, Result};
+use jsonrpc_core::futures::{future, Future};
+use tokio_timer::Timer;
 
 use helpers::errors;
 use helpers::accounts::unwrap_provider;
 use traits::Stratum;
 use types::{
-    Work, Info, AddressValidation, MiningInfo, MinerStats, TemplateParam, Bytes, StratumHeader, SimpleHeader, BlockNumber
+    Work, Info, AddressValidation, MiningInfo, MinerStats, TemplateParam, Bytes, StratumHeader, SimpleHeader, BlockNumber,
+    WorkerShares,
 };
 use aion_types::clean_0x;
 
@@ -56,8 +59,10 @@ where
     sync: Arc<S>,
     miner: Arc<M>,
     account_provider: Option<Arc<AccountProvider>>,
+    stratum: Option<StratumService>,
     recent_block_hash: Mutex<LinkedList<H256>>,
     recent_block_header: Mutex<HashMap<H256, (H256, u64)>>,
+    timer: Timer,
 }
 
 impl<C, S: ?Sized, M> StratumClient<C, S, M>
@@ -72,6 +77,7 @@ where
         sync: &Arc<S>,
         miner: &Arc<M>,
         account_provider: &Option<Arc<AccountProvider>>,
+        stratum: &Option<StratumService>,
     ) -> Self
     {
         StratumClient {
@@ -79,57 +85,71 @@ where
             sync: sync.clone(),
             miner: miner.clone(),
             account_provider: account_provider.clone(),
+            stratum: stratum.clone(),
             recent_block_hash: Mutex::new(LinkedList::new()),
             recent_block_header: Mutex::new(HashMap::with_capacity(STRATUM_RECENT_BLK_COUNT)),
+            timer: Timer::default(),
         }
     }
 
     fn account_provider(&self) -> Result<Arc<AccountProvider>> {
         unwrap_provider(&self.account_provider)
     }
-}
-
-const MAX_QUEUE_SIZE_TO_MINE_ON: usize = 4;
-const STRATUM_BLKTIME_INCLUDED_COUNT: usize = 32;
-const STRATUM_RECENT_BLK_COUNT: usize = 128;
 
-impl<C, S: ?Sized, M> Stratum for StratumClient<C, S, M>
-where
-    C: MiningBlockChainClient + 'static,
-    S: SyncProvider + 'static,
-    M: MinerService + 'static,
-{
-    /// Returns the work of current block
-    fn work(&self, _tpl_param: Trailing<TemplateParam>) -> Result<Work> {
-        if !self.miner.can_produce_work_package() {
-            warn!(target: "miner", "Cannot give work package - engine seals internally.");
-            return Err(errors::no_work_required());
-        }
+    /// Share accounting of the stratum workers mining for the address, whose worker id is the
+    /// address optionally followed by `.` and a worker name
+    fn worker_shares(&self, address: &H256) -> Vec<WorkerShares> {
+        let stratum = match self.stratum {
+            Some(ref stratum) => stratum,
+            None => return Vec::new(),
+        };
+        let mut workers: Vec<WorkerShares> = stratum
+            .worker_stats()
+            .into_iter()
+            .filter(|stats| {
+                let miner = stats.worker.split('.').next().unwrap_or("");
+                clean_0x(miner).parse::<H256>().ok() == Some(*address)
+            })
+            .map(|stats| {
+                WorkerShares {
+                    worker: stats.worker,
+                    difficulty: stats.difficulty,
+                    accepted: stats.accepted,
+                    stale: stats.stale,
+                    invalid: stats.invalid,
+                }
+            })
+            .collect();
+        workers.sort_by(|a, b| a.worker.cmp(&b.worker));
+        workers
+    }
 
+    /// The work of the current block, unless syncing or no author is configured
+    fn current_work(client: &C, miner: &M) -> Result<Work> {
         // check if we're still syncing and return empty strings in that case
         {
             //TODO: check if initial sync is complete here
             //let sync = self.sync;
             if
             /*sync.status().state != SyncState::Idle ||*/
-            self.client.queue_info().total_queue_size() > MAX_QUEUE_SIZE_TO_MINE_ON {
+            client.queue_info().total_queue_size() > MAX_QUEUE_SIZE_TO_MINE_ON {
                 trace!(target: "miner", "Syncing. Cannot give any work.");
                 return Err(errors::no_work());
             }
 
             // Otherwise spin until our submitted block has been included.
             let timeout = Instant::now() + Duration::from_millis(1000);
-            while Instant::now() < timeout && self.client.queue_info().total_queue_size() > 0 {
+            while Instant::now() < timeout && client.queue_info().total_queue_size() > 0 {
                 thread::sleep(Duration::from_millis(1));
             }
         }
 
-        if self.miner.author().is_zero() {
+        if miner.author().is_zero() {
             warn!(target: "miner", "Cannot give work package - no author is configured. Use --author to configure!");
             return Err(errors::no_author());
         }
-        self.miner
-            .map_sealing_work(&*self.client, |b| {
+        miner
+            .map_sealing_work(client, |b| {
                 let pow_hash = b.block().header().mine_hash();
                 let target = b.block().header().boundary();
                 let parent_hash = b.header().parent_hash().clone();
@@ -144,10 +164,68 @@ where
                     number: block_number,
                     reward: reward,
                     transaction_fee: transaction_fee,
+                    long_poll_id: pow_hash,
                 })
             })
             .unwrap_or(Err(errors::internal("No work found.", "")))
     }
+}
+
+const MAX_QUEUE_SIZE_TO_MINE_ON: usize = 4;
+const STRATUM_BLKTIME_INCLUDED_COUNT: usize = 32;
+const STRATUM_RECENT_BLK_COUNT: usize = 128;
+/// Seconds a long-polling `getblocktemplate` waits for the work to change.
+const LONG_POLL_TIMEOUT_SECS: u64 = 30;
+
+impl<C, S: ?Sized, M> Stratum for StratumClient<C, S, M>
+where
+    C: MiningBlockChainClient + 'static,
+    S: SyncProvider + 'static,
+    M: MinerService + 'static,
+{
+    /// Returns the work of current block
+    fn work(&self, tpl_param: Trailing<TemplateParam>) -> BoxFuture<Work> {
+        if !self.miner.can_produce_work_package() {
+            warn!(target: "miner", "Cannot give work package - engine seals internally.");
+            return Box::new(future::err(errors::no_work_required()));
+        }
+
+        // long-poll: wait for the work the miner has to change
+        let mut changed = None;
+        if let Some(long_poll_id) = tpl_param.unwrap_or_default().long_poll_id {
+            let work_hash: H256 = match clean_0x(long_poll_id.as_str()).parse() {
+                Ok(work_hash) => work_hash,
+                Err(_e) => {
+                    return Box::new(future::err(Error::invalid_params("invalid longpollid")))
+                }
+            };
+            // keeps sealing enabled while waiting
+            let current = self
+                .miner
+                .map_sealing_work(&*self.client, |b| b.block().header().mine_hash());
+            if current == Some(work_hash) {
+                changed = Some(self.miner.work_changed(&work_hash));
+            }
+        }
+
+        let client = self.client.clone();
+        let miner = self.miner.clone();
+        match changed {
+            Some(changed) => {
+                let timeout = self
+                    .timer
+                    .sleep(Duration::from_secs(LONG_POLL_TIMEOUT_SECS))
+                    .then(|_| Ok::<(), ()>(()));
+                Box::new(
+                    changed
+                        .then(|_| Ok::<(), ()>(()))
+                        .select(timeout)
+                        .then(move |_| Self::current_work(&*client, &*miner)),
+                )
+            }
+            None => Box::new(future::done(Self::current_work(&*client, &*miner))),
+        }
+    }
 
     /// get block header by number
     fn get_block_by_number(&self, num: BlockNumber) -> Result<StratumHeader> {
@@ -354,6 +432,7 @@ where
             miner_hashrate_share: miner_hashrate_share,
             miner_hashrate: miner_hashrate,
             network_hashrate: network_hashrate,
+            workers: self.worker_shares(&address),
         })
     }
 }
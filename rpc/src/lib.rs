@@ -58,6 +58,8 @@ extern crate key;
 extern crate keychain;
 extern crate logger;
 extern crate solidity;
+extern crate vms;
+extern crate abi;
 extern crate aion_version as version;
 extern crate rlp;
 extern crate stats;
@@ -0,0 +1,55 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Admin rpc interface.
+use jsonrpc_core::Result;
+
+use types::{MemoryUsage, NodeInfo, Peers};
+
+build_rpc_trait! {
+    /// Admin rpc interface. Node management functions.
+    pub trait Admin {
+        /// Returns the connected peers.
+        #[rpc(name = "admin_peers")]
+        fn peers(&self) -> Result<Peers>;
+
+        /// Adds a node (`p2p://<node id>@<ip>:<port>`) to connect to.
+        #[rpc(name = "admin_addPeer")]
+        fn add_peer(&self, String) -> Result<bool>;
+
+        /// Disconnects and drops the peer with the given node id.
+        #[rpc(name = "admin_removePeer")]
+        fn remove_peer(&self, String) -> Result<bool>;
+
+        /// Returns information about the local node.
+        #[rpc(name = "admin_nodeInfo")]
+        fn node_info(&self) -> Result<NodeInfo>;
+
+        /// Returns the memory used by the caches of the node and their budget.
+        #[rpc(name = "admin_memoryUsage")]
+        fn memory_usage(&self) -> Result<MemoryUsage>;
+
+        /// Splits the given megabytes between the caches of the node and resizes them. The
+        /// database cache keeps the size it was opened with.
+        #[rpc(name = "admin_setCacheSize")]
+        fn set_cache_size(&self, u64) -> Result<MemoryUsage>;
+    }
+}
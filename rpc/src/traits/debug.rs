@@ -0,0 +1,66 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Debug rpc interface.
+use jsonrpc_core::Result;
+use jsonrpc_macros::Trailing;
+
+use types::{BlockDump, BlockNumber, H256, OrphanedBlock, PruningStats, ReorgStats};
+
+build_rpc_trait! {
+    /// Debug rpc interface. Node diagnostic functions.
+    pub trait Debug {
+        /// Sets the log levels, in the same format as `--log` (e.g. `sync=debug,miner=trace`).
+        #[rpc(name = "debug_setLogLevel")]
+        fn set_log_level(&self, String) -> Result<bool>;
+
+        /// Clears the block import queue.
+        #[rpc(name = "debug_clearQueue")]
+        fn clear_queue(&self) -> Result<bool>;
+
+        /// Clears the blocks marked as bad in the import queue.
+        #[rpc(name = "debug_clearBad")]
+        fn clear_bad(&self) -> Result<bool>;
+
+        /// Returns hashes of the blocks marked as bad in the import queue.
+        #[rpc(name = "debug_getBadBlocks")]
+        fn bad_blocks(&self) -> Result<Vec<H256>>;
+
+        /// Dumps up to `count` accounts with their code and storage at the given block,
+        /// starting after the given address. Requires fat DB.
+        #[rpc(name = "debug_dumpBlock")]
+        fn dump_block(&self, u64, Option<H256>, Trailing<BlockNumber>) -> Result<BlockDump>;
+
+        /// Returns the blocks retracted from the canonical chain by reorganisations, with
+        /// numbers between the given blocks inclusive.
+        #[rpc(name = "debug_getOrphanedBlocks")]
+        fn orphaned_blocks(&self, BlockNumber, BlockNumber) -> Result<Vec<OrphanedBlock>>;
+
+        /// Returns the reorganisations of the canonical chain since the node started.
+        #[rpc(name = "debug_getReorgStats")]
+        fn reorg_stats(&self) -> Result<ReorgStats>;
+
+        /// Returns the earliest blocks whose state is available, the size of the state on disk
+        /// and the number of blocks waiting to be pruned.
+        #[rpc(name = "debug_getPruningStats")]
+        fn pruning_stats(&self) -> Result<PruningStats>;
+    }
+}
@@ -26,8 +26,9 @@ use std::collections::HashMap;
 use jsonrpc_core::{Result, BoxFuture};
 use jsonrpc_macros::Trailing;
 
-use types::{Block, BlockNumber, Bytes, CallRequest, Filter, FilterChanges, Index};
-use types::{Log, Receipt, SyncStatus, Transaction, Work, Contract};
+use types::{Block, BlockNumber, Bytes, CallRequest, CallResult, Filter, FilterChanges, Index};
+use types::StateOverride;
+use types::{FeeHistory, Log, Receipt, SyncStatus, Transaction, Work, Contract};
 use types::{H64, H256, U256, U128, H128};
 
 build_rpc_trait! {
@@ -58,6 +59,12 @@ build_rpc_trait! {
         #[rpc(name = "eth_gasPrice")]
         fn gas_price(&self) -> Result<U256>;
 
+        /// Returns percentiles of the energy prices paid over the given number of blocks up to
+        /// the given block, per block and per seal type. Unlike the EIP-1559 `eth_feeHistory`,
+        /// percentiles are integers and there is no base fee.
+        #[rpc(name = "aion_feeHistory")]
+        fn fee_history(&self, U256, BlockNumber, Trailing<Vec<usize>>) -> Result<FeeHistory>;
+
         /// Returns accounts list.
         #[rpc(name = "eth_accounts")]
         fn accounts(&self) -> Result<Vec<H256>>;
@@ -110,6 +117,12 @@ build_rpc_trait! {
         #[rpc(name = "eth_call")]
         fn call(&self, CallRequest, Trailing<BlockNumber>) -> BoxFuture<Bytes>;
 
+        /// Executes the calls in order on top of the state of the given block, each call seeing
+        /// the changes of the previous ones. Accounts can be overridden before the first call.
+        #[rpc(name = "eth_callMany")]
+        fn call_many(&self, Vec<CallRequest>, BlockNumber, Trailing<StateOverride>)
+            -> BoxFuture<Vec<CallResult>>;
+
         /// Estimate gas needed for execution of given contract.
         #[rpc(name = "eth_estimateGas")]
         fn estimate_gas(&self, CallRequest, Trailing<BlockNumber>) -> BoxFuture<U256>;
@@ -23,6 +23,8 @@
 //! Ethereum rpc interfaces.
 
 pub mod web3;
+pub mod admin;
+pub mod debug;
 pub mod eth;
 pub mod stratum;
 pub mod eth_pubsub;
@@ -34,6 +36,8 @@ pub mod pb;
 pub mod ping;
 
 pub use self::web3::Web3;
+pub use self::admin::Admin;
+pub use self::debug::Debug;
 pub use self::eth::{Eth, EthFilter};
 pub use self::stratum::Stratum;
 pub use self::eth_pubsub::EthPubSub;
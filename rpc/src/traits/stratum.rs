@@ -20,7 +20,7 @@
  ******************************************************************************/
 
 //! Stratum rpc interface.
-use jsonrpc_core::Result;
+use jsonrpc_core::{BoxFuture, Result};
 use aion_types::{H256, U256};
 use jsonrpc_macros::Trailing;
 
@@ -29,9 +29,10 @@ use types::{Work, AddressValidation, Info, MiningInfo, MinerStats, TemplateParam
 build_rpc_trait! {
     /// Stratum rpc interface.
     pub trait Stratum {
-        /// Returns the work of current block
+        /// Returns the work of current block, once it differs from the work of the given
+        /// long-poll id if any
         #[rpc(name = "getblocktemplate")]
-        fn work(&self, Trailing<TemplateParam>) -> Result<Work>;
+        fn work(&self, Trailing<TemplateParam>) -> BoxFuture<Work>;
 
         /// Submit a proof-of-work solution
         #[rpc(name = "submitblock")]
@@ -0,0 +1,159 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Types of `eth_callMany`.
+
+use std::collections::BTreeMap;
+
+use abi::decode_revert_reason;
+use acore::client::{
+    AccountOverride as EthAccountOverride, Executed, StateOverride as EthStateOverride,
+};
+use types::{Bytes, Log, H128, H256, U256};
+use vms::EvmStatusCode;
+
+/// Account fields replaced before executing the calls.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccountOverride {
+    /// Balance
+    pub balance: Option<U256>,
+    /// Nonce
+    pub nonce: Option<U256>,
+    /// Code
+    pub code: Option<Bytes>,
+    /// Storage slots, slots not listed keep their value
+    #[serde(default)]
+    pub storage: BTreeMap<H128, H128>,
+}
+
+impl Into<EthAccountOverride> for AccountOverride {
+    fn into(self) -> EthAccountOverride {
+        EthAccountOverride {
+            balance: self.balance.map(Into::into),
+            nonce: self.nonce.map(Into::into),
+            code: self.code.map(Into::into),
+            storage: self
+                .storage
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        }
+    }
+}
+
+/// Accounts to override, by address.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct StateOverride(BTreeMap<H256, AccountOverride>);
+
+impl Into<EthStateOverride> for StateOverride {
+    fn into(self) -> EthStateOverride {
+        self.0
+            .into_iter()
+            .map(|(address, account)| (address.into(), account.into()))
+            .collect()
+    }
+}
+
+/// Result of a single call of `eth_callMany`.
+#[derive(Debug, Serialize)]
+pub struct CallResult {
+    /// Output data
+    pub output: Bytes,
+    /// Logs emitted by the call
+    pub logs: Vec<Log>,
+    /// Energy used
+    #[serde(rename = "nrgUsed")]
+    pub gas_used: U256,
+    /// Vm status, if the call reached the vm
+    pub status: Option<String>,
+    /// Error message of a failed call
+    pub error: Option<String>,
+    /// Reason given by a reverted call
+    #[serde(rename = "revertReason")]
+    pub revert_reason: Option<String>,
+}
+
+impl From<Executed> for CallResult {
+    fn from(e: Executed) -> CallResult {
+        let status = EvmStatusCode::from_exception(&e.exception);
+        let revert_reason = match status {
+            Some(EvmStatusCode::Revert) => decode_revert_reason(&e.output),
+            _ => None,
+        };
+        let error = match e.exception.as_str() {
+            "" => None,
+            exception => Some(exception.to_owned()),
+        };
+
+        CallResult {
+            output: e.output.into(),
+            logs: e.logs.into_iter().map(Into::into).collect(),
+            gas_used: e.gas_used.into(),
+            status: status.map(|code| code.name().to_owned()),
+            error: error,
+            revert_reason: revert_reason,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use aion_types::{H128, H256, U256};
+    use acore::client::StateOverride as EthStateOverride;
+    use super::StateOverride;
+
+    #[test]
+    fn state_override_deserialization() {
+        let s = r#"{
+            "0x0000000000000000000000000000000000000000000000000000000000000001": {
+                "balance": "0x10",
+                "nonce": "0x2",
+                "code": "0x6001",
+                "storage": {
+                    "0x00000000000000000000000000000001": "0x0000000000000000000000000000000a"
+                }
+            },
+            "0x0000000000000000000000000000000000000000000000000000000000000002": {}
+        }"#;
+        let overrides: StateOverride = serde_json::from_str(s).unwrap();
+        let overrides: EthStateOverride = overrides.into();
+
+        let first = &overrides[&H256::from(1)];
+        assert_eq!(first.balance, Some(U256::from(0x10)));
+        assert_eq!(first.nonce, Some(U256::from(2)));
+        assert_eq!(first.code, Some(vec![0x60, 0x01]));
+        assert_eq!(first.storage[&H128::from(1)], H128::from(10));
+
+        let second = &overrides[&H256::from(2)];
+        assert_eq!(second, &Default::default());
+    }
+
+    #[test]
+    fn state_override_rejects_unknown_fields() {
+        let s = r#"{
+            "0x0000000000000000000000000000000000000000000000000000000000000001": {"stateDiff": {}}
+        }"#;
+        let overrides: Result<StateOverride, _> = serde_json::from_str(s);
+        assert!(overrides.is_err());
+    }
+}
@@ -0,0 +1,72 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! State dump.
+
+use std::collections::BTreeMap;
+
+use types::{Bytes, H128, H256, U256};
+
+/// Account state in a block dump.
+#[derive(Default, Debug, Serialize)]
+pub struct AccountDump {
+    /// Balance
+    pub balance: U256,
+    /// Nonce
+    pub nonce: U256,
+    /// Code
+    pub code: Bytes,
+    /// Storage
+    pub storage: BTreeMap<H128, H128>,
+}
+
+/// Accounts of the state at a given block.
+#[derive(Default, Debug, Serialize)]
+pub struct BlockDump {
+    /// State root
+    pub root: H256,
+    /// Dumped accounts
+    pub accounts: BTreeMap<H256, AccountDump>,
+    /// Address to pass as `after` to continue the dump, if there are more accounts
+    pub next: Option<H256>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use aion_types::{H128, H256};
+    use super::{AccountDump, BlockDump};
+
+    #[test]
+    fn test_serialize_block_dump() {
+        let mut dump = BlockDump::default();
+        let mut account = AccountDump::default();
+        account.balance = 10.into();
+        account.storage.insert(H128::from(1).into(), H128::from(2).into());
+        dump.accounts.insert(H256::from(5).into(), account);
+
+        let serialized = serde_json::to_string(&dump).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"root":"0x0000000000000000000000000000000000000000000000000000000000000000","accounts":{"0x0000000000000000000000000000000000000000000000000000000000000005":{"balance":"0xa","nonce":"0x0","code":"0x","storage":{"0x00000000000000000000000000000001":"0x00000000000000000000000000000002"}}},"next":null}"#
+        );
+    }
+}
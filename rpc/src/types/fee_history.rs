@@ -0,0 +1,95 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Energy price history.
+
+use types::U256;
+
+/// Energy prices paid over a range of blocks.
+#[derive(Debug, Default, Serialize)]
+pub struct FeeHistory {
+    /// Number of the first block of the range
+    #[serde(rename = "oldestBlock")]
+    pub oldest_block: U256,
+    /// Prices of each block, from the oldest
+    pub blocks: Vec<BlockFees>,
+    /// Prices over the proof-of-work blocks of the range
+    pub pow: SealFees,
+    /// Prices over the proof-of-stake blocks of the range
+    pub pos: SealFees,
+}
+
+/// Energy prices paid in a block.
+#[derive(Debug, Serialize)]
+pub struct BlockFees {
+    /// Block number
+    pub number: U256,
+    /// Seal type, `pow` or `pos`
+    #[serde(rename = "sealType")]
+    pub seal_type: String,
+    /// Energy used over energy limit
+    #[serde(rename = "nrgUsedRatio")]
+    pub gas_used_ratio: f64,
+    /// Requested percentiles of the transaction prices, empty if the block has no transactions
+    pub prices: Vec<U256>,
+}
+
+/// Energy prices paid in blocks of one seal type.
+#[derive(Debug, Default, Serialize)]
+pub struct SealFees {
+    /// Number of blocks
+    pub blocks: u64,
+    /// Number of transactions
+    pub transactions: u64,
+    /// Requested percentiles of the transaction prices, empty if there are no transactions
+    pub prices: Vec<U256>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use super::{BlockFees, FeeHistory, SealFees};
+
+    #[test]
+    fn fee_history_serialization() {
+        let history = FeeHistory {
+            oldest_block: 9.into(),
+            blocks: vec![BlockFees {
+                number: 9.into(),
+                seal_type: "pos".into(),
+                gas_used_ratio: 0.5,
+                prices: vec![10.into(), 20.into()],
+            }],
+            pow: SealFees::default(),
+            pos: SealFees {
+                blocks: 1,
+                transactions: 4,
+                prices: vec![10.into(), 20.into()],
+            },
+        };
+
+        let serialized = serde_json::to_string(&history).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"oldestBlock":"0x9","blocks":[{"number":"0x9","sealType":"pos","nrgUsedRatio":0.5,"prices":["0xa","0x14"]}],"pow":{"blocks":0,"transactions":0,"prices":[]},"pos":{"blocks":1,"transactions":4,"prices":["0xa","0x14"]}}"#
+        );
+    }
+}
@@ -79,6 +79,8 @@ pub struct Filter {
     pub topics: Option<Vec<Topic>>,
     /// Limit
     pub limit: Option<usize>,
+    /// Offset
+    pub offset: Option<usize>,
 }
 
 impl Into<EthFilter> for Filter {
@@ -121,6 +123,7 @@ impl Into<EthFilter> for Filter {
                 ]
             },
             limit: self.limit,
+            offset: self.offset,
         }
     }
 }
@@ -200,6 +203,7 @@ mod tests {
                 address: None,
                 topics: None,
                 limit: None,
+                offset: None,
             }
         );
     }
@@ -218,6 +222,7 @@ mod tests {
                 VariadicValue::Null,
             ]),
             limit: None,
+            offset: None,
         };
 
         let eth_filter: EthFilter = filter.into();
@@ -236,6 +241,7 @@ mod tests {
                     None,
                 ],
                 limit: None,
+                offset: None,
             }
         );
     }
@@ -0,0 +1,112 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Memory used by the caches of the node.
+
+use acore::client::MemoryUsage as EthMemoryUsage;
+use types::U256;
+
+/// Memory used by a cache and its budget, in bytes.
+#[derive(Debug, Serialize)]
+pub struct CacheUsage {
+    /// Memory used, if known
+    pub used: Option<U256>,
+    /// Memory the cache is kept within, if limited by the node
+    pub budget: Option<U256>,
+}
+
+/// Memory used by the caches of the node.
+#[derive(Debug, Serialize)]
+pub struct MemoryUsage {
+    /// Blockchain caches of blocks, details, receipts and blooms
+    pub blockchain: CacheUsage,
+    /// State account and code caches
+    pub state: CacheUsage,
+    /// Block cache of the databases
+    pub db: CacheUsage,
+    /// Block verification queue
+    pub queue: CacheUsage,
+    /// Transaction queue
+    #[serde(rename = "txQueue")]
+    pub tx_queue: CacheUsage,
+    /// Sum of the budgets
+    #[serde(rename = "totalBudget")]
+    pub total_budget: U256,
+}
+
+impl From<EthMemoryUsage> for MemoryUsage {
+    fn from(usage: EthMemoryUsage) -> Self {
+        let budget = usage.budget;
+        MemoryUsage {
+            blockchain: CacheUsage {
+                used: Some(usage.blockchain.into()),
+                budget: Some(budget.blockchain.into()),
+            },
+            state: CacheUsage {
+                used: Some(usage.state.into()),
+                budget: Some(budget.state.into()),
+            },
+            db: CacheUsage {
+                used: None,
+                budget: budget.db.map(Into::into),
+            },
+            queue: CacheUsage {
+                used: Some(usage.queue.into()),
+                budget: Some(budget.queue.into()),
+            },
+            tx_queue: CacheUsage {
+                used: Some(usage.tx_queue.into()),
+                budget: budget.tx_queue.map(Into::into),
+            },
+            total_budget: budget.total().into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use acore::client::{MemoryBudget, MemoryUsage as EthMemoryUsage};
+    use super::MemoryUsage;
+
+    #[test]
+    fn memory_usage_serialization() {
+        let usage = EthMemoryUsage {
+            budget: MemoryBudget {
+                blockchain: 16,
+                state: 32,
+                db: None,
+                queue: 8,
+                tx_queue: Some(4),
+            },
+            blockchain: 1,
+            state: 2,
+            queue: 3,
+            tx_queue: 5,
+        };
+
+        let serialized = serde_json::to_string(&MemoryUsage::from(usage)).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"blockchain":{"used":"0x1","budget":"0x10"},"state":{"used":"0x2","budget":"0x20"},"db":{"used":null,"budget":null},"queue":{"used":"0x3","budget":"0x8"},"txQueue":{"used":"0x5","budget":"0x4"},"totalBudget":"0x3c"}"#
+        );
+    }
+}
@@ -42,6 +42,8 @@ pub struct Work {
     pub reward: U256,
     /// block total transaction fees.
     pub transaction_fee: U256,
+    /// Long-poll id, to wait for a change of this work.
+    pub long_poll_id: H256,
 }
 
 impl Serialize for Work {
@@ -52,13 +54,14 @@ impl Serialize for Work {
     where
         S: Serializer,
     {
-        let mut work = serializer.serialize_struct("Work", 6)?;
+        let mut work = serializer.serialize_struct("Work", 7)?;
         work.serialize_field("headerHash", &self.pow_hash.0.to_hex())?;
         work.serialize_field("previousblockhash", &self.parent_hash.0.to_hex())?;
         work.serialize_field("target", &self.target.0.to_hex())?;
         work.serialize_field("height", &self.number)?;
         work.serialize_field("blockBaseReward", &format!("{:x}", self.reward))?;
         work.serialize_field("blockTxFee", &format!("{:x}", self.transaction_fee))?;
+        work.serialize_field("longpollid", &self.long_poll_id.0.to_hex())?;
         work.end()
     }
 }
@@ -99,6 +102,7 @@ pub struct MinerStats {
     pub miner_hashrate_share: f64,
     pub miner_hashrate: f64,
     pub network_hashrate: f64,
+    pub workers: Vec<WorkerShares>,
 }
 
 impl Serialize for MinerStats {
@@ -109,10 +113,21 @@ impl Serialize for MinerStats {
     where
         S: Serializer,
     {
-        let mut stats = serializer.serialize_struct("MinerStats", 3)?;
+        let mut stats = serializer.serialize_struct("MinerStats", 4)?;
         stats.serialize_field("minerHashrateShare", &self.miner_hashrate_share)?;
         stats.serialize_field("minerHashrate", &format!("{:.4}", self.miner_hashrate))?;
         stats.serialize_field("networkHashrate", &format!("{:.4}", self.network_hashrate))?;
+        stats.serialize_field("workers", &self.workers)?;
         stats.end()
     }
 }
+
+// Share accounting of a stratum worker of the miner
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct WorkerShares {
+    pub worker: String,
+    pub difficulty: U256,
+    pub accepted: u64,
+    pub stale: u64,
+    pub invalid: u64,
+}
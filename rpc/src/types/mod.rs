@@ -27,16 +27,22 @@ mod template_param;
 mod block;
 mod block_number;
 mod bytes;
+mod call_many;
 mod call_request;
 mod confirmations;
 mod contract;
+mod dump;
+mod fee_history;
 mod filter;
 mod hash;
 mod histogram;
 mod index;
 mod log;
+mod memory;
 mod node_kind;
+mod orphans;
 mod provenance;
+mod pruning;
 mod receipt;
 mod rpc_settings;
 mod secretstore;
@@ -54,27 +60,33 @@ pub use self::block::{Block, BlockTransactions, Header};
 pub use self::block_number::BlockNumber;
 pub use self::template_param::TemplateParam;
 pub use self::stratum_header::{SimpleHeader, StratumHeader};
+pub use self::call_many::{AccountOverride, CallResult, StateOverride};
 pub use self::call_request::CallRequest;
 pub use self::confirmations::{
     ConfirmationPayload, ConfirmationRequest, ConfirmationResponse, ConfirmationResponseWithToken,
     TransactionModification, SignRequest, DecryptRequest
 };
 pub use self::contract::{Contract, ContractInfo, Abi, AbiIO};
+pub use self::dump::{AccountDump, BlockDump};
+pub use self::fee_history::{BlockFees, FeeHistory, SealFees};
 pub use self::filter::{Filter, FilterChanges};
 pub use self::hash::{H64, H128, H160, H256, H512, H520, H768, H2048};
 pub use self::histogram::Histogram;
 pub use self::index::Index;
 pub use self::log::Log;
+pub use self::memory::{CacheUsage, MemoryUsage};
 pub use self::node_kind::{NodeKind, Availability, Capability};
+pub use self::orphans::{OrphanedBlock, ReorgStats};
 pub use self::provenance::Origin;
+pub use self::pruning::PruningStats;
 pub use self::receipt::{Receipt, SimpleReceipt, SimpleReceiptLog};
 pub use self::rpc_settings::RpcSettings;
 pub use self::secretstore::EncryptedDocumentKey;
 pub use self::sync::{
-    SyncStatus, SyncInfo, Peers, PeerInfo, PeerNetworkInfo, TransactionStats, ChainStatus, AcitvePeerInfo, PbSyncInfo
+    SyncStatus, SyncInfo, Peers, PeerInfo, PeerNetworkInfo, NodeInfo, TransactionStats, ChainStatus, AcitvePeerInfo, PbSyncInfo
 };
 pub use self::transaction::{Transaction, RichRawTransaction};
 pub use self::transaction_request::TransactionRequest;
 pub use self::transaction_condition::TransactionCondition;
 pub use self::uint::{U128, U256, U64};
-pub use self::mining::{Work, Info, AddressValidation, MiningInfo, MinerStats};
+pub use self::mining::{Work, Info, AddressValidation, MiningInfo, MinerStats, WorkerShares};
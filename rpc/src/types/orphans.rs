@@ -0,0 +1,108 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Blocks retracted by chain reorganisations.
+
+use types::{H256, U256};
+
+/// A block retracted from the canonical chain by a reorganisation.
+#[derive(Debug, Serialize)]
+pub struct OrphanedBlock {
+    /// Block hash
+    pub hash: H256,
+    /// Block number
+    pub number: U256,
+    /// Block author
+    pub miner: H256,
+    /// Seal type, `pow` or `pos`
+    #[serde(rename = "sealType")]
+    pub seal_type: String,
+    /// Block difficulty
+    pub difficulty: U256,
+    /// Number of blocks retracted by the reorganisation
+    #[serde(rename = "reorgDepth")]
+    pub reorg_depth: U256,
+    /// Best block hash after the reorganisation
+    #[serde(rename = "newBest")]
+    pub new_best: H256,
+}
+
+/// Reorganisations of the canonical chain since the node started.
+#[derive(Debug, Default, Serialize)]
+pub struct ReorgStats {
+    /// Number of reorganisations
+    pub reorgs: U256,
+    /// Number of blocks retracted by the last reorganisation
+    #[serde(rename = "lastDepth")]
+    pub last_depth: U256,
+    /// Highest number of blocks retracted by a reorganisation
+    #[serde(rename = "maxDepth")]
+    pub max_depth: U256,
+    /// Number of retracted proof-of-work blocks
+    #[serde(rename = "orphanedPow")]
+    pub orphaned_pow: U256,
+    /// Number of retracted proof-of-stake blocks
+    #[serde(rename = "orphanedPos")]
+    pub orphaned_pos: U256,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use aion_types::H256;
+    use super::{OrphanedBlock, ReorgStats};
+
+    #[test]
+    fn orphaned_block_serialization() {
+        let orphan = OrphanedBlock {
+            hash: H256::from(1).into(),
+            number: 9.into(),
+            miner: H256::from(2).into(),
+            seal_type: "pos".into(),
+            difficulty: 16.into(),
+            reorg_depth: 1.into(),
+            new_best: H256::from(3).into(),
+        };
+
+        let serialized = serde_json::to_string(&orphan).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000001","number":"0x9","miner":"0x0000000000000000000000000000000000000000000000000000000000000002","sealType":"pos","difficulty":"0x10","reorgDepth":"0x1","newBest":"0x0000000000000000000000000000000000000000000000000000000000000003"}"#
+        );
+    }
+
+    #[test]
+    fn reorg_stats_serialization() {
+        let stats = ReorgStats {
+            reorgs: 2.into(),
+            last_depth: 1.into(),
+            max_depth: 3.into(),
+            orphaned_pow: 3.into(),
+            orphaned_pos: 1.into(),
+        };
+
+        let serialized = serde_json::to_string(&stats).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"reorgs":"0x2","lastDepth":"0x1","maxDepth":"0x3","orphanedPow":"0x3","orphanedPos":"0x1"}"#
+        );
+    }
+}
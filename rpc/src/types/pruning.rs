@@ -0,0 +1,67 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! State pruning statistics.
+
+use types::U256;
+
+/// Availability of historical state and progress of state pruning.
+#[derive(Debug, Serialize)]
+pub struct PruningStats {
+    /// First block which everything can be served after
+    #[serde(rename = "earliestChain")]
+    pub earliest_chain: U256,
+    /// Earliest block whose state is queryable
+    #[serde(rename = "earliestState")]
+    pub earliest_state: U256,
+    /// Interval of the earlier blocks whose state is kept
+    #[serde(rename = "checkpointInterval")]
+    pub checkpoint_interval: Option<U256>,
+    /// Size of the state database on disk in bytes
+    #[serde(rename = "stateSize")]
+    pub state_size: Option<U256>,
+    /// Number of blocks past the pruning history still waiting to be pruned
+    #[serde(rename = "pruningBacklog")]
+    pub pruning_backlog: U256,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use super::PruningStats;
+
+    #[test]
+    fn pruning_stats_serialization() {
+        let stats = PruningStats {
+            earliest_chain: 1.into(),
+            earliest_state: 1000.into(),
+            checkpoint_interval: Some(100.into()),
+            state_size: None,
+            pruning_backlog: 2.into(),
+        };
+
+        let serialized = serde_json::to_string(&stats).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"earliestChain":"0x1","earliestState":"0x3e8","checkpointInterval":"0x64","stateSize":null,"pruningBacklog":"0x2"}"#
+        );
+    }
+}
@@ -141,6 +141,7 @@ mod tests {
                 address: None,
                 topics: None,
                 limit: None,
+                offset: None,
             })
         );
         assert_eq!(
@@ -151,6 +152,7 @@ mod tests {
                 address: None,
                 topics: None,
                 limit: Some(10),
+                offset: None,
             })
         );
         assert_eq!(
@@ -165,6 +167,7 @@ mod tests {
                         .unwrap()
                 )]),
                 limit: None,
+                offset: None,
             })
         );
     }
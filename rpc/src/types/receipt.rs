@@ -26,6 +26,8 @@ use acore::receipt::{Receipt as EthReceipt, RichReceipt, LocalizedReceipt};
 use serde::ser::{Serialize, Serializer, SerializeStruct};
 use rustc_hex::ToHex;
 use aion_types::{H256 as AH256};
+use abi::decode_revert_reason;
+use vms::EvmStatusCode;
 
 #[derive(Debug, Clone)]
 pub struct SimpleReceiptLog {
@@ -93,6 +95,10 @@ pub struct Receipt {
     pub output: Option<Bytes>,
     /// status
     pub status: Option<String>,
+    /// vm status code, e.g. `REVERT` or `OUT_OF_NRG`
+    pub vm_status: Option<String>,
+    /// decoded Solidity revert reason
+    pub revert_reason: Option<String>,
 }
 
 impl Serialize for Receipt {
@@ -103,7 +109,7 @@ impl Serialize for Receipt {
     where
         S: Serializer,
     {
-        let mut receipt = serializer.serialize_struct("Receipt", 18)?;
+        let mut receipt = serializer.serialize_struct("Receipt", 20)?;
         receipt.serialize_field("transactionHash", &self.transaction_hash)?;
         receipt.serialize_field("transactionIndex", &self.transaction_index)?;
         receipt.serialize_field("blockHash", &self.block_hash)?;
@@ -121,6 +127,8 @@ impl Serialize for Receipt {
         receipt.serialize_field("logsBloom", &self.logs_bloom.0.to_hex())?;
         receipt.serialize_field("root", &self.state_root.clone().map(|x| x.0.to_hex()))?;
         receipt.serialize_field("status", &self.status)?;
+        receipt.serialize_field("vmStatus", &self.vm_status)?;
+        receipt.serialize_field("revertReason", &self.revert_reason)?;
 
         let mut receipt_logs = Vec::new();
         for l in &self.logs {
@@ -142,6 +150,13 @@ impl Serialize for Receipt {
 
 impl From<LocalizedReceipt> for Receipt {
     fn from(r: LocalizedReceipt) -> Self {
+        // Failures not raised by the vm (e.g. balance transfer) are reported as generic ones.
+        let vm_status =
+            EvmStatusCode::from_exception(&r.error_message).unwrap_or(EvmStatusCode::Failure);
+        let revert_reason = match vm_status {
+            EvmStatusCode::Revert => decode_revert_reason(&r.output),
+            _ => None,
+        };
         Receipt {
             transaction_hash: Some(r.transaction_hash.into()),
             transaction_index: Some(r.transaction_index.into()),
@@ -162,6 +177,8 @@ impl From<LocalizedReceipt> for Receipt {
                 "" => Some(String::from("0x1")),
                 _ => Some(String::from("0x0")),
             },
+            vm_status: Some(vm_status.name().to_owned()),
+            revert_reason: revert_reason,
         }
     }
 }
@@ -185,6 +202,8 @@ impl From<RichReceipt> for Receipt {
             to: None,
             output: None,
             status: None,
+            vm_status: None,
+            revert_reason: None,
         }
     }
 }
@@ -208,6 +227,8 @@ impl From<EthReceipt> for Receipt {
             to: None,
             output: None,
             status: None,
+            vm_status: None,
+            revert_reason: None,
         }
     }
 }
@@ -215,11 +236,13 @@ impl From<EthReceipt> for Receipt {
 #[cfg(test)]
 mod tests {
     use serde_json;
+    use rustc_hex::FromHex;
+    use acore::receipt::LocalizedReceipt;
     use types::{Log, Receipt, Bytes};
 
     #[test]
     fn receipt_serialization() {
-        let s = r#"{"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0","blockHash":"0x4ded588468a978226870a3b44388439a9debfccb7bcae9843b8503fe744cc599","blockNumber":"0x4510c","cumulativeGasUsed":"0x20","cumulativeNrgUsed":"0x20","gasUsed":"0x10","nrgUsed":"0x10","gasPrice":"0x10","nrgPrice":"0x10","gasLimit":"0x10","contractAddress":null,"from":"0xa00a2d0d10ce8a2ea47a76fbb935405df2a12b0e2bc932f188f84b5f16da9c2c","to":"0xa054340a3152d10006b66c4248cfa73e5725056294081c476c0e67ef5ad25334","logsBloom":"0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000f","root":"000000000000000000000000000000000000000000000000000000000000000a","status":null,"logs":[{"address":"0xa00a2d0d10ce8a2ea47a76fbb935405df2a12b0e2bc932f188f84b5f16da9c2c","topics":["0xa6697e974e6a320f454390be03f74955e8978f1a6971ea6730542e37b66179bc","0x4861736852656700000000000000000000000000000000000000000000000000"],"data":"0x","blockNumber":"0x4510c","transactionIndex":"0x0","logIndex":"0x1"}]}"#;
+        let s = r#"{"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0","blockHash":"0x4ded588468a978226870a3b44388439a9debfccb7bcae9843b8503fe744cc599","blockNumber":"0x4510c","cumulativeGasUsed":"0x20","cumulativeNrgUsed":"0x20","gasUsed":"0x10","nrgUsed":"0x10","gasPrice":"0x10","nrgPrice":"0x10","gasLimit":"0x10","contractAddress":null,"from":"0xa00a2d0d10ce8a2ea47a76fbb935405df2a12b0e2bc932f188f84b5f16da9c2c","to":"0xa054340a3152d10006b66c4248cfa73e5725056294081c476c0e67ef5ad25334","logsBloom":"0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000f","root":"000000000000000000000000000000000000000000000000000000000000000a","status":null,"vmStatus":null,"revertReason":null,"logs":[{"address":"0xa00a2d0d10ce8a2ea47a76fbb935405df2a12b0e2bc932f188f84b5f16da9c2c","topics":["0xa6697e974e6a320f454390be03f74955e8978f1a6971ea6730542e37b66179bc","0x4861736852656700000000000000000000000000000000000000000000000000"],"data":"0x","blockNumber":"0x4510c","transactionIndex":"0x0","logIndex":"0x1"}]}"#;
 
         let receipt = Receipt {
             transaction_hash: Some(0.into()),
@@ -274,9 +297,68 @@ mod tests {
             gas_price: Some(0x10.into()),
             output: Some(Bytes::new(vec![])),
             status: None,
+            vm_status: None,
+            revert_reason: None,
         };
 
         let serialized = serde_json::to_string(&receipt).unwrap();
         assert_eq!(serialized, s);
     }
+
+    fn localized_receipt(output: &str, error_message: &str) -> LocalizedReceipt {
+        LocalizedReceipt {
+            transaction_hash: 1.into(),
+            transaction_index: 0,
+            block_hash: 2.into(),
+            block_number: 0x4510c,
+            cumulative_gas_used: 0x20.into(),
+            gas_used: 0x10.into(),
+            contract_address: None,
+            logs: vec![],
+            log_bloom: Default::default(),
+            state_root: 3.into(),
+            gas_price: 0x10.into(),
+            gas_limit: 0x10.into(),
+            from: None,
+            to: None,
+            output: output.from_hex().unwrap(),
+            error_message: error_message.into(),
+        }
+    }
+
+    #[test]
+    fn receipt_vm_status() {
+        let receipt: Receipt = localized_receipt("", "").into();
+        assert_eq!(receipt.status, Some("0x1".into()));
+        assert_eq!(receipt.vm_status, Some("SUCCESS".into()));
+        assert_eq!(receipt.revert_reason, None);
+
+        let receipt: Receipt = localized_receipt("", "Out of gas").into();
+        assert_eq!(receipt.status, Some("0x0".into()));
+        assert_eq!(receipt.vm_status, Some("OUT_OF_NRG".into()));
+        assert_eq!(receipt.revert_reason, None);
+
+        let receipt: Receipt = localized_receipt("", "Error in balance transfer").into();
+        assert_eq!(receipt.vm_status, Some("FAILURE".into()));
+    }
+
+    #[test]
+    fn receipt_revert_reason() {
+        // Error("not owner")
+        let output = concat!(
+            "4b2bae7e",
+            "0000000000000000000000000000000000000000000000000000000000000020",
+            "0000000000000000000000000000000000000000000000000000000000000009",
+            "6e6f74206f776e65720000000000000000000000000000000000000000000000"
+        );
+        let receipt: Receipt = localized_receipt(output, "Revert").into();
+        assert_eq!(receipt.status, Some("0x0".into()));
+        assert_eq!(receipt.vm_status, Some("REVERT".into()));
+        assert_eq!(receipt.revert_reason, Some("not owner".into()));
+
+        // plain revert without a reason
+        let receipt: Receipt = localized_receipt("", "Revert").into();
+        assert_eq!(receipt.vm_status, Some("REVERT".into()));
+        assert_eq!(receipt.revert_reason, None);
+    }
 }
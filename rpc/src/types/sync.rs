@@ -22,7 +22,7 @@
 
 use std::collections::BTreeMap;
 use serde::{Serialize, Serializer};
-use types::{U256, H512};
+use types::{U256, H256, H512};
 
 /// Sync info
 #[derive(Default, Debug, Serialize, PartialEq)]
@@ -62,6 +62,14 @@ pub struct PeerInfo {
     pub caps: Vec<String>,
     /// Network information
     pub network: PeerNetworkInfo,
+    /// Sync mode
+    pub mode: String,
+    /// Best block number
+    #[serde(rename = "highestBlockNumber")]
+    pub highest_block_number: u64,
+    /// Total difficulty
+    #[serde(rename = "totalDifficulty")]
+    pub total_difficulty: U256,
 }
 
 /// Peer network information
@@ -75,6 +83,33 @@ pub struct PeerNetworkInfo {
     pub local_address: String,
 }
 
+/// Local node information
+#[derive(Default, Debug, Serialize)]
+pub struct NodeInfo {
+    /// Node url (`p2p://<node id>@<ip>:<port>`), if the network is up
+    pub enode: Option<String>,
+    /// Client version
+    pub version: String,
+    /// Network id
+    #[serde(rename = "netId")]
+    pub net_id: u32,
+    /// Max number of peers
+    #[serde(rename = "maxPeers")]
+    pub max_peers: u32,
+    /// Best block number
+    #[serde(rename = "bestBlockNumber")]
+    pub best_block_number: u64,
+    /// Best block hash
+    #[serde(rename = "bestBlockHash")]
+    pub best_block_hash: H256,
+    /// Total difficulty of the best block
+    #[serde(rename = "totalDifficulty")]
+    pub total_difficulty: U256,
+    /// Genesis block hash
+    #[serde(rename = "genesisHash")]
+    pub genesis_hash: H256,
+}
+
 /// Sync status
 #[derive(Debug, PartialEq)]
 pub enum SyncStatus {
@@ -142,7 +177,7 @@ pub struct ChainStatus {
 mod tests {
     use serde_json;
     use std::collections::BTreeMap;
-    use super::{SyncInfo, SyncStatus, Peers, TransactionStats, ChainStatus};
+    use super::{SyncInfo, SyncStatus, Peers, PeerInfo, NodeInfo, TransactionStats, ChainStatus};
 
     #[test]
     fn test_serialize_sync_info() {
@@ -164,6 +199,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_serialize_peer_info() {
+        let t = PeerInfo::default();
+        let serialized = serde_json::to_string(&t).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"id":null,"name":"","caps":[],"network":{"remoteAddress":"","localAddress":""},"mode":"","highestBlockNumber":0,"totalDifficulty":"0x0"}"#
+        );
+    }
+
+    #[test]
+    fn test_serialize_node_info() {
+        let t = NodeInfo::default();
+        let serialized = serde_json::to_string(&t).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"enode":null,"version":"","netId":0,"maxPeers":0,"bestBlockNumber":0,"bestBlockHash":"0x0000000000000000000000000000000000000000000000000000000000000000","totalDifficulty":"0x0","genesisHash":"0x0000000000000000000000000000000000000000000000000000000000000000"}"#
+        );
+    }
+
     #[test]
     fn test_serialize_sync_status() {
         let t = SyncStatus::None;
@@ -23,7 +23,31 @@
 #[derive(Default, Debug, Deserialize)]
 pub struct TemplateParam {
     // capabilities, not in use
+    #[serde(default)]
     pub capabilities: Vec<String>,
     // rules, not in use
+    #[serde(default)]
     pub rules: Vec<String>,
+    // long-poll id of the work the miner has, the call blocks until the work changes
+    #[serde(rename = "longpollid")]
+    pub long_poll_id: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use super::TemplateParam;
+
+    #[test]
+    fn template_param_deserialization() {
+        let s = r#"{"capabilities": ["longpoll"], "rules": []}"#;
+        let deserialized: TemplateParam = serde_json::from_str(s).unwrap();
+        assert_eq!(deserialized.capabilities, vec!["longpoll".to_owned()]);
+        assert_eq!(deserialized.long_poll_id, None);
+
+        let s = r#"{"longpollid": "0x01"}"#;
+        let deserialized: TemplateParam = serde_json::from_str(s).unwrap();
+        assert!(deserialized.capabilities.is_empty());
+        assert_eq!(deserialized.long_poll_id, Some("0x01".to_owned()));
+    }
 }
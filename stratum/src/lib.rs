@@ -40,10 +40,12 @@ extern crate tokio_io;
 extern crate logger;
 
 mod traits;
+mod workers;
 
 pub use traits::{
-    JobDispatcher, PushWorkHandler, Error, ServiceConfiguration,
+    JobDispatcher, PushWorkHandler, Error, ServiceConfiguration, ShareResult,
 };
+pub use workers::{ShareDifficulty, WorkerStats};
 
 use jsonrpc_tcp_server::{
     Server as JsonRpcServer, ServerBuilder as JsonRpcServerBuilder,
@@ -52,12 +54,14 @@ use jsonrpc_tcp_server::{
 use jsonrpc_core::{MetaIoHandler, Params, to_value, Value, Metadata, Compatibility};
 use jsonrpc_macros::IoDelegate;
 use std::sync::Arc;
+use std::time::Instant;
 
 use std::net::SocketAddr;
 use std::collections::{HashSet, HashMap};
 use blake2b::blake2b;
-use aion_types::H256;
+use aion_types::{clean_0x, H256, U256};
 use parking_lot::RwLock;
+use workers::{RecentShares, Worker};
 
 type RpcResult = Result<jsonrpc_core::Value, jsonrpc_core::Error>;
 
@@ -80,10 +84,13 @@ pub struct Stratum {
 }
 
 impl Stratum {
+    /// Starts the stratum server. Shares are checked against the block target unless a share
+    /// difficulty is given.
     pub fn start(
         addr: &SocketAddr,
         dispatcher: Arc<JobDispatcher>,
         secret: Option<H256>,
+        share_difficulty: Option<ShareDifficulty>,
     ) -> Result<Arc<Stratum>, Error>
     {
         let implementation = Arc::new(StratumImpl {
@@ -91,6 +98,9 @@ impl Stratum {
             job_que: RwLock::new(HashSet::new()),
             dispatcher,
             workers: Arc::new(RwLock::new(HashMap::new())),
+            worker_stats: RwLock::new(HashMap::new()),
+            recent_shares: RwLock::new(RecentShares::new()),
+            share_difficulty,
             secret,
             notify_counter: RwLock::new(NOTIFY_COUNTER_INITIAL),
         });
@@ -116,6 +126,16 @@ impl Stratum {
 
         Ok(stratum)
     }
+
+    /// Share accounting of the workers authorized since the start
+    pub fn worker_stats(&self) -> Vec<WorkerStats> {
+        self.implementation
+            .worker_stats
+            .read()
+            .values()
+            .cloned()
+            .collect()
+    }
 }
 
 impl PushWorkHandler for Stratum {
@@ -144,8 +164,14 @@ struct StratumImpl {
     job_que: RwLock<HashSet<SocketAddr>>,
     /// Payload manager
     dispatcher: Arc<JobDispatcher>,
-    /// Authorized workers (socket - worker_id)
-    workers: Arc<RwLock<HashMap<SocketAddr, String>>>,
+    /// Authorized workers
+    workers: Arc<RwLock<HashMap<SocketAddr, Worker>>>,
+    /// Share accounting by worker_id
+    worker_stats: RwLock<HashMap<String, WorkerStats>>,
+    /// Accepted shares, to reject duplicates
+    recent_shares: RwLock<RecentShares>,
+    /// Share difficulty if any, else shares are checked against the block target
+    share_difficulty: Option<ShareDifficulty>,
     /// Secret if any
     secret: Option<H256>,
     /// Dispatch notify couinter
@@ -188,7 +214,13 @@ impl StratumImpl {
                     }
                 }
                 trace!(target: "stratum", "New worker #{} registered", worker_id);
-                self.workers.write().insert(meta.addr().clone(), worker_id);
+                let worker = Worker::new(worker_id.clone(), self.share_difficulty.as_ref());
+                self.worker_stats
+                    .write()
+                    .entry(worker_id.clone())
+                    .or_insert_with(|| WorkerStats::new(worker_id))
+                    .difficulty = worker.difficulty;
+                self.workers.write().insert(meta.addr().clone(), worker);
                 to_value(true)
             })
             .map(|v| v.expect("Only true/false is returned and it's always serializable; qed"))
@@ -199,27 +231,35 @@ impl StratumImpl {
         Ok(match params {
             Params::Array(vals) => {
                 // first two elements are service messages (miner address & worker_id & timestamp)
-                match self.dispatcher.submit(
-                    vals.iter()
-                        .skip(3)
-                        .filter_map(|val| {
-                            match *val {
-                                Value::String(ref s) => Some(s.to_owned()),
-                                _ => None,
-                            }
-                        })
-                        .collect::<Vec<String>>(),
-                ) {
-                    Ok(()) => {
-                        self.update_peers(
-                            &meta
-                                .tcp_dispatcher
-                                .expect("tcp_dispatcher is always initialized; qed"),
-                        );
+                let payload = vals
+                    .iter()
+                    .skip(3)
+                    .filter_map(|val| {
+                        match *val {
+                            Value::String(ref s) => Some(s.to_owned()),
+                            _ => None,
+                        }
+                    })
+                    .collect::<Vec<String>>();
+                let tcp_dispatcher = meta
+                    .tcp_dispatcher
+                    .expect("tcp_dispatcher is always initialized; qed");
+                let (result, retarget) = self.submit_share(&meta.addr, payload);
+                if let Some(difficulty) = retarget {
+                    self.push_difficulty(&meta.addr, &difficulty, &tcp_dispatcher);
+                }
+                match result {
+                    ShareResult::Block => {
+                        self.update_peers(&tcp_dispatcher);
                         to_value(true)
                     }
-                    Err(submit_err) => {
-                        warn!(target: "stratum","Error while submitting share: {:?}", submit_err);
+                    ShareResult::Accepted => to_value(true),
+                    ShareResult::Stale => {
+                        trace!(target: "stratum", "Stale share from {}", meta.addr);
+                        to_value(false)
+                    }
+                    ShareResult::Invalid(e) => {
+                        warn!(target: "stratum","Error while submitting share: {}", e);
                         to_value(false)
                     }
                 }
@@ -232,6 +272,77 @@ impl StratumImpl {
         .expect("Only true/false is returned and it's always serializable; qed"))
     }
 
+    /// Checks a share against the share target of the worker and accounts for it, returns the
+    /// new share difficulty of the worker if it was retargeted
+    fn submit_share(&self, addr: &SocketAddr, payload: Vec<String>) -> (ShareResult, Option<U256>) {
+        let share = blake2b(
+            payload
+                .iter()
+                .map(|p| clean_0x(p).to_lowercase())
+                .collect::<Vec<String>>()
+                .join(","),
+        );
+        let worker = self
+            .workers
+            .read()
+            .get(addr)
+            .map(|w| (w.id.clone(), w.share_target()));
+
+        let result = if self.recent_shares.read().contains(&share) {
+            ShareResult::Invalid("Duplicate share".into())
+        } else {
+            let share_target = worker.as_ref().and_then(|w| w.1);
+            match self.dispatcher.submit_share(payload, share_target) {
+                // only valid shares are remembered, a rejected one may be resubmitted
+                ShareResult::Accepted | ShareResult::Block
+                    if !self.recent_shares.write().insert(share) =>
+                {
+                    ShareResult::Invalid("Duplicate share".into())
+                }
+                result => result,
+            }
+        };
+
+        let (worker_id, _) = match worker {
+            Some(worker) => worker,
+            None => return (result, None),
+        };
+        let mut retarget = None;
+        if let Some(worker) = self.workers.write().get_mut(addr) {
+            if result == ShareResult::Accepted || result == ShareResult::Block {
+                worker.on_accepted();
+                if let Some(ref share_difficulty) = self.share_difficulty {
+                    retarget = worker.retarget(share_difficulty, Instant::now());
+                }
+            }
+        }
+        let mut worker_stats = self.worker_stats.write();
+        let stats = worker_stats
+            .entry(worker_id.clone())
+            .or_insert_with(|| WorkerStats::new(worker_id));
+        match result {
+            ShareResult::Accepted | ShareResult::Block => stats.accepted += 1,
+            ShareResult::Stale => stats.stale += 1,
+            ShareResult::Invalid(_) => stats.invalid += 1,
+        }
+        if let Some(difficulty) = retarget {
+            stats.difficulty = difficulty;
+        }
+        (result, retarget)
+    }
+
+    /// Tells a worker its share difficulty
+    fn push_difficulty(&self, addr: &SocketAddr, difficulty: &U256, tcp_dispatcher: &Dispatcher) {
+        match tcp_dispatcher.push_message(addr, difficulty_message(difficulty)) {
+            Ok(_) => {
+                if let Some(worker) = self.workers.write().get_mut(addr) {
+                    worker.difficulty_sent = true;
+                }
+            }
+            Err(e) => warn!(target: "stratum", "Failed to push share difficulty to {}: {:?}", addr, e),
+        }
+    }
+
     /// Helper method
     fn update_peers(&self, tcp_dispatcher: &Dispatcher) {
         if let Some(job) = self.dispatcher.job() {
@@ -243,7 +354,7 @@ impl StratumImpl {
 
     fn push_work_all(&self, payload: String, tcp_dispatcher: &Dispatcher) -> Result<(), Error> {
         let hup_peers = {
-            let workers = self.workers.read();
+            let mut workers = self.workers.write();
             let next_request_id = {
                 let mut counter = self.notify_counter.write();
                 if *counter == ::std::u32::MAX {
@@ -260,12 +371,25 @@ impl StratumImpl {
                 next_request_id, payload
             );
             trace!(target: "stratum", "pushing work for {} workers (payload: '{}')", workers.len(), &workers_msg);
-            for (ref addr, _) in workers.iter() {
+            let now = Instant::now();
+            for (addr, worker) in workers.iter_mut() {
+                if let Some(ref share_difficulty) = self.share_difficulty {
+                    // workers without shares get their share difficulty lowered here
+                    if let Some(difficulty) = worker.retarget(share_difficulty, now) {
+                        if let Some(stats) = self.worker_stats.write().get_mut(&worker.id) {
+                            stats.difficulty = difficulty;
+                        }
+                    }
+                    if !worker.difficulty_sent {
+                        let message = difficulty_message(&worker.difficulty);
+                        worker.difficulty_sent = tcp_dispatcher.push_message(addr, message).is_ok();
+                    }
+                }
                 trace!(target: "stratum", "pushing work to {}", addr);
                 match tcp_dispatcher.push_message(addr, workers_msg.clone()) {
                     Err(PushMessageError::NoSuchPeer) => {
                         trace!(target: "stratum", "Worker no longer connected: {}", &addr);
-                        hup_peers.insert(*addr.clone());
+                        hup_peers.insert(*addr);
                     }
                     Err(e) => {
                         warn!(target: "stratum", "Unexpected transport error: {:?}", e);
@@ -312,6 +436,14 @@ impl StratumImpl {
     }
 }
 
+/// `mining.set_difficulty` notification
+fn difficulty_message(difficulty: &U256) -> String {
+    format!(
+        "{{ \"id\": 0, \"method\": \"mining.set_difficulty\", \"params\": [{}] }}",
+        difficulty
+    )
+}
+
 #[derive(Clone)]
 pub struct SocketMetadata {
     addr: SocketAddr,
@@ -399,6 +531,7 @@ mod tests {
             &SocketAddr::from_str("127.0.0.1:19980").unwrap(),
             Arc::new(VoidManager),
             None,
+            None,
         );
         assert!(stratum.is_ok());
     }
@@ -408,12 +541,47 @@ mod tests {
         init_log();
 
         let addr = SocketAddr::from_str("127.0.0.1:19985").unwrap();
-        let stratum = Stratum::start(&addr, Arc::new(VoidManager), None).unwrap();
+        let stratum = Stratum::start(&addr, Arc::new(VoidManager), None, None).unwrap();
         let request = r#"{"jsonrpc": "2.0", "method": "mining.subscribe", "params": [], "id": 1}"#;
         dummy_request(&addr, request);
         assert_eq!(1, stratum.implementation.subscribers.read().len());
     }
 
+    struct RejectingManager {
+        reject: ::std::sync::atomic::AtomicBool,
+    }
+
+    impl JobDispatcher for RejectingManager {
+        fn submit(&self, _payload: Vec<String>) -> Result<(), Error> {
+            if self.reject.load(::std::sync::atomic::Ordering::SeqCst) {
+                Err(Error::Dispatch("invalid solution".into()))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[test]
+    fn remembers_only_valid_shares() {
+        let addr = SocketAddr::from_str("127.0.0.1:19986").unwrap();
+        let manager = Arc::new(RejectingManager {
+            reject: ::std::sync::atomic::AtomicBool::new(true),
+        });
+        let stratum = Stratum::start(&addr, manager.clone(), None, None).unwrap();
+        let share = || vec!["worker".to_owned(), "0x01".to_owned(), "0x02".to_owned()];
+
+        let (rejected, _) = stratum.implementation.submit_share(&addr, share());
+        manager
+            .reject
+            .store(false, ::std::sync::atomic::Ordering::SeqCst);
+        let (accepted, _) = stratum.implementation.submit_share(&addr, share());
+        let (duplicate, _) = stratum.implementation.submit_share(&addr, share());
+
+        assert_eq!(rejected, ShareResult::Invalid("Dispatch(\"invalid solution\")".into()));
+        assert_eq!(accepted, ShareResult::Block);
+        assert_eq!(duplicate, ShareResult::Invalid("Duplicate share".into()));
+    }
+
     struct DummyManager {
         initial_payload: String,
     }
@@ -449,7 +617,7 @@ mod tests {
     #[test]
     fn receives_initial_paylaod() {
         let addr = SocketAddr::from_str("127.0.0.1:19975").unwrap();
-        let _stratum = Stratum::start(&addr, DummyManager::new(), None)
+        let _stratum = Stratum::start(&addr, DummyManager::new(), None, None)
             .expect("There should be no error starting stratum");
         let request = r#"{"jsonrpc": "2.0", "method": "mining.subscribe", "params": [], "id": 2}"#;
 
@@ -468,6 +636,7 @@ mod tests {
             &addr,
             Arc::new(DummyManager::build().of_initial(r#"["dummy autorize payload"]"#)),
             None,
+            None,
         )
         .expect("There should be no error starting stratum");
 
@@ -490,6 +659,7 @@ mod tests {
             &addr,
             Arc::new(DummyManager::build().of_initial(r#"["dummy autorize payload"]"#)),
             None,
+            None,
         )
         .expect("There should be no error starting stratum");
 
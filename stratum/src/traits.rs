@@ -42,6 +42,19 @@ impl From<PushMessageError> for Error {
     fn from(err: PushMessageError) -> Self { Error::Tcp(format!("Push message error: {:?}", err)) }
 }
 
+/// Outcome of a share submitted by a worker
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShareResult {
+    /// The share meets the share target
+    Accepted,
+    /// The share meets the block target as well, the block was submitted
+    Block,
+    /// The share solves work which is unknown or out of date
+    Stale,
+    /// The share is malformed, has an invalid solution or misses the share target
+    Invalid(String),
+}
+
 /// Interface that can provide pow/blockchain-specific responses for the clients
 pub trait JobDispatcher: Send + Sync {
     // json for initial client handshake
@@ -52,6 +65,13 @@ pub trait JobDispatcher: Send + Sync {
     fn job(&self) -> Option<String> { None }
     // miner job result
     fn submit(&self, payload: Vec<String>) -> Result<(), Error>;
+    // miner share result, checked against the share target if any, else against the block target
+    fn submit_share(&self, payload: Vec<String>, _share_target: Option<H256>) -> ShareResult {
+        match self.submit(payload) {
+            Ok(()) => ShareResult::Block,
+            Err(e) => ShareResult::Invalid(format!("{:?}", e)),
+        }
+    }
 }
 
 /// Interface that can handle requests to push job for workers
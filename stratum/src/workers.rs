@@ -0,0 +1,278 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Share difficulty and accounting of the stratum workers
+
+use std::cmp;
+use std::collections::{HashSet, VecDeque};
+use std::time::{Duration, Instant};
+use aion_types::{H256, U256};
+
+/// Maximum number of accepted shares remembered to reject duplicates
+const MAX_RECENT_SHARES: usize = 65536;
+/// Maximum factor of a single share difficulty retarget
+const MAX_RETARGET_FACTOR: u64 = 4;
+
+/// Share difficulty of the workers, variable between `minimum` and `maximum`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShareDifficulty {
+    /// Share difficulty of a new worker
+    pub initial: U256,
+    /// Lower bound of the variable share difficulty
+    pub minimum: U256,
+    /// Upper bound of the variable share difficulty
+    pub maximum: U256,
+    /// Time between two shares of a worker the variable difficulty aims at
+    pub target_share_time: Duration,
+    /// Time between two share difficulty retargets of a worker
+    pub retarget_time: Duration,
+}
+
+impl ShareDifficulty {
+    fn clamp(&self, difficulty: U256) -> U256 {
+        cmp::min(cmp::max(difficulty, self.minimum), self.maximum)
+    }
+}
+
+/// Share accounting of a worker
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkerStats {
+    /// Worker id given at authorization
+    pub worker: String,
+    /// Latest share difficulty, zero when shares are checked against the block target
+    pub difficulty: U256,
+    /// Number of accepted shares
+    pub accepted: u64,
+    /// Number of shares of unknown or out of date work
+    pub stale: u64,
+    /// Number of invalid or duplicate shares
+    pub invalid: u64,
+}
+
+impl WorkerStats {
+    pub fn new(worker: String) -> WorkerStats {
+        WorkerStats {
+            worker: worker,
+            difficulty: U256::zero(),
+            accepted: 0,
+            stale: 0,
+            invalid: 0,
+        }
+    }
+}
+
+/// Authorized worker of a connection
+pub struct Worker {
+    /// Worker id given at authorization
+    pub id: String,
+    /// Share difficulty, zero when shares are checked against the block target
+    pub difficulty: U256,
+    /// Whether the worker was told its current share difficulty
+    pub difficulty_sent: bool,
+    retarget_shares: u64,
+    last_retarget: Instant,
+}
+
+impl Worker {
+    pub fn new(id: String, share_difficulty: Option<&ShareDifficulty>) -> Worker {
+        Worker {
+            id: id,
+            difficulty: share_difficulty.map_or(U256::zero(), |d| d.clamp(d.initial)),
+            difficulty_sent: false,
+            retarget_shares: 0,
+            last_retarget: Instant::now(),
+        }
+    }
+
+    /// Share target of the worker, none when shares are checked against the block target
+    pub fn share_target(&self) -> Option<H256> {
+        if self.difficulty.is_zero() {
+            None
+        } else {
+            Some(difficulty_to_target(&self.difficulty))
+        }
+    }
+
+    /// Counts an accepted share towards the next retarget
+    pub fn on_accepted(&mut self) { self.retarget_shares += 1; }
+
+    /// Retargets the share difficulty from the share rate since the last retarget, returns the
+    /// new difficulty if it changed
+    pub fn retarget(&mut self, share_difficulty: &ShareDifficulty, now: Instant) -> Option<U256> {
+        let elapsed = now.duration_since(self.last_retarget);
+        if elapsed < share_difficulty.retarget_time {
+            return None;
+        }
+        let elapsed_ms = cmp::max(duration_ms(&elapsed), 1);
+        let current = self.difficulty;
+        // scale the difficulty by the ratio of the target to the actual time between shares
+        let scaled = current
+            .saturating_mul(U256::from(self.retarget_shares))
+            .saturating_mul(U256::from(duration_ms(&share_difficulty.target_share_time)))
+            / U256::from(elapsed_ms);
+        let factor = U256::from(MAX_RETARGET_FACTOR);
+        let bounded = cmp::min(
+            cmp::max(scaled, current / factor),
+            current.saturating_mul(factor),
+        );
+        let difficulty = share_difficulty.clamp(bounded);
+
+        self.retarget_shares = 0;
+        self.last_retarget = now;
+        if difficulty == current {
+            return None;
+        }
+        self.difficulty = difficulty;
+        self.difficulty_sent = false;
+        Some(difficulty)
+    }
+}
+
+/// Accepted shares, to reject duplicates
+pub struct RecentShares {
+    order: VecDeque<H256>,
+    shares: HashSet<H256>,
+}
+
+impl RecentShares {
+    pub fn new() -> RecentShares {
+        RecentShares {
+            order: VecDeque::new(),
+            shares: HashSet::new(),
+        }
+    }
+
+    /// Whether the share was already accepted
+    pub fn contains(&self, share: &H256) -> bool { self.shares.contains(share) }
+
+    /// Remembers an accepted share, returns false if it was already accepted
+    pub fn insert(&mut self, share: H256) -> bool {
+        if !self.shares.insert(share) {
+            return false;
+        }
+        self.order.push_back(share);
+        if self.order.len() > MAX_RECENT_SHARES {
+            if let Some(oldest) = self.order.pop_front() {
+                self.shares.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+/// Target a solution hash must be below to meet the difficulty
+pub fn difficulty_to_target(difficulty: &U256) -> H256 {
+    if *difficulty <= U256::one() {
+        U256::max_value().into()
+    } else {
+        (((U256::one() << 255) / *difficulty) << 1).into()
+    }
+}
+
+fn duration_ms(duration: &Duration) -> u64 {
+    duration.as_secs() * 1000 + duration.subsec_nanos() as u64 / 1_000_000
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use aion_types::{H256, U256};
+    use super::{RecentShares, ShareDifficulty, Worker, difficulty_to_target};
+
+    fn share_difficulty() -> ShareDifficulty {
+        ShareDifficulty {
+            initial: U256::from(1000),
+            minimum: U256::from(100),
+            maximum: U256::from(2000),
+            target_share_time: Duration::from_secs(10),
+            retarget_time: Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn retargets_on_share_rate() {
+        let share_difficulty = share_difficulty();
+        let mut worker = Worker::new("miner1".into(), Some(&share_difficulty));
+        let start = worker.last_retarget;
+        assert_eq!(worker.difficulty, U256::from(1000));
+
+        // too early
+        assert_eq!(worker.retarget(&share_difficulty, start), None);
+
+        // 9 shares in 60 seconds instead of 6
+        for _ in 0..9 {
+            worker.on_accepted();
+        }
+        let now = start + Duration::from_secs(60);
+        assert_eq!(worker.retarget(&share_difficulty, now), Some(U256::from(1500)));
+        assert!(!worker.difficulty_sent);
+
+        // 6 shares in 60 seconds, on target
+        for _ in 0..6 {
+            worker.on_accepted();
+        }
+        let now = now + Duration::from_secs(60);
+        assert_eq!(worker.retarget(&share_difficulty, now), None);
+
+        // a burst of shares is bounded by the retarget factor and the maximum
+        for _ in 0..100 {
+            worker.on_accepted();
+        }
+        let now = now + Duration::from_secs(60);
+        assert_eq!(worker.retarget(&share_difficulty, now), Some(U256::from(2000)));
+
+        // no shares
+        let now = now + Duration::from_secs(60);
+        assert_eq!(worker.retarget(&share_difficulty, now), Some(U256::from(500)));
+        let now = now + Duration::from_secs(60);
+        assert_eq!(worker.retarget(&share_difficulty, now), Some(U256::from(125)));
+        let now = now + Duration::from_secs(60);
+        assert_eq!(worker.retarget(&share_difficulty, now), Some(U256::from(100)));
+        assert_eq!(worker.retarget_shares, 0);
+    }
+
+    #[test]
+    fn block_target_without_share_difficulty() {
+        let worker = Worker::new("miner1".into(), None);
+        assert_eq!(worker.share_target(), None);
+        let worker = Worker::new("miner1".into(), Some(&share_difficulty()));
+        assert_eq!(worker.share_target(), Some(difficulty_to_target(&U256::from(1000))));
+    }
+
+    #[test]
+    fn remembers_recent_shares() {
+        let mut shares = RecentShares::new();
+        assert!(!shares.contains(&H256::from(1)));
+        assert!(shares.insert(H256::from(1)));
+        assert!(shares.contains(&H256::from(1)));
+        assert!(!shares.insert(H256::from(1)));
+        assert!(shares.insert(H256::from(2)));
+    }
+
+    #[test]
+    fn converts_difficulty_to_target() {
+        assert_eq!(difficulty_to_target(&U256::one()), H256::from(U256::max_value()));
+        assert_eq!(
+            difficulty_to_target(&U256::from(2)),
+            H256::from(U256::one() << 255)
+        );
+    }
+}
@@ -145,6 +145,12 @@ impl P2pMgr {
 
     pub fn get_local_node() -> &'static Node { LOCAL_NODE.get() }
 
+    pub fn is_enabled() -> bool {
+        ENABLED
+            .try_get()
+            .map_or(false, |enabled| enabled.load(Ordering::SeqCst))
+    }
+
     pub fn disable() {
         ENABLED.get().store(false, Ordering::SeqCst);
         Self::reset();
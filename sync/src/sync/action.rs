@@ -31,6 +31,7 @@ pub enum SyncAction {
     BLOCKSBODIESRES = 5,
     BROADCASTTX = 6,
     BROADCASTBLOCK = 7,
+    BROADCASTEVIDENCE = 8,
     UNKNOWN = 0xFF,
 }
 
@@ -45,6 +46,7 @@ impl SyncAction {
             SyncAction::BLOCKSBODIESRES => 5 as u8,
             SyncAction::BROADCASTTX => 6 as u8,
             SyncAction::BROADCASTBLOCK => 7 as u8,
+            SyncAction::BROADCASTEVIDENCE => 8 as u8,
             SyncAction::UNKNOWN => 0xFF as u8,
         }
     }
@@ -59,6 +61,7 @@ impl SyncAction {
             5 => SyncAction::BLOCKSBODIESRES,
             6 => SyncAction::BROADCASTTX,
             7 => SyncAction::BROADCASTBLOCK,
+            8 => SyncAction::BROADCASTEVIDENCE,
             _ => SyncAction::UNKNOWN,
         }
     }
@@ -75,6 +78,7 @@ impl fmt::Display for SyncAction {
             SyncAction::BLOCKSBODIESRES => "BLOCKSBODIESRES",
             SyncAction::BROADCASTTX => "BROADCASTTX",
             SyncAction::BROADCASTBLOCK => "BROADCASTBLOCK",
+            SyncAction::BROADCASTEVIDENCE => "BROADCASTEVIDENCE",
             SyncAction::UNKNOWN => "UNKNOWN",
         };
         write!(f, "{}", printable)
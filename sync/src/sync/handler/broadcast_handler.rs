@@ -107,6 +107,44 @@ impl BroadcastsHandler {
         }
     }
 
+    pub fn propagate_evidence(evidence: &[u8]) {
+        // broadcast double-sign evidence
+        let active_nodes = P2pMgr::get_nodes(ALIVE);
+
+        if active_nodes.len() > 0 {
+            let mut req = ChannelBuffer::new();
+            req.head.ver = Version::V0.value();
+            req.head.ctrl = Control::SYNC.value();
+            req.head.action = SyncAction::BROADCASTEVIDENCE.value();
+            req.body.put_slice(evidence);
+            req.head.len = req.body.len() as u32;
+
+            for node in active_nodes.iter() {
+                P2pMgr::send(node.node_hash, req.clone());
+                trace!(target: "sync", "Sync broadcast double-sign evidence sent...");
+            }
+        }
+    }
+
+    pub fn handle_broadcast_evidence(node: &mut Node, req: ChannelBuffer) {
+        trace!(target: "sync", "BROADCASTEVIDENCE received.");
+
+        // new evidence is propagated further once the client reports it back through
+        // `ChainNotify::double_sign_evidence`
+        let client = SyncStorage::get_block_chain();
+        match client.import_evidence(req.body) {
+            Ok(true) => {
+                info!(target: "sync", "Double-sign evidence received from: {}.", node.get_ip_addr());
+            }
+            Ok(false) => {
+                trace!(target: "sync", "Double-sign evidence already known.");
+            }
+            Err(e) => {
+                warn!(target: "sync", "Bad double-sign evidence from: {} : {}", node.get_ip_addr(), e);
+            }
+        }
+    }
+
     pub fn handle_broadcast_block(node: &mut Node, req: ChannelBuffer) {
         trace!(target: "sync", "BROADCASTBLOCK received.");
 
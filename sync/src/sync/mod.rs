@@ -26,6 +26,7 @@ use futures::{Future, Stream};
 use rlp::UntrustedRlp;
 use std::collections::BTreeMap;
 use std::ops::Index;
+use std::net::SocketAddrV4;
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime};
 use tokio::runtime::TaskExecutor;
@@ -268,6 +269,9 @@ impl SyncMgr {
                             SyncAction::BROADCASTBLOCK => {
                                 BroadcastsHandler::handle_broadcast_block(node, req);
                             }
+                            SyncAction::BROADCASTEVIDENCE => {
+                                BroadcastsHandler::handle_broadcast_evidence(node, req);
+                            }
                             _ => {
                                 trace!(target: "sync", "UNKNOWN received.");
                             }
@@ -388,13 +392,29 @@ impl SyncProvider for Sync {
         for peer in peer_nodes.iter() {
             let peer_info = PeerInfo {
                 id: Some(peer.get_node_id()),
+                addr: peer.get_ip_addr(),
+                revision: String::from_utf8_lossy(&peer.revision).trim().to_owned(),
+                mode: format!("{}", peer.mode),
+                highest_block_number: peer.best_block_num,
+                total_difficulty: peer.current_total_difficulty,
+                is_active: peer.state_code & ALIVE == ALIVE,
             };
             peer_info_list.push(peer_info);
         }
         peer_info_list
     }
 
-    fn enode(&self) -> Option<String> { Some(P2pMgr::get_local_node().get_node_id()) }
+    fn enode(&self) -> Option<String> {
+        if !P2pMgr::is_enabled() {
+            return None;
+        }
+        let local_node = P2pMgr::get_local_node();
+        Some(format!(
+            "p2p://{}@{}",
+            local_node.get_node_id(),
+            local_node.get_ip_addr()
+        ))
+    }
 
     fn transactions_stats(&self) -> BTreeMap<H256, TransactionStats> { BTreeMap::new() }
 
@@ -425,6 +445,10 @@ pub trait NetworkManager: Send + ::std::marker::Sync {
     fn stop_network(&self);
     /// Query the current configuration of the network
     fn network_config(&self) -> NetworkConfig;
+    /// Add a node (`p2p://<node id>@<ip>:<port>`) to be connected
+    fn add_node(&self, node: &str) -> Result<(), String>;
+    /// Disconnect and drop a peer by its node id
+    fn remove_node(&self, node_id: &str) -> bool;
 }
 
 impl NetworkManager for Sync {
@@ -454,6 +478,51 @@ impl NetworkManager for Sync {
     }
 
     fn network_config(&self) -> NetworkConfig { NetworkConfig::from(self.network.config.clone()) }
+
+    fn add_node(&self, node_str: &str) -> Result<(), String> {
+        if !P2pMgr::is_enabled() {
+            return Err("Network is not started".into());
+        }
+        validate_node_str(node_str)?;
+
+        let mut node = Node::new_with_node_str(node_str.to_owned());
+        node.node_hash = P2pMgr::calculate_hash(&node.get_node_id());
+        node.state_code = DISCONNECTED;
+        if P2pMgr::get_node(node.node_hash).is_some() {
+            return Err(format!("Known node: {}", node.get_node_id()));
+        }
+
+        let node_hash = node.node_hash;
+        P2pMgr::add_node(node);
+        match P2pMgr::get_node(node_hash) {
+            Some(_) => Ok(()),
+            None => Err("Max peers reached".into()),
+        }
+    }
+
+    fn remove_node(&self, node_id: &str) -> bool {
+        if !P2pMgr::is_enabled() {
+            return false;
+        }
+        let node_hash = P2pMgr::calculate_hash(&node_id);
+        P2pMgr::remove_peer(node_hash).is_some()
+    }
+}
+
+/// Check that a node string has the form `p2p://<node id>@<ip>:<port>`.
+fn validate_node_str(node_str: &str) -> Result<(), String> {
+    let invalid = || format!("Invalid node: {}, expected p2p://<node id>@<ip>:<port>", node_str);
+    if !node_str.starts_with("p2p://") {
+        return Err(invalid());
+    }
+    let (_, node) = node_str.split_at(PROTOCOL_LENGTH);
+    if node.len() <= NODE_ID_LENGTH || node.as_bytes()[NODE_ID_LENGTH] != b'@' {
+        return Err(invalid());
+    }
+    node[NODE_ID_LENGTH + 1..]
+        .parse::<SocketAddrV4>()
+        .map(|_| ())
+        .map_err(|_| invalid())
 }
 
 impl ChainNotify for Sync {
@@ -564,6 +633,11 @@ impl ChainNotify for Sync {
             }
         }
     }
+
+    fn double_sign_evidence(&self, evidence: Vec<u8>) {
+        debug!(target: "sync", "Propagating double-sign evidence...");
+        BroadcastsHandler::propagate_evidence(&evidence);
+    }
 }
 
 /// Configuration for IPC service.
@@ -495,7 +495,20 @@ impl SyncStatus {
 }
 
 pub struct PeerInfo {
+    /// node id
     pub id: Option<String>,
+    /// remote p2p address
+    pub addr: String,
+    /// client revision
+    pub revision: String,
+    /// sync mode
+    pub mode: String,
+    /// Best block number
+    pub highest_block_number: u64,
+    /// Total difficulty
+    pub total_difficulty: U256,
+    /// whether the handshake is done
+    pub is_active: bool,
 }
 pub struct TransactionStats {
     pub first_seen: u64,
@@ -51,6 +51,7 @@ mod function;
 mod log;
 mod operation;
 mod param;
+mod revert;
 mod signature;
 mod util;
 
@@ -67,6 +68,7 @@ pub use param::Param;
 pub use log::{Log, RawLog, LogParam};
 pub use event::Event;
 pub use event_param::EventParam;
+pub use revert::decode_revert_reason;
 
 /// ABI address.
 pub type Address = aion_types::Address;
@@ -0,0 +1,78 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Solidity revert reason decoding.
+
+use signature::short_signature;
+use {decode, ParamType, Token};
+
+/// Decodes the reason string of a `revert("...")` / `require(..., "...")`, which Solidity
+/// returns ABI-encoded as a call to `Error(string)`.
+///
+/// Returns `None` if the output is not an encoded `Error(string)`.
+pub fn decode_revert_reason(output: &[u8]) -> Option<String> {
+    if output.len() < 4 || output[..4] != short_signature("Error", &[ParamType::String]) {
+        return None;
+    }
+
+    match decode(&[ParamType::String], &output[4..]) {
+        Ok(mut tokens) => {
+            match tokens.pop() {
+                Some(Token::String(reason)) => Some(reason),
+                _ => None,
+            }
+        }
+        Err(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode_revert_reason;
+    use {Function, Param, ParamType, Token};
+
+    #[test]
+    fn test_decode_revert_reason() {
+        let error = Function {
+            name: "Error".to_owned(),
+            inputs: vec![Param {
+                name: "reason".to_owned(),
+                kind: ParamType::String,
+            }],
+            outputs: vec![],
+            constant: false,
+        };
+        let output = error
+            .encode_input(&[Token::String("insufficient balance".to_owned())])
+            .unwrap();
+
+        assert_eq!(
+            decode_revert_reason(&output),
+            Some("insufficient balance".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_decode_revert_reason_invalid() {
+        assert_eq!(decode_revert_reason(&[]), None);
+        assert_eq!(decode_revert_reason(&[0u8; 36]), None);
+    }
+}
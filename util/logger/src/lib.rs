@@ -39,10 +39,10 @@ use std::{env, thread, fs};
 use std::sync::{Weak, Arc};
 use std::io::Write;
 use isatty::{stderr_isatty, stdout_isatty};
-use env_logger::LogBuilder;
+use env_logger::{LogBuilder, Logger};
 use regex::Regex;
 use ansi_term::Colour;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
 
 pub use rotating::{RotatingLogger, init_log};
 
@@ -67,13 +67,34 @@ impl Default for LogConfig {
 
 lazy_static! {
     static ref ROTATING_LOGGER: Mutex<Weak<RotatingLogger>> = Mutex::new(Default::default());
+    static ref LOG_RELOADER: Mutex<Option<LogReloader>> = Mutex::new(None);
 }
 
-/// Sets up the logger
-pub fn setup_log(config: &LogConfig) -> Result<Arc<RotatingLogger>, String> {
+type LogFormat = Fn(&rlog::LogRecord) -> String + Sync + Send;
+
+/// Global logger delegating to an env logger that can be rebuilt at runtime.
+struct ReloadableLogger(Arc<RwLock<Logger>>);
+
+impl rlog::Log for ReloadableLogger {
+    fn enabled(&self, metadata: &rlog::LogMetadata) -> bool {
+        rlog::Log::enabled(&*self.0.read(), metadata)
+    }
+
+    fn log(&self, record: &rlog::LogRecord) { rlog::Log::log(&*self.0.read(), record) }
+}
+
+/// Everything needed to rebuild the global logger with new levels.
+struct LogReloader {
+    logger: Arc<RwLock<Logger>>,
+    max_level: rlog::MaxLogLevelFilter,
+    config: LogConfig,
+    format: Arc<LogFormat>,
+}
+
+/// Creates a log builder with the default filters and the levels of the config.
+fn log_builder(config: &LogConfig) -> Result<LogBuilder, String> {
     use rlog::*;
 
-    let mut levels = String::new();
     let mut builder = LogBuilder::new();
     // Disable info logging by default for some modules:
     builder.filter(Some("ws"), LogLevelFilter::Warn);
@@ -91,8 +112,6 @@ pub fn setup_log(config: &LogConfig) -> Result<Arc<RotatingLogger>, String> {
     );
 
     if let Ok(lvl) = env::var("RUST_LOG") {
-        levels.push_str(&lvl);
-        levels.push_str(",");
         builder.parse(&lvl);
     }
     // parse config mode
@@ -105,6 +124,20 @@ pub fn setup_log(config: &LogConfig) -> Result<Arc<RotatingLogger>, String> {
         }
     }
 
+    Ok(builder)
+}
+
+/// Sets up the logger
+pub fn setup_log(config: &LogConfig) -> Result<Arc<RotatingLogger>, String> {
+    use rlog::*;
+
+    let mut levels = String::new();
+    if let Ok(lvl) = env::var("RUST_LOG") {
+        levels.push_str(&lvl);
+        levels.push_str(",");
+    }
+    let mut builder = log_builder(config)?;
+
     let isatty = stderr_isatty();
     let enable_color = config.color && isatty;
     let logs = Arc::new(RotatingLogger::new(levels));
@@ -170,21 +203,80 @@ pub fn setup_log(config: &LogConfig) -> Result<Arc<RotatingLogger>, String> {
         ret
     };
 
-    builder.format(format);
-    builder
-        .init()
-        .and_then(|_| {
-            *ROTATING_LOGGER.lock() = Arc::downgrade(&logs);
-            Ok(logs)
-        })
-        // couldn't create new logger - try to fall back on previous logger.
-        .or_else(|err| {
-            match ROTATING_LOGGER.lock().upgrade() {
-                Some(l) => Ok(l),
-                // no previous logger. fatal.
-                None => Err(format!("{:?}", err)),
+    let format: Arc<LogFormat> = Arc::new(format);
+    let shared_format = format.clone();
+    builder.format(move |record: &LogRecord| (*shared_format)(record));
+    let env_logger = Arc::new(RwLock::new(builder.build()));
+
+    set_logger(|max_level| {
+        max_level.set(env_logger.read().filter());
+        *LOG_RELOADER.lock() = Some(LogReloader {
+            logger: env_logger.clone(),
+            max_level: max_level,
+            config: config.clone(),
+            format: format,
+        });
+        Box::new(ReloadableLogger(env_logger.clone()))
+    })
+    .and_then(|_| {
+        *ROTATING_LOGGER.lock() = Arc::downgrade(&logs);
+        Ok(logs)
+    })
+    // couldn't create new logger - try to fall back on previous logger.
+    .or_else(|err| {
+        match ROTATING_LOGGER.lock().upgrade() {
+            Some(l) => Ok(l),
+            // no previous logger. fatal.
+            None => Err(format!("{:?}", err)),
+        }
+    })
+}
+
+/// Changes the log levels of the logger created by `setup_log` at runtime.
+/// `levels` has the same format as `LogConfig::targets`, e.g. `sync=debug,miner=trace`.
+pub fn set_log_levels(levels: &str) -> Result<(), String> {
+    validate_levels(levels)?;
+
+    let reloader = LOG_RELOADER.lock();
+    let reloader = reloader
+        .as_ref()
+        .ok_or_else(|| "Logger is not initialized".to_owned())?;
+
+    let mut builder = log_builder(&reloader.config)?;
+    builder.parse(levels);
+    let format = reloader.format.clone();
+    builder.format(move |record: &rlog::LogRecord| (*format)(record));
+    let env_logger = builder.build();
+
+    reloader.max_level.set(env_logger.filter());
+    *reloader.logger.write() = env_logger;
+
+    if let Some(rotating) = ROTATING_LOGGER.lock().upgrade() {
+        rotating.set_levels(levels.to_owned());
+    }
+    Ok(())
+}
+
+/// Checks that every `module=level` directive names a valid level.
+fn validate_levels(levels: &str) -> Result<(), String> {
+    use rlog::LogLevelFilter;
+
+    // Anything after a '/' is a message filter regex.
+    let directives = levels.splitn(2, '/').next().unwrap_or("");
+    for directive in directives.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+        let parts: Vec<&str> = directive.split('=').collect();
+        match parts.len() {
+            1 => {}
+            2 => {
+                parts[1]
+                    .trim()
+                    .parse::<LogLevelFilter>()
+                    .map_err(|_| format!("Invalid log level: {}", directive))?;
             }
-        })
+            _ => return Err(format!("Invalid log directive: {}", directive)),
+        }
+    }
+    Ok(())
 }
 
 fn kill_color(s: &str) -> String {
@@ -211,3 +303,12 @@ fn should_remove_multiple_colour() {
     let after = kill_color(&t);
     assert_eq!(after, "test again");
 }
+
+#[test]
+fn should_validate_log_levels() {
+    assert!(validate_levels("sync=debug,miner=trace").is_ok());
+    assert!(validate_levels("info").is_ok());
+    assert!(validate_levels("sync=debug/foo=bar").is_ok());
+    assert!(validate_levels("sync=loud").is_err());
+    assert!(validate_levels("sync=debug=trace").is_err());
+}
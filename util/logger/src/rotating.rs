@@ -52,7 +52,7 @@ const LOG_SIZE: usize = 128;
 /// Logger implementation that keeps up to `LOG_SIZE` log elements.
 pub struct RotatingLogger {
     /// Defined logger levels
-    levels: String,
+    levels: RwLock<String>,
     /// Logs array. Latest log is always at index 0
     logs: RwLock<ArrayVec<[String; LOG_SIZE]>>,
 }
@@ -62,7 +62,7 @@ impl RotatingLogger {
     /// It does not enforce levels - it's just read only.
     pub fn new(levels: String) -> Self {
         RotatingLogger {
-            levels: levels,
+            levels: RwLock::new(levels),
             logs: RwLock::new(ArrayVec::<[_; LOG_SIZE]>::new()),
         }
     }
@@ -77,7 +77,10 @@ impl RotatingLogger {
     }
 
     /// Return levels
-    pub fn levels(&self) -> &str { &self.levels }
+    pub fn levels(&self) -> String { self.levels.read().clone() }
+
+    /// Update levels after the logger has been reconfigured
+    pub fn set_levels(&self, levels: String) { *self.levels.write() = levels; }
 
     /// Return logs
     pub fn logs(&self) -> RwLockReadGuard<ArrayVec<[String; LOG_SIZE]>> { self.logs.read() }
@@ -101,6 +104,18 @@ mod test {
         assert_eq!(levels, "test");
     }
 
+    #[test]
+    fn should_update_log_levels() {
+        // given
+        let logger = logger();
+
+        // when
+        logger.set_levels("sync=debug".to_owned());
+
+        // then
+        assert_eq!(logger.levels(), "sync=debug");
+    }
+
     #[test]
     fn should_return_latest_logs() {
         // given
@@ -82,6 +82,18 @@ impl<K: Eq + Hash, V: HeapSizeOf> MemoryLruCache<K, V> {
         }
     }
 
+    /// Change the maximum size in bytes, removing the least recently used items until the cache
+    /// fits in it.
+    pub fn set_max_size(&mut self, max_size: usize) {
+        self.max_size = max_size;
+        while self.cur_size > self.max_size {
+            match self.inner.remove_lru() {
+                Some((_, v)) => self.cur_size -= heap_size_of(&v),
+                _ => break,
+            }
+        }
+    }
+
     /// Get a reference to an item in the cache. It is a logic error for its
     /// heap size to be altered while borrowed.
     pub fn get_mut(&mut self, key: &K) -> Option<&mut V> { self.inner.get_mut(key) }
@@ -112,4 +124,19 @@ mod tests {
 
         assert_eq!(cache.current_size(), size2);
     }
+
+    #[test]
+    fn shrinks_to_new_max_size() {
+        let mut cache = MemoryLruCache::new(256);
+        let val1 = vec![0u8; 100];
+        let val2 = vec![0u8; 50];
+        let size2 = heap_size_of(&val2);
+        cache.insert("hello", val1);
+        cache.insert("world", val2);
+
+        cache.set_max_size(size2);
+        assert!(cache.get_mut(&"hello").is_none());
+        assert!(cache.get_mut(&"world").is_some());
+        assert_eq!(cache.current_size(), size2);
+    }
 }
@@ -96,6 +96,13 @@ where T: Clone
             .map(|i| self.in_use.remove(i))
     }
 
+    /// Returns a reference to the first item in use that `f` returns `true` with, without
+    /// taking it out of the queue.
+    pub fn find_used_if<P>(&self, predicate: P) -> Option<&T>
+    where P: Fn(&T) -> bool {
+        self.in_use.iter().find(|r| predicate(r))
+    }
+
     /// Returns `Some` item which is the first that `f` returns `true` with a reference to it
     /// as a parameter or `None` if no such item exists in the queue.
     pub fn clone_used_if<P>(&mut self, predicate: P) -> Option<T>
@@ -200,6 +207,17 @@ fn should_find_again_when_pushed_and_cloned() {
     assert!(q.take_used_if(|i| i == &1).unwrap() == 1);
 }
 
+#[test]
+fn should_find_again_when_pushed_and_found() {
+    let mut q = UsingQueue::new(2);
+    q.push(1);
+    assert!(q.find_used_if(|i| i == &1).is_none());
+    q.use_last_ref();
+    assert!(q.find_used_if(|i| i == &1) == Some(&1));
+    assert!(q.take_used_if(|i| i == &1).unwrap() == 1);
+    assert!(q.find_used_if(|i| i == &1).is_none());
+}
+
 #[test]
 fn should_find_when_others_used() {
     let mut q = UsingQueue::new(2);
@@ -105,6 +105,55 @@ impl fmt::Display for EvmStatusCode {
     }
 }
 
+impl EvmStatusCode {
+    /// All status codes, used to map exception messages back to status codes.
+    const ALL: [EvmStatusCode; 11] = [
+        EvmStatusCode::Success,
+        EvmStatusCode::Failure,
+        EvmStatusCode::OutOfGas,
+        EvmStatusCode::BadInstruction,
+        EvmStatusCode::BadJumpDescription,
+        EvmStatusCode::StackOverflow,
+        EvmStatusCode::StackUnderflow,
+        EvmStatusCode::Revert,
+        EvmStatusCode::StaticModeError,
+        EvmStatusCode::Rejected,
+        EvmStatusCode::InternalError,
+    ];
+
+    /// Machine readable name of the status code.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            EvmStatusCode::Success => "SUCCESS",
+            EvmStatusCode::Failure => "FAILURE",
+            EvmStatusCode::OutOfGas => "OUT_OF_NRG",
+            EvmStatusCode::BadInstruction => "BAD_INSTRUCTION",
+            EvmStatusCode::BadJumpDescription => "BAD_JUMP_DESTINATION",
+            EvmStatusCode::StackOverflow => "STACK_OVERFLOW",
+            EvmStatusCode::StackUnderflow => "STACK_UNDERFLOW",
+            EvmStatusCode::Revert => "REVERT",
+            EvmStatusCode::StaticModeError => "STATIC_MODE_ERROR",
+            EvmStatusCode::Rejected => "REJECTED",
+            EvmStatusCode::InternalError => "INTERNAL_ERROR",
+        }
+    }
+
+    /// Recover the status code from an execution exception message.
+    ///
+    /// The vm reports a failed status as its `Display` string (see `FastVMFactory::exec`), an
+    /// empty message means success. Returns `None` for messages not produced by the vm, e.g.
+    /// failed balance transfers.
+    pub fn from_exception(exception: &str) -> Option<EvmStatusCode> {
+        if exception.is_empty() {
+            return Some(EvmStatusCode::Success);
+        }
+        EvmStatusCode::ALL
+            .iter()
+            .find(|code| code.to_string() == exception)
+            .cloned()
+    }
+}
+
 #[derive(Debug, Clone)]
 #[repr(C)]
 // status_code: Success, Revert, OutOfGas